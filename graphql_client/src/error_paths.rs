@@ -0,0 +1,188 @@
+//! Access to the errors of a response by the path of their field, to handle partial failures
+//! next to the data they affect.
+//!
+//! ```
+//! # use serde::{Deserialize, Serialize};
+//! # use serde_json::json;
+//! use graphql_client::Response;
+//!
+//! #[derive(Debug, Serialize, Deserialize)]
+//! struct ResponseData {
+//!     repository: Option<Repository>,
+//! }
+//!
+//! #[derive(Debug, Serialize, Deserialize)]
+//! struct Repository {
+//!     name: String,
+//!     issues: Option<Vec<String>>,
+//! }
+//!
+//! let response: Response<ResponseData> = serde_json::from_value(json!({
+//!     "data": { "repository": { "name": "graphql-client", "issues": null } },
+//!     "errors": [
+//!         { "message": "Issues are disabled", "path": ["repository", "issues", 2] },
+//!         { "message": "Rate limit close" },
+//!     ],
+//! }))
+//! .unwrap();
+//!
+//! let errors = response.errors_at(&["repository", "issues"]);
+//! assert_eq!(errors.len(), 1);
+//! assert_eq!(errors[0].message, "Issues are disabled");
+//!
+//! // The `issues` are null, so the nearest field of the error is the repository.
+//! let annotated = response.annotated_errors();
+//! assert_eq!(annotated[0].field_path.len(), 1);
+//! assert_eq!(annotated[0].field.as_ref().unwrap()["name"], "graphql-client");
+//! assert_eq!(annotated[1].field, None);
+//! ```
+
+#[allow(unused_imports)]
+use crate::__alloc::*;
+use crate::{Error, PathFragment, Response};
+use serde::Serialize;
+use serde_json::Value;
+
+/// An error of a response, linked to the nearest field of the data on its path.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnnotatedError<'a> {
+    /// The error returned by the server.
+    pub error: &'a Error,
+    /// The path of the deepest field on the path of the error with a value other than `null` in
+    /// the data. It is the path of the error itself when its field has a value, and empty when the
+    /// error has no path, or when none of its fields have a value.
+    pub field_path: &'a [PathFragment],
+    /// The value of that field, serialized, or `None` when the path is empty.
+    pub field: Option<Value>,
+}
+
+impl<Data> Response<Data> {
+    /// The errors at the given path, or below it, like `&["repository", "issues"]`. The indices
+    /// of lists are given as decimal text. The errors without a path are never returned.
+    pub fn errors_at(&self, path: &[&str]) -> Vec<&Error> {
+        errors_at(self.errors.as_ref().map(Vec::as_slice).unwrap_or(&[]), path)
+    }
+}
+
+impl<Data: Serialize> Response<Data> {
+    /// The errors, in order, each linked to the nearest field of the data on its path.
+    ///
+    /// The data is serialized to find the fields, so the response types need to serialize to
+    /// the shape of the response, with its null fields, like with `serialize_responses = "true"`.
+    pub fn annotated_errors(&self) -> Vec<AnnotatedError<'_>> {
+        let data = self
+            .data
+            .as_ref()
+            .and_then(|data| serde_json::to_value(data).ok());
+
+        self.errors
+            .iter()
+            .flatten()
+            .map(|error| annotate(error, data.as_ref()))
+            .collect()
+    }
+}
+
+/// The errors at the given path, or below it.
+pub(crate) fn errors_at<'a>(errors: &'a [Error], path: &[&str]) -> Vec<&'a Error> {
+    errors
+        .iter()
+        .filter(|error| match &error.path {
+            Some(error_path) => {
+                error_path.len() >= path.len()
+                    && error_path
+                        .iter()
+                        .zip(path)
+                        .all(|(fragment, segment)| matches(fragment, segment))
+            }
+            None => false,
+        })
+        .collect()
+}
+
+/// Whether a fragment of the path of an error is the given segment.
+fn matches(fragment: &PathFragment, segment: &str) -> bool {
+    match fragment {
+        PathFragment::Key(key) => key == segment,
+        PathFragment::Index(index) => segment.parse::<i64>().ok() == Some(*index),
+    }
+}
+
+/// Links an error to the deepest field with a value on its path.
+fn annotate<'a>(error: &'a Error, data: Option<&Value>) -> AnnotatedError<'a> {
+    let path = error.path.as_ref().map(Vec::as_slice).unwrap_or(&[]);
+    let mut field_path = &path[..0];
+    let mut field = None;
+
+    if let Some(mut value) = data {
+        for (depth, fragment) in path.iter().enumerate() {
+            let child = match fragment {
+                PathFragment::Key(key) => value.get(key.as_str()),
+                PathFragment::Index(index) if *index >= 0 => value.get(*index as usize),
+                PathFragment::Index(_) => None,
+            };
+            value = match child {
+                Some(child) if !child.is_null() => child,
+                _ => break,
+            };
+            field_path = &path[..=depth];
+            field = Some(value);
+        }
+    }
+
+    AnnotatedError {
+        error,
+        field_path,
+        field: field.cloned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(message: &str, path: Option<Vec<PathFragment>>) -> Error {
+        Error {
+            message: message.to_owned(),
+            locations: None,
+            path,
+            extensions: None,
+        }
+    }
+
+    #[test]
+    fn errors_at_matches_keys_and_indices() {
+        let errors = vec![
+            error(
+                "first",
+                Some(vec![
+                    PathFragment::Key("users".to_owned()),
+                    PathFragment::Index(0),
+                    PathFragment::Key("email".to_owned()),
+                ]),
+            ),
+            error(
+                "second",
+                Some(vec![
+                    PathFragment::Key("users".to_owned()),
+                    PathFragment::Index(1),
+                ]),
+            ),
+            error("global", None),
+        ];
+
+        let messages = |path: &[&str]| -> Vec<String> {
+            errors_at(&errors, path)
+                .iter()
+                .map(|error| error.message.clone())
+                .collect()
+        };
+
+        assert_eq!(messages(&["users"]), vec!["first", "second"]);
+        assert_eq!(messages(&["users", "1"]), vec!["second"]);
+        assert_eq!(messages(&["users", "0", "email"]), vec!["first"]);
+        assert!(messages(&["users", "0", "email", "domain"]).is_empty());
+        assert!(messages(&["posts"]).is_empty());
+        assert_eq!(messages(&[]), vec!["first", "second"]);
+    }
+}
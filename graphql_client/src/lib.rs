@@ -2,10 +2,14 @@
 //!
 //! The main interface to this library is the custom derive that generates modules from a GraphQL query and schema. See the docs for the [`GraphQLQuery`] trait for a full example.
 
+#![cfg_attr(not(feature = "std"), no_std)]
 #![deny(missing_docs)]
 #![deny(rust_2018_idioms)]
 #![deny(warnings)]
 
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+
 #[allow(unused_imports)]
 #[macro_use]
 extern crate graphql_query_derive;
@@ -13,13 +17,75 @@ extern crate graphql_query_derive;
 #[doc(hidden)]
 pub use graphql_query_derive::*;
 
+#[doc(hidden)]
+pub use serde_json;
+
+/// The allocating types and macros used by the generated code, re-exported so it also compiles in
+/// `no_std` crates, where they are not in the prelude.
+#[doc(hidden)]
+pub mod __alloc {
+    #[cfg(not(feature = "std"))]
+    pub use alloc::{
+        borrow::ToOwned,
+        boxed::Box,
+        collections::BTreeMap,
+        format,
+        string::{String, ToString},
+        vec,
+        vec::Vec,
+    };
+    #[cfg(feature = "std")]
+    pub use std::{
+        borrow::ToOwned,
+        boxed::Box,
+        collections::BTreeMap,
+        format,
+        string::{String, ToString},
+        vec,
+        vec::Vec,
+    };
+}
+
+/// The Rust types of the built-in GraphQL scalars, imported by all the generated modules instead
+/// of each module declaring its own aliases. `String` keeps its name in Rust.
+pub mod scalars {
+    #[allow(unused_imports)]
+    use crate::__alloc::*;
+
+    /// The GraphQL `Boolean` scalar.
+    pub type Boolean = bool;
+    /// The GraphQL `Float` scalar.
+    pub type Float = f64;
+    /// The GraphQL `Int` scalar. It is 32 bits wide in the specification, but some servers send
+    /// larger values.
+    pub type Int = i64;
+    /// The GraphQL `ID` scalar, serialized as a string.
+    pub type ID = String;
+}
+
+#[allow(unused_imports)]
+use crate::__alloc::*;
+
 use serde::*;
 
+pub mod error_paths;
+pub mod local;
+#[cfg(feature = "std")]
+pub mod partial;
+#[cfg(feature = "registry")]
+pub mod registry;
+pub mod validation;
 #[cfg(feature = "web")]
 pub mod web;
 
-use std::collections::HashMap;
-use std::fmt::{self, Display};
+#[cfg(feature = "registry")]
+#[doc(hidden)]
+pub use inventory;
+
+use core::fmt::{self, Display};
+
+/// The map holding the extensions of an error.
+pub type Extensions = __alloc::BTreeMap<String, serde_json::Value>;
 
 #[cfg(test)]
 doc_comment::doctest!("../../README.md");
@@ -71,6 +137,10 @@ pub trait GraphQLQuery {
     /// The top-level shape of the response data (the `data` field in the GraphQL response). In practice this should be generated, since it is hard to write by hand without error.
     type ResponseData: for<'de> serde::Deserialize<'de>;
 
+    /// The fields of the response resolved locally, selected with the `@client` directive. See
+    /// [local::LocalResolvers].
+    const CLIENT_FIELDS: &'static [local::ClientField] = &[];
+
     /// Produce a GraphQL query struct that can be JSON serialized and sent to a GraphQL API.
     fn build_query(variables: Self::Variables) -> QueryBody<Self::Variables>;
 }
@@ -107,7 +177,7 @@ pub trait GraphQLQuery {
 /// }
 /// ```
 pub trait GraphQLQueryCLI
-    where Self: std::marker::Sized+serde::Serialize {
+    where Self: core::marker::Sized+serde::Serialize {
     /// The top-level shape of the response data (the `data` field in the GraphQL response). In practice this should be generated, since it is hard to write by hand without error.
     type ResponseData: for<'de> serde::Deserialize<'de>;
 
@@ -131,6 +201,65 @@ where
     pub operation_name: &'static str,
 }
 
+impl<Variables> QueryBody<Variables>
+where
+    Variables: serde::Serialize,
+{
+    /// Encodes the request in the `application/x-www-form-urlencoded` format, for the query
+    /// string of `GET` requests or the body of form posts. The variables are encoded as JSON, and
+    /// left out when they are `null`.
+    ///
+    /// ```
+    /// # use graphql_client::QueryBody;
+    /// let body = QueryBody {
+    ///     variables: (),
+    ///     query: "query Hello { hello }",
+    ///     operation_name: "Hello",
+    /// };
+    ///
+    /// assert_eq!(
+    ///     body.to_query_string().unwrap(),
+    ///     "query=query+Hello+%7B+hello+%7D&operationName=Hello"
+    /// );
+    /// ```
+    pub fn to_query_string(&self) -> Result<String, serde_json::Error> {
+        let variables = serde_json::to_string(&self.variables)?;
+        let mut encoded = String::new();
+
+        encoded.push_str("query=");
+        form_urlencode(&mut encoded, self.query);
+        encoded.push_str("&operationName=");
+        form_urlencode(&mut encoded, self.operation_name);
+
+        if variables != "null" {
+            encoded.push_str("&variables=");
+            form_urlencode(&mut encoded, &variables);
+        }
+
+        Ok(encoded)
+    }
+}
+
+/// Appends `input` to `out`, percent-encoded as in the `application/x-www-form-urlencoded`
+/// serializer of the URL standard.
+fn form_urlencode(out: &mut String, input: &str) {
+    const HEX: &[u8; 16] = b"0123456789ABCDEF";
+
+    for byte in input.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'*' | b'-' | b'.' | b'_' => {
+                out.push(byte as char)
+            }
+            b' ' => out.push('+'),
+            _ => {
+                out.push('%');
+                out.push(HEX[(byte >> 4) as usize] as char);
+                out.push(HEX[(byte & 0xf) as usize] as char);
+            }
+        }
+    }
+}
+
 /// Represents a location inside a query string. Used in errors. See [`Error`].
 #[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
 pub struct Location {
@@ -147,7 +276,25 @@ pub enum PathFragment {
     /// A key inside an object
     Key(String),
     /// An index inside an array
-    Index(i32),
+    Index(i64),
+}
+
+impl PathFragment {
+    /// The object key, if this fragment is one.
+    pub fn as_key(&self) -> Option<&str> {
+        match self {
+            PathFragment::Key(key) => Some(key),
+            PathFragment::Index(_) => None,
+        }
+    }
+
+    /// The array index, if this fragment is one.
+    pub fn as_index(&self) -> Option<i64> {
+        match self {
+            PathFragment::Key(_) => None,
+            PathFragment::Index(idx) => Some(*idx),
+        }
+    }
 }
 
 impl Display for PathFragment {
@@ -233,7 +380,7 @@ pub struct Error {
     /// Which path in the query the error applies to, e.g. `["users", 0, "email"]`.
     pub path: Option<Vec<PathFragment>>,
     /// Additional errors. Their exact format is defined by the server.
-    pub extensions: Option<HashMap<String, serde_json::Value>>,
+    pub extensions: Option<Extensions>,
 }
 
 impl Display for Error {
@@ -325,11 +472,357 @@ pub struct Response<Data> {
     pub errors: Option<Vec<Error>>,
 }
 
+impl<Data> Response<Data> {
+    /// Returns the data if the server returned it without any errors, and the errors otherwise,
+    /// discarding any partial data.
+    ///
+    /// ```
+    /// # use serde_json::json;
+    /// # use serde::Deserialize;
+    /// #
+    /// # #[derive(Debug, Deserialize, PartialEq)]
+    /// # struct ResponseData {
+    /// #     something: i32
+    /// # }
+    /// #
+    /// # fn main() -> Result<(), failure::Error> {
+    /// use graphql_client::Response;
+    ///
+    /// let body: Response<ResponseData> = serde_json::from_value(json!({
+    ///     "data": { "something": 1 },
+    ///     "errors": [{ "message": "Seismic activity detected" }],
+    /// }))?;
+    ///
+    /// let errors = body.into_result().unwrap_err();
+    ///
+    /// assert_eq!(errors.errors[0].message, "Seismic activity detected");
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn into_result(self) -> Result<Data, GraphQLErrors> {
+        let errors = self.errors.unwrap_or_default();
+
+        match self.data {
+            Some(data) if errors.is_empty() => Ok(data),
+            _ => Err(GraphQLErrors { errors }),
+        }
+    }
+}
+
+/// The errors of a response, or the lack of data in a response without errors. See
+/// [`Response::into_result`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphQLErrors {
+    /// The top-level errors returned by the server. This is empty when the response had neither
+    /// data nor errors.
+    pub errors: Vec<Error>,
+}
+
+impl Display for GraphQLErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.errors.is_empty() {
+            return write!(f, "The response has no data");
+        }
+
+        for (i, error) in self.errors.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", error)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for GraphQLErrors {}
+
+/// All the ways an operation can fail, by class, so callers can branch on the kind of failure
+/// rather than on the error types of each client.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OperationError {
+    /// The request could not be sent, or the response could not be received.
+    Transport(String),
+    /// The server answered with a non-2xx HTTP status, and no GraphQL response.
+    Http(u16),
+    /// The response is not a valid GraphQL response for the operation.
+    Deserialization(String),
+    /// The server returned GraphQL errors, or no data.
+    GraphQL(GraphQLErrors),
+}
+
+impl Display for OperationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OperationError::Transport(message) => write!(f, "Transport error: {}", message),
+            OperationError::Http(status) => write!(f, "HTTP error: status {}", status),
+            OperationError::Deserialization(message) => {
+                write!(f, "Invalid response: {}", message)
+            }
+            OperationError::GraphQL(errors) => write!(f, "{}", errors),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for OperationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            OperationError::GraphQL(errors) => Some(errors),
+            _ => None,
+        }
+    }
+}
+
+impl From<GraphQLErrors> for OperationError {
+    fn from(errors: GraphQLErrors) -> OperationError {
+        OperationError::GraphQL(errors)
+    }
+}
+
+/// The pagination information of a [Relay-style connection](https://relay.dev/graphql/connections.htm).
+///
+/// The structs generated for selections of all four fields of a page info convert to it with
+/// `From`, so pagination code can be shared between operations. The structs generated for the
+/// connections themselves get an `iter_nodes()` method, iterating over the nodes of their edges.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageInfo {
+    /// Whether there are more items after the end cursor.
+    pub has_next_page: bool,
+    /// Whether there are more items before the start cursor.
+    pub has_previous_page: bool,
+    /// The cursor of the first item of the page.
+    pub start_cursor: Option<String>,
+    /// The cursor of the last item of the page.
+    pub end_cursor: Option<String>,
+}
+
+/// An operation going through a list page by page, like a Relay-style connection with an
+/// `$after: String` variable for the cursor.
+///
+/// It is implemented by the derive for the operations with that variable that select, outside of
+/// any list, a single connection with the nodes of its edges and all four fields of its page info.
+/// Other operations are made paginated with the `pagination_cursor`, `pagination_nodes` and
+/// `pagination_page_info` attributes. `web::Client::paginate` uses it to fetch all the pages.
+pub trait Paginated: GraphQLQuery {
+    /// The type of the nodes of the connection.
+    type Node;
+
+    /// The variable for the cursor, and how it moves from page to page.
+    const CURSOR: PaginationCursor = PaginationCursor::EndCursor("after");
+
+    /// Takes the nodes and the page info of the connection out of the response data, or `None`
+    /// when the connection is null.
+    fn into_page(data: Self::ResponseData) -> Option<(Vec<Self::Node>, PageInfo)>;
+}
+
+/// The variable for the cursor of a [Paginated] operation, and how it moves from page to page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaginationCursor {
+    /// The variable takes the end cursor of the previous page, as long as it has a next page.
+    EndCursor(&'static str),
+    /// The variable is the number of nodes to skip, increased by the number of nodes of each page,
+    /// until a page is empty. The page info is not used.
+    Offset(&'static str),
+}
+
+/// A custom scalar parsed and validated by code of its own, rather than by a `Deserialize`
+/// implementation.
+///
+/// With the `scalar_trait` option, the generated code (de)serializes every field and variable of a
+/// custom scalar type through this trait, so validation and parsing — decoding a cursor, reading
+/// an amount of money — are written once and used by all the operations.
+///
+/// ```
+/// use graphql_client::GraphQLScalar;
+/// use serde_json::{json, Value};
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Cents(u64);
+///
+/// impl GraphQLScalar for Cents {
+///     type Error = String;
+///
+///     fn from_json(value: Value) -> Result<Self, String> {
+///         let amount = value.as_str().ok_or("expected a string")?;
+///         let mut parts = amount.splitn(2, '.');
+///         let units: u64 = parts.next().unwrap_or("").parse().map_err(|_| "invalid amount")?;
+///         let cents: u64 = parts.next().unwrap_or("0").parse().map_err(|_| "invalid amount")?;
+///
+///         Ok(Cents(units * 100 + cents))
+///     }
+///
+///     fn to_json(&self) -> Value {
+///         Value::String(format!("{}.{:02}", self.0 / 100, self.0 % 100))
+///     }
+/// }
+///
+/// assert_eq!(Cents::from_json(json!("12.05")), Ok(Cents(1205)));
+/// assert_eq!(Cents(1205).to_json(), json!("12.05"));
+/// assert!(Cents::from_json(json!(12)).is_err());
+/// ```
+pub trait GraphQLScalar: Sized {
+    /// Why a JSON value is not a valid value of the scalar.
+    type Error: Display;
+
+    /// Parses the scalar from its JSON value in a response.
+    fn from_json(value: serde_json::Value) -> Result<Self, Self::Error>;
+
+    /// The JSON value of the scalar, as sent in variables.
+    fn to_json(&self) -> serde_json::Value;
+
+    /// Checks the format of the scalar before it is sent, in the `validate` method of the
+    /// variables generated with the `validate_variables` option. Any value is valid by default.
+    fn validate(&self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// The serde glue between the generated code and [`GraphQLScalar`], used through
+/// `#[serde(with = "::graphql_client::__scalar")]`.
+#[doc(hidden)]
+pub mod __scalar {
+    use super::GraphQLScalar;
+    #[allow(unused_imports)]
+    use crate::__alloc::*;
+    use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+    use serde_json::Value;
+
+    /// A custom scalar, possibly wrapped in options and lists.
+    pub trait ScalarField: Sized {
+        /// The JSON value of the field.
+        fn to_json(&self) -> Value;
+
+        /// Parses the field from its JSON value.
+        fn from_json(value: Value) -> Result<Self, String>;
+    }
+
+    impl<T: GraphQLScalar> ScalarField for T {
+        fn to_json(&self) -> Value {
+            GraphQLScalar::to_json(self)
+        }
+
+        fn from_json(value: Value) -> Result<Self, String> {
+            <T as GraphQLScalar>::from_json(value).map_err(|err| err.to_string())
+        }
+    }
+
+    impl<T: ScalarField> ScalarField for Option<T> {
+        fn to_json(&self) -> Value {
+            self.as_ref().map(T::to_json).unwrap_or(Value::Null)
+        }
+
+        fn from_json(value: Value) -> Result<Self, String> {
+            match value {
+                Value::Null => Ok(None),
+                value => T::from_json(value).map(Some),
+            }
+        }
+    }
+
+    impl<T: ScalarField> ScalarField for Vec<T> {
+        fn to_json(&self) -> Value {
+            Value::Array(self.iter().map(T::to_json).collect())
+        }
+
+        fn from_json(value: Value) -> Result<Self, String> {
+            match value {
+                Value::Array(items) => items.into_iter().map(T::from_json).collect(),
+                _ => Err("expected a list".to_owned()),
+            }
+        }
+    }
+
+    /// Serializes a field through [`GraphQLScalar::to_json`].
+    pub fn serialize<T: ScalarField, S: Serializer>(
+        value: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        value.to_json().serialize(serializer)
+    }
+
+    /// Deserializes a field through [`GraphQLScalar::from_json`].
+    pub fn deserialize<'de, T: ScalarField, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<T, D::Error> {
+        let value = Value::deserialize(deserializer)?;
+        T::from_json(value).map_err(de::Error::custom)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use serde_json::json;
 
+    #[test]
+    fn query_body_to_query_string() {
+        #[derive(Serialize)]
+        struct Variables {
+            name: &'static str,
+        }
+
+        let body = QueryBody {
+            variables: Variables { name: "Zoë & co" },
+            query: "query Greet($name: String!) { greet(name: $name) }",
+            operation_name: "Greet",
+        };
+
+        assert_eq!(
+            body.to_query_string().unwrap(),
+            "query=query+Greet%28%24name%3A+String%21%29+%7B+greet%28name%3A+%24name%29+%7D\
+             &operationName=Greet\
+             &variables=%7B%22name%22%3A%22Zo%C3%AB+%26+co%22%7D"
+        );
+    }
+
+    #[test]
+    fn response_into_result() {
+        let ok: Response<i32> = Response {
+            data: Some(1),
+            errors: Some(Vec::new()),
+        };
+        assert_eq!(ok.into_result(), Ok(1));
+
+        let no_data: Response<i32> = Response {
+            data: None,
+            errors: None,
+        };
+        let no_data = no_data.into_result().unwrap_err();
+        assert!(no_data.errors.is_empty());
+        assert_eq!(no_data.to_string(), "The response has no data");
+
+        let error = Error {
+            message: "Seismic activity detected".to_owned(),
+            locations: None,
+            path: None,
+            extensions: None,
+        };
+        let partial: Response<i32> = Response {
+            data: Some(1),
+            errors: Some(vec![error.clone()]),
+        };
+        assert_eq!(
+            partial.into_result(),
+            Err(GraphQLErrors {
+                errors: vec![error]
+            })
+        );
+    }
+
+    #[test]
+    fn path_fragment_accessors() {
+        let path: Vec<PathFragment> =
+            serde_json::from_value(json!(["users", 4_294_967_296i64, "email"])).unwrap();
+
+        assert_eq!(path[0].as_key(), Some("users"));
+        assert_eq!(path[1].as_index(), Some(4_294_967_296));
+        assert_eq!(path[1].as_key(), None);
+        assert_eq!(path[2].to_string(), "email");
+    }
+
     #[test]
     fn graphql_error_works_with_just_message() {
         let err = json!({
@@ -398,7 +891,7 @@ mod tests {
 
         let deserialized_error: Error = serde_json::from_value(err).unwrap();
 
-        let mut expected_extensions = HashMap::new();
+        let mut expected_extensions = Extensions::new();
         expected_extensions.insert("code".to_owned(), json!("CAN_NOT_FETCH_BY_ID"));
         expected_extensions.insert("timestamp".to_owned(), json!("Fri Feb 9 14:33:09 UTC 2018"));
         let expected_extensions = Some(expected_extensions);
@@ -427,4 +920,23 @@ mod tests {
             }
         )
     }
+
+    #[test]
+    fn operation_error_classes() {
+        let no_data: Response<i32> = Response {
+            data: None,
+            errors: None,
+        };
+        let err = OperationError::from(no_data.into_result().unwrap_err());
+
+        assert_eq!(
+            err,
+            OperationError::GraphQL(GraphQLErrors { errors: Vec::new() })
+        );
+        assert_eq!(err.to_string(), "The response has no data");
+        assert_eq!(
+            OperationError::Http(503).to_string(),
+            "HTTP error: status 503"
+        );
+    }
 }
@@ -0,0 +1,214 @@
+//! Local resolvers, producing the values of the fields selected with the `@client` directive.
+//!
+//! These fields are removed from the query sent to the server, and are optional in the generated
+//! response types. The functions registered in [LocalResolvers] fill them in after the network
+//! round-trip, before the response is deserialized.
+//!
+//! ```
+//! use graphql_client::local::{ClientField, LocalResolvers};
+//! use graphql_client::Response;
+//! use serde::Deserialize;
+//! use serde_json::json;
+//!
+//! #[derive(Debug, Deserialize, PartialEq)]
+//! #[serde(rename_all = "camelCase")]
+//! struct Item {
+//!     id: String,
+//!     is_in_cart: Option<bool>,
+//! }
+//!
+//! #[derive(Debug, Deserialize, PartialEq)]
+//! struct ResponseData {
+//!     items: Vec<Item>,
+//! }
+//!
+//! const CLIENT_FIELDS: &[ClientField] = &[ClientField {
+//!     path: "items.isInCart",
+//!     type_name: "Item",
+//!     field_name: "isInCart",
+//! }];
+//!
+//! # fn main() -> Result<(), failure::Error> {
+//! let mut resolvers = LocalResolvers::new();
+//! resolvers.register("Item", "isInCart", |item| item["id"] == "1");
+//!
+//! let response = serde_json::from_value(json!({
+//!     "data": { "items": [{ "id": "1" }, { "id": "2" }] },
+//! }))?;
+//! let response: Response<ResponseData> = resolvers.resolve_response(CLIENT_FIELDS, response)?;
+//!
+//! let items = response.data.unwrap().items;
+//! assert_eq!(items[0].is_in_cart, Some(true));
+//! assert_eq!(items[1].is_in_cart, Some(false));
+//! # Ok(())
+//! # }
+//! ```
+
+#[allow(unused_imports)]
+use crate::__alloc::*;
+use serde_json::Value;
+
+/// A field resolved locally in the response of an operation, as listed in the `CLIENT_FIELDS`
+/// constant of the generated modules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientField {
+    /// The response names of the fields leading to the field from the root of the response data,
+    /// itself included, separated with dots. The lists on the way are traversed.
+    pub path: &'static str,
+    /// The name of the type the field is selected on.
+    pub type_name: &'static str,
+    /// The name of the field in the schema.
+    pub field_name: &'static str,
+}
+
+/// A function producing the value of a client field from the object it is selected on.
+type Resolver = Box<dyn Fn(&Value) -> Value + Send + Sync>;
+
+/// The functions producing the values of the client fields, by type and field name, like the local
+/// resolvers of Apollo Client.
+#[derive(Default)]
+pub struct LocalResolvers {
+    resolvers: BTreeMap<(String, String), Resolver>,
+}
+
+impl LocalResolvers {
+    /// An empty set of resolvers.
+    pub fn new() -> LocalResolvers {
+        LocalResolvers::default()
+    }
+
+    /// Resolves the `field_name` field of the `type_name` type with the function, called with the
+    /// object the field is selected on, as received from the server. A value that does not
+    /// serialize to JSON resolves the field to `null`.
+    pub fn register<F, T>(&mut self, type_name: &str, field_name: &str, resolver: F) -> &mut Self
+    where
+        F: Fn(&Value) -> T + Send + Sync + 'static,
+        T: serde::Serialize,
+    {
+        self.resolvers.insert(
+            (type_name.to_owned(), field_name.to_owned()),
+            Box::new(move |parent| serde_json::to_value(resolver(parent)).unwrap_or(Value::Null)),
+        );
+        self
+    }
+
+    /// Sets the values of the client fields in the response data. The fields without a resolver
+    /// are left out, and deserialize to `None`.
+    pub fn resolve(&self, fields: &[ClientField], data: &mut Value) {
+        for field in fields {
+            let resolver = self
+                .resolvers
+                .get(&(field.type_name.to_owned(), field.field_name.to_owned()));
+            if let Some(resolver) = resolver {
+                let path: Vec<&str> = field.path.split('.').collect();
+                resolve_at(resolver, &path, data);
+            }
+        }
+    }
+
+    /// Resolves the client fields in the data of a response, and deserializes it.
+    pub fn resolve_response<Data>(
+        &self,
+        fields: &[ClientField],
+        response: crate::Response<Value>,
+    ) -> Result<crate::Response<Data>, serde_json::Error>
+    where
+        Data: serde::de::DeserializeOwned,
+    {
+        let data = match response.data {
+            Some(mut data) => {
+                self.resolve(fields, &mut data);
+                Some(serde_json::from_value(data)?)
+            }
+            None => None,
+        };
+
+        Ok(crate::Response {
+            data,
+            errors: response.errors,
+        })
+    }
+}
+
+/// Resolves the field at the end of the path, through the lists and skipping the null objects.
+fn resolve_at(resolver: &Resolver, path: &[&str], value: &mut Value) {
+    if let Value::Array(items) = value {
+        for item in items {
+            resolve_at(resolver, path, item);
+        }
+        return;
+    }
+
+    match path.split_first() {
+        Some((response_name, rest)) if rest.is_empty() => {
+            if value.is_object() {
+                let resolved = resolver(value);
+                if let Value::Object(object) = value {
+                    object.insert((*response_name).to_owned(), resolved);
+                }
+            }
+        }
+        Some((response_name, rest)) => {
+            if let Some(child) = value.get_mut(*response_name) {
+                resolve_at(resolver, rest, child);
+            }
+        }
+        None => (),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn client_fields_are_resolved_through_lists_and_nulls() {
+        let mut resolvers = LocalResolvers::new();
+        resolvers.register("User", "initials", |user| {
+            user["name"]
+                .as_str()
+                .unwrap_or("")
+                .chars()
+                .take(1)
+                .collect::<String>()
+        });
+        let fields = [ClientField {
+            path: "teams.members.initials",
+            type_name: "User",
+            field_name: "initials",
+        }];
+        let mut data = json!({
+            "teams": [
+                { "members": [{ "name": "Ada" }, null] },
+                { "members": null },
+            ]
+        });
+
+        resolvers.resolve(&fields, &mut data);
+
+        assert_eq!(
+            data,
+            json!({
+                "teams": [
+                    { "members": [{ "name": "Ada", "initials": "A" }, null] },
+                    { "members": null },
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn fields_without_resolvers_are_left_out() {
+        let fields = [ClientField {
+            path: "viewer.isLoggedIn",
+            type_name: "Viewer",
+            field_name: "isLoggedIn",
+        }];
+        let mut data = json!({ "viewer": {} });
+
+        LocalResolvers::new().resolve(&fields, &mut data);
+
+        assert_eq!(data, json!({ "viewer": {} }));
+    }
+}
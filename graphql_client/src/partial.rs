@@ -0,0 +1,288 @@
+//! Lenient deserialization of the responses with errors, keeping the healthy part of their data.
+//!
+//! The server nulls the fields it could not resolve and reports an error with their path. A null
+//! in a non-nullable field should propagate to its nearest nullable parent, but servers that do
+//! not propagate it, or response types stricter than the schema, make the typed deserialization
+//! of the whole response fail. [`Response::into_partial`] softens the fields on the paths of the
+//! errors instead: each one is nulled at the deepest level the response types accept, so it
+//! deserializes to `None`, and the error is recorded next to its path.
+//!
+//! ```
+//! # use serde::Deserialize;
+//! # use serde_json::json;
+//! use graphql_client::{PathFragment, Response};
+//!
+//! #[derive(Debug, Deserialize)]
+//! struct ResponseData {
+//!     users: Vec<Option<User>>,
+//! }
+//!
+//! #[derive(Debug, Deserialize)]
+//! struct User {
+//!     name: String,
+//! }
+//!
+//! let response: Response<serde_json::Value> = serde_json::from_value(json!({
+//!     "data": { "users": [{ "name": "Ada" }, { "name": null }] },
+//!     "errors": [{ "message": "Name unavailable", "path": ["users", 1, "name"] }],
+//! }))
+//! .unwrap();
+//!
+//! let partial = response.into_partial::<ResponseData>().unwrap();
+//!
+//! let users = partial.data.unwrap().users;
+//! assert_eq!(users[0].as_ref().unwrap().name, "Ada");
+//! assert!(users[1].is_none());
+//! assert_eq!(
+//!     partial.softened[0].path,
+//!     vec![PathFragment::Key("users".to_owned()), PathFragment::Index(1)]
+//! );
+//! assert_eq!(partial.softened[0].error.message, "Name unavailable");
+//! ```
+
+use crate::{Error, PathFragment, Response};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+/// A response deserialized with [`Response::into_partial`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartialResponse<Data> {
+    /// The data, with the fields on the paths of the errors softened, or `None` when the server
+    /// returned none, or when no field up to the root of the data could be softened.
+    pub data: Option<Data>,
+    /// All the top-level errors returned by the server.
+    pub errors: Vec<Error>,
+    /// The fields that were nulled to deserialize the data, one for each error with a path.
+    pub softened: Vec<SoftenedField>,
+}
+
+impl<Data> PartialResponse<Data> {
+    /// Whether the data was deserialized without softening any field.
+    pub fn is_complete(&self) -> bool {
+        self.data.is_some() && self.softened.is_empty()
+    }
+
+    /// The errors at the given path, or below it, like `&["repository", "issues"]`. See
+    /// [`Response::errors_at`].
+    pub fn errors_at(&self, path: &[&str]) -> Vec<&Error> {
+        crate::error_paths::errors_at(&self.errors, path)
+    }
+}
+
+/// A field nulled to deserialize the data of a response, and the error that caused it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SoftenedField {
+    /// The path of the nulled field: the path of the error, or of the nearest parent of its field
+    /// that deserializes to `None`. It is empty when the whole data was discarded.
+    pub path: Vec<PathFragment>,
+    /// The error reported for the field, or for one of its children.
+    pub error: Error,
+}
+
+impl Response<Value> {
+    /// Deserializes the data, softening the fields on the paths of the errors so the healthy part
+    /// of the data is kept. See the [`partial`](crate::partial) module.
+    ///
+    /// This fails like the strict deserialization when the data does not match `Data` outside the
+    /// paths of the errors.
+    pub fn into_partial<Data: DeserializeOwned>(
+        self,
+    ) -> Result<PartialResponse<Data>, serde_json::Error> {
+        let errors = self.errors.unwrap_or_default();
+        let data = match self.data {
+            Some(data) => data,
+            None => {
+                return Ok(PartialResponse {
+                    data: None,
+                    errors,
+                    softened: Vec::new(),
+                })
+            }
+        };
+
+        let paths: Vec<&[PathFragment]> = errors
+            .iter()
+            .map(|error| error.path.as_ref().map(Vec::as_slice).unwrap_or(&[]))
+            .collect();
+        // How many fragments of the path of each error lead to the nulled value. The errors start
+        // at the value of their own field, and move up to its parent each time the deserialization
+        // fails there, until the data deserializes or an error reaches the root of the data.
+        let mut depths: Vec<usize> = paths.iter().map(|path| path.len()).collect();
+
+        let data: Option<Data> = loop {
+            let mut softened_data = data.clone();
+            for (path, depth) in paths.iter().zip(&depths) {
+                null_at(&mut softened_data, &path[..*depth]);
+            }
+
+            let err = match serde_path_to_error::deserialize(softened_data) {
+                Ok(deserialized) => break Some(deserialized),
+                Err(err) => err,
+            };
+            // The failure is at the nulled value of an error, or at one of its parents when the
+            // value is buffered, like in untagged enums.
+            let failed_at = failed_path(err.path());
+            let culprit = paths
+                .iter()
+                .zip(depths.iter_mut())
+                .find(|(path, depth)| **depth > 0 && path[..**depth].starts_with(&failed_at));
+
+            match culprit {
+                Some((_, depth)) if *depth > 1 => {
+                    *depth = std::cmp::min(*depth - 1, failed_at.len().max(1))
+                }
+                Some(_) => {
+                    depths.iter_mut().for_each(|depth| *depth = 0);
+                    break None;
+                }
+                None => return Err(err.into_inner()),
+            }
+        };
+
+        let softened = errors
+            .iter()
+            .zip(paths.iter().zip(&depths))
+            .filter(|(_, (path, _))| !path.is_empty())
+            .map(|(error, (path, depth))| SoftenedField {
+                path: path[..*depth].to_vec(),
+                error: error.clone(),
+            })
+            .collect();
+
+        Ok(PartialResponse {
+            data,
+            errors,
+            softened,
+        })
+    }
+}
+
+/// The path of a deserialization error in the response data, up to the first fragment that does
+/// not match a field or a list item, like the variant of an untagged enum.
+fn failed_path(path: &serde_path_to_error::Path) -> Vec<PathFragment> {
+    path.iter()
+        .map(|segment| match segment {
+            serde_path_to_error::Segment::Map { key } => Some(PathFragment::Key(key.clone())),
+            serde_path_to_error::Segment::Seq { index } => Some(PathFragment::Index(*index as i64)),
+            _ => None,
+        })
+        .take_while(Option::is_some)
+        .map(Option::unwrap)
+        .collect()
+}
+
+/// Replaces the value at the given path with `null`, if the path exists. An empty path, for the
+/// errors without one, leaves the value unchanged.
+fn null_at(value: &mut Value, path: &[PathFragment]) {
+    if path.is_empty() {
+        return;
+    }
+
+    let mut value = value;
+    for fragment in path {
+        let child = match fragment {
+            PathFragment::Key(key) => value.get_mut(key.as_str()),
+            PathFragment::Index(index) if *index >= 0 => value.get_mut(*index as usize),
+            PathFragment::Index(_) => None,
+        };
+        value = match child {
+            Some(child) => child,
+            None => return,
+        };
+    }
+
+    *value = Value::Null;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct ResponseData {
+        viewer: Viewer,
+        motd: Option<String>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Viewer {
+        login: String,
+        avatar: Option<Avatar>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Avatar {
+        url: String,
+    }
+
+    fn key(key: &str) -> PathFragment {
+        PathFragment::Key(key.to_owned())
+    }
+
+    #[test]
+    fn errors_soften_the_nearest_optional_field() {
+        let response: Response<Value> = serde_json::from_value(json!({
+            "data": {
+                "viewer": { "login": "octocat", "avatar": { "url": null } },
+                "motd": null,
+            },
+            "errors": [
+                { "message": "Avatar unavailable", "path": ["viewer", "avatar", "url"] },
+                { "message": "No message", "path": ["motd"] },
+                { "message": "Slow query" },
+            ],
+        }))
+        .unwrap();
+
+        let partial = response.into_partial::<ResponseData>().unwrap();
+
+        assert_eq!(
+            partial.data,
+            Some(ResponseData {
+                viewer: Viewer {
+                    login: "octocat".to_owned(),
+                    avatar: None,
+                },
+                motd: None,
+            })
+        );
+        assert_eq!(partial.errors.len(), 3);
+        let paths: Vec<&[PathFragment]> = partial
+            .softened
+            .iter()
+            .map(|field| field.path.as_slice())
+            .collect();
+        assert_eq!(
+            paths,
+            vec![&[key("viewer"), key("avatar")][..], &[key("motd")]]
+        );
+        assert!(!partial.is_complete());
+    }
+
+    #[test]
+    fn non_nullable_roots_discard_the_data() {
+        let response: Response<Value> = serde_json::from_value(json!({
+            "data": { "viewer": { "login": null, "avatar": null }, "motd": "Hi" },
+            "errors": [{ "message": "Forbidden", "path": ["viewer", "login"] }],
+        }))
+        .unwrap();
+
+        let partial = response.into_partial::<ResponseData>().unwrap();
+
+        assert_eq!(partial.data, None);
+        assert_eq!(partial.softened[0].path, Vec::new());
+    }
+
+    #[test]
+    fn mismatches_outside_the_error_paths_fail() {
+        let response: Response<Value> = serde_json::from_value(json!({
+            "data": { "viewer": { "login": 42, "avatar": null }, "motd": null },
+            "errors": [{ "message": "Oops" }],
+        }))
+        .unwrap();
+
+        assert!(response.into_partial::<ResponseData>().is_err());
+    }
+}
@@ -0,0 +1,41 @@
+//! A registry of all the GraphQL operations generated in the application and its dependencies,
+//! for example to upload persisted queries or to review the operations an application ships.
+//!
+//! The operations are registered when generated with the `register_operations` option, which the
+//! `registry` feature turns on by default.
+
+/// The type of a GraphQL operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OperationKind {
+    /// A `query` operation.
+    Query,
+    /// A `mutation` operation.
+    Mutation,
+    /// A `subscription` operation.
+    Subscription,
+}
+
+/// An operation in the registry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegisteredOperation {
+    /// The name of the operation.
+    pub name: &'static str,
+    /// The type of the operation.
+    pub kind: OperationKind,
+    /// The query document sent with the operation, as in the generated `QUERY` constant.
+    pub query: &'static str,
+    /// The hex-encoded SHA-256 hash of `query`, as used by automatic persisted queries.
+    pub hash: &'static str,
+}
+
+inventory::collect!(RegisteredOperation);
+
+/// All the registered operations, in no particular order.
+pub fn operations() -> impl Iterator<Item = &'static RegisteredOperation> {
+    inventory::iter::<RegisteredOperation>.into_iter()
+}
+
+/// The registered operation with the given hash, if any.
+pub fn find_by_hash(hash: &str) -> Option<&'static RegisteredOperation> {
+    operations().find(|operation| operation.hash == hash)
+}
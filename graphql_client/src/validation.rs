@@ -0,0 +1,140 @@
+//! Validation of the variables before they are sent, for the constraints of the schema their types
+//! cannot express. The generated code implements [Validate] for the variables, the input objects
+//! and the enums with the `validate_variables` option, and `Variables::validate` returns the
+//! invalid values rather than leaving the server to reject the request.
+
+#[allow(unused_imports)]
+use crate::__alloc::*;
+use crate::GraphQLScalar;
+use core::fmt::{self, Display};
+
+/// A variable, or a part of one, breaking a constraint of the schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    /// The path to the invalid value, with the GraphQL names of the variables and the fields, and
+    /// the indices in the lists, like `input.tags[2]`.
+    pub path: String,
+    /// What is wrong with the value.
+    pub message: String,
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ValidationError {}
+
+/// The values checked by the generated `validate` methods.
+pub trait Validate {
+    /// Adds the errors of the value, found at the given path, to `errors`.
+    fn validate_at(&self, path: &str, errors: &mut Vec<ValidationError>);
+}
+
+impl<T: Validate + ?Sized> Validate for Box<T> {
+    fn validate_at(&self, path: &str, errors: &mut Vec<ValidationError>) {
+        (**self).validate_at(path, errors)
+    }
+}
+
+/// The path of a field of the value at the given path.
+pub fn field_path(path: &str, field_name: &str) -> String {
+    if path.is_empty() {
+        field_name.to_owned()
+    } else {
+        format!("{}.{}", path, field_name)
+    }
+}
+
+/// Checks that a non-null custom scalar does not serialize to `null`, like a
+/// `serde_json::Value::Null` for a `JSON!` variable.
+pub fn check_non_null<T: serde::Serialize + ?Sized>(
+    value: &T,
+    path: &str,
+    errors: &mut Vec<ValidationError>,
+) {
+    if let Ok(serde_json::Value::Null) = serde_json::to_value(value) {
+        errors.push(ValidationError {
+            path: path.to_owned(),
+            message: "A non-null value is null.".to_owned(),
+        });
+    }
+}
+
+/// Checks the format of a custom scalar with [GraphQLScalar::validate].
+pub fn check_scalar<T: GraphQLScalar>(value: &T, path: &str, errors: &mut Vec<ValidationError>) {
+    if let Err(err) = value.validate() {
+        errors.push(ValidationError {
+            path: path.to_owned(),
+            message: err.to_string(),
+        });
+    }
+}
+
+/// Reports an enum value that is not in the schema, like the `Other` variant of the generated
+/// enums.
+pub fn unknown_enum_value(
+    value: &str,
+    enum_name: &str,
+    path: &str,
+    errors: &mut Vec<ValidationError>,
+) {
+    errors.push(ValidationError {
+        path: path.to_owned(),
+        message: format!("`{}` is not a value of the `{}` enum.", value, enum_name),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    struct Even(u32);
+
+    impl GraphQLScalar for Even {
+        type Error = String;
+
+        fn from_json(value: Value) -> Result<Self, String> {
+            Ok(Even(value.as_u64().ok_or("expected a number")? as u32))
+        }
+
+        fn to_json(&self) -> Value {
+            json!(self.0)
+        }
+
+        fn validate(&self) -> Result<(), String> {
+            if self.0 % 2 == 0 {
+                Ok(())
+            } else {
+                Err(format!("{} is odd.", self.0))
+            }
+        }
+    }
+
+    #[test]
+    fn scalars_are_checked() {
+        let mut errors = Vec::new();
+
+        check_non_null(&Value::Null, "input.metadata", &mut errors);
+        check_non_null(&json!({}), "input.other", &mut errors);
+        check_scalar(&Even(3), &field_path("", "count"), &mut errors);
+        check_scalar(&Even(4), "count", &mut errors);
+
+        assert_eq!(
+            errors,
+            vec![
+                ValidationError {
+                    path: "input.metadata".to_owned(),
+                    message: "A non-null value is null.".to_owned(),
+                },
+                ValidationError {
+                    path: "count".to_owned(),
+                    message: "3 is odd.".to_owned(),
+                },
+            ]
+        );
+    }
+}
@@ -3,9 +3,16 @@
 
 use crate::*;
 use failure::*;
-use futures::{Future, IntoFuture};
+use futures::future::{Either, Shared};
+use futures::sync::mpsc;
+use futures::{Async, Future, IntoFuture, Poll, Stream};
 use log::*;
+use serde::de::DeserializeOwned;
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use wasm_bindgen::closure::Closure;
 use wasm_bindgen::{JsCast, JsValue};
 use wasm_bindgen_futures::JsFuture;
 
@@ -19,12 +26,27 @@ use wasm_bindgen_futures::JsFuture;
 pub struct Client {
     endpoint: String,
     headers: HashMap<String, String>,
+    rate_limiter: Option<Mutex<TokenBucket>>,
+    metrics: Option<Arc<dyn Metrics + Send + Sync>>,
+    local_resolvers: Option<Arc<crate::local::LocalResolvers>>,
+    compress_requests: bool,
+    get_queries: bool,
+    http_cache: Arc<Mutex<HttpCache>>,
+    deduplicate: bool,
+}
+
+/// The status and the text of a response, shared by the identical requests in flight.
+type SharedResponse = Shared<Box<dyn Future<Item = (u16, String), Error = ClientError>>>;
+
+thread_local! {
+    /// The requests in flight, by URL, headers and body, when they are deduplicated.
+    static IN_FLIGHT: RefCell<HashMap<String, SharedResponse>> = RefCell::new(HashMap::new());
 }
 
 /// All the ways a request can go wrong.
 ///
 /// not exhaustive
-#[derive(Debug, Fail, PartialEq)]
+#[derive(Debug, Clone, Fail, PartialEq)]
 pub enum ClientError {
     /// The body couldn't be built
     #[fail(display = "Request body is not a valid string")]
@@ -43,6 +65,9 @@ pub enum ClientError {
     /// Response shape does not match the generated code
     #[fail(display = "Response shape error")]
     ResponseShape,
+    /// The server answered with a non-2xx status, without a GraphQL response
+    #[fail(display = "HTTP error: status {}", _0)]
+    Http(u16),
     /// Response could not be converted to text
     #[fail(display = "Response conversion to text failed (Response.text threw)")]
     ResponseText,
@@ -63,6 +88,13 @@ impl Client {
         Client {
             endpoint: endpoint.into(),
             headers: HashMap::new(),
+            rate_limiter: None,
+            metrics: None,
+            local_resolvers: None,
+            compress_requests: false,
+            get_queries: false,
+            http_cache: Arc::new(Mutex::new(HttpCache::default())),
+            deduplicate: false,
         }
     }
 
@@ -71,8 +103,72 @@ impl Client {
         self.headers.insert(name.into(), value.into());
     }
 
+    /// Limit the requests to `requests_per_second` on average, with bursts of up to `burst`
+    /// requests. The requests over the budget are delayed rather than failed, so loops over
+    /// generated queries, like pagination, can run at full speed without being throttled by the
+    /// endpoint. The slot of a request is reserved when [call] is called.
+    ///
+    /// Panics if `requests_per_second` is not positive.
+    pub fn set_rate_limit(&mut self, requests_per_second: f64, burst: u32) {
+        assert!(
+            requests_per_second > 0.0,
+            "The rate limit must be positive, got {} requests per second",
+            requests_per_second
+        );
+        self.rate_limiter = Some(Mutex::new(TokenBucket::new(
+            requests_per_second,
+            burst,
+            js_sys::Date::now(),
+        )));
+    }
+
+    /// Report the requests to the given [Metrics], for example to export them to Prometheus or
+    /// StatsD.
+    pub fn set_metrics<M>(&mut self, metrics: M)
+    where
+        M: Metrics + Send + Sync + 'static,
+    {
+        self.metrics = Some(Arc::new(metrics));
+    }
+
+    /// Compress the request bodies with gzip, sending them with a `Content-Encoding: gzip` header,
+    /// for the gateways that require it or handle large documents better that way. The compressed
+    /// responses need nothing: the browser decodes them transparently.
+    pub fn set_request_compression(&mut self, enabled: bool) {
+        self.compress_requests = enabled;
+    }
+
+    /// Send the `query` operations with `GET` requests, in the query string, rather than `POST`
+    /// requests. The mutations are still posted.
+    ///
+    /// The responses are cached as told by the `ETag` and `Cache-Control` headers of the server,
+    /// by operation and variables: a response is reused without a request until its `max-age`,
+    /// then revalidated with an `If-None-Match` request, and reused again when the server answers
+    /// `304 Not Modified`.
+    pub fn set_get_queries(&mut self, enabled: bool) {
+        self.get_queries = enabled;
+    }
+
+    /// Coalesce the identical requests, with the same operation and variables, sent while one of
+    /// them is in flight: a single request is sent, and its response is shared by all the calls.
+    /// This prevents the refetches of the same data by many components at once. The metrics and
+    /// the tracing spans only report the request that was sent.
+    pub fn set_deduplication(&mut self, enabled: bool) {
+        self.deduplicate = enabled;
+    }
+
+    /// Resolve the fields selected with the `@client` directive with the given resolvers, after
+    /// the response is received and before it is deserialized.
+    pub fn set_local_resolvers(&mut self, local_resolvers: crate::local::LocalResolvers) {
+        self.local_resolvers = Some(Arc::new(local_resolvers));
+    }
+
     /// Perform a query.
     ///
+    /// With the `tracing` feature, each call is recorded as a `graphql` span with the name and the
+    /// type of the operation, its duration in milliseconds and the number of errors in the
+    /// response.
+    ///
     // Lint disabled: We can pass by value because it's always an empty struct.
     #[allow(clippy::needless_pass_by_value)]
     pub fn call<Q: GraphQLQuery + 'static>(
@@ -80,37 +176,164 @@ impl Client {
         _query: Q,
         variables: Q::Variables,
     ) -> impl Future<Item = crate::Response<Q::ResponseData>, Error = ClientError> + 'static {
+        match &self.local_resolvers {
+            Some(local_resolvers) if !Q::CLIENT_FIELDS.is_empty() => {
+                let local_resolvers = local_resolvers.clone();
+                Either::A(
+                    self.send::<_, serde_json::Value>(Q::build_query(variables))
+                        .and_then(move |response| {
+                            local_resolvers
+                                .resolve_response(Q::CLIENT_FIELDS, response)
+                                .map_err(|_| ClientError::ResponseShape)
+                        }),
+                )
+            }
+            _ => Either::B(self.send(Q::build_query(variables))),
+        }
+    }
+
+    /// Sends the query body, see [call].
+    fn send<V: serde::Serialize + 'static, Data: DeserializeOwned + 'static>(
+        &self,
+        query_body: QueryBody<V>,
+    ) -> impl Future<Item = crate::Response<Data>, Error = ClientError> + 'static {
+        // The queries sent with `GET` requests are cached by URL.
+        let operation_type = operation_type(query_body.query, query_body.operation_name);
+        let query_url = if self.get_queries && operation_type == Some("query") {
+            match query_body.to_query_string() {
+                Ok(query_string) => Some(get_url(&self.endpoint, &query_string)),
+                Err(_) => {
+                    return Either::A(Either::A(
+                        Err::<crate::Response<Data>, _>(ClientError::Body).into_future(),
+                    ))
+                }
+            }
+        } else {
+            None
+        };
+        let cached = query_url.as_ref().and_then(|url| {
+            self.http_cache
+                .lock()
+                .expect("HTTP cache is poisoned")
+                .get(url)
+        });
+        if let Some(cached) = &cached {
+            if cached.fresh_until > js_sys::Date::now() {
+                return Either::A(Either::A(
+                    serde_json::from_str::<crate::Response<Data>>(&cached.body)
+                        .map_err(|_| ClientError::ResponseShape)
+                        .into_future(),
+                ));
+            }
+        }
+        let body = match serde_json::to_string(&query_body) {
+            Ok(body) => body,
+            Err(_) => return Either::A(Either::A(Err(ClientError::Body).into_future())),
+        };
+
+        // A request identical to one in flight shares its response.
+        let in_flight_key = if self.deduplicate {
+            let url = query_url.as_ref().unwrap_or(&self.endpoint);
+            Some(in_flight_key(url, &self.headers, &body))
+        } else {
+            None
+        };
+        if let Some(key) = &in_flight_key {
+            if let Some(in_flight) =
+                IN_FLIGHT.with(|in_flight| in_flight.borrow().get(key).cloned())
+            {
+                return Either::A(Either::B(
+                    in_flight
+                        .map(|response| (*response).clone())
+                        .map_err(|err| (*err).clone())
+                        .and_then(|(status, text)| parse_response(status, &text)),
+                ));
+            }
+        }
+        let http_cache = self.http_cache.clone();
+        let is_get = query_url.is_some();
+        let (request_url, response_url) = (query_url.clone(), query_url);
+
         // this can be removed when we convert to async/await
         let endpoint = self.endpoint.clone();
         let custom_headers = self.headers.clone();
+        let compress_requests = self.compress_requests && !is_get;
+        let delay = self
+            .rate_limiter
+            .as_ref()
+            .map(|rate_limiter| {
+                rate_limiter
+                    .lock()
+                    .expect("rate limiter is poisoned")
+                    .reserve(js_sys::Date::now())
+            })
+            .unwrap_or(0.0);
+        let operation_name = query_body.operation_name;
+        let started_metrics = self.metrics.clone();
+        // What was received, for the metrics.
+        let status = Rc::new(Cell::new(None));
+        let response_bytes = Rc::new(Cell::new(None));
+        let (received_status, received_bytes) = (status.clone(), response_bytes.clone());
 
-        web_sys::window()
+        let response = web_sys::window()
             .ok_or_else(|| ClientError::NoWindow)
             .into_future()
+            .and_then(move |window| wait(window, delay))
             .and_then(move |window| {
-                serde_json::to_string(&Q::build_query(variables))
-                    .map_err(|_| ClientError::Body)
-                    .map(move |body| (window, body))
+                // The `GET` requests are sent in the URL.
+                let body = if is_get {
+                    Vec::new()
+                } else if compress_requests {
+                    gzip(body.as_bytes()).map_err(|_| ClientError::Body)?
+                } else {
+                    body.into_bytes()
+                };
+                if let Some(metrics) = &started_metrics {
+                    metrics.request_started(operation_name, body.len());
+                }
+
+                Ok((window, body))
             })
             .and_then(move |(window, body)| {
                 let mut request_init = web_sys::RequestInit::new();
-                request_init
-                    .method("POST")
-                    .body(Some(&JsValue::from_str(&body)));
+                let url = match &request_url {
+                    Some(url) => {
+                        request_init.method("GET");
+                        url
+                    }
+                    None => {
+                        request_init
+                            .method("POST")
+                            .body(Some(&js_sys::Uint8Array::from(&body[..])));
+                        &endpoint
+                    }
+                };
 
-                web_sys::Request::new_with_str_and_init(&endpoint, &request_init)
+                web_sys::Request::new_with_str_and_init(url, &request_init)
                     .map_err(|_| ClientError::JsException)
                     .map(|request| (window, request))
                 // "Request constructor threw");
             })
             .and_then(move |(window, request)| {
                 let headers = request.headers();
-                headers
-                    .set("Content-Type", "application/json")
-                    .map_err(|_| ClientError::RequestError)?;
+                if !is_get {
+                    headers
+                        .set("Content-Type", "application/json")
+                        .map_err(|_| ClientError::RequestError)?;
+                }
                 headers
                     .set("Accept", "application/json")
                     .map_err(|_| ClientError::RequestError)?;
+                if compress_requests {
+                    headers
+                        .set("Content-Encoding", "gzip")
+                        .map_err(|_| ClientError::RequestError)?;
+                }
+                if let Some(etag) = cached.and_then(|cached| cached.etag) {
+                    headers
+                        .set("If-None-Match", &etag)
+                        .map_err(|_| ClientError::RequestError)?;
+                }
 
                 for (header_name, header_value) in custom_headers.iter() {
                     headers
@@ -130,26 +353,821 @@ impl Client {
                     .map_err(|_| ClientError::Cast)
             })
             .and_then(move |cast_response| {
-                cast_response.text().map_err(|_| ClientError::ResponseText)
+                let status = cast_response.status();
+                received_status.set(Some(status));
+                let headers = cast_response.headers();
+                let cache_headers = CacheHeaders {
+                    etag: headers.get("ETag").ok().and_then(|etag| etag),
+                    cache_control: headers
+                        .get("Cache-Control")
+                        .ok()
+                        .and_then(|cache_control| cache_control),
+                };
+                cast_response
+                    .text()
+                    .map(|text_promise| (status, cache_headers, text_promise))
+                    .map_err(|_| ClientError::ResponseText)
             })
-            .and_then(move |text_promise| {
-                JsFuture::from(text_promise).map_err(|_| ClientError::ResponseText)
+            .and_then(move |(status, cache_headers, text_promise)| {
+                JsFuture::from(text_promise)
+                    .map(move |text| (status, cache_headers, text))
+                    .map_err(|_| ClientError::ResponseText)
             })
-            .and_then(|text| {
+            .map(move |(status, cache_headers, text)| {
                 let response_text = text.as_string().unwrap_or_default();
+                received_bytes.set(Some(response_text.len()));
                 debug!("response text as string: {:?}", response_text);
-                serde_json::from_str(&response_text).map_err(|_| ClientError::ResponseShape)
-            })
+                match &response_url {
+                    Some(url) => http_cache.lock().expect("HTTP cache is poisoned").update(
+                        url,
+                        status,
+                        &cache_headers,
+                        response_text,
+                        js_sys::Date::now(),
+                    ),
+                    None => (status, response_text),
+                }
+            });
+
+        let response = match in_flight_key {
+            Some(key) => {
+                let removed_key = key.clone();
+                let response: Box<dyn Future<Item = _, Error = _>> =
+                    Box::new(response.then(move |result| {
+                        IN_FLIGHT.with(|in_flight| in_flight.borrow_mut().remove(&removed_key));
+                        result
+                    }));
+                let response = response.shared();
+                IN_FLIGHT.with(|in_flight| in_flight.borrow_mut().insert(key, response.clone()));
+
+                Either::A(
+                    response
+                        .map(|response| (*response).clone())
+                        .map_err(|err| (*err).clone()),
+                )
+            }
+            None => Either::B(response),
+        }
+        .and_then(|(status, text)| parse_response(status, &text));
+
+        let response = match self.metrics.clone() {
+            Some(metrics) => {
+                let started_at = js_sys::Date::now();
+
+                Either::A(response.then(move |result| {
+                    metrics.request_finished(
+                        operation_name,
+                        &RequestOutcome {
+                            status: status.get(),
+                            response_bytes: response_bytes.get(),
+                            error_count: result
+                                .as_ref()
+                                .ok()
+                                .and_then(|response| response.errors.as_ref())
+                                .map(Vec::len)
+                                .unwrap_or(0),
+                            error: result.as_ref().err(),
+                            duration_ms: js_sys::Date::now() - started_at,
+                        },
+                    );
+                    result
+                }))
+            }
+            None => Either::B(response),
+        };
+
+        #[cfg(feature = "tracing")]
+        let response = {
+            let span = tracing::info_span!(
+                "graphql",
+                operation_name,
+                operation_type,
+                duration_ms = tracing::field::Empty,
+                error_count = tracing::field::Empty,
+            );
+            let outcome_span = span.clone();
+            let started_at = js_sys::Date::now();
+
+            response
+                .then(move |result| {
+                    record_outcome(&outcome_span, started_at, &result);
+                    result
+                })
+                .instrument(span)
+        };
+
+        Either::B(response)
+    }
+
+    /// Perform a query, returning its data, or an error telling the class of the failure: see
+    /// [OperationError]. Unlike [call], a response with GraphQL errors is an error.
+    ///
+    /// [OperationError]: crate::OperationError
+    pub fn execute<Q: GraphQLQuery + 'static>(
+        &self,
+        query: Q,
+        variables: Q::Variables,
+    ) -> impl Future<Item = Q::ResponseData, Error = crate::OperationError> + 'static {
+        self.call(query, variables).then(|result| match result {
+            Ok(response) => response.into_result().map_err(crate::OperationError::from),
+            Err(err) => Err(crate::OperationError::from(err)),
+        })
+    }
+
+    /// Fetch all the pages of the connection selected by a [Paginated] query, yielding its nodes.
+    ///
+    /// The first page is fetched with the given variables, and each following page with the
+    /// cursor variable moved as told by [Paginated::CURSOR]: by default, the `after` variable is
+    /// set to the end cursor of the previous page, until the page info tells there is no next
+    /// page. The stream ends after the first error.
+    ///
+    /// [Paginated]: crate::Paginated
+    /// [Paginated::CURSOR]: crate::Paginated::CURSOR
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn paginate<'a, Q: Paginated + 'static>(
+        &'a self,
+        _query: Q,
+        variables: Q::Variables,
+    ) -> impl Stream<Item = Q::Node, Error = crate::OperationError> + 'a {
+        let QueryBody {
+            variables,
+            query,
+            operation_name,
+        } = Q::build_query(variables);
+        let variables = match serde_json::to_value(&variables) {
+            Ok(variables) => variables,
+            Err(_) => {
+                return Either::A(futures::stream::once::<Q::Node, _>(Err(
+                    ClientError::Body.into()
+                )))
+            }
+        };
+
+        let pages = futures::stream::unfold(Some(variables), move |variables| {
+            let variables = variables?;
+            let query_body = QueryBody {
+                variables: variables.clone(),
+                query,
+                operation_name,
+            };
+
+            Some(self.send(query_body).then(move |result| {
+                let data: Q::ResponseData = result?.into_result()?;
+                let (nodes, page_info) = Q::into_page(data).unwrap_or_default();
+                let next_variables = next_variables(Q::CURSOR, variables, nodes.len(), page_info);
+
+                Ok::<_, crate::OperationError>((nodes, next_variables))
+            }))
+        });
+
+        Either::B(pages.map(futures::stream::iter_ok).flatten())
+    }
+
+    /// Subscribe to a `@live` query, yielding the response every time the server pushes a new
+    /// revision of the result, as GraphQL Yoga and Hasura do over server-sent events.
+    ///
+    /// The query is sent in the query string of a `GET` request by an `EventSource`, which cannot
+    /// send the headers added with [add_header]: authenticate with cookies instead. The revisions
+    /// have to be complete results, not JSON patches. The connection is closed when the stream is
+    /// dropped, and the stream ends after the first error.
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn live<Q: GraphQLQuery + 'static>(
+        &self,
+        _query: Q,
+        variables: Q::Variables,
+    ) -> LiveQuery<Q::ResponseData> {
+        let (sender, revisions) = mpsc::unbounded();
+        let mut live_query = LiveQuery {
+            revisions,
+            finished: false,
+            event_source: None,
+            _on_message: None,
+            _on_error: None,
+        };
+
+        let query_string = match Q::build_query(variables).to_query_string() {
+            Ok(query_string) => query_string,
+            Err(_) => {
+                let _ = sender.unbounded_send(Err(ClientError::Body));
+                return live_query;
+            }
+        };
+        let event_source = match web_sys::EventSource::new(&get_url(&self.endpoint, &query_string))
+        {
+            Ok(event_source) => event_source,
+            Err(_) => {
+                let _ = sender.unbounded_send(Err(ClientError::RequestError));
+                return live_query;
+            }
+        };
+
+        let local_resolvers = self
+            .local_resolvers
+            .clone()
+            .filter(|_| !Q::CLIENT_FIELDS.is_empty());
+        let revision_sender = sender.clone();
+        let on_message = Closure::wrap(Box::new(move |event: web_sys::MessageEvent| {
+            let text = event.data().as_string().unwrap_or_default();
+            debug!("live query revision: {:?}", text);
+            let revision = match &local_resolvers {
+                Some(local_resolvers) => serde_json::from_str(&text).and_then(|response| {
+                    local_resolvers.resolve_response(Q::CLIENT_FIELDS, response)
+                }),
+                None => serde_json::from_str(&text),
+            };
+            let _ =
+                revision_sender.unbounded_send(revision.map_err(|_| ClientError::ResponseShape));
+        }) as Box<dyn FnMut(web_sys::MessageEvent)>);
+
+        // The event source reconnects by itself, unless the error is fatal.
+        let errored_source = event_source.clone();
+        let on_error = Closure::wrap(Box::new(move |_: JsValue| {
+            if errored_source.ready_state() == web_sys::EventSource::CLOSED {
+                let _ = sender.unbounded_send(Err(ClientError::Network(
+                    "The live query connection was closed".into(),
+                )));
+            }
+        }) as Box<dyn FnMut(JsValue)>);
+
+        event_source.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+        event_source.set_onerror(Some(on_error.as_ref().unchecked_ref()));
+        live_query.event_source = Some(event_source);
+        live_query._on_message = Some(on_message);
+        live_query._on_error = Some(on_error);
+
+        live_query
+    }
+}
+
+/// The revisions of the result of a `@live` query, see [Client::live].
+pub struct LiveQuery<Data> {
+    revisions: mpsc::UnboundedReceiver<Result<crate::Response<Data>, ClientError>>,
+    finished: bool,
+    event_source: Option<web_sys::EventSource>,
+    // The callbacks of the event source live as long as the stream.
+    _on_message: Option<Closure<dyn FnMut(web_sys::MessageEvent)>>,
+    _on_error: Option<Closure<dyn FnMut(JsValue)>>,
+}
+
+impl<Data> Stream for LiveQuery<Data> {
+    type Item = crate::Response<Data>;
+    type Error = ClientError;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        if self.finished {
+            return Ok(Async::Ready(None));
+        }
+
+        match self.revisions.poll() {
+            Ok(Async::Ready(Some(Ok(response)))) => Ok(Async::Ready(Some(response))),
+            Ok(Async::Ready(Some(Err(err)))) => {
+                self.finished = true;
+                Err(err)
+            }
+            Ok(Async::Ready(None)) | Err(()) => Ok(Async::Ready(None)),
+            Ok(Async::NotReady) => Ok(Async::NotReady),
+        }
+    }
+}
+
+impl<Data> Drop for LiveQuery<Data> {
+    fn drop(&mut self) {
+        if let Some(event_source) = &self.event_source {
+            event_source.close();
+        }
+    }
+}
+
+/// Deserializes the text of a response with the given status.
+fn parse_response<Data: DeserializeOwned>(
+    status: u16,
+    text: &str,
+) -> Result<crate::Response<Data>, ClientError> {
+    serde_json::from_str(text).map_err(|_| {
+        // A GraphQL response is returned whatever the status, but an error page is better
+        // reported with its status.
+        if status < 200 || status >= 300 {
+            ClientError::Http(status)
+        } else {
+            ClientError::ResponseShape
+        }
+    })
+}
+
+/// The key of a request among the requests in flight: the requests with the same URL, headers and
+/// body share their response.
+fn in_flight_key(url: &str, headers: &HashMap<String, String>, body: &str) -> String {
+    let mut headers: Vec<_> = headers.iter().collect();
+    headers.sort();
+    format!("{}\n{:?}\n{}", url, headers, body)
+}
+
+/// Compresses a request body with gzip.
+fn gzip(body: &[u8]) -> std::io::Result<Vec<u8>> {
+    use std::io::Write;
+
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(body)?;
+    encoder.finish()
+}
+
+/// The type of the operation with the given name in the document: `query`, `mutation` or
+/// `subscription`.
+fn operation_type(document: &str, operation_name: &str) -> Option<&'static str> {
+    let words = document
+        .lines()
+        .map(|line| line.splitn(2, '#').next().unwrap_or(""))
+        .flat_map(|line| line.split(|c: char| !(c.is_alphanumeric() || c == '_')))
+        .filter(|word| !word.is_empty());
+    let mut previous = "";
+
+    for word in words {
+        if word == operation_name {
+            match previous {
+                "query" => return Some("query"),
+                "mutation" => return Some("mutation"),
+                "subscription" => return Some("subscription"),
+                _ => (),
+            }
+        }
+        previous = word;
+    }
+
+    None
+}
+
+/// The headers of a response telling how to cache it.
+#[derive(Debug, Default)]
+struct CacheHeaders {
+    etag: Option<String>,
+    cache_control: Option<String>,
+}
+
+/// A response to a `GET` query, with its validator.
+#[derive(Debug, Clone)]
+struct CachedResponse {
+    etag: Option<String>,
+    /// Until when the response can be reused without revalidation, in milliseconds.
+    fresh_until: f64,
+    body: String,
+}
+
+/// The responses to the `GET` queries, by URL, so by operation and variables.
+#[derive(Debug, Default)]
+struct HttpCache {
+    responses: HashMap<String, CachedResponse>,
+}
+
+impl HttpCache {
+    fn get(&self, url: &str) -> Option<CachedResponse> {
+        self.responses.get(url).cloned()
+    }
+
+    /// Stores the response received at `now` as told by its headers, and returns its status and
+    /// body: the stored ones when the server answered `304 Not Modified`.
+    fn update(
+        &mut self,
+        url: &str,
+        status: u16,
+        headers: &CacheHeaders,
+        body: String,
+        now: f64,
+    ) -> (u16, String) {
+        let mut no_store = false;
+        let mut max_age = 0.0;
+        for directive in headers
+            .cache_control
+            .as_ref()
+            .map(String::as_str)
+            .unwrap_or("")
+            .split(',')
+            .map(|directive| directive.trim().to_ascii_lowercase())
+        {
+            if directive == "no-store" {
+                no_store = true;
+            } else if directive.starts_with("max-age=") {
+                max_age = directive["max-age=".len()..].parse().unwrap_or(0.0);
+            }
+        }
+
+        match status {
+            304 => match self.responses.get_mut(url) {
+                Some(cached) => {
+                    cached.fresh_until = now + max_age * 1000.0;
+                    if headers.etag.is_some() {
+                        cached.etag = headers.etag.clone();
+                    }
+                    (200, cached.body.clone())
+                }
+                None => (status, body),
+            },
+            200..=299 if no_store || (headers.etag.is_none() && max_age <= 0.0) => {
+                self.responses.remove(url);
+                (status, body)
+            }
+            200..=299 => {
+                self.responses.insert(
+                    url.to_owned(),
+                    CachedResponse {
+                        etag: headers.etag.clone(),
+                        fresh_until: now + max_age * 1000.0,
+                        body: body.clone(),
+                    },
+                );
+                (status, body)
+            }
+            _ => (status, body),
+        }
+    }
+}
+
+/// The URL of the `GET` request for a query, with the given query string.
+fn get_url(endpoint: &str, query_string: &str) -> String {
+    let separator = if endpoint.contains('?') { '&' } else { '?' };
+    format!("{}{}{}", endpoint, separator, query_string)
+}
+
+impl From<ClientError> for crate::OperationError {
+    fn from(err: ClientError) -> crate::OperationError {
+        match err {
+            ClientError::Http(status) => crate::OperationError::Http(status),
+            ClientError::ResponseShape => crate::OperationError::Deserialization(err.to_string()),
+            err => crate::OperationError::Transport(err.to_string()),
+        }
+    }
+}
+
+/// The variables for the page after the one with `node_count` nodes and the page info, if any.
+fn next_variables(
+    cursor: PaginationCursor,
+    mut variables: serde_json::Value,
+    node_count: usize,
+    page_info: PageInfo,
+) -> Option<serde_json::Value> {
+    match cursor {
+        PaginationCursor::EndCursor(name) => {
+            match (page_info.has_next_page, page_info.end_cursor) {
+                (true, Some(end_cursor)) => {
+                    variables[name] = serde_json::Value::String(end_cursor);
+                    Some(variables)
+                }
+                _ => None,
+            }
+        }
+        PaginationCursor::Offset(_) if node_count == 0 => None,
+        PaginationCursor::Offset(name) => {
+            let offset = variables[name].as_u64().unwrap_or(0) + node_count as u64;
+            variables[name] = serde_json::Value::from(offset);
+            Some(variables)
+        }
+    }
+}
+
+/// Callbacks receiving the requests of a [Client], to wire metrics exporters like Prometheus or
+/// StatsD. The methods do nothing by default.
+pub trait Metrics {
+    /// A request for the operation is about to be sent, with a body of `request_bytes` bytes.
+    fn request_started(&self, operation_name: &str, request_bytes: usize) {
+        let _ = (operation_name, request_bytes);
+    }
+
+    /// A request for the operation has completed, successfully or not.
+    fn request_finished(&self, operation_name: &str, outcome: &RequestOutcome<'_>) {
+        let _ = (operation_name, outcome);
+    }
+}
+
+/// The outcome of a request, reported to [Metrics::request_finished].
+#[derive(Debug)]
+pub struct RequestOutcome<'a> {
+    /// The HTTP status of the response, if one was received.
+    pub status: Option<u16>,
+    /// The size of the response body in bytes, if one was received.
+    pub response_bytes: Option<usize>,
+    /// The number of GraphQL errors in the response.
+    pub error_count: usize,
+    /// Why the request failed, if it did. A response with GraphQL errors is not a failure.
+    pub error: Option<&'a ClientError>,
+    /// The time from the call to the outcome, in milliseconds, including any delay from the rate
+    /// limit.
+    pub duration_ms: f64,
+}
+
+/// Records the duration and the outcome of an operation on its span.
+#[cfg(feature = "tracing")]
+fn record_outcome<Data>(
+    span: &tracing::Span,
+    started_at: f64,
+    result: &Result<crate::Response<Data>, ClientError>,
+) {
+    span.record("duration_ms", &(js_sys::Date::now() - started_at));
+
+    match result {
+        Ok(response) => {
+            let error_count = response.errors.as_ref().map(Vec::len).unwrap_or(0) as u64;
+            span.record("error_count", &error_count);
+            tracing::debug!(error_count, "GraphQL response received");
+        }
+        Err(err) => tracing::warn!(error = %err, "GraphQL request failed"),
+    }
+}
+
+/// Polls a future inside a tracing span, like `tracing::Instrument` does for the futures of the
+/// standard library.
+#[cfg(feature = "tracing")]
+trait Instrument: Future + Sized {
+    fn instrument(self, span: tracing::Span) -> Instrumented<Self> {
+        Instrumented { future: self, span }
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl<F: Future> Instrument for F {}
+
+/// A future entering its span on every poll.
+#[cfg(feature = "tracing")]
+struct Instrumented<F> {
+    future: F,
+    span: tracing::Span,
+}
+
+#[cfg(feature = "tracing")]
+impl<F: Future> Future for Instrumented<F> {
+    type Item = F::Item;
+    type Error = F::Error;
+
+    fn poll(&mut self) -> Poll<F::Item, F::Error> {
+        let _entered = self.span.enter();
+        self.future.poll()
+    }
+}
+
+/// Resolves to the window after `milliseconds`, using `setTimeout` if there is a delay.
+fn wait(
+    window: web_sys::Window,
+    milliseconds: f64,
+) -> impl Future<Item = web_sys::Window, Error = ClientError> {
+    if milliseconds <= 0.0 {
+        return Either::A(Ok(window).into_future());
+    }
+
+    let timeout = js_sys::Promise::new(&mut |resolve, reject| {
+        if let Err(err) = window.set_timeout_with_callback_and_timeout_and_arguments_0(
+            &resolve,
+            milliseconds.ceil() as i32,
+        ) {
+            let _ = reject.call1(&JsValue::NULL, &err);
+        }
+    });
+
+    Either::B(
+        JsFuture::from(timeout)
+            .map(move |_| window)
+            .map_err(|_| ClientError::JsException),
+    )
+}
+
+/// A token bucket: `burst` requests can be sent at once, then the bucket refills at `rate`.
+#[derive(Debug)]
+struct TokenBucket {
+    /// The refill rate, in requests per millisecond.
+    rate: f64,
+    /// The capacity of the bucket.
+    burst: f64,
+    /// The available tokens. It is negative when requests are waiting for tokens.
+    tokens: f64,
+    /// When the bucket was last refilled, in milliseconds.
+    refilled_at: f64,
+}
+
+impl TokenBucket {
+    fn new(requests_per_second: f64, burst: u32, now: f64) -> TokenBucket {
+        let burst = f64::from(burst.max(1));
+
+        TokenBucket {
+            rate: requests_per_second / 1000.0,
+            burst,
+            tokens: burst,
+            refilled_at: now,
+        }
+    }
+
+    /// Takes a token for a request made at `now`, returning how many milliseconds the request has
+    /// to wait for it.
+    fn reserve(&mut self, now: f64) -> f64 {
+        let elapsed = (now - self.refilled_at).max(0.0);
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.burst);
+        self.refilled_at = now;
+        self.tokens -= 1.0;
+
+        if self.tokens >= 0.0 {
+            0.0
+        } else {
+            -self.tokens / self.rate
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use serde_json::json;
 
     #[test]
     fn client_new() {
         Client::new("https://example.com/graphql");
         Client::new("/graphql");
     }
+
+    #[test]
+    fn client_set_metrics() {
+        struct NoMetrics;
+
+        impl Metrics for NoMetrics {}
+
+        let mut client = Client::new("/graphql");
+        client.set_metrics(NoMetrics);
+    }
+
+    #[test]
+    fn client_errors_are_classified() {
+        assert_eq!(
+            crate::OperationError::from(ClientError::Http(502)),
+            crate::OperationError::Http(502)
+        );
+        assert_eq!(
+            crate::OperationError::from(ClientError::ResponseShape),
+            crate::OperationError::Deserialization("Response shape error".to_owned())
+        );
+        assert_eq!(
+            crate::OperationError::from(ClientError::Network("offline".to_owned())),
+            crate::OperationError::Transport("Network error".to_owned())
+        );
+    }
+
+    #[test]
+    fn request_bodies_are_gzipped() {
+        use std::io::Read;
+
+        let body = r#"{"query":"query Hello { hello }","operationName":"Hello"}"#;
+        let compressed = gzip(body.as_bytes()).unwrap();
+
+        let mut decompressed = String::new();
+        flate2::read::GzDecoder::new(&compressed[..])
+            .read_to_string(&mut decompressed)
+            .unwrap();
+        assert_eq!(decompressed, body);
+    }
+
+    #[test]
+    fn queries_are_told_from_mutations() {
+        let document = "
+            # query Mutated
+            mutation Mutated($id: ID!) { delete(id: $id) }
+            query Listed { items { id } }
+            subscription Watched { items { id } }
+        ";
+
+        assert_eq!(operation_type(document, "Listed"), Some("query"));
+        assert_eq!(operation_type(document, "Mutated"), Some("mutation"));
+        assert_eq!(operation_type(document, "Watched"), Some("subscription"));
+        assert_eq!(operation_type(document, "Unknown"), None);
+    }
+
+    #[test]
+    fn http_cache_revalidates_with_etags() {
+        let mut cache = HttpCache::default();
+        let headers = CacheHeaders {
+            etag: Some("\"v1\"".to_owned()),
+            cache_control: Some("public, max-age=60".to_owned()),
+        };
+
+        let received = cache.update("/graphql?q", 200, &headers, "{}".to_owned(), 0.0);
+        assert_eq!(received, (200, "{}".to_owned()));
+        let cached = cache.get("/graphql?q").unwrap();
+        assert_eq!(cached.etag.as_ref().map(String::as_str), Some("\"v1\""));
+        assert_eq!(cached.fresh_until, 60_000.0);
+
+        let not_modified = CacheHeaders::default();
+        let revalidated = cache.update("/graphql?q", 304, &not_modified, String::new(), 90_000.0);
+        assert_eq!(revalidated, (200, "{}".to_owned()));
+        assert_eq!(cache.get("/graphql?q").unwrap().fresh_until, 90_000.0);
+    }
+
+    #[test]
+    fn http_cache_does_not_store_uncacheable_responses() {
+        let mut cache = HttpCache::default();
+        let headers = CacheHeaders {
+            etag: Some("\"v1\"".to_owned()),
+            cache_control: Some("no-store".to_owned()),
+        };
+
+        cache.update("/graphql?q", 200, &headers, "{}".to_owned(), 0.0);
+        cache.update(
+            "/graphql?r",
+            200,
+            &CacheHeaders::default(),
+            "{}".to_owned(),
+            0.0,
+        );
+
+        assert!(cache.get("/graphql?q").is_none());
+        assert!(cache.get("/graphql?r").is_none());
+    }
+
+    #[test]
+    fn get_urls_keep_the_query_string_of_the_endpoint() {
+        assert_eq!(get_url("/graphql", "query=q"), "/graphql?query=q");
+        assert_eq!(
+            get_url("/graphql?tenant=a", "query=q"),
+            "/graphql?tenant=a&query=q"
+        );
+    }
+
+    #[test]
+    fn token_bucket_allows_bursts() {
+        let mut bucket = TokenBucket::new(2.0, 3, 0.0);
+
+        assert_eq!(bucket.reserve(0.0), 0.0);
+        assert_eq!(bucket.reserve(0.0), 0.0);
+        assert_eq!(bucket.reserve(0.0), 0.0);
+        // Two requests per second: one token every 500ms.
+        assert_eq!(bucket.reserve(0.0), 500.0);
+        assert_eq!(bucket.reserve(0.0), 1000.0);
+    }
+
+    #[test]
+    fn token_bucket_refills() {
+        let mut bucket = TokenBucket::new(10.0, 1, 0.0);
+
+        assert_eq!(bucket.reserve(0.0), 0.0);
+        assert_eq!(bucket.reserve(50.0), 50.0);
+        // The reserved token was paid for at 100ms.
+        assert_eq!(bucket.reserve(150.0), 50.0);
+        // The bucket does not fill over its capacity while idle.
+        assert_eq!(bucket.reserve(10_000.0), 0.0);
+        assert_eq!(bucket.reserve(10_000.0), 100.0);
+    }
+
+    #[test]
+    fn next_variables_follow_the_end_cursor() {
+        let cursor = PaginationCursor::EndCursor("after");
+        let page_info = PageInfo {
+            has_next_page: true,
+            end_cursor: Some("b".to_owned()),
+            ..PageInfo::default()
+        };
+
+        assert_eq!(
+            next_variables(cursor, json!({ "first": 2 }), 2, page_info.clone()),
+            Some(json!({ "first": 2, "after": "b" }))
+        );
+        let last_page = PageInfo {
+            has_next_page: false,
+            ..page_info
+        };
+        assert_eq!(next_variables(cursor, json!({}), 2, last_page), None);
+    }
+
+    #[test]
+    fn next_variables_increase_the_offset() {
+        let cursor = PaginationCursor::Offset("offset");
+
+        assert_eq!(
+            next_variables(cursor, json!({ "limit": 10 }), 10, PageInfo::default()),
+            Some(json!({ "limit": 10, "offset": 10 }))
+        );
+        assert_eq!(
+            next_variables(cursor, json!({ "offset": 10 }), 3, PageInfo::default()),
+            Some(json!({ "offset": 13 }))
+        );
+        assert_eq!(
+            next_variables(cursor, json!({ "offset": 13 }), 0, PageInfo::default()),
+            None
+        );
+    }
+
+    #[test]
+    fn identical_requests_have_the_same_in_flight_key() {
+        let mut headers = HashMap::new();
+        headers.insert("Authorization".to_owned(), "Bearer token".to_owned());
+        headers.insert("X-Client".to_owned(), "web".to_owned());
+        let mut same_headers = HashMap::new();
+        same_headers.insert("X-Client".to_owned(), "web".to_owned());
+        same_headers.insert("Authorization".to_owned(), "Bearer token".to_owned());
+        let body = r#"{"variables":{"id":1},"query":"query Q { a }","operationName":"Q"}"#;
+
+        assert_eq!(
+            in_flight_key("/graphql", &headers, body),
+            in_flight_key("/graphql", &same_headers, body)
+        );
+        assert_ne!(
+            in_flight_key("/graphql", &headers, body),
+            in_flight_key("/graphql", &headers, &body.replace("1", "2"))
+        );
+        assert_ne!(
+            in_flight_key("/graphql", &headers, body),
+            in_flight_key("/graphql", &HashMap::new(), body)
+        );
+    }
 }
@@ -0,0 +1,65 @@
+use graphql_client::GraphQLQuery;
+use serde_json::json;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    query_path = "tests/operation_selection/queries.graphql",
+    schema_path = "tests/operation_selection/schema.graphql",
+    response_derives = "Debug,PartialEq",
+    all_operations = "true"
+)]
+pub struct Queries;
+
+#[test]
+fn all_operations_are_generated() {
+    assert_eq!(queries::OPERATION_NAMES, &["Heights", "Echo"]);
+
+    let response: queries::echo::ResponseData =
+        serde_json::from_value(json!({ "echo": "tiramisù" })).unwrap();
+    assert_eq!(response.echo.as_ref().map(String::as_str), Some("tiramisù"));
+
+    let body = queries::Heights::build_query(queries::heights::Variables {
+        building_id: "12".to_string(),
+        mountain_name: None,
+    });
+    assert_eq!(body.operation_name, "Heights");
+    assert_eq!(body.query, queries::QUERY);
+}
+
+#[test]
+fn operations_are_selected_by_name() {
+    let body = queries::build_query("Echo", json!({ "msg": "hi" })).unwrap();
+
+    assert_eq!(
+        serde_json::to_value(&body).unwrap(),
+        json!({
+            "variables": { "msg": "hi" },
+            "query": queries::QUERY,
+            "operationName": "Echo",
+        })
+    );
+    assert!(queries::build_query("Unknown", ()).is_none());
+}
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "tests/operation_selection/schema.graphql",
+    query_string = "query Echo($msg: String) { echo(msg: $msg) } query Shout($msg: String) { echo(msg: $msg) }",
+    all_operations = "true",
+    share_variables = "true"
+)]
+pub struct SharedVariables;
+
+fn echo_variables(msg: &str) -> shared_variables::echo::Variables {
+    shared_variables::echo::Variables {
+        msg: Some(msg.to_string()),
+    }
+}
+
+#[test]
+fn identical_variables_are_shared() {
+    let body = shared_variables::Shout::build_query(echo_variables("hi"));
+
+    assert_eq!(body.operation_name, "Shout");
+    assert_eq!(body.variables.msg.as_ref().map(String::as_str), Some("hi"));
+}
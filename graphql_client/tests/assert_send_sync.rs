@@ -0,0 +1,23 @@
+use graphql_client::*;
+use serde_json::json;
+
+// The derive fails to compile if the generated types are not `Send + Sync + 'static`.
+#[derive(GraphQLQuery)]
+#[graphql(
+    query_path = "tests/relay/query.graphql",
+    schema_path = "tests/relay/schema.graphql",
+    assert_send_sync = "true"
+)]
+pub struct IssuesQuery;
+
+#[test]
+fn response_data_can_be_sent_to_another_thread() {
+    let response: issues_query::ResponseData =
+        serde_json::from_value(json!({ "repository": null })).unwrap();
+
+    let repository = std::thread::spawn(move || response.repository)
+        .join()
+        .unwrap();
+
+    assert!(repository.is_none());
+}
@@ -0,0 +1,76 @@
+use graphql_client::*;
+use serde_json::json;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_string = "
+        schema { query: Query }
+        type Item { id: ID!, name: String!, isInCart: Boolean! }
+        type Query { items: [Item!]!, cartSize: Int! }
+    ",
+    query_string = "query Items { items { id name isInCart @client } cartSize @client }",
+    response_derives = "Debug, PartialEq"
+)]
+pub struct Items;
+
+#[test]
+fn client_fields_are_not_sent_to_the_server() {
+    assert!(!items::QUERY.contains("isInCart"));
+    assert!(!items::QUERY.contains("cartSize"));
+    assert!(items::QUERY.contains("name"));
+    assert!(!items::normalized_query().contains("@client"));
+}
+
+#[test]
+fn client_fields_are_optional_in_the_response() {
+    let response = json!({ "items": [{ "id": "1", "name": "Tea" }] });
+
+    let mut data: items::ResponseData = serde_json::from_value(response).unwrap();
+
+    assert_eq!(data.items[0].is_in_cart, None);
+    assert_eq!(data.cart_size, None);
+
+    // They are set locally.
+    data.items[0].is_in_cart = Some(true);
+    data.cart_size = Some(1);
+}
+
+#[test]
+fn client_fields_are_listed_with_their_path() {
+    assert_eq!(
+        <Items as GraphQLQuery>::CLIENT_FIELDS,
+        &[
+            local::ClientField {
+                path: "items.isInCart",
+                type_name: "Item",
+                field_name: "isInCart",
+            },
+            local::ClientField {
+                path: "cartSize",
+                type_name: "Query",
+                field_name: "cartSize",
+            },
+        ][..]
+    );
+}
+
+#[test]
+fn client_fields_are_resolved_locally() {
+    let mut resolvers = local::LocalResolvers::new();
+    resolvers
+        .register("Item", "isInCart", |item| item["id"] == "2")
+        .register("Query", "cartSize", |_| 1);
+    let response = serde_json::from_value(json!({
+        "data": { "items": [{ "id": "1", "name": "Tea" }, { "id": "2", "name": "Milk" }] }
+    }))
+    .unwrap();
+
+    let response: Response<items::ResponseData> = resolvers
+        .resolve_response(items::CLIENT_FIELDS, response)
+        .unwrap();
+
+    let data = response.data.unwrap();
+    assert_eq!(data.items[0].is_in_cart, Some(false));
+    assert_eq!(data.items[1].is_in_cart, Some(true));
+    assert_eq!(data.cart_size, Some(1));
+}
@@ -0,0 +1,40 @@
+use graphql_client::*;
+use serde_json::json;
+use std::collections::VecDeque;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    query_path = "tests/relay/query.graphql",
+    schema_path = "tests/relay/schema.graphql",
+    collection_types = "IssueEdge = std::collections::VecDeque"
+)]
+pub struct IssuesQuery;
+
+#[test]
+fn lists_use_the_configured_collection_types() {
+    let response: issues_query::ResponseData = serde_json::from_value(json!({
+        "repository": {
+            "issues": {
+                "edges": [null, { "cursor": "b", "node": { "title": "Collections" } }],
+                "pageInfo": {
+                    "hasNextPage": false,
+                    "hasPreviousPage": false,
+                    "startCursor": null,
+                    "endCursor": "b",
+                },
+            },
+        },
+    }))
+    .unwrap();
+    let issues = &response.repository.as_ref().unwrap().issues;
+
+    let edges: &VecDeque<_> = issues.edges.as_ref().unwrap();
+    assert_eq!(edges.len(), 2);
+    assert_eq!(
+        issues
+            .iter_nodes()
+            .map(|issue| issue.title.as_str())
+            .collect::<Vec<_>>(),
+        vec!["Collections"]
+    );
+}
@@ -24,6 +24,15 @@ pub struct DenyDeprecation;
 )]
 pub struct WarnDeprecation;
 
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "tests/deprecation/schema.graphql",
+    query_path = "tests/deprecation/query.graphql",
+    deprecated = "deny",
+    allowed_deprecations = "TestUser.deprecatedWithReason"
+)]
+pub struct DenyWithAllowedDeprecation;
+
 #[test]
 fn deprecation_allow() {
     // Make any deprecations be a compile error.
@@ -67,3 +76,19 @@ fn deprecation_warn() {
         }),
     };
 }
+
+#[test]
+fn deprecation_deny_with_allowed_deprecations() {
+    // The allowed field is generated without being marked as deprecated, the other one is left
+    // out as with `deny`.
+    #![deny(deprecated)]
+    let _ = deny_with_allowed_deprecation::ResponseData {
+        current_user: Some(
+            deny_with_allowed_deprecation::DenyWithAllowedDeprecationCurrentUser {
+                id: Some("abcd".to_owned()),
+                name: Some("Angela Merkel".to_owned()),
+                deprecated_with_reason: Some("foo".to_owned()),
+            },
+        ),
+    };
+}
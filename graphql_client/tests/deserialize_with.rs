@@ -0,0 +1,63 @@
+use graphql_client::*;
+use serde::{Deserialize, Deserializer};
+use serde_json::json;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_string = "
+        schema { query: Query }
+        type User { name: String!, age: Int, score: Int! }
+        type Query { user: User! }
+    ",
+    query_string = r#"query UserAge {
+        user {
+            name
+            age @rust(deserialize_with: "crate::number_from_string")
+            score @rust(deserialize_with: "crate::required_number_from_string")
+        }
+    }"#,
+    response_derives = "Debug, PartialEq"
+)]
+pub struct UserAge;
+
+// The server sends these numbers as strings.
+pub fn number_from_string<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<i64>, D::Error> {
+    Option::<String>::deserialize(deserializer)?
+        .map(|age| age.parse().map_err(serde::de::Error::custom))
+        .transpose()
+}
+
+pub fn required_number_from_string<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<i64, D::Error> {
+    String::deserialize(deserializer)?
+        .parse()
+        .map_err(serde::de::Error::custom)
+}
+
+#[test]
+fn fields_are_deserialized_with_the_functions_of_their_directive() {
+    let response = json!({ "user": { "name": "Ada", "age": "36", "score": "12" } });
+
+    let data: user_age::ResponseData = serde_json::from_value(response).unwrap();
+
+    assert_eq!(data.user.age, Some(36));
+    assert_eq!(data.user.score, 12);
+}
+
+#[test]
+fn missing_optional_fields_are_none() {
+    let response = json!({ "user": { "name": "Ada", "score": "12" } });
+
+    let data: user_age::ResponseData = serde_json::from_value(response).unwrap();
+
+    assert_eq!(data.user.age, None);
+}
+
+#[test]
+fn the_directive_is_not_sent_to_the_server() {
+    assert!(!user_age::QUERY.contains("@rust"));
+    assert!(!user_age::normalized_query().contains("@rust"));
+}
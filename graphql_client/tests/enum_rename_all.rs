@@ -0,0 +1,67 @@
+use graphql_client::*;
+use serde_json::json;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    query_path = "tests/enum_rename_all/query.graphql",
+    schema_path = "tests/enum_rename_all/schema.graphql",
+    response_derives = "Debug",
+    enum_rename_all = "true"
+)]
+pub struct EpisodesQuery;
+
+#[test]
+fn enum_rename_all_deserialization() {
+    use episodes_query::Episode;
+
+    let response: episodes_query::ResponseData = serde_json::from_value(json!({
+        "episodes": ["NEW_HOPE", "EMPIRE_STRIKES_BACK", "returnOfTheJedi", "PHANTOM_MENACE"],
+    }))
+    .unwrap();
+
+    assert_eq!(
+        response.episodes,
+        vec![
+            Episode::NewHope,
+            Episode::EmpireStrikesBack,
+            Episode::ReturnOfTheJedi,
+            Episode::Other,
+        ]
+    );
+}
+
+#[test]
+fn enum_rename_all_serialization() {
+    use episodes_query::Episode;
+
+    assert_eq!(
+        serde_json::to_value(&[Episode::NewHope, Episode::ReturnOfTheJedi]).unwrap(),
+        json!(["NEW_HOPE", "returnOfTheJedi"])
+    );
+}
+
+#[test]
+fn enum_rename_all_enums_are_copy() {
+    let episode = episodes_query::Episode::EmpireStrikesBack;
+    let copied = episode;
+
+    assert_eq!(episode, copied);
+}
+
+#[test]
+fn enum_rename_all_constants() {
+    use episodes_query::Episode;
+
+    assert_eq!(
+        Episode::ALL,
+        &[
+            Episode::NewHope,
+            Episode::EmpireStrikesBack,
+            Episode::ReturnOfTheJedi
+        ]
+    );
+    assert_eq!(
+        Episode::VARIANTS,
+        &["NEW_HOPE", "EMPIRE_STRIKES_BACK", "returnOfTheJedi"]
+    );
+}
@@ -0,0 +1,72 @@
+use graphql_client::*;
+use serde_json::json;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    query_path = "tests/enum_rename_all/query.graphql",
+    schema_path = "tests/enum_rename_all/schema.graphql",
+    response_derives = "Debug",
+    enum_wire_values = "Episode = kebab-case, Episode.returnOfTheJedi = rotj"
+)]
+pub struct EpisodesQuery;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    query_string = "query NumberedEpisodes { episodes }",
+    schema_path = "tests/enum_rename_all/schema.graphql",
+    response_derives = "Debug",
+    enum_wire_values = "Episode = integer, Episode.returnOfTheJedi = 6"
+)]
+pub struct NumberedEpisodes;
+
+#[test]
+fn string_wire_values_roundtrip() {
+    use episodes_query::Episode;
+
+    let response: episodes_query::ResponseData = serde_json::from_value(json!({
+        "episodes": ["new-hope", "empire-strikes-back", "rotj", "NEW_HOPE"],
+    }))
+    .unwrap();
+
+    assert_eq!(
+        response.episodes,
+        vec![
+            Episode::NEW_HOPE,
+            Episode::EMPIRE_STRIKES_BACK,
+            Episode::returnOfTheJedi,
+            Episode::Other("NEW_HOPE".to_owned()),
+        ]
+    );
+    assert_eq!(
+        serde_json::to_value(&response.episodes[..3]).unwrap(),
+        json!(["new-hope", "empire-strikes-back", "rotj"])
+    );
+    assert_eq!(
+        Episode::VARIANTS,
+        &["NEW_HOPE", "EMPIRE_STRIKES_BACK", "returnOfTheJedi"]
+    );
+}
+
+#[test]
+fn integer_wire_values_roundtrip() {
+    use numbered_episodes::Episode;
+
+    let response: numbered_episodes::ResponseData = serde_json::from_value(json!({
+        "episodes": [0, 1, 6, 7],
+    }))
+    .unwrap();
+
+    assert_eq!(
+        response.episodes,
+        vec![
+            Episode::NEW_HOPE,
+            Episode::EMPIRE_STRIKES_BACK,
+            Episode::returnOfTheJedi,
+            Episode::Other("7".to_owned()),
+        ]
+    );
+    assert_eq!(
+        serde_json::to_value(&response.episodes).unwrap(),
+        json!([0, 1, 6, 7])
+    );
+}
@@ -0,0 +1,56 @@
+use graphql_client::*;
+use serde_json::json;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    query_path = "tests/relay/query.graphql",
+    schema_path = "tests/relay/schema.graphql",
+    response_derives = "Debug",
+    serialize_responses = "true"
+)]
+pub struct IssuesQuery;
+
+#[test]
+fn errors_are_linked_to_the_nearest_generated_field() {
+    let response: Response<issues_query::ResponseData> = serde_json::from_value(json!({
+        "data": {
+            "repository": {
+                "issues": {
+                    "edges": [{ "cursor": "a", "node": null }],
+                    "pageInfo": {
+                        "hasNextPage": false,
+                        "hasPreviousPage": false,
+                        "startCursor": "a",
+                        "endCursor": "a",
+                    },
+                },
+            },
+        },
+        "errors": [{
+            "message": "Issue deleted",
+            "path": ["repository", "issues", "edges", 0, "node"],
+        }],
+    }))
+    .unwrap();
+
+    assert_eq!(response.errors_at(&["repository", "issues"]).len(), 1);
+    assert!(response
+        .errors_at(&["repository", "pullRequests"])
+        .is_empty());
+
+    let annotated = response.annotated_errors();
+    assert_eq!(annotated.len(), 1);
+    assert_eq!(
+        annotated[0].field_path,
+        &[
+            PathFragment::Key("repository".to_owned()),
+            PathFragment::Key("issues".to_owned()),
+            PathFragment::Key("edges".to_owned()),
+            PathFragment::Index(0),
+        ][..]
+    );
+    assert_eq!(
+        annotated[0].field,
+        Some(json!({ "cursor": "a", "node": null }))
+    );
+}
@@ -0,0 +1,44 @@
+use graphql_client::*;
+use serde_json::json;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "tests/more_derives/schema.graphql",
+    query_path = "tests/more_derives/query.graphql",
+    response_derives = "Debug, PartialEq",
+    serialize_responses = "true",
+    extra_fields = "true"
+)]
+pub struct MoreDerives;
+
+#[test]
+fn unselected_fields_are_kept() {
+    let response: more_derives::ResponseData = serde_json::from_value(json!({
+        "currentUser": {
+            "id": "abcd",
+            "name": "Angela Merkel",
+            "avatarUrl": "https://example.com/avatar.png",
+        },
+        "viewerCount": 3,
+    }))
+    .unwrap();
+
+    let user = response.current_user.as_ref().unwrap();
+    assert_eq!(user.name.as_ref().unwrap(), "Angela Merkel");
+    assert_eq!(
+        user.extra["avatarUrl"],
+        json!("https://example.com/avatar.png")
+    );
+    assert_eq!(response.extra["viewerCount"], json!(3));
+    assert!(!user.extra.contains_key("name"));
+}
+
+#[test]
+fn extra_fields_are_serialized_back() {
+    let value = json!({
+        "currentUser": { "id": "abcd", "name": null, "role": "admin" },
+    });
+    let response: more_derives::ResponseData = serde_json::from_value(value.clone()).unwrap();
+
+    assert_eq!(serde_json::to_value(&response).unwrap(), value);
+}
@@ -0,0 +1,10 @@
+use graphql_client::*;
+
+// Generates a test checking that the responses stored in `tests/fixtures/UnionQuery` deserialize.
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "tests/unions/union_schema.graphql",
+    query_path = "tests/unions/union_query.graphql",
+    test_fixtures_path = "tests/fixtures"
+)]
+pub struct UnionQuery;
@@ -0,0 +1,42 @@
+use graphql_client::*;
+use serde_json::json;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "tests/flatten_wrappers/schema.graphql",
+    query_path = "tests/flatten_wrappers/query.graphql",
+    response_derives = "Debug, PartialEq",
+    flatten_wrappers = "true"
+)]
+pub struct FlattenWrappers;
+
+#[test]
+fn single_field_wrappers_are_flattened() {
+    let response_data: flatten_wrappers::ResponseData = serde_json::from_value(json!({
+        "viewer": { "user": { "id": "1", "name": "Ada" } },
+        "friends": { "friends": [{ "name": "Grace" }, null] },
+    }))
+    .unwrap();
+
+    let expected = flatten_wrappers::ResponseData {
+        viewer: Some(flatten_wrappers::FlattenWrappersViewerUser {
+            id: "1".to_owned(),
+            name: Some("Ada".to_owned()),
+        }),
+        friends: Some(vec![Some("Grace".to_owned()), None]),
+    };
+
+    assert_eq!(response_data, expected);
+}
+
+#[test]
+fn null_wrappers_are_none() {
+    let response_data: flatten_wrappers::ResponseData = serde_json::from_value(json!({
+        "viewer": null,
+        "friends": { "friends": null },
+    }))
+    .unwrap();
+
+    assert_eq!(response_data.viewer, None);
+    assert_eq!(response_data.friends, None);
+}
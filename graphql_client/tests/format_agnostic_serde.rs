@@ -0,0 +1,58 @@
+use graphql_client::*;
+use serde_json::json;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_string = "
+        schema { query: Query }
+        union SearchResult = Human | Droid | Starship
+        type Human { name: String! height: Float }
+        type Droid { name: String! }
+        type Starship { name: String! }
+        type Query { search(text: String): [SearchResult!]! }
+    ",
+    query_string = "
+        query Search($text: String) {
+            search(text: $text) {
+                __typename
+                ... on Human { name height }
+                ... on Droid { name }
+            }
+        }
+    ",
+    response_derives = "Debug, PartialEq, Serialize",
+    format_agnostic_serde = "true"
+)]
+pub struct Search;
+
+#[test]
+fn responses_roundtrip_through_bincode() {
+    let response = json!({
+        "search": [
+            { "__typename": "Human", "name": "Leia", "height": null },
+            { "__typename": "Droid", "name": "R2-D2" },
+            { "__typename": "Starship" },
+        ],
+    });
+    let data: search::ResponseData = serde_json::from_value(response.clone()).unwrap();
+
+    let bytes = bincode::serialize(&data).unwrap();
+    let cached: search::ResponseData = bincode::deserialize(&bytes).unwrap();
+
+    assert_eq!(cached, data);
+    assert_eq!(serde_json::to_value(&cached).unwrap(), response);
+}
+
+#[test]
+fn null_variables_are_serialized() {
+    let variables = search::Variables { text: None };
+
+    assert_eq!(
+        serde_json::to_value(&variables).unwrap(),
+        json!({ "text": null })
+    );
+
+    let bytes = bincode::serialize(&variables).unwrap();
+    let text: Option<String> = bincode::deserialize(&bytes).unwrap();
+    assert_eq!(text, None);
+}
@@ -72,3 +72,30 @@ fn recursive_fragment() {
         }),
     };
 }
+
+#[test]
+fn normalized_query() {
+    let normalized = fragment_reference::normalized_query();
+
+    assert!(normalized.starts_with("query FragmentReference"));
+    assert!(normalized.contains("fragment FragmentReference on QueryRoot"));
+    assert!(!normalized.contains("snake_case_fragment"));
+    assert!(!normalized.contains("RecursiveFragment"));
+
+    let normalized = recursive_fragment_query::normalized_query();
+
+    assert_eq!(normalized.matches("fragment RecursiveFragment").count(), 1);
+}
+
+#[test]
+fn fragment_consts() {
+    assert_eq!(fragment_reference::FRAGMENTS, &["FragmentReference"]);
+    assert!(fragment_reference::FRAGMENT_FRAGMENT_REFERENCE
+        .starts_with("fragment FragmentReference on QueryRoot {"));
+
+    assert_eq!(snake_case_fragment::FRAGMENTS, &["snake_case_fragment"]);
+    assert!(snake_case_fragment::FRAGMENT_SNAKE_CASE_FRAGMENT.contains("inFragment"));
+
+    assert_eq!(recursive_fragment_query::FRAGMENTS, &["RecursiveFragment"]);
+    assert!(recursive_fragment_query::FRAGMENT_RECURSIVE_FRAGMENT.contains("...RecursiveFragment"));
+}
@@ -0,0 +1,51 @@
+use graphql_client::*;
+use serde_json::json;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_string = "
+        schema { query: Query }
+        type Address { street: String!, city: String }
+        type User { id: ID!, name: String!, nickname: String, address: Address, tags: [String!]! }
+        input AddressInput { street: String!, city: String, zip: String }
+        input UserInput { name: String!, nickname: String, address: AddressInput, tags: [String!] }
+        type Query { user(id: ID!): User }
+    ",
+    query_string = "
+        query EditUser($id: ID!) {
+          user(id: $id) { id name nickname address { street city } tags }
+        }
+    ",
+    input_derives = "Debug, PartialEq",
+    input_conversions = "user = UserInput"
+)]
+pub struct EditUser;
+
+#[test]
+fn responses_convert_to_inputs() {
+    let response: edit_user::ResponseData = serde_json::from_value(json!({
+        "user": {
+            "id": "1",
+            "name": "Ada",
+            "nickname": null,
+            "address": { "street": "Main Street", "city": "London" },
+            "tags": ["admin"],
+        }
+    }))
+    .unwrap();
+
+    let input: edit_user::UserInput = response.user.unwrap().into();
+    assert_eq!(
+        input,
+        edit_user::UserInput {
+            name: "Ada".to_string(),
+            nickname: None,
+            address: Some(edit_user::AddressInput {
+                street: "Main Street".to_string(),
+                city: Some("London".to_string()),
+                zip: None,
+            }),
+            tags: Some(vec!["admin".to_string()]),
+        }
+    );
+}
@@ -95,3 +95,11 @@ fn indirectly_recursive_input_objects_can_be_constructed() {
         })),
     };
 }
+
+#[test]
+fn enum_constants() {
+    use input_object_variables_query::Category;
+
+    assert_eq!(Category::ALL, &[Category::PROFESSIONAL, Category::PERSONAL]);
+    assert_eq!(Category::VARIANTS, &["PROFESSIONAL", "PERSONAL"]);
+}
@@ -0,0 +1,15 @@
+use graphql_client::*;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "${WORKSPACE_ROOT}/graphql_client/tests/unions/union_schema.graphql",
+    query_path = "${CARGO_MANIFEST_DIR}/tests/unions/union_query.graphql",
+    response_derives = "Debug"
+)]
+pub struct UnionQuery;
+
+#[test]
+fn interpolated_paths() {
+    let body = UnionQuery::build_query(());
+    assert_eq!(body.operation_name, "UnionQuery");
+}
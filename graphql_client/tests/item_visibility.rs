@@ -0,0 +1,24 @@
+use graphql_client::*;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "tests/more_derives/schema.graphql",
+    query_path = "tests/more_derives/query.graphql",
+    item_visibility = "pub(crate)"
+)]
+struct MoreDerives;
+
+#[test]
+fn crate_visible_items_can_be_used_within_the_crate() {
+    let response_data = more_derives::ResponseData {
+        current_user: Some(more_derives::MoreDerivesCurrentUser {
+            id: Some("abcd".to_owned()),
+            name: Some("Angela Merkel".to_owned()),
+        }),
+    };
+
+    assert_eq!(
+        response_data.current_user.and_then(|user| user.name),
+        Some("Angela Merkel".to_owned())
+    );
+}
@@ -0,0 +1,35 @@
+use graphql_client::*;
+use serde_json::json;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    query_path = "tests/json_scalars/query.graphql",
+    schema_path = "tests/json_scalars/schema.graphql",
+    json_scalars = "true"
+)]
+pub struct SettingsQuery;
+
+#[test]
+fn json_scalars_are_values() {
+    let response: settings_query::ResponseData = serde_json::from_value(json!({
+        "settings": {
+            "id": "1",
+            "values": { "theme": "dark", "columns": [1, 2] },
+        },
+    }))
+    .unwrap();
+
+    assert_eq!(response.settings.unwrap().values["columns"], json!([1, 2]));
+}
+
+#[test]
+fn json_scalar_variables_are_values() {
+    let variables = settings_query::Variables {
+        defaults: Some(json!({ "theme": "light" })),
+    };
+
+    assert_eq!(
+        serde_json::to_value(variables).unwrap(),
+        json!({ "defaults": { "theme": "light" } })
+    );
+}
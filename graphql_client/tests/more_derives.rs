@@ -27,3 +27,29 @@ fn response_derives_can_be_added() {
     assert_ne!(response_data, response_data_2);
     assert!(response_data < response_data_2);
 }
+
+#[test]
+fn response_data_can_be_converted_from_a_json_value() {
+    use std::convert::TryFrom;
+
+    let value = serde_json::json!({
+        "currentUser": { "id": "abcd", "name": null },
+    });
+
+    let expected = more_derives::ResponseData {
+        current_user: Some(more_derives::MoreDerivesCurrentUser {
+            id: Some("abcd".to_owned()),
+            name: None,
+        }),
+    };
+
+    assert_eq!(
+        more_derives::ResponseData::from_json(value.clone()).unwrap(),
+        expected
+    );
+    assert_eq!(
+        more_derives::ResponseData::try_from(value).unwrap(),
+        expected
+    );
+    assert!(more_derives::ResponseData::from_json(serde_json::json!([])).is_err());
+}
@@ -0,0 +1,61 @@
+use graphql_client::*;
+use serde_json::json;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_string = "
+        schema { query: Query }
+        type Link { href: String! }
+        input LinkInput { url: String! URL: String }
+        type Query { link(input: LinkInput, Input: LinkInput): Link }
+    ",
+    query_string = "
+        query Links($input: LinkInput, $Input: LinkInput) {
+            url: link(input: $input) { href }
+            URL: link(Input: $Input) { href }
+        }
+    ",
+    response_derives = "Debug, PartialEq"
+)]
+pub struct Links;
+
+#[test]
+fn fields_only_differing_by_case_are_numbered() {
+    use links::*;
+
+    let response: ResponseData = serde_json::from_value(json!({
+        "url": { "href": "https://example.com" },
+        "URL": null,
+    }))
+    .unwrap();
+
+    assert_eq!(
+        response,
+        ResponseData {
+            url_2: Some(LinksUrl2 {
+                href: "https://example.com".to_owned(),
+            }),
+            url: None,
+        }
+    );
+}
+
+#[test]
+fn input_fields_and_variables_only_differing_by_case_are_renamed() {
+    use links::*;
+
+    let variables = Variables {
+        input_2: Some(LinkInput {
+            url_2: "https://example.com".to_owned(),
+            url: Some("HTTPS://EXAMPLE.COM".to_owned()),
+        }),
+        input: None,
+    };
+
+    assert_eq!(
+        serde_json::to_value(&variables).unwrap(),
+        json!({
+            "input": { "url": "https://example.com", "URL": "HTTPS://EXAMPLE.COM" },
+        })
+    );
+}
@@ -0,0 +1,29 @@
+use graphql_client::*;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "tests/more_derives/schema.graphql",
+    query_path = "tests/more_derives/query.graphql",
+    nested_accessors = "true"
+)]
+pub struct MoreDerives;
+
+#[test]
+fn nested_accessors_flatten_the_options() {
+    let response_data = more_derives::ResponseData {
+        current_user: Some(more_derives::MoreDerivesCurrentUser {
+            id: Some("abcd".to_owned()),
+            name: None,
+        }),
+    };
+
+    assert_eq!(
+        response_data.current_user_id().collect::<Vec<_>>(),
+        vec!["abcd"]
+    );
+    assert_eq!(response_data.current_user_name().count(), 0);
+
+    let empty = more_derives::ResponseData { current_user: None };
+
+    assert_eq!(empty.current_user_id().count(), 0);
+}
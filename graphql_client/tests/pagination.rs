@@ -0,0 +1,62 @@
+use graphql_client::*;
+use serde_json::json;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    query_path = "tests/pagination/query.graphql",
+    schema_path = "tests/pagination/schema.graphql",
+    pagination_cursor = "offset",
+    pagination_nodes = "users.items"
+)]
+pub struct UsersQuery;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    query_path = "tests/pagination/query.graphql",
+    schema_path = "tests/pagination/schema.graphql",
+    pagination_cursor = "cursor",
+    pagination_nodes = "feed.entries",
+    pagination_page_info = "feed.info"
+)]
+pub struct FeedQuery;
+
+#[test]
+fn offset_pagination() {
+    assert_eq!(UsersQuery::CURSOR, PaginationCursor::Offset("offset"));
+
+    let response = serde_json::from_value(json!({
+        "users": { "items": [{ "name": "ada" }, { "name": "grace" }] },
+    }))
+    .unwrap();
+    let (users, page_info) = UsersQuery::into_page(response).unwrap();
+
+    let names: Vec<String> = users.into_iter().map(|user| user.name).collect();
+    assert_eq!(names, vec!["ada", "grace"]);
+    assert_eq!(page_info, PageInfo::default());
+}
+
+#[test]
+fn cursor_pagination() {
+    assert_eq!(FeedQuery::CURSOR, PaginationCursor::EndCursor("cursor"));
+
+    let response = serde_json::from_value(json!({
+        "feed": {
+            "entries": [{ "id": "1" }, null, { "id": "3" }],
+            "info": { "hasNextPage": true, "endCursor": "Mw==" },
+        },
+    }))
+    .unwrap();
+    let (entries, page_info) = FeedQuery::into_page(response).unwrap();
+
+    let ids: Vec<String> = entries.into_iter().map(|entry| entry.id).collect();
+    assert_eq!(ids, vec!["1", "3"]);
+    assert!(page_info.has_next_page);
+    assert_eq!(page_info.end_cursor, Some("Mw==".to_string()));
+}
+
+#[test]
+fn cursor_pagination_of_a_null_feed() {
+    let response = serde_json::from_value(json!({ "feed": null })).unwrap();
+
+    assert!(FeedQuery::into_page(response).is_none());
+}
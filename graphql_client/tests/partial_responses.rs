@@ -0,0 +1,65 @@
+use graphql_client::partial::PartialResponse;
+use graphql_client::*;
+use serde_json::json;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    query_path = "tests/relay/query.graphql",
+    schema_path = "tests/relay/schema.graphql",
+    response_derives = "Debug"
+)]
+pub struct IssuesQuery;
+
+#[test]
+fn failed_fields_are_softened_to_their_nullable_parent() {
+    // The server did not propagate the null of the non-nullable `title` to the `node`.
+    let response: Response<serde_json::Value> = serde_json::from_value(json!({
+        "data": {
+            "repository": {
+                "issues": {
+                    "edges": [
+                        { "cursor": "a", "node": { "title": "Partial responses" } },
+                        { "cursor": "b", "node": { "title": null } },
+                    ],
+                    "pageInfo": {
+                        "hasNextPage": false,
+                        "hasPreviousPage": false,
+                        "startCursor": "a",
+                        "endCursor": "b",
+                    },
+                },
+            },
+        },
+        "errors": [{
+            "message": "Title redacted",
+            "path": ["repository", "issues", "edges", 1, "node", "title"],
+        }],
+    }))
+    .unwrap();
+
+    let partial: PartialResponse<issues_query::ResponseData> = response.into_partial().unwrap();
+
+    let data = partial.data.as_ref().unwrap();
+    let titles: Vec<&str> = data
+        .repository
+        .as_ref()
+        .unwrap()
+        .issues
+        .iter_nodes()
+        .map(|issue| issue.title.as_str())
+        .collect();
+    assert_eq!(titles, vec!["Partial responses"]);
+
+    assert_eq!(partial.softened.len(), 1);
+    assert_eq!(
+        partial.softened[0].path,
+        vec![
+            PathFragment::Key("repository".to_owned()),
+            PathFragment::Key("issues".to_owned()),
+            PathFragment::Key("edges".to_owned()),
+            PathFragment::Index(1),
+            PathFragment::Key("node".to_owned()),
+        ]
+    );
+    assert_eq!(partial.softened[0].error.message, "Title redacted");
+}
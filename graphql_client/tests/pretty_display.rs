@@ -0,0 +1,42 @@
+use graphql_client::*;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "tests/more_derives/schema.graphql",
+    query_path = "tests/more_derives/query.graphql",
+    pretty_display = "true"
+)]
+pub struct MoreDerives;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    query_path = "tests/unions/union_query.graphql",
+    schema_path = "tests/unions/union_schema.graphql",
+    pretty_display = "true"
+)]
+pub struct UnionQuery;
+
+#[test]
+fn pretty_display_writes_an_indented_tree() {
+    let response_data = more_derives::ResponseData {
+        current_user: Some(more_derives::MoreDerivesCurrentUser {
+            id: Some("abcd".to_owned()),
+            name: None,
+        }),
+    };
+
+    assert_eq!(
+        response_data.to_string(),
+        "currentUser:\n  name: null\n  id: \"abcd\"\n"
+    );
+}
+
+#[test]
+fn pretty_display_writes_union_variants() {
+    let response_data: union_query::ResponseData =
+        serde_json::from_str(include_str!("unions/union_query_response.json")).unwrap();
+
+    assert!(response_data.to_string().starts_with(
+        "names:\n  -\n    __typename: Person\n    firstName: \"Audrey\"\n    lastName: \"Lorde\"\n  -\n    __typename: Dog\n    name: \"Laïka\"\n"
+    ));
+}
@@ -0,0 +1,23 @@
+use graphql_client::*;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "tests/operation_selection/schema.graphql",
+    query_string = "query Echo($msg: String) { echo(msg: $msg) }",
+    response_derives = "Debug, PartialEq"
+)]
+pub struct Echo;
+
+#[test]
+fn inline_query_strings() {
+    assert_eq!(echo::QUERY, "query Echo($msg: String) { echo(msg: $msg) }");
+
+    let body = Echo::build_query(echo::Variables {
+        msg: Some("hi".to_string()),
+    });
+    assert_eq!(body.operation_name, "Echo");
+
+    let response: echo::ResponseData =
+        serde_json::from_value(serde_json::json!({ "echo": "hi" })).unwrap();
+    assert_eq!(response.echo.as_ref().map(String::as_str), Some("hi"));
+}
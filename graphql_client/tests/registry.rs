@@ -0,0 +1,42 @@
+#![cfg(feature = "registry")]
+
+use graphql_client::registry::{self, OperationKind};
+use graphql_client::GraphQLQuery;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    query_path = "tests/operation_selection/queries.graphql",
+    schema_path = "tests/operation_selection/schema.graphql"
+)]
+pub struct Heights;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    query_path = "tests/operation_selection/queries.graphql",
+    schema_path = "tests/operation_selection/schema.graphql",
+    register_operations = "false"
+)]
+pub struct Echo;
+
+#[test]
+fn operations_are_registered() {
+    let heights = registry::operations()
+        .find(|operation| operation.name == "Heights")
+        .unwrap();
+
+    assert_eq!(heights.kind, OperationKind::Query);
+    assert_eq!(heights.query, heights::QUERY);
+    assert_eq!(heights.hash, heights::QUERY_HASH);
+    assert_eq!(registry::find_by_hash(heights::QUERY_HASH), Some(heights));
+}
+
+#[test]
+fn registration_can_be_disabled() {
+    assert!(registry::operations().all(|operation| operation.name != "Echo"));
+}
+
+#[test]
+fn query_hash_is_the_sha256_of_the_query() {
+    assert_eq!(heights::QUERY_HASH.len(), 64);
+    assert_eq!(heights::QUERY_HASH, echo::QUERY_HASH);
+}
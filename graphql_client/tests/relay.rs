@@ -0,0 +1,78 @@
+use graphql_client::*;
+use serde_json::json;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    query_path = "tests/relay/query.graphql",
+    schema_path = "tests/relay/schema.graphql",
+    response_derives = "Debug"
+)]
+pub struct IssuesQuery;
+
+fn response() -> issues_query::ResponseData {
+    serde_json::from_value(json!({
+        "repository": {
+            "issues": {
+                "edges": [
+                    { "cursor": "a", "node": { "title": "Support subscriptions" } },
+                    null,
+                    { "cursor": "c", "node": null },
+                    { "cursor": "d", "node": { "title": "Relay helpers" } },
+                ],
+                "pageInfo": {
+                    "hasNextPage": true,
+                    "hasPreviousPage": false,
+                    "startCursor": "a",
+                    "endCursor": "d",
+                },
+            },
+        },
+    }))
+    .unwrap()
+}
+
+#[test]
+fn iter_nodes_skips_null_edges_and_nodes() {
+    let response = response();
+    let issues = &response.repository.as_ref().unwrap().issues;
+
+    let titles: Vec<&str> = issues
+        .iter_nodes()
+        .map(|issue| issue.title.as_str())
+        .collect();
+
+    assert_eq!(titles, vec!["Support subscriptions", "Relay helpers"]);
+}
+
+#[test]
+fn page_info_conversion() {
+    let response = response();
+    let issues = &response.repository.as_ref().unwrap().issues;
+
+    assert_eq!(
+        PageInfo::from(&issues.page_info),
+        PageInfo {
+            has_next_page: true,
+            has_previous_page: false,
+            start_cursor: Some("a".to_string()),
+            end_cursor: Some("d".to_string()),
+        }
+    );
+}
+
+#[test]
+fn into_page_takes_the_nodes_and_the_page_info() {
+    let (nodes, page_info) = IssuesQuery::into_page(response()).unwrap();
+
+    let titles: Vec<String> = nodes.into_iter().map(|issue| issue.title).collect();
+    assert_eq!(titles, vec!["Support subscriptions", "Relay helpers"]);
+    assert_eq!(page_info.end_cursor, Some("d".to_string()));
+    assert!(page_info.has_next_page);
+}
+
+#[test]
+fn into_page_of_a_null_connection() {
+    let response = serde_json::from_value(json!({ "repository": null })).unwrap();
+
+    assert!(IssuesQuery::into_page(response).is_none());
+}
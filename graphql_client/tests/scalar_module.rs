@@ -0,0 +1,28 @@
+use graphql_client::*;
+use serde_json::json;
+
+// The scalars are only defined in this module, not next to the struct under derive.
+mod scalars {
+    pub type NetworkAddress = std::net::Ipv4Addr;
+}
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    query_path = "tests/custom_scalars/query.graphql",
+    schema_path = "tests/custom_scalars/schema.graphql",
+    scalar_module = "crate::scalars"
+)]
+pub struct CustomScalarQuery;
+
+#[test]
+fn scalar_module() {
+    let response: custom_scalar_query::ResponseData = serde_json::from_value(json!({
+        "address": "127.0.1.2",
+    }))
+    .unwrap();
+
+    assert_eq!(
+        response.address,
+        Some(scalars::NetworkAddress::new(127, 0, 1, 2))
+    );
+}
@@ -0,0 +1,122 @@
+use graphql_client::*;
+use serde_json::{json, Value};
+
+// Neither `Serialize` nor `Deserialize`: the generated code goes through `GraphQLScalar`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cents(u64);
+
+impl GraphQLScalar for Cents {
+    type Error = String;
+
+    fn from_json(value: Value) -> Result<Self, String> {
+        let amount = value.as_str().ok_or("expected a string")?;
+        let mut parts = amount.splitn(2, '.');
+        let units: u64 = parts
+            .next()
+            .unwrap_or("")
+            .parse()
+            .map_err(|_| format!("invalid amount: {}", amount))?;
+        let cents: u64 = parts
+            .next()
+            .unwrap_or("0")
+            .parse()
+            .map_err(|_| format!("invalid amount: {}", amount))?;
+
+        Ok(Cents(units * 100 + cents))
+    }
+
+    fn to_json(&self) -> Value {
+        Value::String(format!("{}.{:02}", self.0 / 100, self.0 % 100))
+    }
+}
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    query_path = "tests/scalar_trait/query.graphql",
+    schema_path = "tests/scalar_trait/schema.graphql",
+    response_derives = "Debug, PartialEq",
+    scalar_trait = "true"
+)]
+pub struct ProductsQuery;
+
+#[test]
+fn scalar_trait_deserialization() {
+    use products_query::*;
+
+    let response: ResponseData = serde_json::from_value(json!({
+        "products": [
+            {
+                "name": "Tea",
+                "price": "4.50",
+                "discountedPrice": null,
+                "priceHistory": ["4", "4.20"],
+            },
+            {
+                "name": "Coffee",
+                "price": "3.05",
+                "discountedPrice": "2.99",
+                "priceHistory": [],
+            },
+        ],
+    }))
+    .unwrap();
+
+    assert_eq!(
+        response.products,
+        vec![
+            ProductsQueryProducts {
+                name: "Tea".to_string(),
+                price: Cents(450),
+                discounted_price: None,
+                price_history: vec![Cents(400), Cents(420)],
+            },
+            ProductsQueryProducts {
+                name: "Coffee".to_string(),
+                price: Cents(305),
+                discounted_price: Some(Cents(299)),
+                price_history: vec![],
+            },
+        ]
+    );
+}
+
+#[test]
+fn scalar_trait_missing_optional_fields() {
+    let response: products_query::ResponseData = serde_json::from_value(json!({
+        "products": [{ "name": "Tea", "price": "4.50", "priceHistory": [] }],
+    }))
+    .unwrap();
+
+    assert_eq!(response.products[0].discounted_price, None);
+}
+
+#[test]
+fn scalar_trait_validation_errors() {
+    let err = serde_json::from_value::<products_query::ResponseData>(json!({
+        "products": [{ "name": "Tea", "price": "four", "priceHistory": [] }],
+    }))
+    .unwrap_err();
+
+    assert!(err.to_string().contains("invalid amount: four"));
+}
+
+#[test]
+fn scalar_trait_serialization() {
+    use products_query::*;
+
+    let variables = Variables {
+        filter: ProductFilter {
+            min_price: Cents(100),
+            max_price: None,
+        },
+        max_discounted_price: Some(Cents(1005)),
+    };
+
+    assert_eq!(
+        serde_json::to_value(variables).unwrap(),
+        json!({
+            "filter": { "minPrice": "1.00" },
+            "maxDiscountedPrice": "10.05",
+        })
+    );
+}
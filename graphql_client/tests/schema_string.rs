@@ -0,0 +1,24 @@
+use graphql_client::*;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_string = "
+        schema { query: Query }
+        type Query { greeting(name: String!): String! }
+    ",
+    query_string = "query Greet($name: String!) { greeting(name: $name) }",
+    response_derives = "Debug, PartialEq"
+)]
+pub struct Greet;
+
+#[test]
+fn inline_schema_strings() {
+    let body = Greet::build_query(greet::Variables {
+        name: "world".to_string(),
+    });
+    assert_eq!(body.operation_name, "Greet");
+
+    let response: greet::ResponseData =
+        serde_json::from_value(serde_json::json!({ "greeting": "hello world" })).unwrap();
+    assert_eq!(response.greeting, "hello world");
+}
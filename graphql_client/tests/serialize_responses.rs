@@ -0,0 +1,29 @@
+use graphql_client::*;
+use serde_json::json;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_string = "
+        schema { query: Query }
+        enum Role { ADMIN, GUEST }
+        type User { id: ID!, firstName: String, role: Role! }
+        type Query { users: [User!]! }
+    ",
+    query_string = "query Users { users { id firstName role } }",
+    response_derives = "Debug, PartialEq",
+    serialize_responses = "true"
+)]
+pub struct Users;
+
+#[test]
+fn responses_serialize_to_their_json_shape() {
+    let response = json!({
+        "users": [
+            { "id": "1", "firstName": "Ada", "role": "ADMIN" },
+            { "id": "2", "firstName": null, "role": "GUEST" },
+        ]
+    });
+
+    let data: users::ResponseData = serde_json::from_value(response.clone()).unwrap();
+    assert_eq!(serde_json::to_value(&data).unwrap(), response);
+}
@@ -0,0 +1,94 @@
+use graphql_client::*;
+use serde_json::json;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_string = "
+        schema { query: Query }
+        union Pet = Dog | Cat
+        type Dog { name: String! barks: Boolean! }
+        type Cat { name: String! lives: Int! }
+        type Query { pets: [Pet!]! }
+    ",
+    query_string = "
+        query Pets {
+            pets { __typename ... on Dog { name barks } ... on Cat { name lives } }
+        }
+    ",
+    response_derives = "Debug, PartialEq",
+    typename_fallback = "untagged"
+)]
+pub struct Pets;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_string = "
+        schema { query: Query }
+        interface Animal { name: String! }
+        type Dog implements Animal { name: String! barks: Boolean! }
+        type Cat implements Animal { name: String! lives: Int! }
+        type Query { animals: [Animal!]! }
+    ",
+    query_string = "
+        query Animals {
+            animals { __typename name ... on Dog { barks } }
+        }
+    ",
+    response_derives = "Debug, PartialEq",
+    typename_fallback = "Cat"
+)]
+pub struct Animals;
+
+#[test]
+fn unions_without_typename_are_deserialized_by_shape() {
+    use pets::*;
+
+    let response: ResponseData = serde_json::from_value(json!({
+        "pets": [
+            { "__typename": "Dog", "name": "Rex", "barks": true },
+            { "name": "Tom", "lives": 9 },
+        ],
+    }))
+    .unwrap();
+
+    assert_eq!(
+        response.pets,
+        vec![
+            PetsPets::Dog(PetsPetsOnDog {
+                name: "Rex".to_owned(),
+                barks: true,
+            }),
+            PetsPets::Cat(PetsPetsOnCat {
+                name: "Tom".to_owned(),
+                lives: 9,
+            }),
+        ]
+    );
+}
+
+#[test]
+fn interfaces_without_typename_fall_back_to_the_default_variant() {
+    use animals::*;
+
+    let response: ResponseData = serde_json::from_value(json!({
+        "animals": [
+            { "__typename": "Dog", "name": "Rex", "barks": false },
+            { "name": "Tom" },
+        ],
+    }))
+    .unwrap();
+
+    assert_eq!(
+        response.animals,
+        vec![
+            AnimalsAnimals {
+                name: "Rex".to_owned(),
+                on: AnimalsAnimalsOn::Dog(AnimalsAnimalsOnDog { barks: false }),
+            },
+            AnimalsAnimals {
+                name: "Tom".to_owned(),
+                on: AnimalsAnimalsOn::Cat,
+            },
+        ]
+    );
+}
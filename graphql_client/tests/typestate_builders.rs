@@ -0,0 +1,64 @@
+use graphql_client::*;
+
+// Custom scalars
+type Email = String;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    query_path = "tests/input_object_variables/input_object_variables_query.graphql",
+    schema_path = "tests/input_object_variables/input_object_variables_schema.graphql",
+    typestate_builders = "true"
+)]
+pub struct InputObjectVariablesQuery;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    query_path = "tests/input_object_variables/input_object_variables_query.graphql",
+    schema_path = "tests/input_object_variables/input_object_variables_schema.graphql",
+    typestate_builders = "true"
+)]
+pub struct RecursiveInputQuery;
+
+#[test]
+fn builders_set_the_fields() {
+    use input_object_variables_query::*;
+
+    let variables = Variables::builder()
+        .msg(
+            Message::builder()
+                .to(Recipient::builder()
+                    .name("Sarah Connor".to_string())
+                    .email("sarah.connor@example.com".to_string())
+                    .build())
+                .build(),
+        )
+        .build();
+
+    assert_eq!(
+        serde_json::to_string(&variables).unwrap(),
+        r#"{"msg":{"to":{"email":"sarah.connor@example.com","name":"Sarah Connor"}}}"#
+    );
+}
+
+#[test]
+fn optional_fields_default_to_none() {
+    let variables = input_object_variables_query::Variables::builder().build();
+
+    assert!(variables.msg.is_none());
+}
+
+#[test]
+fn required_variables_and_recursive_inputs() {
+    use recursive_input_query::*;
+
+    let input = RecursiveInput::builder()
+        .head("hi".to_string())
+        .tail(Box::new(
+            RecursiveInput::builder().head("there".to_string()).build(),
+        ))
+        .build();
+    let variables = Variables::builder().input(input).build();
+
+    assert_eq!(variables.input.head, "hi");
+    assert_eq!(variables.input.tail.unwrap().head, "there");
+}
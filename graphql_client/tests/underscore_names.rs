@@ -0,0 +1,48 @@
+use graphql_client::*;
+use serde_json::json;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_string = "
+        schema { query: Query }
+        enum Size { _ _2XL SMALL }
+        type Document { _id: ID! id: Int! _: String __meta: String size: Size! }
+        type Query { document: Document }
+    ",
+    query_string = "
+        query Document {
+            document { __typename _id id _ __meta size }
+        }
+    ",
+    response_derives = "Debug, PartialEq"
+)]
+pub struct Document;
+
+#[test]
+fn leading_underscores_are_kept() {
+    use document::*;
+
+    let response: ResponseData = serde_json::from_value(json!({
+        "document": {
+            "__typename": "Document",
+            "_id": "6f1c",
+            "id": 1,
+            "_": "blank",
+            "__meta": null,
+            "size": "_",
+        },
+    }))
+    .unwrap();
+
+    assert_eq!(
+        response.document,
+        Some(DocumentDocument {
+            typename: "Document".to_owned(),
+            _id: "6f1c".to_owned(),
+            id: 1,
+            __: Some("blank".to_owned()),
+            __meta: None,
+            size: Size::__,
+        })
+    );
+}
@@ -78,3 +78,41 @@ fn fragment_on_union() {
 
     assert_eq!(response_data, expected);
 }
+
+#[test]
+fn union_variant_accessors() {
+    let response_data: union_query::ResponseData = serde_json::from_str(RESPONSE).unwrap();
+    let names = response_data.names.unwrap();
+
+    assert!(names[0].is_person());
+    assert!(!names[0].is_dog());
+    assert_eq!(
+        names[1].as_dog().map(|dog| dog.name.as_str()),
+        Some("Laïka")
+    );
+    assert_eq!(names[1].as_person(), None);
+    assert_eq!(
+        names
+            .into_iter()
+            .filter_map(union_query::UnionQueryNames::into_organization)
+            .map(|organization| organization.title)
+            .collect::<Vec<_>>(),
+        vec!["Mozilla".to_string()]
+    );
+}
+
+#[test]
+fn union_variant_typenames() {
+    let response_data: union_query::ResponseData = serde_json::from_str(RESPONSE).unwrap();
+    let names = response_data.names.unwrap();
+
+    assert_eq!(names[0].typename(), "Person");
+    assert_eq!(
+        names[1].typename(),
+        union_query::UnionQueryNames::DOG_TYPENAME
+    );
+    assert_eq!(
+        union_query::UnionQueryNames::ORGANIZATION_TYPENAME,
+        "Organization"
+    );
+}
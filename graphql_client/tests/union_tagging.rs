@@ -0,0 +1,97 @@
+use graphql_client::*;
+use serde_json::json;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_string = "
+        schema { query: Query }
+        union Pet = Dog | Cat | Fish
+        type Dog { name: String! barks: Boolean! }
+        type Cat { name: String! lives: Int! }
+        type Fish { name: String! }
+        type Query { pets: [Pet!]! }
+    ",
+    query_string = "
+        query AdjacentPets {
+            pets { __typename ... on Dog { name barks } ... on Cat { name lives } }
+        }
+    ",
+    response_derives = "Debug, PartialEq",
+    union_tagging = "adjacent:fields"
+)]
+pub struct AdjacentPets;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_string = "
+        schema { query: Query }
+        union Pet = Dog | Cat
+        type Dog { name: String! barks: Boolean! }
+        type Cat { name: String! lives: Int! }
+        type Query { pets: [Pet!]! }
+    ",
+    query_string = "
+        query UntaggedPets {
+            pets { ... on Dog { name barks } ... on Cat { name lives } }
+        }
+    ",
+    response_derives = "Debug, PartialEq",
+    union_tagging = "untagged"
+)]
+pub struct UntaggedPets;
+
+#[test]
+fn adjacently_tagged_unions() {
+    use adjacent_pets::*;
+
+    let response: ResponseData = serde_json::from_value(json!({
+        "pets": [
+            { "__typename": "Dog", "fields": { "name": "Rex", "barks": true } },
+            { "__typename": "Cat", "fields": { "name": "Tom", "lives": 9 } },
+            { "__typename": "Fish" },
+        ],
+    }))
+    .unwrap();
+
+    assert_eq!(
+        response.pets,
+        vec![
+            AdjacentPetsPets::Dog(AdjacentPetsPetsOnDog {
+                name: "Rex".to_owned(),
+                barks: true,
+            }),
+            AdjacentPetsPets::Cat(AdjacentPetsPetsOnCat {
+                name: "Tom".to_owned(),
+                lives: 9,
+            }),
+            AdjacentPetsPets::Fish,
+        ]
+    );
+}
+
+#[test]
+fn untagged_unions_are_deserialized_by_shape() {
+    use untagged_pets::*;
+
+    let response: ResponseData = serde_json::from_value(json!({
+        "pets": [
+            { "name": "Rex", "barks": false },
+            { "name": "Tom", "lives": 9 },
+        ],
+    }))
+    .unwrap();
+
+    assert_eq!(
+        response.pets,
+        vec![
+            UntaggedPetsPets::Dog(UntaggedPetsPetsOnDog {
+                name: "Rex".to_owned(),
+                barks: false,
+            }),
+            UntaggedPetsPets::Cat(UntaggedPetsPetsOnCat {
+                name: "Tom".to_owned(),
+                lives: 9,
+            }),
+        ]
+    );
+}
@@ -0,0 +1,70 @@
+use graphql_client::validation::ValidationError;
+use graphql_client::*;
+use serde_json::json;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_string = "
+        schema { mutation: Mutation }
+        scalar JSON
+        enum Status { ACTIVE ARCHIVED }
+        input Filter { status: Status! statuses: [Status!] metadata: JSON! }
+        type Mutation { archive(filter: Filter!, status: Status): Boolean }
+    ",
+    query_string = "
+        mutation Archive($filter: Filter!, $status: Status) {
+            archive(filter: $filter, status: $status)
+        }
+    ",
+    json_scalars = "true",
+    validate_variables = "true"
+)]
+pub struct Archive;
+
+#[test]
+fn valid_variables_pass() {
+    use archive::*;
+
+    let variables = Variables {
+        filter: Filter {
+            status: Status::ACTIVE,
+            statuses: Some(vec![Status::ARCHIVED]),
+            metadata: json!({}),
+        },
+        status: None,
+    };
+
+    assert_eq!(variables.validate(), Ok(()));
+}
+
+#[test]
+fn invalid_variables_are_reported_with_their_paths() {
+    use archive::*;
+
+    let variables = Variables {
+        filter: Filter {
+            status: Status::ACTIVE,
+            statuses: Some(vec![Status::ACTIVE, Status::Other("DELETED".to_owned())]),
+            metadata: serde_json::Value::Null,
+        },
+        status: Some(Status::Other("PENDING".to_owned())),
+    };
+
+    assert_eq!(
+        variables.validate(),
+        Err(vec![
+            ValidationError {
+                path: "filter.metadata".to_owned(),
+                message: "A non-null value is null.".to_owned(),
+            },
+            ValidationError {
+                path: "filter.statuses[1]".to_owned(),
+                message: "`DELETED` is not a value of the `Status` enum.".to_owned(),
+            },
+            ValidationError {
+                path: "status".to_owned(),
+                message: "`PENDING` is not a value of the `Status` enum.".to_owned(),
+            },
+        ])
+    );
+}
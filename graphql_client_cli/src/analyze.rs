@@ -0,0 +1,133 @@
+use failure::*;
+use graphql_client_codegen::{ComplexityOptions, OperationComplexity, SchemaDocument};
+use std::path::PathBuf;
+
+pub(crate) struct AnalyzeParams {
+    pub query_paths: Vec<PathBuf>,
+    pub schema_path: PathBuf,
+    pub list_multipliers: Option<String>,
+    pub default_list_multiplier: Option<u64>,
+}
+
+/// Prints the depth, field count and complexity of the operations of the query documents.
+pub(crate) fn analyze(params: AnalyzeParams) -> Result<(), failure::Error> {
+    let AnalyzeParams {
+        query_paths,
+        schema_path,
+        list_multipliers,
+        default_list_multiplier,
+    } = params;
+
+    let mut options = ComplexityOptions::new();
+    if let Some(list_multipliers) = list_multipliers {
+        options.set_list_multipliers(&list_multipliers)?;
+    }
+    if let Some(default_list_multiplier) = default_list_multiplier {
+        options.set_default_list_multiplier(default_list_multiplier);
+    }
+
+    let schema = SchemaDocument::read(&schema_path)?;
+    let mut reports = Vec::with_capacity(query_paths.len());
+
+    for query_path in query_paths {
+        let query_string = std::fs::read_to_string(&query_path)
+            .with_context(|_| format!("Could not read {}", query_path.display()))?;
+        let operations = schema
+            .complexity(&query_string, &options)
+            .with_context(|_| format!("Analysis failed for {}", query_path.display()))?;
+        reports.push((query_path, operations));
+    }
+
+    print!("{}", report(&reports));
+
+    Ok(())
+}
+
+/// The operations of each document, in a table.
+fn report(reports: &[(PathBuf, Vec<OperationComplexity>)]) -> String {
+    let rows: Vec<[String; 5]> = reports
+        .iter()
+        .flat_map(|(path, operations)| {
+            operations.iter().map(move |operation| {
+                let name = if operation.name.is_empty() {
+                    format!("{} (anonymous)", path.display())
+                } else {
+                    format!("{} {}", path.display(), operation.name)
+                };
+                [
+                    name,
+                    operation.depth.to_string(),
+                    operation.field_count.to_string(),
+                    operation.alias_count.to_string(),
+                    operation.complexity.to_string(),
+                ]
+            })
+        })
+        .collect();
+
+    let header = [
+        "Operation".to_owned(),
+        "Depth".to_owned(),
+        "Fields".to_owned(),
+        "Aliases".to_owned(),
+        "Complexity".to_owned(),
+    ];
+    let width = rows
+        .iter()
+        .chain(Some(&header))
+        .map(|row| row[0].len())
+        .max()
+        .unwrap_or(0);
+
+    std::iter::once(&header)
+        .chain(&rows)
+        .map(|row| {
+            format!(
+                "{:width$}  {:>5}  {:>6}  {:>7}  {:>10}\n",
+                row[0],
+                row[1],
+                row[2],
+                row[3],
+                row[4],
+                width = width
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_lists_the_operations_of_each_document() {
+        let reports = vec![(
+            PathBuf::from("users.graphql"),
+            vec![
+                OperationComplexity {
+                    name: "Users".to_owned(),
+                    depth: 2,
+                    field_count: 3,
+                    alias_count: 0,
+                    complexity: 21,
+                },
+                OperationComplexity {
+                    name: String::new(),
+                    depth: 1,
+                    field_count: 1,
+                    alias_count: 0,
+                    complexity: 1,
+                },
+            ],
+        )];
+
+        assert_eq!(
+            report(&reports),
+            "\
+Operation                  Depth  Fields  Aliases  Complexity
+users.graphql Users            2       3        0          21
+users.graphql (anonymous)      1       1        0           1
+"
+        );
+    }
+}
@@ -1,35 +1,102 @@
+use crate::plugins::{run_plugins, Plugin};
 use failure::*;
-use graphql_client_codegen::{
-    generate_module_token_stream, CodegenMode, GraphQLClientCodegenOptions,
-};
+use graphql_client_codegen::{CodegenMode, GraphQLClientCodegenOptions, SchemaDocument};
+use quote::ToTokens;
 use std::fs::File;
 use std::io::Write as _;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use syn::Token;
 
+#[derive(Clone, Default)]
 pub(crate) struct CliCodegenParams {
-    pub query_path: PathBuf,
+    pub query_paths: Vec<PathBuf>,
     pub schema_path: PathBuf,
     pub selected_operation: Option<String>,
     pub input_derives: Option<String>,
     pub response_derives: Option<String>,
+    pub response_struct_derives: Option<String>,
+    pub response_enum_derives: Option<String>,
     pub deprecation_strategy: Option<String>,
+    pub allowed_deprecations: Option<String>,
+    pub collection_types: Option<String>,
+    pub enum_wire_values: Option<String>,
+    pub input_conversions: Option<String>,
+    pub test_fixtures_path: Option<PathBuf>,
     pub no_formatting: bool,
     pub module_visibility: Option<String>,
+    pub item_visibility: Option<String>,
+    pub scalar_module: Option<String>,
     pub output_directory: Option<PathBuf>,
+    pub module_file: Option<String>,
+    pub crate_name: Option<String>,
+    pub include_query_in_docs: bool,
+    pub pretty_display: bool,
+    pub nested_accessors: bool,
+    pub flatten_wrappers: bool,
+    pub rust_version: Option<String>,
+    pub union_tagging: Option<String>,
+    pub typename_fallback: Option<String>,
+    pub deny_unused: bool,
+    pub enum_rename_all: bool,
+    pub scalar_trait: bool,
+    pub assert_send_sync: bool,
+    pub json_scalars: bool,
+    pub extra_fields: bool,
+    pub typestate_builders: bool,
+    pub register_operations: bool,
+    pub split_files: bool,
+    pub serialize_responses: bool,
+    pub format_agnostic_serde: bool,
+    pub timings: bool,
+    pub typescript: bool,
+    pub type_bindings: bool,
+    pub plugins: Vec<Plugin>,
 }
 
 pub(crate) fn generate_code(params: CliCodegenParams) -> Result<(), failure::Error> {
     let CliCodegenParams {
         input_derives,
         response_derives,
+        response_struct_derives,
+        response_enum_derives,
         deprecation_strategy,
+        allowed_deprecations,
+        collection_types,
+        enum_wire_values,
+        input_conversions,
+        test_fixtures_path,
         no_formatting,
         output_directory,
+        module_file,
+        crate_name,
         module_visibility: _module_visibility,
-        query_path,
+        item_visibility,
+        scalar_module,
+        query_paths,
         schema_path,
         selected_operation,
+        include_query_in_docs,
+        pretty_display,
+        nested_accessors,
+        flatten_wrappers,
+        rust_version,
+        union_tagging,
+        typename_fallback,
+        deny_unused,
+        enum_rename_all,
+        scalar_trait,
+        assert_send_sync,
+        json_scalars,
+        extra_fields,
+        typestate_builders,
+        register_operations,
+        split_files,
+        serialize_responses,
+        format_agnostic_serde,
+        timings,
+        typescript,
+        type_bindings,
+        plugins,
     } = params;
 
     let deprecation_strategy = deprecation_strategy.as_ref().and_then(|s| s.parse().ok());
@@ -55,32 +122,437 @@ pub(crate) fn generate_code(params: CliCodegenParams) -> Result<(), failure::Err
         options.set_response_derives(response_derives);
     }
 
+    if let Some(response_struct_derives) = response_struct_derives {
+        options.set_response_struct_derives(response_struct_derives);
+    }
+
+    if let Some(response_enum_derives) = response_enum_derives {
+        options.set_response_enum_derives(response_enum_derives);
+    }
+
     if let Some(deprecation_strategy) = deprecation_strategy {
         options.set_deprecation_strategy(deprecation_strategy);
     }
 
-    let gen = generate_module_token_stream(query_path.clone(), &schema_path, options)?;
+    if let Some(allowed_deprecations) = allowed_deprecations {
+        options.set_allowed_deprecations(allowed_deprecations);
+    }
+
+    if let Some(collection_types) = collection_types {
+        options.set_collection_types(collection_types);
+    }
+
+    if let Some(enum_wire_values) = enum_wire_values {
+        options.set_enum_wire_values(enum_wire_values);
+    }
+
+    if let Some(input_conversions) = input_conversions {
+        options.set_input_conversions(input_conversions);
+    }
+
+    if let Some(test_fixtures_path) = test_fixtures_path {
+        options.set_test_fixtures_path(test_fixtures_path);
+    }
+
+    if let Some(item_visibility) = item_visibility {
+        let item_visibility: syn::Visibility = syn::parse_str(&item_visibility)
+            .map_err(|err| format_err!("Invalid item visibility: {}", err))?;
+        options.set_item_visibility(item_visibility);
+    }
+
+    // The generated modules of a crate find the custom scalars in its `scalars` module.
+    let scalar_module =
+        scalar_module.or_else(|| crate_name.as_ref().map(|_| "crate::scalars".to_owned()));
+    if let Some(scalar_module) = scalar_module {
+        let scalar_module: syn::Path = syn::parse_str(&scalar_module)
+            .map_err(|err| format_err!("Invalid scalar module: {}", err))?;
+        options.set_scalar_module(scalar_module);
+    }
+
+    options.set_include_query_in_docs(include_query_in_docs);
+    options.set_pretty_display(pretty_display);
+    options.set_nested_accessors(nested_accessors);
+    options.set_flatten_wrappers(flatten_wrappers);
+    options.set_deny_unused(deny_unused);
+    options.set_enum_rename_all(enum_rename_all);
+    options.set_scalar_trait(scalar_trait);
+    options.set_assert_send_sync(assert_send_sync);
+    options.set_json_scalars(json_scalars);
+    options.set_extra_fields(extra_fields);
+    options.set_typestate_builders(typestate_builders);
+    options.set_register_operations(register_operations);
+    options.set_split_files(split_files);
+    options.set_serialize_responses(serialize_responses);
+    options.set_format_agnostic_serde(format_agnostic_serde);
+    options.set_timings(timings);
+
+    if let Some(rust_version) = rust_version {
+        options.set_rust_version(rust_version.parse()?);
+    }
+
+    if let Some(union_tagging) = union_tagging {
+        options.set_union_tagging(union_tagging.parse()?);
+    }
+
+    if let Some(typename_fallback) = typename_fallback {
+        options.set_typename_fallback(typename_fallback.parse()?);
+    }
+
+    // A crate takes the generated modules in its src directory, declared by its lib.rs.
+    let (output_directory, module_file) = match &crate_name {
+        Some(crate_name) => {
+            let crate_directory = output_directory.ok_or_else(|| {
+                format_err!("A crate can only be written with an output directory")
+            })?;
+            if module_file.is_some() {
+                return Err(format_err!(
+                    "The module file of a crate is always src/lib.rs"
+                ));
+            }
+
+            write_crate_manifest(&crate_directory, crate_name, register_operations)?;
+            let src_directory = crate_directory.join("src");
+            std::fs::create_dir_all(&src_directory)?;
+            (Some(src_directory), Some("lib.rs".to_owned()))
+        }
+        None => (output_directory, module_file),
+    };
+
+    let module_file = match (module_file, &output_directory) {
+        (Some(module_file), Some(output_directory)) => Some(output_directory.join(module_file)),
+        (Some(_), None) => {
+            return Err(format_err!(
+                "A module file can only be written with an output directory"
+            ))
+        }
+        (None, _) => None,
+    };
+
+    // The schema is only parsed once for all the query documents.
+    let schema = SchemaDocument::read(&schema_path)?;
 
-    let generated_code = gen.to_string();
-    let generated_code = if cfg!(feature = "rustfmt") && !no_formatting {
-        format(&generated_code)
+    let mut dest_file_paths: Vec<PathBuf> = Vec::with_capacity(query_paths.len());
+
+    for query_path in query_paths {
+        let dest_file_path =
+            dest_file_path(&query_path, output_directory.as_ref().map(PathBuf::as_path))?;
+        // The files of a split module go to a directory named after the query document.
+        let dest_file_path = if split_files {
+            dest_file_path.with_extension("")
+        } else {
+            dest_file_path
+        };
+        if dest_file_paths.contains(&dest_file_path) {
+            return Err(format_err!(
+                "Several query documents would be generated to {}",
+                dest_file_path.display()
+            ));
+        }
+
+        let gen = schema
+            .generate_module_token_stream(&query_path, &options)
+            .with_context(|_| format!("Code generation failed for {}", query_path.display()))?;
+        let files = if split_files {
+            std::fs::create_dir_all(&dest_file_path)?;
+            split_module(syn::parse2(gen)?)?
+                .into_iter()
+                .map(|(name, code)| (dest_file_path.join(name), code))
+                .collect()
+        } else {
+            vec![(dest_file_path.clone(), gen.to_string())]
+        };
+
+        for (path, generated_code) in files {
+            let generated_code = run_plugins(&plugins, &query_path, &path, generated_code)
+                .with_context(|_| format!("A plugin failed for {}", query_path.display()))?;
+            let generated_code = if cfg!(feature = "rustfmt") && !no_formatting {
+                format(&generated_code)
+            } else {
+                generated_code
+            };
+
+            let mut file = File::create(&path)?;
+            write!(file, "{}", generated_code)?;
+        }
+        if typescript || type_bindings {
+            let query_string = std::fs::read_to_string(&query_path)
+                .with_context(|_| format!("Could not read {}", query_path.display()))?;
+            if typescript {
+                let declarations = schema.typescript(&query_string).with_context(|_| {
+                    format!("TypeScript generation failed for {}", query_path.display())
+                })?;
+                std::fs::write(dest_file_path.with_extension("d.ts"), declarations)?;
+            }
+            if type_bindings {
+                let bindings = schema
+                    .type_bindings(&query_string, &options)
+                    .with_context(|_| format!("Binding failed for {}", query_path.display()))?;
+                std::fs::write(
+                    dest_file_path.with_extension("bindings.json"),
+                    serde_json::to_string_pretty(&bindings)?,
+                )?;
+            }
+        }
+        dest_file_paths.push(dest_file_path);
+    }
+
+    if let Some(module_file) = module_file {
+        if dest_file_paths.contains(&module_file) {
+            return Err(format_err!(
+                "The module file {} would overwrite generated code",
+                module_file.display()
+            ));
+        }
+
+        let mut declarations = module_declarations(&dest_file_paths)?;
+        if crate_name.is_some() {
+            declarations = format!(
+                "{}{}",
+                crate_scalars(&module_file, &dest_file_paths)?,
+                declarations
+            );
+        }
+
+        let mut file = File::create(&module_file)?;
+        write!(file, "{}", declarations)?;
+    }
+
+    Ok(())
+}
+
+/// The declarations of the modules generated to the given files, or directories for split modules,
+/// each re-exported by the module file declaring them.
+fn module_declarations(dest_file_paths: &[PathBuf]) -> Result<String, failure::Error> {
+    let mut dest_file_paths: Vec<&PathBuf> = dest_file_paths.iter().collect();
+    dest_file_paths.sort();
+
+    let mut declarations = String::new();
+    for path in dest_file_paths {
+        let (name, file_name) = match (
+            path.file_stem().and_then(std::ffi::OsStr::to_str),
+            path.file_name().and_then(std::ffi::OsStr::to_str),
+        ) {
+            (Some(name), Some(file_name)) => (name, file_name),
+            _ => {
+                return Err(format_err!(
+                    "Invalid generated file name: {}",
+                    path.display()
+                ))
+            }
+        };
+
+        // Query documents are often named in kebab-case.
+        let module_name: String = name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        if syn::parse_str::<syn::Ident>(&module_name).is_err() {
+            return Err(format_err!("Invalid module name: {}", module_name));
+        }
+
+        if module_name != name {
+            // The directories of split modules have no extension.
+            let module_path = match path.extension() {
+                Some(_) => file_name.to_owned(),
+                None => format!("{}/mod.rs", file_name),
+            };
+            declarations.push_str(&format!("#[path = \"{}\"]\n", module_path));
+        }
+        declarations.push_str(&format!(
+            "pub mod {name};\npub use self::{name}::*;\n",
+            name = module_name
+        ));
+    }
+
+    Ok(declarations)
+}
+
+/// Writes the Cargo.toml of a crate of generated code, unless the crate already has one, so its
+/// manifest can be edited, for example to add the dependencies of the custom scalars.
+fn write_crate_manifest(
+    crate_directory: &Path,
+    crate_name: &str,
+    register_operations: bool,
+) -> Result<(), failure::Error> {
+    if crate_name.is_empty()
+        || !crate_name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format_err!("Invalid crate name: {}", crate_name));
+    }
+
+    let manifest_path = crate_directory.join("Cargo.toml");
+    if manifest_path.exists() {
+        return Ok(());
+    }
+
+    // The registry of the operations is behind a feature of graphql_client.
+    let features = if register_operations {
+        ", features = [\"registry\"]"
     } else {
-        generated_code
+        ""
     };
+    std::fs::create_dir_all(crate_directory)?;
+    std::fs::write(
+        manifest_path,
+        format!(
+            "[package]\n\
+             name = \"{name}\"\n\
+             version = \"0.1.0\"\n\
+             edition = \"2018\"\n\
+             \n\
+             [dependencies]\n\
+             graphql_client = {{ version = \"{version}\"{features} }}\n\
+             serde = {{ version = \"^1.0\", features = [\"derive\"] }}\n",
+            name = crate_name,
+            version = env!("CARGO_PKG_VERSION"),
+            features = features,
+        ),
+    )?;
+    Ok(())
+}
+
+/// The declaration of the `scalars` module of a crate of generated code, next to its `lib.rs`,
+/// where the generated modules find the custom scalars. The module is only written when it is
+/// missing, since it is edited to map the scalars to Rust types.
+fn crate_scalars(
+    module_file: &Path,
+    dest_file_paths: &[PathBuf],
+) -> Result<String, failure::Error> {
+    let scalars_path = module_file.with_file_name("scalars.rs");
+    if dest_file_paths.contains(&scalars_path) {
+        return Err(format_err!(
+            "The generated code would overwrite the scalars of the crate, {}",
+            scalars_path.display()
+        ));
+    }
+
+    if !scalars_path.exists() {
+        std::fs::write(
+            &scalars_path,
+            "//! The Rust types of the custom scalars of the schema, like `pub type DateTime = String;`.\n",
+        )?;
+    }
+
+    Ok("pub mod scalars;\npub use self::scalars::*;\n".to_owned())
+}
+
+/// The submodules of the generated code that are written to their own files.
+const SPLIT_MODULES: &[&str] = &["types", "enums", "inputs", "operations"];
+
+/// Splits the generated module into files: `mod.rs` with the items of the module, and one file per
+/// submodule grouping the generated types.
+fn split_module(generated: syn::File) -> Result<Vec<(String, String)>, failure::Error> {
+    let module = generated
+        .items
+        .into_iter()
+        .filter_map(|item| match item {
+            syn::Item::Mod(module) => Some(module),
+            _ => None,
+        })
+        .next()
+        .ok_or_else(|| format_err!("The generated code has no module to split."))?;
+    let (_, items) = module
+        .content
+        .ok_or_else(|| format_err!("The generated module has no content."))?;
+
+    let mut files = Vec::new();
+    let mut module_items = Vec::with_capacity(items.len());
+
+    for item in items {
+        match item {
+            syn::Item::Mod(mut submodule)
+                if SPLIT_MODULES.contains(&submodule.ident.to_string().as_str()) =>
+            {
+                if let Some((_, items)) = submodule.content.take() {
+                    let file = syn::File {
+                        shebang: None,
+                        attrs: Vec::new(),
+                        items,
+                    };
+                    files.push((
+                        format!("{}.rs", submodule.ident),
+                        file.into_token_stream().to_string(),
+                    ));
+                }
+                submodule.semi = Some(Default::default());
+                module_items.push(syn::Item::Mod(submodule));
+            }
+            item => module_items.push(item),
+        }
+    }
+
+    // The inner attributes of the module, like its documentation, become those of `mod.rs`.
+    let file = syn::File {
+        shebang: None,
+        attrs: module
+            .attrs
+            .into_iter()
+            .filter(|attr| match attr.style {
+                syn::AttrStyle::Inner(_) => true,
+                syn::AttrStyle::Outer => false,
+            })
+            .collect(),
+        items: module_items,
+    };
+    files.push(("mod.rs".to_owned(), file.into_token_stream().to_string()));
+
+    Ok(files)
+}
+
+/// The query documents at the given path: the path itself for a file, all the `.graphql` and
+/// `.gql` files under it for a directory, and the documents it matches for a glob.
+pub(crate) fn query_documents(path: PathBuf) -> Result<Vec<PathBuf>, failure::Error> {
+    if path.is_file() {
+        return Ok(vec![path]);
+    }
+
+    let pattern = if path.is_dir() {
+        path.join("**").join("*")
+    } else {
+        path
+    };
+    let pattern = pattern
+        .to_str()
+        .ok_or_else(|| format_err!("Invalid query path: {}", pattern.display()))?;
+
+    let mut paths = Vec::new();
+    for path in glob::glob(pattern)? {
+        let path = path?;
+        if is_query_document(&path) {
+            paths.push(path);
+        }
+    }
+
+    if paths.is_empty() {
+        return Err(format_err!("No query documents found at {}", pattern));
+    }
+
+    Ok(paths)
+}
 
+/// Whether the path is a `.graphql` or `.gql` file.
+pub(crate) fn is_query_document(path: &Path) -> bool {
+    path.extension()
+        .and_then(std::ffi::OsStr::to_str)
+        .map(|extension| extension == "graphql" || extension == "gql")
+        .unwrap_or(false)
+}
+
+/// Where the code generated from a query document goes: next to it, or in the output directory.
+fn dest_file_path(
+    query_path: &Path,
+    output_directory: Option<&Path>,
+) -> Result<PathBuf, failure::Error> {
     let query_file_name: ::std::ffi::OsString = query_path
         .file_name()
         .map(ToOwned::to_owned)
         .ok_or_else(|| format_err!("Failed to find a file name in the provided query path."))?;
 
-    let dest_file_path: PathBuf = output_directory
+    Ok(output_directory
         .map(|output_dir| output_dir.join(query_file_name).with_extension("rs"))
-        .unwrap_or_else(move || query_path.with_extension("rs"));
-
-    let mut file = File::create(dest_file_path)?;
-    write!(file, "{}", generated_code)?;
-
-    Ok(())
+        .unwrap_or_else(|| query_path.with_extension("rs")))
 }
 
 #[allow(unused_variables)]
@@ -105,3 +577,151 @@ fn format(codes: &str) -> String {
     #[cfg(not(feature = "rustfmt"))]
     unreachable!()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_documents_in_a_directory() -> Result<(), failure::Error> {
+        let dir = std::env::temp_dir().join(format!("graphql-client-cli-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("nested"))?;
+        for file in &["a.graphql", "nested/b.gql", "notes.txt"] {
+            std::fs::write(dir.join(file), "")?;
+        }
+
+        let mut documents = query_documents(dir.clone())?;
+        documents.sort();
+        assert_eq!(
+            documents,
+            vec![dir.join("a.graphql"), dir.join("nested").join("b.gql")]
+        );
+        assert_eq!(
+            query_documents(dir.join("*.graphql"))?,
+            vec![dir.join("a.graphql")]
+        );
+        assert!(query_documents(dir.join("*.json")).is_err());
+
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn split_module_files() -> Result<(), failure::Error> {
+        use graphql_client_codegen::SchemaFormat;
+
+        let schema = SchemaDocument::parse(
+            r#"
+            schema { query: Query }
+            enum Color { RED, BLUE }
+            input Filter { color: Color }
+            type Query { paint(filter: Filter): Color }
+            "#,
+            SchemaFormat::GraphQL,
+        )?;
+        let mut options = GraphQLClientCodegenOptions::new(CodegenMode::Cli);
+        options.set_split_files(true);
+        let generated = schema.generate_module_token_stream_from_str(
+            "query Paint($filter: Filter) { paint(filter: $filter) }",
+            &options,
+        )?;
+
+        let files = split_module(syn::parse2(generated)?)?;
+        let file = |name: &str| {
+            files
+                .iter()
+                .find(|(file_name, _)| file_name == name)
+                .map(|(_, code)| code.as_str())
+                .unwrap()
+        };
+
+        assert!(file("mod.rs").contains("pub mod enums ;"));
+        assert!(file("mod.rs").contains("pub const QUERY"));
+        assert!(file("enums.rs").contains("pub enum Color"));
+        assert!(file("inputs.rs").contains("pub struct Filter"));
+        assert!(file("types.rs").contains("pub struct ResponseData"));
+        assert!(file("operations.rs").contains("pub struct Paint"));
+        Ok(())
+    }
+
+    #[test]
+    fn crate_manifest_and_scalars() -> Result<(), failure::Error> {
+        let dir = std::env::temp_dir().join(format!("graphql-client-crate-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("src"))?;
+
+        write_crate_manifest(&dir, "myapi-graphql", true)?;
+        let manifest = std::fs::read_to_string(dir.join("Cargo.toml"))?;
+        assert!(manifest.contains("name = \"myapi-graphql\""));
+        assert!(manifest.contains("features = [\"registry\"]"));
+        assert!(write_crate_manifest(&dir, "myapi graphql", false).is_err());
+
+        // The manifest and the scalars are kept when the crate is generated again.
+        std::fs::write(dir.join("Cargo.toml"), "edited")?;
+        write_crate_manifest(&dir, "myapi-graphql", false)?;
+        assert_eq!(std::fs::read_to_string(dir.join("Cargo.toml"))?, "edited");
+
+        let lib = dir.join("src").join("lib.rs");
+        assert_eq!(
+            crate_scalars(&lib, &[dir.join("src").join("users.rs")])?,
+            "pub mod scalars;\npub use self::scalars::*;\n"
+        );
+        assert!(dir.join("src").join("scalars.rs").exists());
+        assert!(crate_scalars(&lib, &[dir.join("src").join("scalars.rs")]).is_err());
+
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn crate_modules_import_the_custom_scalars() -> Result<(), failure::Error> {
+        let dir = std::env::temp_dir().join(format!(
+            "graphql-client-crate-scalars-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir)?;
+        std::fs::write(
+            dir.join("schema.graphql"),
+            "schema { query: Query }\nscalar DateTime\ntype Query { now: DateTime! }\n",
+        )?;
+        std::fs::write(dir.join("now.graphql"), "query Now { now }\n")?;
+
+        generate_code(CliCodegenParams {
+            query_paths: vec![dir.join("now.graphql")],
+            schema_path: dir.join("schema.graphql"),
+            no_formatting: true,
+            output_directory: Some(dir.join("api")),
+            crate_name: Some("api".to_owned()),
+            ..Default::default()
+        })?;
+
+        let generated = std::fs::read_to_string(dir.join("api").join("src").join("now.rs"))?;
+        assert!(generated.contains("use crate :: scalars :: * ;"));
+        assert!(!generated.contains("super :: DateTime"));
+        assert!(std::fs::read_to_string(dir.join("api").join("src").join("lib.rs"))?
+            .starts_with("pub mod scalars;\n"));
+
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn module_declarations_for_generated_files() -> Result<(), failure::Error> {
+        let declarations = module_declarations(&[
+            PathBuf::from("generated/users.rs"),
+            PathBuf::from("generated/all-repositories.rs"),
+            PathBuf::from("generated/issues"),
+            PathBuf::from("generated/open-issues"),
+        ])?;
+
+        assert_eq!(
+            declarations,
+            "#[path = \"all-repositories.rs\"]\n\
+             pub mod all_repositories;\npub use self::all_repositories::*;\n\
+             pub mod issues;\npub use self::issues::*;\n\
+             #[path = \"open-issues/mod.rs\"]\n\
+             pub mod open_issues;\npub use self::open_issues::*;\n\
+             pub mod users;\npub use self::users::*;\n"
+        );
+        Ok(())
+    }
+}
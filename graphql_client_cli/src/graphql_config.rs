@@ -0,0 +1,275 @@
+//! Support for the [graphql-config](https://graphql-config.com) files used by the JavaScript
+//! tooling, so the CLI shares the schema, documents and endpoints of a project with its editor and
+//! linting setup.
+
+use failure::{format_err, ResultExt};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// The names of the configuration files, in the order they are looked up in a directory. The
+/// `.graphqlconfig` files use the legacy format.
+const CONFIG_FILE_NAMES: &[&str] = &[
+    "graphql.config.json",
+    "graphql.config.yaml",
+    "graphql.config.yml",
+    ".graphqlrc",
+    ".graphqlrc.json",
+    ".graphqlrc.yaml",
+    ".graphqlrc.yml",
+    ".graphqlconfig",
+    ".graphqlconfig.yaml",
+    ".graphqlconfig.yml",
+];
+
+/// The contents of a graphql-config file, in either the current or the legacy format. YAML being
+/// a superset of JSON, both are read with the YAML parser.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawConfig {
+    schema: Option<OneOrMany>,
+    documents: Option<OneOrMany>,
+    /// The schema, in the legacy format.
+    schema_path: Option<String>,
+    /// The documents, in the legacy format.
+    includes: Option<Vec<String>>,
+    #[serde(default)]
+    extensions: RawExtensions,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum OneOrMany {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl OneOrMany {
+    fn into_vec(self) -> Vec<String> {
+        match self {
+            OneOrMany::One(item) => vec![item],
+            OneOrMany::Many(items) => items,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct RawExtensions {
+    #[serde(default)]
+    endpoints: BTreeMap<String, RawEndpoint>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum RawEndpoint {
+    Url(String),
+    Full {
+        url: String,
+        #[serde(default)]
+        headers: BTreeMap<String, String>,
+    },
+}
+
+/// A GraphQL endpoint defined in the configuration.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Endpoint {
+    pub(crate) url: String,
+    pub(crate) headers: BTreeMap<String, String>,
+}
+
+/// The configuration of a project, from a graphql-config file.
+#[derive(Debug)]
+pub(crate) struct GraphQLConfig {
+    /// The directory of the configuration file, which the paths are relative to.
+    root: PathBuf,
+    /// The schema pointers: local files or URLs.
+    schema: Vec<String>,
+    /// The globs matching the query documents.
+    documents: Vec<String>,
+    /// The endpoint named `default`, or the first one.
+    endpoint: Option<Endpoint>,
+}
+
+impl GraphQLConfig {
+    /// Looks for a configuration file in the current directory and its ancestors.
+    pub(crate) fn find() -> Result<Option<GraphQLConfig>, failure::Error> {
+        let current_dir = std::env::current_dir()?;
+
+        for dir in current_dir.ancestors() {
+            for file_name in CONFIG_FILE_NAMES {
+                let path = dir.join(file_name);
+                if path.is_file() {
+                    return GraphQLConfig::read(&path).map(Some);
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Reads the configuration file at the given path.
+    pub(crate) fn read(path: &Path) -> Result<GraphQLConfig, failure::Error> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|_| format!("Could not read {}", path.display()))?;
+        let root = path
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(PathBuf::new);
+
+        GraphQLConfig::parse(root, &contents)
+            .with_context(|_| format!("Invalid GraphQL configuration in {}", path.display()))
+            .map_err(Into::into)
+    }
+
+    fn parse(root: PathBuf, contents: &str) -> Result<GraphQLConfig, failure::Error> {
+        let raw: RawConfig = serde_yaml::from_str(contents)?;
+
+        let schema = match (raw.schema, raw.schema_path) {
+            (Some(schema), _) => schema.into_vec(),
+            (None, Some(schema_path)) => vec![schema_path],
+            (None, None) => Vec::new(),
+        };
+        let documents = match (raw.documents, raw.includes) {
+            (Some(documents), _) => documents.into_vec(),
+            (None, Some(includes)) => includes,
+            (None, None) => Vec::new(),
+        };
+
+        let mut endpoints = raw.extensions.endpoints;
+        let endpoint = endpoints
+            .remove("default")
+            .or_else(|| endpoints.into_iter().next().map(|(_, endpoint)| endpoint))
+            .map(|endpoint| match endpoint {
+                RawEndpoint::Url(url) => Endpoint {
+                    url,
+                    headers: BTreeMap::new(),
+                },
+                RawEndpoint::Full { url, headers } => Endpoint { url, headers },
+            });
+
+        Ok(GraphQLConfig {
+            root,
+            schema,
+            documents,
+            endpoint,
+        })
+    }
+
+    /// The first local schema file.
+    pub(crate) fn schema_path(&self) -> Option<PathBuf> {
+        self.schema
+            .iter()
+            .find(|pointer| !is_url(pointer))
+            .map(|pointer| self.root.join(pointer))
+    }
+
+    /// The endpoint to introspect: the configured endpoint, or a schema given as a URL.
+    pub(crate) fn endpoint(&self) -> Option<Endpoint> {
+        self.endpoint.clone().or_else(|| {
+            self.schema
+                .iter()
+                .find(|pointer| is_url(pointer))
+                .map(|url| Endpoint {
+                    url: url.clone(),
+                    headers: BTreeMap::new(),
+                })
+        })
+    }
+
+    /// The query documents matched by the document globs.
+    pub(crate) fn document_paths(&self) -> Result<Vec<PathBuf>, failure::Error> {
+        let mut paths = Vec::new();
+
+        for pattern in &self.documents {
+            let pattern = self.root.join(pattern);
+            let pattern = pattern
+                .to_str()
+                .ok_or_else(|| format_err!("Invalid document glob: {}", pattern.display()))?;
+
+            for path in glob::glob(pattern)? {
+                let path = path?;
+                if crate::generate::is_query_document(&path) && !paths.contains(&path) {
+                    paths.push(path);
+                }
+            }
+        }
+
+        Ok(paths)
+    }
+}
+
+fn is_url(pointer: &str) -> bool {
+    pointer.starts_with("http://") || pointer.starts_with("https://")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_the_current_format() {
+        let config = GraphQLConfig::parse(
+            PathBuf::from("project"),
+            r#"
+schema: schema.graphql
+documents:
+  - "src/**/*.graphql"
+extensions:
+  endpoints:
+    default:
+      url: https://example.com/graphql
+      headers:
+        Authorization: Bearer abcd
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.schema_path(),
+            Some(PathBuf::from("project/schema.graphql"))
+        );
+        assert_eq!(config.documents, vec!["src/**/*.graphql".to_string()]);
+        let endpoint = config.endpoint().unwrap();
+        assert_eq!(endpoint.url, "https://example.com/graphql");
+        assert_eq!(endpoint.headers["Authorization"], "Bearer abcd");
+    }
+
+    #[test]
+    fn reads_the_legacy_format() {
+        let config = GraphQLConfig::parse(
+            PathBuf::new(),
+            r#"{
+                "schemaPath": "schema.json",
+                "includes": ["queries/*.graphql"],
+                "extensions": {
+                    "endpoints": {
+                        "dev": "http://localhost:4000/graphql"
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.schema_path(), Some(PathBuf::from("schema.json")));
+        assert_eq!(config.documents, vec!["queries/*.graphql".to_string()]);
+        assert_eq!(
+            config.endpoint(),
+            Some(Endpoint {
+                url: "http://localhost:4000/graphql".to_string(),
+                headers: BTreeMap::new(),
+            })
+        );
+    }
+
+    #[test]
+    fn a_schema_url_is_the_endpoint() {
+        let config =
+            GraphQLConfig::parse(PathBuf::new(), "schema: https://example.com/graphql").unwrap();
+
+        assert_eq!(config.schema_path(), None);
+        assert_eq!(
+            config.endpoint().unwrap().url,
+            "https://example.com/graphql"
+        );
+    }
+}
@@ -13,11 +13,24 @@ use std::str::FromStr;
 #[allow(dead_code)]
 struct IntrospectionQuery;
 
+/// The introspection query, also asking for the schema description, the `specifiedByURL` of the
+/// scalars, whether the directives are repeatable and the deprecated arguments and input fields.
+/// These are recent additions to the specification, so older servers reject it.
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/graphql/introspection_schema.graphql",
+    query_path = "src/graphql/extended_introspection_query.graphql",
+    response_derives = "Serialize"
+)]
+#[allow(dead_code)]
+struct ExtendedIntrospectionQuery;
+
 pub fn introspect_schema(
     location: &str,
     output: Option<PathBuf>,
     authorization: Option<String>,
     headers: Vec<Header>,
+    extended: bool,
 ) -> Result<(), failure::Error> {
     use std::io::Write;
 
@@ -26,10 +39,10 @@ pub fn introspect_schema(
         None => Box::new(::std::io::stdout()),
     };
 
-    let request_body: graphql_client::QueryBody<()> = graphql_client::QueryBody {
-        variables: (),
-        query: introspection_query::QUERY,
-        operation_name: introspection_query::OPERATION_NAME,
+    let request_body: graphql_client::QueryBody<()> = if extended {
+        ExtendedIntrospectionQuery::build_query(())
+    } else {
+        IntrospectionQuery::build_query(())
     };
 
     let client = reqwest::Client::new();
@@ -0,0 +1,198 @@
+//! The limits on the operations declared in the `graphql-client.toml` file of a project, checked
+//! by `graphql-client lint` before the operations reach a server enforcing them.
+
+use failure::{format_err, ResultExt};
+use graphql_client_codegen::{ComplexityOptions, OperationComplexity, SchemaDocument};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// The name of the configuration file, looked up in the current directory and its ancestors.
+const CONFIG_FILE_NAME: &str = "graphql-client.toml";
+
+/// The `graphql-client.toml` file.
+#[derive(Debug, Default, Deserialize)]
+struct ClientConfig {
+    #[serde(default)]
+    lint: LintRules,
+}
+
+/// The `[lint]` table of the configuration file.
+#[derive(Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub(crate) struct LintRules {
+    max_depth: Option<usize>,
+    max_complexity: Option<u64>,
+    max_aliases: Option<usize>,
+    /// The size of the lists returned by fields, by schema coordinate, for the complexity.
+    #[serde(default)]
+    list_multipliers: BTreeMap<String, u64>,
+    default_list_multiplier: Option<u64>,
+}
+
+impl LintRules {
+    /// Reads the rules of the configuration file at the given path, or of the one in the current
+    /// directory or its ancestors. No configuration file means no rules.
+    pub(crate) fn find(config_path: Option<PathBuf>) -> Result<LintRules, failure::Error> {
+        if let Some(config_path) = config_path {
+            return LintRules::read(&config_path);
+        }
+
+        let current_dir = std::env::current_dir()?;
+        for dir in current_dir.ancestors() {
+            let path = dir.join(CONFIG_FILE_NAME);
+            if path.is_file() {
+                return LintRules::read(&path);
+            }
+        }
+
+        Ok(LintRules::default())
+    }
+
+    fn read(path: &Path) -> Result<LintRules, failure::Error> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|_| format!("Could not read {}", path.display()))?;
+
+        LintRules::parse(&contents)
+            .with_context(|_| format!("Invalid configuration in {}", path.display()))
+            .map_err(Into::into)
+    }
+
+    fn parse(contents: &str) -> Result<LintRules, failure::Error> {
+        let config: ClientConfig = toml::from_str(contents)?;
+        Ok(config.lint)
+    }
+
+    fn complexity_options(&self) -> ComplexityOptions {
+        let mut options = ComplexityOptions::new();
+        for (coordinate, multiplier) in &self.list_multipliers {
+            options.set_list_multiplier(coordinate.as_str(), *multiplier);
+        }
+        if let Some(default_list_multiplier) = self.default_list_multiplier {
+            options.set_default_list_multiplier(default_list_multiplier);
+        }
+        options
+    }
+
+    /// How the operation breaks the rules.
+    fn violations(&self, operation: &OperationComplexity) -> Vec<String> {
+        let mut violations = Vec::new();
+
+        if let Some(max_depth) = self.max_depth.filter(|max| operation.depth > *max) {
+            violations.push(format!(
+                "depth {} exceeds max-depth {}",
+                operation.depth, max_depth
+            ));
+        }
+        if let Some(max_complexity) = self
+            .max_complexity
+            .filter(|max| operation.complexity > *max)
+        {
+            violations.push(format!(
+                "complexity {} exceeds max-complexity {}",
+                operation.complexity, max_complexity
+            ));
+        }
+        if let Some(max_aliases) = self.max_aliases.filter(|max| operation.alias_count > *max) {
+            violations.push(format!(
+                "{} aliases exceed max-aliases {}",
+                operation.alias_count, max_aliases
+            ));
+        }
+
+        violations
+    }
+}
+
+/// Checks the operations of the query documents against the rules, failing with the list of the
+/// operations breaking them.
+pub(crate) fn lint(
+    query_paths: Vec<PathBuf>,
+    schema_path: PathBuf,
+    rules: LintRules,
+) -> Result<(), failure::Error> {
+    let options = rules.complexity_options();
+    let schema = SchemaDocument::read(&schema_path)?;
+    let mut violations = Vec::new();
+
+    for query_path in query_paths {
+        let query_string = std::fs::read_to_string(&query_path)
+            .with_context(|_| format!("Could not read {}", query_path.display()))?;
+        let operations = schema
+            .complexity(&query_string, &options)
+            .with_context(|_| format!("Analysis failed for {}", query_path.display()))?;
+
+        for operation in operations {
+            for violation in rules.violations(&operation) {
+                let name = if operation.name.is_empty() {
+                    "(anonymous)"
+                } else {
+                    operation.name.as_str()
+                };
+                violations.push(format!("{} {}: {}", query_path.display(), name, violation));
+            }
+        }
+    }
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(format_err!(
+            "{} lint violation(s):\n{}",
+            violations.len(),
+            violations.join("\n")
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_the_lint_rules() {
+        let rules = LintRules::parse(
+            r#"
+[lint]
+max-depth = 5
+max-complexity = 1000
+max-aliases = 2
+default-list-multiplier = 20
+
+[lint.list-multipliers]
+"Query.users" = 50
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(rules.max_depth, Some(5));
+        assert_eq!(rules.max_complexity, Some(1000));
+        assert_eq!(rules.max_aliases, Some(2));
+        let options = rules.complexity_options();
+        assert_eq!(options.list_multiplier("Query.users"), Some(50));
+        assert_eq!(options.default_list_multiplier(), 20);
+    }
+
+    #[test]
+    fn unknown_rules_are_rejected() {
+        assert!(LintRules::parse("[lint]\nmax-height = 5").is_err());
+        assert_eq!(LintRules::parse("").unwrap(), LintRules::default());
+    }
+
+    #[test]
+    fn operations_over_the_limits_are_violations() {
+        let rules = LintRules::parse("[lint]\nmax-depth = 3\nmax-aliases = 1").unwrap();
+        let operation = OperationComplexity {
+            name: "Users".to_owned(),
+            depth: 4,
+            field_count: 10,
+            alias_count: 1,
+            complexity: 5000,
+        };
+
+        assert_eq!(
+            rules.violations(&operation),
+            vec!["depth 4 exceeds max-depth 3".to_owned()]
+        );
+    }
+}
@@ -4,8 +4,15 @@ use log::Level;
 #[cfg(feature = "rustfmt")]
 extern crate rustfmt_nightly as rustfmt;
 
+mod analyze;
 mod generate;
+mod graphql_config;
 mod introspect_schema;
+mod lint;
+mod plugins;
+use failure::format_err;
+use graphql_config::GraphQLConfig;
+use std::env;
 use std::path::PathBuf;
 use structopt::StructOpt;
 
@@ -14,27 +21,42 @@ enum Cli {
     /// Get the schema from a live GraphQL API. The schema is printed to stdout.
     #[structopt(name = "introspect-schema")]
     IntrospectSchema {
-        /// The URL of a GraphQL endpoint to introspect.
-        schema_location: String,
+        /// The URL of a GraphQL endpoint to introspect. Defaults to the GRAPHQL_ENDPOINT
+        /// environment variable, then to the endpoint of the graphql-config file of the project.
+        schema_location: Option<String>,
         /// Where to write the JSON for the introspected schema.
         #[structopt(parse(from_os_str))]
         #[structopt(long = "output")]
         output: Option<PathBuf>,
-        /// Set the contents of the Authorizaiton header.
+        /// Set the contents of the Authorizaiton header. Defaults to the GRAPHQL_TOKEN environment
+        /// variable.
         #[structopt(long = "authorization")]
         authorization: Option<String>,
+        /// Read the environment variables from this file instead of the .env file of the current
+        /// directory.
+        #[structopt(parse(from_os_str))]
+        #[structopt(long = "env-file")]
+        env_file: Option<PathBuf>,
         /// Specify custom headers.
         /// --header 'X-Name: Value'
         #[structopt(long = "header")]
         headers: Vec<introspect_schema::Header>,
+        /// Also ask for the schema description, the specifiedByURL of the scalars, whether the
+        /// directives are repeatable and the deprecated arguments and input fields, which older
+        /// servers do not support.
+        #[structopt(long = "extended")]
+        extended: bool,
     },
     #[structopt(name = "generate")]
     Generate {
-        /// Path to GraphQL schema file (.json or .graphql).
+        /// Path to GraphQL schema file (.json or .graphql). Defaults to the schema of the
+        /// graphql-config file of the project.
         #[structopt(short = "s", long = "schema-path")]
-        schema_path: PathBuf,
-        /// Path to the GraphQL query file.
-        query_path: PathBuf,
+        schema_path: Option<PathBuf>,
+        /// Path to the GraphQL query file, or to a directory or a glob of query files, which share
+        /// the parsed schema. Defaults to all the documents of the graphql-config file of the
+        /// project.
+        query_path: Option<PathBuf>,
         /// Name of target query. If you don't set this parameter, cli generate all queries in query file.
         #[structopt(long = "selected-operation")]
         selected_operation: Option<String>,
@@ -46,10 +68,38 @@ enum Cli {
         /// --response-derives='Debug'
         #[structopt(short = "r", long = "response-derives")]
         response_derives: Option<String>,
+        /// Derives for the generated response structs, replacing the response derives for them.
+        /// --response-struct-derives='Debug,PartialEq'
+        #[structopt(long = "response-struct-derives")]
+        response_struct_derives: Option<String>,
+        /// Derives for the generated enums, replacing the response derives for them.
+        /// --response-enum-derives='Debug,Hash'
+        #[structopt(long = "response-enum-derives")]
+        response_enum_derives: Option<String>,
         /// You can choose deprecation strategy from allow, deny, or warn.
         /// Default value is warn.
         #[structopt(short = "d", long = "deprecation-strategy")]
         deprecation_strategy: Option<String>,
+        /// Deprecated fields exempted from the deny deprecation strategy.
+        /// --allowed-deprecations='User.login,Query.legacyUsers'
+        #[structopt(long = "allowed-deprecations")]
+        allowed_deprecations: Option<String>,
+        /// Containers for the lists instead of Vec, by the GraphQL type of their items.
+        /// --collection-types='ID = std::collections::BTreeSet, * = im::Vector'
+        #[structopt(long = "collection-types")]
+        collection_types: Option<String>,
+        /// Wire values of the enums the backend does not (de)serialize like the schema.
+        /// --enum-wire-values='Episode = integer, Color = lowercase, Color.DARK_BLUE = navy'
+        #[structopt(long = "enum-wire-values")]
+        enum_wire_values: Option<String>,
+        /// Response objects to convert to input objects, by their dotted path in the response.
+        /// --input-conversions='user = UserInput, user.address = AddressInput'
+        #[structopt(long = "input-conversions")]
+        input_conversions: Option<String>,
+        /// The directory of the stored responses, in subdirectories named after the operations,
+        /// to generate tests checking that they still deserialize. Relative to the crate root.
+        #[structopt(long = "test-fixtures-path")]
+        test_fixtures_path: Option<PathBuf>,
         /// If you don't want to execute rustfmt to generated code, set this option.
         /// Default value is false.
         /// Formating feature is disabled as default installation.
@@ -59,12 +109,164 @@ enum Cli {
         /// Default value is pub.
         #[structopt(short = "m", long = "module-visibility")]
         module_visibility: Option<String>,
+        /// The visibility of the generated structs and enums inside the modules, for example
+        /// `pub(crate)`. Default value is pub.
+        #[structopt(long = "item-visibility")]
+        item_visibility: Option<String>,
+        /// The module defining the custom scalar types, for example `crate::scalars`, to import in
+        /// the generated modules instead of aliasing the scalars from their parent module.
+        #[structopt(long = "scalar-module")]
+        scalar_module: Option<String>,
         /// The directory in which the code will be generated.
         ///
         /// If this option is omitted, the code will be generated next to the .graphql
         /// file, with the same name and the .rs extension.
         #[structopt(short = "o", long = "output-directory")]
         output_directory: Option<PathBuf>,
+        /// Also write a module file, like `mod.rs` or `lib.rs`, to the output directory, declaring
+        /// and re-exporting all the generated modules.
+        #[structopt(long = "module-file")]
+        module_file: Option<String>,
+        /// Write a standalone crate with this name to the output directory: a Cargo.toml, and the
+        /// generated modules in its src directory, declared in src/lib.rs. The custom scalars are
+        /// defined in src/scalars.rs, which is kept when the crate is generated again.
+        #[structopt(long = "crate-name")]
+        crate_name: Option<String>,
+        /// Include the text of the query in the documentation of the generated modules.
+        #[structopt(long = "include-query-in-docs")]
+        include_query_in_docs: bool,
+        /// Generate a `Display` implementation writing the response types as an indented tree of
+        /// the selected fields.
+        #[structopt(long = "pretty-display")]
+        pretty_display: bool,
+        /// Generate accessors on `ResponseData` returning iterators over nested selections,
+        /// flattening the optional and list types on the way.
+        #[structopt(long = "nested-accessors")]
+        nested_accessors: bool,
+        /// Flatten the fields selecting a single field on an object into their parent struct.
+        #[structopt(long = "flatten-wrappers")]
+        flatten_wrappers: bool,
+        /// The oldest Rust version the generated code has to compile with, e.g. 1.31. Items
+        /// needing a more recent compiler are left out.
+        #[structopt(long = "rust-version")]
+        rust_version: Option<String>,
+        /// How the enums of the selections on unions and interfaces are represented: `internal`,
+        /// tagged with a `__typename` field (the default), `adjacent` or `adjacent:<key>`, with
+        /// their fields under a `content` or `<key>` field next to `__typename`, or `untagged`.
+        #[structopt(long = "union-tagging")]
+        union_tagging: Option<String>,
+        /// What the enums of the selections on unions and interfaces are deserialized to when a
+        /// response object has no `__typename`: `untagged`, the first variant matching its shape,
+        /// or the name of the type of a default variant.
+        #[structopt(long = "typename-fallback")]
+        typename_fallback: Option<String>,
+        /// Fail on the fragments and variables the query document never uses, instead of generating
+        /// code producing warnings about them.
+        #[structopt(long = "deny-unused")]
+        deny_unused: bool,
+        /// Generate enums with CamelCase variants and a serde rename_all attribute, instead of
+        /// renaming each variant.
+        #[structopt(long = "enum-rename-all")]
+        enum_rename_all: bool,
+        /// (De)serialize the custom scalars through the GraphQLScalar trait of the graphql_client
+        /// crate, instead of their serde implementations.
+        #[structopt(long = "scalar-trait")]
+        scalar_trait: bool,
+        /// Check at compile time that the generated types are Send + Sync + 'static.
+        #[structopt(long = "assert-send-sync")]
+        assert_send_sync: bool,
+        /// Generate the JSON, JSONObject and Json scalars as serde_json::Value, instead of aliasing
+        /// them from the parent module.
+        #[structopt(long = "json-scalars")]
+        json_scalars: bool,
+        /// Keep the fields of the responses that are not selected in an extra map on the response
+        /// structs.
+        #[structopt(long = "extra-fields")]
+        extra_fields: bool,
+        /// Generate typestate builders for the variables and the input objects, with a build()
+        /// method once all the required fields are set.
+        #[structopt(long = "typestate-builders")]
+        typestate_builders: bool,
+        /// Register the operation in the graphql_client registry, which requires its registry
+        /// feature.
+        #[structopt(long = "register-operations")]
+        register_operations: bool,
+        /// Write the code generated for each query document to a directory, with separate
+        /// `types.rs`, `enums.rs`, `inputs.rs` and `operations.rs` files next to a `mod.rs`,
+        /// instead of a single file.
+        #[structopt(long = "split-files")]
+        split_files: bool,
+        /// Also derive Serialize on the response types, serializing them in the same shape as the
+        /// JSON response.
+        #[structopt(long = "serialize-responses")]
+        serialize_responses: bool,
+        /// Generate serde code working with the formats that are not self-describing, like bincode:
+        /// the optional fields are always serialized, and the unions are tagged with the index of
+        /// their variant in these formats.
+        #[structopt(long = "format-agnostic-serde")]
+        format_agnostic_serde: bool,
+        /// Print the time spent parsing the schema and the query documents, binding each
+        /// operation to the schema and generating its code.
+        #[structopt(long = "timings")]
+        timings: bool,
+        /// Also write TypeScript declarations of the variables and the responses of the
+        /// operations, to a .d.ts file next to the generated code.
+        #[structopt(long = "typescript")]
+        typescript: bool,
+        /// Also write the bindings of the fields of the query documents to the generated types, to
+        /// a .bindings.json file next to the generated code, for the editor plugins.
+        #[structopt(long = "type-bindings")]
+        type_bindings: bool,
+        /// A command reading the generated code on stdin and printing the code to write instead.
+        /// It gets the paths of the query document and of the generated file in the
+        /// GRAPHQL_CLIENT_QUERY_PATH and GRAPHQL_CLIENT_OUTPUT_PATH environment variables.
+        /// --plugin 'my-plugin --flag'
+        #[structopt(long = "plugin")]
+        plugins: Vec<String>,
+        /// A command reading a JSON description of the generated items on stdin and printing code
+        /// to append to them. It runs after the --plugin commands, with the same environment
+        /// variables.
+        #[structopt(long = "plugin-json")]
+        json_plugins: Vec<String>,
+    },
+    /// Print the depth, the number of fields and aliases and the complexity score of the operations,
+    /// to keep them under the limits of the server.
+    #[structopt(name = "analyze")]
+    Analyze {
+        /// Path to GraphQL schema file (.json or .graphql). Defaults to the schema of the
+        /// graphql-config file of the project.
+        #[structopt(short = "s", long = "schema-path")]
+        schema_path: Option<PathBuf>,
+        /// Path to the GraphQL query file, or to a directory or a glob of query files. Defaults to
+        /// all the documents of the graphql-config file of the project.
+        query_path: Option<PathBuf>,
+        /// The size of the lists returned by fields, by schema coordinate. The `first` and `last`
+        /// arguments of the fields take precedence, and these multipliers take precedence over the
+        /// `@listSize(assumedSize: ...)` directives of the schema.
+        /// --list-multipliers='Query.users = 50, User.friends = 20'
+        #[structopt(long = "list-multipliers")]
+        list_multipliers: Option<String>,
+        /// The size of the other lists. Default value is 10.
+        #[structopt(long = "default-list-multiplier")]
+        default_list_multiplier: Option<u64>,
+    },
+    /// Check that the operations stay under the max-depth, max-complexity and max-aliases limits
+    /// of the [lint] table of the graphql-client.toml file, failing with the operations exceeding
+    /// them.
+    #[structopt(name = "lint")]
+    Lint {
+        /// Path to GraphQL schema file (.json or .graphql). Defaults to the schema of the
+        /// graphql-config file of the project.
+        #[structopt(short = "s", long = "schema-path")]
+        schema_path: Option<PathBuf>,
+        /// Path to the GraphQL query file, or to a directory or a glob of query files. Defaults to
+        /// all the documents of the graphql-config file of the project.
+        query_path: Option<PathBuf>,
+        /// The configuration file declaring the limits. Defaults to the graphql-client.toml file
+        /// of the current directory or its parents.
+        #[structopt(parse(from_os_str))]
+        #[structopt(long = "config")]
+        config: Option<PathBuf>,
     },
 }
 
@@ -77,32 +279,209 @@ fn main() -> Result<(), failure::Error> {
             schema_location,
             output,
             authorization,
-            headers,
-        } => introspect_schema::introspect_schema(&schema_location, output, authorization, headers),
+            env_file,
+            mut headers,
+            extended,
+        } => {
+            load_env_file(env_file)?;
+            let schema_location = schema_location.or_else(|| env::var("GRAPHQL_ENDPOINT").ok());
+            let authorization = authorization.or_else(|| env::var("GRAPHQL_TOKEN").ok());
+            let schema_location = match schema_location {
+                Some(schema_location) => schema_location,
+                None => {
+                    let endpoint = GraphQLConfig::find()?
+                        .and_then(|config| config.endpoint())
+                        .ok_or_else(|| {
+                            format_err!(
+                                "No schema location, and no endpoint in a graphql-config file"
+                            )
+                        })?;
+                    for (name, value) in endpoint.headers {
+                        headers.push(format!("{}: {}", name, value).parse()?);
+                    }
+                    endpoint.url
+                }
+            };
+            introspect_schema::introspect_schema(
+                &schema_location,
+                output,
+                authorization,
+                headers,
+                extended,
+            )
+        }
         Cli::Generate {
             input_derives,
             response_derives,
+            response_struct_derives,
+            response_enum_derives,
             deprecation_strategy,
+            allowed_deprecations,
+            collection_types,
+            enum_wire_values,
+            input_conversions,
+            test_fixtures_path,
             module_visibility,
+            item_visibility,
+            scalar_module,
             no_formatting,
             output_directory,
+            module_file,
+            crate_name,
             query_path,
             schema_path,
             selected_operation,
-        } => generate::generate_code(generate::CliCodegenParams {
-            input_derives,
-            response_derives,
-            deprecation_strategy,
-            module_visibility,
-            no_formatting,
-            output_directory,
+            include_query_in_docs,
+            pretty_display,
+            nested_accessors,
+            flatten_wrappers,
+            rust_version,
+            union_tagging,
+            typename_fallback,
+            deny_unused,
+            enum_rename_all,
+            scalar_trait,
+            assert_send_sync,
+            json_scalars,
+            extra_fields,
+            typestate_builders,
+            register_operations,
+            split_files,
+            serialize_responses,
+            format_agnostic_serde,
+            timings,
+            typescript,
+            type_bindings,
+            plugins,
+            json_plugins,
+        } => {
+            let (schema_path, query_paths) = project_paths(schema_path, query_path)?;
+            let params = generate::CliCodegenParams {
+                input_derives,
+                response_derives,
+                response_struct_derives,
+                response_enum_derives,
+                deprecation_strategy,
+                allowed_deprecations,
+                collection_types,
+                enum_wire_values,
+                input_conversions,
+                test_fixtures_path,
+                module_visibility,
+                item_visibility,
+                scalar_module,
+                no_formatting,
+                output_directory,
+                module_file,
+                crate_name,
+                query_paths,
+                schema_path,
+                selected_operation,
+                include_query_in_docs,
+                pretty_display,
+                nested_accessors,
+                flatten_wrappers,
+                rust_version,
+                union_tagging,
+                typename_fallback,
+                deny_unused,
+                enum_rename_all,
+                scalar_trait,
+                assert_send_sync,
+                json_scalars,
+                extra_fields,
+                typestate_builders,
+                register_operations,
+                split_files,
+                serialize_responses,
+                format_agnostic_serde,
+                timings,
+                typescript,
+                type_bindings,
+                plugins: plugins
+                    .into_iter()
+                    .map(crate::plugins::Plugin::Code)
+                    .chain(json_plugins.into_iter().map(crate::plugins::Plugin::Items))
+                    .collect(),
+            };
+
+            generate::generate_code(params)
+        }
+        Cli::Analyze {
+            schema_path,
             query_path,
+            list_multipliers,
+            default_list_multiplier,
+        } => {
+            let (schema_path, query_paths) = project_paths(schema_path, query_path)?;
+
+            analyze::analyze(analyze::AnalyzeParams {
+                query_paths,
+                schema_path,
+                list_multipliers,
+                default_list_multiplier,
+            })
+        }
+        Cli::Lint {
             schema_path,
-            selected_operation,
-        }),
+            query_path,
+            config,
+        } => {
+            let rules = lint::LintRules::find(config)?;
+            let (schema_path, query_paths) = project_paths(schema_path, query_path)?;
+
+            lint::lint(query_paths, schema_path, rules)
+        }
     }
 }
 
+/// The schema and the query documents at the given paths, or else in the graphql-config file of
+/// the project.
+fn project_paths(
+    schema_path: Option<PathBuf>,
+    query_path: Option<PathBuf>,
+) -> Result<(PathBuf, Vec<PathBuf>), failure::Error> {
+    let config = if schema_path.is_none() || query_path.is_none() {
+        GraphQLConfig::find()?
+    } else {
+        None
+    };
+    let schema_path = match schema_path {
+        Some(schema_path) => schema_path,
+        None => config
+            .as_ref()
+            .and_then(GraphQLConfig::schema_path)
+            .ok_or_else(|| format_err!("No schema path, and no schema in a graphql-config file"))?,
+    };
+    let query_paths = match (query_path, &config) {
+        (Some(query_path), _) => generate::query_documents(query_path)?,
+        (None, Some(config)) => config.document_paths()?,
+        (None, None) => {
+            return Err(format_err!(
+                "No query path, and no documents in a graphql-config file"
+            ))
+        }
+    };
+
+    Ok((schema_path, query_paths))
+}
+
+/// Loads the variables of the given env file, or of the `.env` file in the current directory if
+/// there is one. The variables already set in the environment take precedence.
+fn load_env_file(env_file: Option<PathBuf>) -> Result<(), failure::Error> {
+    match env_file {
+        Some(path) => {
+            dotenv::from_path(&path)
+                .map_err(|err| format_err!("Could not load {}: {}", path.display(), err))?;
+        }
+        None => {
+            dotenv::dotenv().ok();
+        }
+    }
+
+    Ok(())
+}
+
 fn set_env_logger() {
     use std::io::Write;
 
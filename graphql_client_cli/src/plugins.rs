@@ -0,0 +1,209 @@
+//! The plugins of `graphql-client generate`: commands post-processing the generated code.
+
+use failure::*;
+use serde::Serialize;
+use std::io::Write as _;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// A command post-processing the generated code before it is written, so custom impls,
+/// annotations or companion files are added without forking the generator. The commands get the
+/// query document and the file being generated in the `GRAPHQL_CLIENT_QUERY_PATH` and
+/// `GRAPHQL_CLIENT_OUTPUT_PATH` environment variables.
+#[derive(Clone, Debug)]
+pub(crate) enum Plugin {
+    /// Reads the generated code on stdin, and prints the code to write instead.
+    Code(String),
+    /// Reads a JSON description of the generated items on stdin, and prints code to append.
+    Items(String),
+}
+
+/// What the `Items` plugins read on stdin.
+#[derive(Serialize)]
+struct PluginInput<'a> {
+    query_path: &'a Path,
+    output_path: &'a Path,
+    items: Vec<GeneratedItem>,
+}
+
+/// An item of the generated code, as described to the `Items` plugins.
+#[derive(Debug, PartialEq, Serialize)]
+struct GeneratedItem {
+    /// The kind of the item: `mod`, `struct`, `enum`, `type`, `const`, `static`, `fn` or `trait`.
+    kind: &'static str,
+    /// The path of the item in the generated file, like `star_wars_query::ResponseData`.
+    path: String,
+}
+
+/// Runs the plugins one after the other on the code generated from a query document.
+pub(crate) fn run_plugins(
+    plugins: &[Plugin],
+    query_path: &Path,
+    output_path: &Path,
+    code: String,
+) -> Result<String, failure::Error> {
+    plugins.iter().try_fold(code, |code, plugin| match plugin {
+        Plugin::Code(command) => run(command, query_path, output_path, code.into_bytes()),
+        Plugin::Items(command) => {
+            let input = PluginInput {
+                query_path,
+                output_path,
+                items: generated_items(&code)?,
+            };
+            let appended = run(
+                command,
+                query_path,
+                output_path,
+                serde_json::to_vec(&input)?,
+            )?;
+            Ok(format!("{}\n{}", code, appended))
+        }
+    })
+}
+
+/// Runs a plugin command with the given input, returning what it printed.
+fn run(
+    command: &str,
+    query_path: &Path,
+    output_path: &Path,
+    input: Vec<u8>,
+) -> Result<String, failure::Error> {
+    let mut words = command.split_whitespace();
+    let program = words
+        .next()
+        .ok_or_else(|| format_err!("Empty plugin command"))?;
+
+    let mut child = Command::new(program)
+        .args(words)
+        .env("GRAPHQL_CLIENT_QUERY_PATH", query_path)
+        .env("GRAPHQL_CLIENT_OUTPUT_PATH", output_path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .with_context(|_| format!("Could not run the plugin `{}`", command))?;
+
+    // The input is written from another thread, so a plugin printing as it reads cannot block on
+    // a full pipe.
+    let mut stdin = child
+        .stdin
+        .take()
+        .expect("the stdin of the plugin is piped");
+    let writer = std::thread::spawn(move || stdin.write_all(&input));
+    let output = child.wait_with_output()?;
+    // A plugin may exit without reading all its input.
+    let _ = writer.join();
+
+    if !output.status.success() {
+        return Err(format_err!(
+            "The plugin `{}` failed with {}",
+            command,
+            output.status
+        ));
+    }
+
+    String::from_utf8(output.stdout)
+        .map_err(|_| format_err!("The plugin `{}` printed invalid UTF-8", command))
+}
+
+/// The named items of the generated code, in order, with the items of the modules after them.
+fn generated_items(code: &str) -> Result<Vec<GeneratedItem>, failure::Error> {
+    let file = syn::parse_file(code)?;
+    let mut items = Vec::new();
+    collect_items(&file.items, "", &mut items);
+    Ok(items)
+}
+
+fn collect_items(items: &[syn::Item], prefix: &str, collected: &mut Vec<GeneratedItem>) {
+    for item in items {
+        let (kind, ident) = match item {
+            syn::Item::Mod(item) => ("mod", &item.ident),
+            syn::Item::Struct(item) => ("struct", &item.ident),
+            syn::Item::Enum(item) => ("enum", &item.ident),
+            syn::Item::Type(item) => ("type", &item.ident),
+            syn::Item::Const(item) => ("const", &item.ident),
+            syn::Item::Static(item) => ("static", &item.ident),
+            syn::Item::Fn(item) => ("fn", &item.sig.ident),
+            syn::Item::Trait(item) => ("trait", &item.ident),
+            _ => continue,
+        };
+        let path = if prefix.is_empty() {
+            ident.to_string()
+        } else {
+            format!("{}::{}", prefix, ident)
+        };
+
+        collected.push(GeneratedItem {
+            kind,
+            path: path.clone(),
+        });
+
+        if let syn::Item::Mod(syn::ItemMod {
+            content: Some((_, content)),
+            ..
+        }) = item
+        {
+            collect_items(content, &path, collected);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_items_are_described() -> Result<(), failure::Error> {
+        let items = generated_items(
+            "pub mod users { pub struct ResponseData; impl ResponseData {} pub mod enums { pub enum Role {} } }",
+        )?;
+
+        assert_eq!(
+            items,
+            vec![
+                GeneratedItem {
+                    kind: "mod",
+                    path: "users".to_owned(),
+                },
+                GeneratedItem {
+                    kind: "struct",
+                    path: "users::ResponseData".to_owned(),
+                },
+                GeneratedItem {
+                    kind: "mod",
+                    path: "users::enums".to_owned(),
+                },
+                GeneratedItem {
+                    kind: "enum",
+                    path: "users::enums::Role".to_owned(),
+                },
+            ]
+        );
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn plugins_rewrite_and_extend_the_code() -> Result<(), failure::Error> {
+        let plugins = vec![
+            Plugin::Code("sed s/users/Users/".to_owned()),
+            Plugin::Items("echo impl Extra for Users {}".to_owned()),
+        ];
+
+        let code = run_plugins(
+            &plugins,
+            Path::new("users.graphql"),
+            Path::new("users.rs"),
+            "pub struct users;".to_owned(),
+        )?;
+
+        assert_eq!(code, "pub struct Users;\nimpl Extra for Users {}\n");
+        assert!(run_plugins(
+            &[Plugin::Code("false".to_owned())],
+            Path::new("users.graphql"),
+            Path::new("users.rs"),
+            String::new(),
+        )
+        .is_err());
+        Ok(())
+    }
+}
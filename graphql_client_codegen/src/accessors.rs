@@ -0,0 +1,158 @@
+use crate::deprecation::{DeprecationStatus, DeprecationStrategy};
+use crate::field_type::FieldType;
+use crate::objects::GqlObject;
+use crate::query::QueryContext;
+use crate::selection::{Selection, SelectionItem};
+use heck::SnakeCase;
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::quote;
+use std::collections::BTreeSet;
+
+/// A field on the path from the root of the response to an accessor's target.
+#[derive(Clone)]
+pub(crate) struct PathSegment<'a> {
+    pub(crate) field_name: Ident,
+    pub(crate) field_type: FieldType<'a>,
+}
+
+/// Generates accessor methods on `ResponseData` for every path of at least two nested object
+/// fields, returning an iterator over the values at the end of the path. The `Option` and `Vec`
+/// layers along the way are flattened, so `repository { issues { nodes } }` gives
+/// `fn repository_issues_nodes(&self) -> impl Iterator<Item = &...>`.
+pub(crate) fn nested_accessors<'schema>(
+    context: &QueryContext<'_, 'schema>,
+    root: &'schema GqlObject<'schema>,
+    selection: &Selection<'_>,
+    prefix: &str,
+) -> TokenStream {
+    let mut methods = Vec::new();
+    let mut names = BTreeSet::new();
+    collect_accessors(
+        context,
+        root,
+        selection,
+        prefix,
+        &mut Vec::new(),
+        &mut Vec::new(),
+        &mut names,
+        &mut methods,
+    );
+
+    quote! {
+        #[allow(deprecated)]
+        impl ResponseData {
+            #(#methods)*
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn collect_accessors<'schema>(
+    context: &QueryContext<'_, 'schema>,
+    object: &'schema GqlObject<'schema>,
+    selection: &Selection<'_>,
+    prefix: &str,
+    path: &mut Vec<PathSegment<'schema>>,
+    path_names: &mut Vec<String>,
+    names: &mut BTreeSet<String>,
+    methods: &mut Vec<TokenStream>,
+) {
+    let siblings = selection.response_names();
+    for item in selection {
+        let field = match item {
+            SelectionItem::Field(field) => field,
+            _ => continue,
+        };
+        let alias = field.alias.unwrap_or(field.name);
+        let schema_field = match object.fields.iter().find(|f| f.name == field.name) {
+            Some(schema_field) => schema_field,
+            None => continue,
+        };
+
+        // Denied fields are not generated at all.
+        if let (DeprecationStatus::Deprecated(_), DeprecationStrategy::Deny) = (
+            &schema_field.deprecation,
+            context.field_deprecation_strategy(object.name, field.name),
+        ) {
+            continue;
+        }
+
+        let field_prefix =
+            crate::shared::field_prefix(context, prefix, alias, siblings.iter().cloned());
+        let flattened = crate::shared::flatten_field(
+            context,
+            &crate::shared::selected_field_type(field, &schema_field.type_),
+            &field.fields,
+            field_prefix,
+        );
+        path.push(PathSegment {
+            field_name: Ident::new(
+                &crate::shared::field_name(context, alias, siblings.iter().cloned()),
+                Span::call_site(),
+            ),
+            field_type: flattened.type_.clone(),
+        });
+        path_names.push(alias.to_snake_case());
+        let leaf_name = flattened.type_.inner_name_str();
+
+        let method_name = path_names.join("_");
+        if path.len() > 1 && names.insert(method_name.clone()) {
+            let leaf_type = FieldType::Named(leaf_name).to_rust(context, &flattened.prefix);
+            methods.push(accessor(
+                &context.item_visibility,
+                &method_name,
+                path,
+                &leaf_type,
+            ));
+        }
+
+        if let Some(child) = context.schema.objects.get(leaf_name) {
+            collect_accessors(
+                context,
+                child,
+                flattened.selection,
+                &flattened.prefix,
+                path,
+                path_names,
+                names,
+                methods,
+            );
+        }
+
+        path.pop();
+        path_names.pop();
+    }
+}
+
+pub(crate) fn accessor(
+    vis: &syn::Visibility,
+    method_name: &str,
+    path: &[PathSegment<'_>],
+    leaf_type: &TokenStream,
+) -> TokenStream {
+    let method_name = Ident::new(method_name, Span::call_site());
+    let body = path
+        .iter()
+        .fold(quote!(::core::iter::once(self)), |expr, segment| {
+            let field_name = &segment.field_name;
+            let flattened = flatten(&segment.field_type, quote!(&value.#field_name));
+            quote!(#expr.flat_map(|value| #flattened))
+        });
+
+    quote! {
+        #vis fn #method_name(&self) -> impl Iterator<Item = &#leaf_type> + '_ {
+            #body
+        }
+    }
+}
+
+/// An iterator over the values inside the `Option` and `Vec` layers of `expr`.
+fn flatten(field_type: &FieldType<'_>, expr: TokenStream) -> TokenStream {
+    match field_type {
+        FieldType::Named(_) => quote!(::core::iter::once(#expr)),
+        FieldType::Optional(inner) | FieldType::Vector(inner) => {
+            let inner = flatten(inner, quote!(value));
+            quote!((#expr).iter().flat_map(|value| #inner))
+        }
+    }
+}
@@ -0,0 +1,127 @@
+//! Typestate builders for the `Variables` and input object structs: each required field is a type
+//! parameter of the builder, so `build()` only exists once all of them are set.
+
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::quote;
+
+/// A field of a struct to build.
+pub(crate) struct BuilderField {
+    pub(crate) name: Ident,
+    /// The type of the field in the struct.
+    pub(crate) ty: TokenStream,
+    /// The type taken by the setter of an optional field, inside the `Option`, or `None` for a
+    /// required field.
+    pub(crate) optional: Option<TokenStream>,
+}
+
+/// The builder of the struct with the given fields, as `{struct_name}Builder`, and the
+/// `builder()` function starting it.
+pub(crate) fn typestate_builder(
+    vis: &syn::Visibility,
+    struct_name: &Ident,
+    fields: &[BuilderField],
+) -> TokenStream {
+    // The setter of a field named `build` would clash with `build()`.
+    if fields.is_empty() || fields.iter().any(|field| field.name == "build") {
+        return quote!();
+    }
+
+    let builder_name = Ident::new(&format!("{}Builder", struct_name), Span::call_site());
+    let required: Vec<&BuilderField> = fields
+        .iter()
+        .filter(|field| field.optional.is_none())
+        .collect();
+    let params: Vec<Ident> = (0..required.len())
+        .map(|i| Ident::new(&format!("__R{}", i), Span::call_site()))
+        .collect();
+
+    let field_names: Vec<&Ident> = fields.iter().map(|field| &field.name).collect();
+    // The index of the type parameter of a required field.
+    let param_index = |field: &BuilderField| required.iter().position(|r| r.name == field.name);
+    let builder_field_types = fields.iter().map(|field| match param_index(field) {
+        Some(i) => {
+            let param = &params[i];
+            quote!(#param)
+        }
+        None => field.ty.clone(),
+    });
+    let initial_values = fields.iter().map(|field| match field.optional {
+        Some(_) => quote!(None),
+        None => quote!(()),
+    });
+    let unset = params.iter().map(|_| quote!(()));
+
+    let setters = fields.iter().map(|field| {
+        let name = &field.name;
+        match (&field.optional, param_index(field)) {
+            (Some(ty), _) => quote! {
+                #vis fn #name(mut self, #name: #ty) -> Self {
+                    self.#name = Some(#name);
+                    self
+                }
+            },
+            (None, Some(i)) => {
+                let ty = &field.ty;
+                let state = params.iter().enumerate().map(|(j, param)| {
+                    if i == j {
+                        quote!(#ty)
+                    } else {
+                        quote!(#param)
+                    }
+                });
+                let values = field_names.iter().map(|other| {
+                    if *other == name {
+                        quote!(#name)
+                    } else {
+                        quote!(#other: self.#other)
+                    }
+                });
+
+                quote! {
+                    #vis fn #name(self, #name: #ty) -> #builder_name<#(#state),*> {
+                        #builder_name {
+                            #(#values,)*
+                        }
+                    }
+                }
+            }
+            (None, None) => unreachable!("required fields have a type parameter"),
+        }
+    });
+    let required_types = required.iter().map(|field| &field.ty);
+    let builder_doc = format!(
+        "A builder for [`{}`], with a `build()` method once all the required fields are set.",
+        struct_name
+    );
+
+    quote! {
+        #[doc = #builder_doc]
+        #vis struct #builder_name<#(#params = ()),*> {
+            #(#field_names: #builder_field_types,)*
+        }
+
+        impl #struct_name {
+            /// A builder for the struct, where the required fields have to be set before it can
+            /// be built.
+            #vis fn builder() -> #builder_name<#(#unset),*> {
+                #builder_name {
+                    #(#field_names: #initial_values,)*
+                }
+            }
+        }
+
+        #[allow(deprecated)]
+        impl<#(#params),*> #builder_name<#(#params),*> {
+            #(#setters)*
+        }
+
+        #[allow(deprecated)]
+        impl #builder_name<#(#required_types),*> {
+            #vis fn build(self) -> #struct_name {
+                #struct_name {
+                    #(#field_names: self.#field_names,)*
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,368 @@
+//! The directives only the client understands, configuring the generated code or marking the
+//! fields resolved locally: they are removed from the query sent to the server.
+
+use crate::error::CodegenError;
+use crate::operations::Operation;
+use crate::query::QueryContext;
+use crate::selection::{Selection as SelectedFields, SelectionItem};
+use graphql_parser::query::{
+    Definition, Directive, Document, OperationDefinition, Selection, SelectionSet, Value,
+};
+use proc_macro2::TokenStream;
+use quote::quote;
+
+/// `@rust(deserialize_with: "path::to::function")` on a selected field deserializes it with the
+/// function instead of the `Deserialize` implementation of its type.
+const RUST_DIRECTIVE: &str = "rust";
+
+/// `@client` on a selected field resolves it locally: the field is not sent to the server, and it
+/// is optional in the response.
+const CLIENT_DIRECTIVE: &str = "client";
+
+/// `@connection(key: "feed")` on a paginated field names it for the normalized caches of Apollo
+/// and Relay. The client has no such cache, and servers reject the directive, so it is only
+/// removed from the sent query.
+const CONNECTION_DIRECTIVE: &str = "connection";
+
+/// The function deserializing the field, from its `@rust(deserialize_with: ...)` directive.
+pub(crate) fn deserialize_with(directives: &[Directive]) -> Option<&str> {
+    directives
+        .iter()
+        .filter(|directive| directive.name == RUST_DIRECTIVE)
+        .flat_map(|directive| &directive.arguments)
+        .filter_map(|(name, value)| match value {
+            Value::String(path) if name == "deserialize_with" => Some(path.as_str()),
+            _ => None,
+        })
+        .next()
+}
+
+/// Whether the field is resolved locally, from its `@client` directive.
+pub(crate) fn is_client_field(directives: &[Directive]) -> bool {
+    directives
+        .iter()
+        .any(|directive| directive.name == CLIENT_DIRECTIVE)
+}
+
+/// The text of the query sent to the server, when the document uses client directives, after
+/// checking their arguments. `None` when the document can be sent as it is written.
+pub(crate) fn sent_query(document: &Document) -> Result<Option<String>, CodegenError> {
+    let mut sent = document.clone();
+    let mut stripped = false;
+    let mut error = None;
+
+    for_each_selection_set(&mut sent, &mut |selection_set| {
+        for item in &selection_set.items {
+            if let Selection::Field(field) = item {
+                for directive in field
+                    .directives
+                    .iter()
+                    .filter(|directive| directive.name == RUST_DIRECTIVE)
+                {
+                    if let Err(err) = check_rust_directive(&field.name, directive) {
+                        error.get_or_insert(err);
+                    }
+                }
+            }
+        }
+
+        let was_empty = selection_set.items.is_empty();
+        stripped |= strip_selection_set(selection_set);
+
+        if !was_empty && selection_set.items.is_empty() {
+            error.get_or_insert(CodegenError::Query {
+                message: format!(
+                    "Every field of this selection has the `@{}` directive, so the server would receive an empty selection.",
+                    CLIENT_DIRECTIVE
+                ),
+                location: Some(selection_set.span.0.into()),
+            });
+        }
+    });
+
+    match error {
+        Some(err) => Err(err),
+        None if stripped => Ok(Some(sent.to_string())),
+        None => Ok(None),
+    }
+}
+
+/// Removes the client directives and the fields resolved locally from the document.
+pub(crate) fn strip(document: &mut Document) {
+    for_each_selection_set(document, &mut |selection_set| {
+        strip_selection_set(selection_set);
+    });
+}
+
+/// Removes the fields resolved locally from the selection set, and the client directives from
+/// the other fields. Returns whether anything was removed.
+fn strip_selection_set(selection_set: &mut SelectionSet) -> bool {
+    let items = selection_set.items.len();
+    selection_set.items.retain(|item| match item {
+        Selection::Field(field) => !is_client_field(&field.directives),
+        _ => true,
+    });
+    let mut stripped = selection_set.items.len() != items;
+
+    for item in &mut selection_set.items {
+        if let Selection::Field(field) = item {
+            let directives = field.directives.len();
+            field.directives.retain(|directive| {
+                directive.name != RUST_DIRECTIVE && directive.name != CONNECTION_DIRECTIVE
+            });
+            stripped |= field.directives.len() != directives;
+        }
+    }
+
+    stripped
+}
+
+/// The `CLIENT_FIELDS` constant of the generated module, listing the fields of the operation that
+/// are resolved locally, for the local resolvers of `graphql_client`.
+pub(crate) fn client_fields<'query>(
+    context: &QueryContext<'query, '_>,
+    operation: &Operation<'query>,
+) -> TokenStream {
+    let mut fields = Vec::new();
+    collect_client_fields(
+        context,
+        operation.root_name(&context.schema),
+        &operation.selection,
+        "",
+        &mut Vec::new(),
+        &mut fields,
+    );
+
+    quote! {
+        /// The fields resolved locally, with the `@client` directive.
+        pub const CLIENT_FIELDS: &'static [::graphql_client::local::ClientField] = &[#(#fields),*];
+    }
+}
+
+/// Collects the client fields in the selection on the type, at the given path in the response.
+fn collect_client_fields<'query>(
+    context: &QueryContext<'query, '_>,
+    type_name: &str,
+    selection: &SelectedFields<'query>,
+    path: &str,
+    spread_fragments: &mut Vec<&'query str>,
+    fields: &mut Vec<TokenStream>,
+) {
+    for item in selection {
+        match item {
+            SelectionItem::Field(field) => {
+                let response_name = field.alias.unwrap_or(field.name);
+                let field_path = if path.is_empty() {
+                    response_name.to_owned()
+                } else {
+                    format!("{}.{}", path, response_name)
+                };
+
+                // The selection of a client field is resolved along with it.
+                if field.client {
+                    let field_name = field.name;
+                    fields.push(quote! {
+                        ::graphql_client::local::ClientField {
+                            path: #field_path,
+                            type_name: #type_name,
+                            field_name: #field_name,
+                        }
+                    });
+                    continue;
+                }
+
+                let schema_fields = context
+                    .schema
+                    .objects
+                    .get(type_name)
+                    .map(|object| &object.fields)
+                    .or_else(|| {
+                        context
+                            .schema
+                            .interfaces
+                            .get(type_name)
+                            .map(|interface| &interface.fields)
+                    });
+                if let Some(schema_field) = schema_fields
+                    .and_then(|schema_fields| schema_fields.iter().find(|f| f.name == field.name))
+                {
+                    collect_client_fields(
+                        context,
+                        schema_field.type_.inner_name_str(),
+                        &field.fields,
+                        &field_path,
+                        spread_fragments,
+                        fields,
+                    );
+                }
+            }
+            SelectionItem::FragmentSpread(spread) => {
+                // A fragment spread in its own selection would never end.
+                if spread_fragments.contains(&spread.fragment_name) {
+                    continue;
+                }
+                if let Some(fragment) = context.fragments.get(spread.fragment_name) {
+                    spread_fragments.push(spread.fragment_name);
+                    collect_client_fields(
+                        context,
+                        fragment.on.name(),
+                        &fragment.selection,
+                        path,
+                        spread_fragments,
+                        fields,
+                    );
+                    spread_fragments.pop();
+                }
+            }
+            SelectionItem::InlineFragment(inline_fragment) => collect_client_fields(
+                context,
+                inline_fragment.on,
+                &inline_fragment.fields,
+                path,
+                spread_fragments,
+                fields,
+            ),
+        }
+    }
+}
+
+fn check_rust_directive(field_name: &str, directive: &Directive) -> Result<(), CodegenError> {
+    let error = |message: String| CodegenError::Query {
+        message,
+        location: Some(directive.position.into()),
+    };
+
+    for (name, value) in &directive.arguments {
+        match (name.as_str(), value) {
+            ("deserialize_with", Value::String(_)) => (),
+            ("deserialize_with", _) => {
+                return Err(error(format!(
+                    "The `deserialize_with` argument of `@{}` on `{}` must be a string, the path of a function.",
+                    RUST_DIRECTIVE, field_name
+                )))
+            }
+            (name, _) => {
+                return Err(error(format!(
+                    "Unknown argument `{}` of `@{}` on `{}`. The only argument is `deserialize_with`.",
+                    name, RUST_DIRECTIVE, field_name
+                )))
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Calls `f` on every selection set of the operations and fragments of the document, before the
+/// selection sets nested in it.
+fn for_each_selection_set(document: &mut Document, f: &mut dyn FnMut(&mut SelectionSet)) {
+    for definition in &mut document.definitions {
+        let selection_set = match definition {
+            Definition::Operation(OperationDefinition::SelectionSet(selection_set)) => {
+                selection_set
+            }
+            Definition::Operation(OperationDefinition::Query(query)) => &mut query.selection_set,
+            Definition::Operation(OperationDefinition::Mutation(mutation)) => {
+                &mut mutation.selection_set
+            }
+            Definition::Operation(OperationDefinition::Subscription(subscription)) => {
+                &mut subscription.selection_set
+            }
+            Definition::Fragment(fragment) => &mut fragment.selection_set,
+        };
+        visit_selection_set(selection_set, f);
+    }
+}
+
+fn visit_selection_set(selection_set: &mut SelectionSet, f: &mut dyn FnMut(&mut SelectionSet)) {
+    f(selection_set);
+
+    for item in &mut selection_set.items {
+        match item {
+            Selection::Field(field) if !field.selection_set.items.is_empty() => {
+                visit_selection_set(&mut field.selection_set, f)
+            }
+            Selection::Field(_) | Selection::FragmentSpread(_) => (),
+            Selection::InlineFragment(inline_fragment) => {
+                visit_selection_set(&mut inline_fragment.selection_set, f)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn client_directives_are_removed_from_the_sent_query() {
+        let document = graphql_parser::parse_query(
+            r#"query Q { user { createdAt @rust(deserialize_with: "crate::timestamp") @include(if: true) } }"#,
+        )
+        .unwrap();
+
+        let sent = sent_query(&document).unwrap().unwrap();
+
+        assert!(!sent.contains("@rust"));
+        assert!(sent.contains("createdAt @include(if: true)"));
+    }
+
+    #[test]
+    fn connection_directives_are_removed_from_the_sent_query() {
+        let document = graphql_parser::parse_query(
+            r#"query Q($after: String) { feed(first: 10, after: $after) @connection(key: "feed") { id } }"#,
+        )
+        .unwrap();
+
+        let sent = sent_query(&document).unwrap().unwrap();
+
+        assert!(!sent.contains("@connection"));
+        assert!(sent.contains("feed(first: 10, after: $after) {"));
+    }
+
+    #[test]
+    fn documents_without_client_directives_are_sent_as_they_are() {
+        let document = graphql_parser::parse_query("query Q { user { createdAt } }").unwrap();
+
+        assert!(sent_query(&document).unwrap().is_none());
+    }
+
+    #[test]
+    fn client_fields_are_removed_from_the_sent_query() {
+        let document = graphql_parser::parse_query(
+            "query Q { user { name isSelected @client { since } } ...F } fragment F on Query { cart @client total }",
+        )
+        .unwrap();
+
+        let sent = sent_query(&document).unwrap().unwrap();
+
+        assert!(sent.contains("name"));
+        assert!(!sent.contains("isSelected"));
+        assert!(!sent.contains("since"));
+        assert!(!sent.contains("cart"));
+    }
+
+    #[test]
+    fn selections_of_client_fields_only_are_rejected() {
+        let document =
+            graphql_parser::parse_query("query Q { user { isSelected @client } }").unwrap();
+
+        let err = sent_query(&document).unwrap_err();
+
+        assert!(err.to_string().contains("empty selection"));
+    }
+
+    #[test]
+    fn unknown_arguments_of_the_rust_directive_are_rejected() {
+        let document = graphql_parser::parse_query(
+            r#"query Q { user { createdAt @rust(serialize_with: "f") } }"#,
+        )
+        .unwrap();
+
+        let err = sent_query(&document).unwrap_err();
+
+        assert!(err
+            .to_string()
+            .contains("Unknown argument `serialize_with`"));
+    }
+}
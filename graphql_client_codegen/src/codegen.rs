@@ -1,11 +1,14 @@
+use crate::error::PositionedError;
 use crate::fragments::GqlFragment;
 use crate::operations::Operation;
 use crate::query::QueryContext;
-use crate::{schema, CodegenMode};
+use crate::{schema, CodegenMode, RustVersion, UnionTagging};
 use crate::selection::Selection;
+use crate::type_bindings::TypeBinding;
 use failure::*;
+use heck::CamelCase;
 use graphql_parser::query;
-use proc_macro2::TokenStream;
+use proc_macro2::{Ident, Span, TokenStream};
 use quote::*;
 
 /// Selects the first operation matching `struct_name`. Returns `None` when the query document defines no operation, or when the selected operation does not match any defined operation.
@@ -32,14 +35,106 @@ pub(crate) fn all_operations(query: &query::Document) -> Vec<Operation<'_>> {
     operations
 }
 
+/// The `validate` method of the variables, and their `Validate` implementation.
+fn variables_validation(context: &QueryContext<'_, '_>, operation: &Operation<'_>) -> TokenStream {
+    let validate_impl = crate::validation::struct_impl(
+        context,
+        &Ident::new("Variables", Span::call_site()),
+        &operation.variable_validated_fields(context),
+    );
+    let vis = &context.item_visibility;
+
+    quote! {
+        impl Variables {
+            /// Checks the variables against the constraints of the schema their types cannot
+            /// express, returning the invalid values.
+            #vis fn validate(&self) -> ::core::result::Result<(), ::graphql_client::__alloc::Vec<::graphql_client::validation::ValidationError>> {
+                let mut errors = Vec::new();
+                ::graphql_client::validation::Validate::validate_at(self, "", &mut errors);
+                if errors.is_empty() {
+                    Ok(())
+                } else {
+                    Err(errors)
+                }
+            }
+        }
+
+        #validate_impl
+    }
+}
+
 /// The main code generation function.
 pub(crate) fn response_for_query(
     schema: &schema::Schema<'_>,
     query: &query::Document,
     operation: &Operation<'_>,
     options: &crate::GraphQLClientCodegenOptions,
+    shared_variables: Option<&str>,
 ) -> Result<TokenStream, failure::Error> {
+    response_and_type_bindings(schema, query, operation, options, shared_variables)
+        .map(|(code, _)| code)
+}
+
+/// The generated code, with the fields of the query document bound to it. The `Variables` struct
+/// aliases the one of the `shared_variables` module when it is set.
+pub(crate) fn response_and_type_bindings(
+    schema: &schema::Schema<'_>,
+    query: &query::Document,
+    operation: &Operation<'_>,
+    options: &crate::GraphQLClientCodegenOptions,
+    shared_variables: Option<&str>,
+) -> Result<(TokenStream, Vec<TypeBinding>), failure::Error> {
     let mut context = QueryContext::new(schema, options.deprecation_strategy());
+    context.item_visibility = options.item_visibility();
+    context.pretty_display = options.pretty_display();
+    context.flatten_wrappers = options.flatten_wrappers();
+    context.enum_rename_all = options.enum_rename_all();
+    context.scalar_trait = options.scalar_trait();
+    context.json_scalars = options.json_scalars();
+    context.extra_fields = options.extra_fields();
+    context.typestate_builders = options.typestate_builders();
+    context.serialize_responses = options.serialize_responses();
+    context.validate_variables = options.validate_variables();
+    context.format_agnostic_serde = options.format_agnostic_serde();
+    context.union_tagging = options.union_tagging().clone();
+    context.typename_fallback = options.typename_fallback().cloned();
+
+    // The flattened fields would be serialized without their wrappers.
+    if options.serialize_responses() && options.flatten_wrappers() {
+        return Err(format_err!(
+            "The serialize_responses and flatten_wrappers options cannot be used together."
+        ));
+    }
+
+    // These rely on `#[serde(flatten)]`, or on JSON values, which need a self-describing format.
+    if options.format_agnostic_serde() {
+        let incompatible_options = [
+            ("extra_fields", options.extra_fields()),
+            ("json_scalars", options.json_scalars()),
+            ("scalar_trait", options.scalar_trait()),
+            ("flatten_wrappers", options.flatten_wrappers()),
+        ];
+        if let Some((name, _)) = incompatible_options.iter().find(|(_, enabled)| *enabled) {
+            return Err(format_err!(
+                "The format_agnostic_serde and {} options cannot be used together.",
+                name
+            ));
+        }
+    }
+
+    // The fallback buffers the objects as JSON values, and applies to tagged enums only.
+    if options.typename_fallback().is_some() {
+        if options.format_agnostic_serde() {
+            return Err(format_err!(
+                "The format_agnostic_serde and typename_fallback options cannot be used together."
+            ));
+        }
+        if *options.union_tagging() == UnionTagging::Untagged {
+            return Err(format_err!(
+                "The typename_fallback option cannot be used with the untagged union tagging."
+            ));
+        }
+    }
 
     if let Some(derives) = options.response_derives() {
         context.ingest_response_derives(&derives)?;
@@ -47,6 +142,21 @@ pub(crate) fn response_for_query(
     if let Some(derives) = options.input_derives() {
         context.ingest_input_derives(&derives)?;
     }
+    if let Some(derives) = options.response_struct_derives() {
+        context.ingest_response_struct_derives(&derives)?;
+    }
+    if let Some(derives) = options.response_enum_derives() {
+        context.ingest_response_enum_derives(&derives)?;
+    }
+    if let Some(allowed_deprecations) = options.allowed_deprecations() {
+        context.ingest_allowed_deprecations(allowed_deprecations)?;
+    }
+    if let Some(collection_types) = options.collection_types() {
+        context.ingest_collection_types(collection_types)?;
+    }
+    if let Some(enum_wire_values) = options.enum_wire_values() {
+        context.ingest_enum_wire_values(enum_wire_values)?;
+    }
 
     let mut definitions = Vec::new();
 
@@ -56,10 +166,22 @@ pub(crate) fn response_for_query(
             query::Definition::Fragment(fragment) => {
                 let &query::TypeCondition::On(ref on) = &fragment.type_condition;
                 let on = schema.fragment_target(on).ok_or_else(|| {
-                    format_err!(
-                        "Fragment {} is defined on unknown type: {}",
-                        &fragment.name,
-                        on,
+                    PositionedError::new(
+                        fragment.position,
+                        format_args!(
+                            "Fragment {} is defined on unknown type: {}.{}",
+                            &fragment.name,
+                            on,
+                            crate::suggestions::did_you_mean(
+                                on,
+                                schema
+                                    .objects
+                                    .keys()
+                                    .chain(schema.interfaces.keys())
+                                    .chain(schema.unions.keys())
+                                    .cloned()
+                            ),
+                        ),
                     )
                 })?;
                 context.fragments.insert(
@@ -122,18 +244,48 @@ pub(crate) fn response_for_query(
 
     operation.compute_variable_requirements(&context);
 
+    let item_visibility = &context.item_visibility;
     let variables_struct = match options.mode {
+        CodegenMode::Derive if shared_variables.is_some() => {
+            let module = Ident::new(shared_variables.unwrap_or_default(), Span::call_site());
+            let doc = format!(
+                "The operation declares the same variables as the one of the `{}` module.",
+                module
+            );
+            quote! {
+                #[doc = #doc]
+                #item_visibility type Variables = super::#module::Variables;
+            }
+        }
         CodegenMode::Derive => {
             let (variables_derives, fields, default_constructors) = operation.expand_variables(&context);
+            let builder = if options.typestate_builders() {
+                crate::builders::typestate_builder(
+                    item_visibility,
+                    &Ident::new("Variables", Span::call_site()),
+                    &operation.variable_builder_fields(&context),
+                )
+            } else {
+                quote!()
+            };
+            let validation = if options.validate_variables() {
+                variables_validation(&context, operation)
+            } else {
+                quote!()
+            };
             quote! (
                 #variables_derives
-                pub struct Variables {
+                #item_visibility struct Variables {
                     #(#fields,)*
                 }
 
                 impl Variables {
                     #(#default_constructors)*
                 }
+
+                #builder
+
+                #validation
             )
         },
         CodegenMode::Cli => {
@@ -142,6 +294,14 @@ pub(crate) fn response_for_query(
         }
     };
 
+    // The conversions require the input objects they convert to.
+    let input_conversions = match options.input_conversions() {
+        Some(conversions) => {
+            crate::conversions::input_conversions(&context, operation, conversions)?
+        }
+        None => quote!(),
+    };
+
     let input_object_definitions: Result<Vec<TokenStream>, _> = context
         .schema
         .inputs
@@ -156,50 +316,214 @@ pub(crate) fn response_for_query(
         .collect();
     let input_object_definitions = input_object_definitions?;
 
-    let scalar_definitions: Vec<TokenStream> = context
+    let required_scalars = context
         .schema
         .scalars
         .values()
-        .filter_map(|s| {
-            if s.is_required.get() {
-                Some(s.to_rust())
+        .filter(|s| s.is_required.get());
+    let scalar_definitions: Vec<TokenStream> = match options.scalar_module() {
+        // The aliases of the JSON scalars take precedence over the glob import.
+        Some(scalar_module) => std::iter::once(quote! {
+            #[allow(unused_imports)]
+            use #scalar_module::*;
+        })
+        .chain(
+            required_scalars
+                .filter(|s| s.is_json(&context))
+                .map(|s| s.to_json_value()),
+        )
+        .collect(),
+        None => required_scalars
+            .map(|s| {
+                if s.is_json(&context) {
+                    s.to_json_value()
+                } else {
+                    s.to_rust()
+                }
+            })
+            .collect(),
+    };
+
+    let response_derives = context.response_derives();
+    let extra_field = crate::shared::extra_field(&context);
+    let response_data_display = if context.pretty_display {
+        let root_name = operation.root_name(&context.schema);
+        let definition = &context.schema.objects[root_name];
+        let body = crate::shared::pretty_fields_for_selection(
+            definition.name,
+            &definition.fields,
+            &context,
+            &operation.selection,
+        )?;
+        crate::shared::pretty_display_impl(
+            &context.item_visibility,
+            &Ident::new("ResponseData", Span::call_site()),
+            &body,
+        )
+    } else {
+        quote!()
+    };
+    let response_data_try_from = if options.supports(RustVersion::TRY_FROM) {
+        quote! {
+            impl ::core::convert::TryFrom<::graphql_client::serde_json::Value> for ResponseData {
+                type Error = ::graphql_client::serde_json::Error;
+
+                fn try_from(value: ::graphql_client::serde_json::Value) -> ::core::result::Result<Self, Self::Error> {
+                    ResponseData::from_json(value)
+                }
+            }
+        }
+    } else {
+        quote!()
+    };
+    let nested_accessors = if options.nested_accessors() {
+        let definition = &context.schema.objects[operation.root_name(&context.schema)];
+        crate::accessors::nested_accessors(
+            &context,
+            definition,
+            &operation.selection,
+            &operation.name,
+        )
+    } else {
+        quote!()
+    };
+    let send_sync_assertions = if options.assert_send_sync() {
+        // In CLI mode, the variables are the struct of the operation, generated outside of this.
+        let variables = match options.mode {
+            CodegenMode::Derive => quote!(assert_send_sync::<Variables>();),
+            CodegenMode::Cli => quote!(),
+        };
+
+        quote! {
+            #[allow(dead_code)]
+            fn __assert_send_sync() {
+                fn assert_send_sync<T: Send + Sync + 'static>() {}
+
+                assert_send_sync::<ResponseData>();
+                #variables
+            }
+        }
+    } else {
+        quote!()
+    };
+    // The struct under derive is next to the module, and is only known in derive mode.
+    let paginated_impl = match options.mode {
+        CodegenMode::Derive => {
+            let struct_ident = Ident::new(&operation.name.to_camel_case(), Span::call_site());
+            crate::relay::paginated_impl(&context, operation, &struct_ident, options)?
+        }
+        CodegenMode::Cli => None,
+    };
+
+    let client_fields = crate::client_directives::client_fields(&context, operation);
+    let deprecation_warnings = crate::shared::warning_items("Deprecation", &context.warnings());
+    let name_collision_warnings =
+        crate::shared::warning_items("NameCollision", &context.name_collision_warnings());
+
+    let response_types = quote! {
+        #(#fragment_definitions)*
+
+        #(#definitions)*
+
+        #response_derives
+
+        #item_visibility struct ResponseData {
+            #(#response_data_fields,)*
+            #extra_field
+        }
+
+        impl ResponseData {
+            /// Deserializes the response data from an already parsed JSON value.
+            #item_visibility fn from_json(
+                value: ::graphql_client::serde_json::Value,
+            ) -> ::core::result::Result<Self, ::graphql_client::serde_json::Error> {
+                ::graphql_client::serde_json::from_value(value)
+            }
+        }
+
+        #response_data_try_from
+
+        #response_data_display
+
+        #nested_accessors
+
+        #input_conversions
+    };
+    let input_object_definitions = quote!(#(#input_object_definitions)*);
+    let enum_definitions = quote!(#(#enum_definitions)*);
+
+    let (response_types, input_object_definitions, enum_definitions) = if options.split_files() {
+        (
+            submodule("types", response_types),
+            submodule("inputs", input_object_definitions),
+            submodule("enums", enum_definitions),
+        )
+    } else {
+        (response_types, input_object_definitions, enum_definitions)
+    };
+
+    // The fields selected on the root are generated in the `ResponseData` struct.
+    let type_bindings = context
+        .type_bindings()
+        .into_iter()
+        .map(|binding| {
+            if binding.parent_path == operation.name {
+                TypeBinding {
+                    parent_path: "ResponseData".to_owned(),
+                    ..binding
+                }
             } else {
-                None
+                binding
             }
         })
         .collect();
 
-    let response_derives = context.response_derives();
-
-    Ok(quote! {
+    let code = quote! {
         use serde::{Serialize, Deserialize};
+        #[allow(unused_imports)]
+        use ::graphql_client::__alloc::*;
 
-        #[allow(dead_code)]
-        type Boolean = bool;
-        #[allow(dead_code)]
-        type Float = f64;
-        #[allow(dead_code)]
-        type Int = i64;
-        #[allow(dead_code)]
-        type ID = String;
+        #[allow(unused_imports)]
+        use ::graphql_client::scalars::{Boolean, Float, Int, ID};
 
         #(#scalar_definitions)*
 
-        #(#input_object_definitions)*
+        #input_object_definitions
 
-        #(#enum_definitions)*
+        #enum_definitions
 
-        #(#fragment_definitions)*
+        #variables_struct
 
-        #(#definitions)*
+        #response_types
 
-        #variables_struct
+        #paginated_impl
 
-        #response_derives
+        #client_fields
 
-        pub struct ResponseData {
-            #(#response_data_fields,)*
+        #send_sync_assertions
+
+        #deprecation_warnings
+
+        #name_collision_warnings
+    };
+
+    Ok((code, type_bindings))
+}
+
+/// A public submodule with the given items, re-exported by the parent module. The CLI writes it to
+/// its own file when splitting the generated code.
+pub(crate) fn submodule(name: &str, items: TokenStream) -> TokenStream {
+    let name = Ident::new(name, Span::call_site());
+
+    quote! {
+        pub mod #name {
+            #[allow(unused_imports)]
+            use super::*;
+
+            #items
         }
 
-    })
+        #[allow(unused_imports)]
+        pub use self::#name::*;
+    }
 }
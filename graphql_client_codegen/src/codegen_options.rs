@@ -1,8 +1,10 @@
+use crate::complexity::ComplexityOptions;
 use crate::deprecation::DeprecationStrategy;
 use derivative::*;
-use proc_macro2::Ident;
+use failure::format_err;
+use proc_macro2::{Ident, TokenStream};
 use std::path::{Path, PathBuf};
-use syn::Visibility;
+use syn::{Token, VisPublic, Visibility};
 
 /// Which context is this code generation effort taking place.
 #[derive(Debug)]
@@ -29,19 +31,111 @@ pub struct GraphQLClientCodegenOptions {
     input_derives: Option<String>,
     /// Comma-separated list of additional response traits we want to derive.
     response_derives: Option<String>,
+    /// Comma-separated list of traits to derive on the response structs, instead of the response derives.
+    response_struct_derives: Option<String>,
+    /// Comma-separated list of traits to derive on the enums, instead of the response derives.
+    response_enum_derives: Option<String>,
     /// The deprecation strategy to adopt.
     deprecation_strategy: Option<DeprecationStrategy>,
+    /// Comma-separated list of deprecated fields exempted from the `deny` deprecation strategy.
+    allowed_deprecations: Option<String>,
+    /// Comma-separated list of the containers for the lists, as `Type = path::Container`.
+    collection_types: Option<String>,
+    /// Comma-separated list of the wire values of the enums, as `Enum = format` or
+    /// `Enum.VALUE = wire value`.
+    enum_wire_values: Option<String>,
     /// Target module visibility.
     #[derivative(Debug = "ignore")]
     module_visibility: Option<Visibility>,
+    /// Visibility of the generated structs and enums inside the module.
+    #[derivative(Debug = "ignore")]
+    item_visibility: Option<Visibility>,
+    /// The module defining the custom scalars, imported instead of aliasing them.
+    #[derivative(Debug = "ignore")]
+    scalar_module: Option<syn::Path>,
+    /// The variable for the cursor of a paginated operation.
+    pagination_cursor: Option<String>,
+    /// The dotted path to the nodes of a paginated operation.
+    pagination_nodes: Option<String>,
+    /// The dotted path to the page info of a paginated operation.
+    pagination_page_info: Option<String>,
+    /// Comma-separated list of the response objects to convert to input objects, as
+    /// `path = InputType`.
+    input_conversions: Option<String>,
+    /// The directory of the stored responses to test the deserialization of the responses with.
+    test_fixtures_path: Option<PathBuf>,
     /// A path to a file to include in the module to force Cargo to take into account changes in
     /// the query files when recompiling.
     query_file: Option<PathBuf>,
     /// A path to a file to include in the module to force Cargo to take into account changes in
     /// the schema files when recompiling.
     schema_file: Option<PathBuf>,
+    /// Whether to include the query text in the generated module documentation.
+    include_query_in_docs: bool,
+    /// Whether to generate a pretty `Display` implementation for the response types.
+    pretty_display: bool,
+    /// Whether to generate accessors on `ResponseData` flattening nested selections.
+    nested_accessors: bool,
+    /// Whether to flatten the fields selecting a single field on an object.
+    flatten_wrappers: bool,
+    /// The oldest Rust version the generated code has to compile with.
+    rust_version: Option<RustVersion>,
+    /// Whether unused fragments and variables are errors rather than warnings.
+    deny_unused: bool,
+    /// Whether to generate enums with idiomatic variant names and a serde `rename_all` attribute.
+    enum_rename_all: bool,
+    /// Whether to (de)serialize the custom scalars through the `GraphQLScalar` trait.
+    scalar_trait: bool,
+    /// Whether to check at compile time that the generated types are `Send + Sync + 'static`.
+    assert_send_sync: bool,
+    /// Whether to generate the `JSON` scalars as `serde_json::Value`.
+    json_scalars: bool,
+    /// Whether to keep the fields of the responses that are not selected in an `extra` map.
+    extra_fields: bool,
+    /// Whether to generate typestate builders for the variables and the input objects.
+    typestate_builders: bool,
+    /// Whether to register the operation in the `graphql_client::registry`.
+    register_operations: bool,
+    /// Whether to generate code for all the operations of the document, in derive mode.
+    all_operations: bool,
+    /// Whether the operations declaring the same variables share one `Variables` struct.
+    share_variables: bool,
+    /// Whether to group the generated items in `types`, `enums`, `inputs` and `operations`
+    /// submodules, for the CLI to write them to separate files.
+    split_files: bool,
+    /// Whether to also derive `Serialize` on the response types.
+    serialize_responses: bool,
+    /// Whether to generate serde code working with the formats that are not self-describing.
+    format_agnostic_serde: bool,
+    /// How the enums of the selections on unions and interfaces are represented for serde.
+    union_tagging: UnionTagging,
+    /// What the enums of the selections on unions and interfaces fall back to without `__typename`.
+    typename_fallback: Option<TypenameFallback>,
+    /// Whether to generate a `validate` method on the variables.
+    validate_variables: bool,
+    /// The deepest nesting of fields allowed in the operations.
+    max_depth: Option<usize>,
+    /// The highest complexity score allowed for the operations.
+    max_complexity: Option<u64>,
+    /// How the complexity of the operations is computed.
+    complexity_options: ComplexityOptions,
+    /// Whether to print the time spent in each stage of the code generation.
+    timings: bool,
+    /// The callback run on each generated struct and enum.
+    #[derivative(Debug = "ignore")]
+    type_hook: Option<Box<TypeHook>>,
+    /// The callback run on the whole generated code.
+    #[derivative(Debug = "ignore")]
+    post_process: Option<Box<PostProcess>>,
 }
 
+/// A callback run on each generated struct and enum, with its path in the generated code, like
+/// `star_wars_query::ResponseData`, and the tokens of its definition.
+pub type TypeHook = dyn Fn(&str, &mut TokenStream);
+
+/// A callback run on the whole generated code.
+pub type PostProcess = dyn Fn(&mut TokenStream);
+
 impl GraphQLClientCodegenOptions {
     /// Creates an empty options object with default params. It probably wants to be configured.
     pub fn new(mode: CodegenMode) -> GraphQLClientCodegenOptions {
@@ -49,13 +143,52 @@ impl GraphQLClientCodegenOptions {
             mode,
             input_derives: Default::default(),
             response_derives: Default::default(),
+            response_struct_derives: Default::default(),
+            response_enum_derives: Default::default(),
             deprecation_strategy: Default::default(),
+            allowed_deprecations: Default::default(),
+            collection_types: Default::default(),
+            enum_wire_values: Default::default(),
             module_visibility: Default::default(),
+            item_visibility: Default::default(),
+            scalar_module: Default::default(),
+            pagination_cursor: Default::default(),
+            pagination_nodes: Default::default(),
+            pagination_page_info: Default::default(),
+            input_conversions: Default::default(),
+            test_fixtures_path: Default::default(),
             operation_name: Default::default(),
             struct_ident: Default::default(),
             struct_name: Default::default(),
             query_file: Default::default(),
             schema_file: Default::default(),
+            include_query_in_docs: false,
+            pretty_display: false,
+            nested_accessors: false,
+            flatten_wrappers: false,
+            rust_version: None,
+            deny_unused: false,
+            enum_rename_all: false,
+            scalar_trait: false,
+            assert_send_sync: false,
+            json_scalars: cfg!(feature = "json_scalars"),
+            extra_fields: false,
+            typestate_builders: false,
+            register_operations: cfg!(feature = "registry"),
+            all_operations: false,
+            share_variables: false,
+            split_files: false,
+            serialize_responses: false,
+            format_agnostic_serde: false,
+            union_tagging: UnionTagging::default(),
+            typename_fallback: None,
+            validate_variables: false,
+            max_depth: None,
+            max_complexity: None,
+            complexity_options: ComplexityOptions::new(),
+            timings: false,
+            type_hook: None,
+            post_process: None,
         }
     }
 
@@ -66,6 +199,18 @@ impl GraphQLClientCodegenOptions {
             .unwrap_or(&Visibility::Inherited)
     }
 
+    /// The visibility of the generated structs and enums inside the module. Defaults to `pub`.
+    pub(crate) fn item_visibility(&self) -> Visibility {
+        self.item_visibility
+            .clone()
+            .unwrap_or_else(public_visibility)
+    }
+
+    /// The module defining the custom scalars, if they are not aliased from the parent module.
+    pub(crate) fn scalar_module(&self) -> Option<&syn::Path> {
+        self.scalar_module.as_ref()
+    }
+
     /// The deprecation strategy to adopt.
     pub(crate) fn deprecation_strategy(&self) -> DeprecationStrategy {
         self.deprecation_strategy.clone().unwrap_or_default()
@@ -97,16 +242,157 @@ impl GraphQLClientCodegenOptions {
         self.response_derives = Some(response_derives);
     }
 
+    /// Comma-separated list of traits to derive on the response structs, instead of the response derives.
+    pub fn response_struct_derives(&self) -> Option<&str> {
+        self.response_struct_derives.as_ref().map(String::as_str)
+    }
+
+    /// Comma-separated list of traits to derive on the response structs, instead of the response derives.
+    pub fn set_response_struct_derives(&mut self, response_struct_derives: String) {
+        self.response_struct_derives = Some(response_struct_derives);
+    }
+
+    /// Comma-separated list of traits to derive on the enums, instead of the response derives.
+    pub fn response_enum_derives(&self) -> Option<&str> {
+        self.response_enum_derives.as_ref().map(String::as_str)
+    }
+
+    /// Comma-separated list of traits to derive on the enums, instead of the response derives.
+    pub fn set_response_enum_derives(&mut self, response_enum_derives: String) {
+        self.response_enum_derives = Some(response_enum_derives);
+    }
+
     /// The deprecation strategy to adopt.
     pub fn set_deprecation_strategy(&mut self, deprecation_strategy: DeprecationStrategy) {
         self.deprecation_strategy = Some(deprecation_strategy);
     }
 
+    /// Comma-separated list of deprecated fields exempted from the `deny` deprecation strategy.
+    pub fn allowed_deprecations(&self) -> Option<&str> {
+        self.allowed_deprecations.as_ref().map(String::as_str)
+    }
+
+    /// Comma-separated list of deprecated fields exempted from the `deny` deprecation strategy,
+    /// as `Type.field`, e.g. `"User.login, Query.legacyUsers"`. These fields are generated without
+    /// any deprecation warning, so a few existing usages can be kept while denying new ones.
+    pub fn set_allowed_deprecations(&mut self, allowed_deprecations: String) {
+        self.allowed_deprecations = Some(allowed_deprecations);
+    }
+
+    /// The variable for the cursor of a paginated operation, if it is configured rather than
+    /// detected from a Relay connection.
+    pub fn pagination_cursor(&self) -> Option<&str> {
+        self.pagination_cursor.as_ref().map(String::as_str)
+    }
+
+    /// Makes the operation paginated, with the given variable for the cursor. An `Int` variable is
+    /// an offset, increased by the number of nodes of each page, until a page is empty. Any other
+    /// variable takes the `endCursor` of the page info, as long as `hasNextPage` is true.
+    pub fn set_pagination_cursor(&mut self, pagination_cursor: String) {
+        self.pagination_cursor = Some(pagination_cursor);
+    }
+
+    /// The dotted path to the nodes of a paginated operation.
+    pub fn pagination_nodes(&self) -> Option<&str> {
+        self.pagination_nodes.as_ref().map(String::as_str)
+    }
+
+    /// The dotted path, through the response names of the fields, from the response data to the
+    /// list of nodes of a paginated operation, e.g. `"repository.issues.nodes"`.
+    pub fn set_pagination_nodes(&mut self, pagination_nodes: String) {
+        self.pagination_nodes = Some(pagination_nodes);
+    }
+
+    /// The dotted path to the page info of a paginated operation.
+    pub fn pagination_page_info(&self) -> Option<&str> {
+        self.pagination_page_info.as_ref().map(String::as_str)
+    }
+
+    /// The dotted path, through the response names of the fields, from the response data to the
+    /// page info of a paginated operation, selecting `hasNextPage` and `endCursor`. It is not
+    /// needed with an offset.
+    pub fn set_pagination_page_info(&mut self, pagination_page_info: String) {
+        self.pagination_page_info = Some(pagination_page_info);
+    }
+
+    /// Comma-separated list of the response objects to convert to input objects, as
+    /// `path = InputType`.
+    pub fn input_conversions(&self) -> Option<&str> {
+        self.input_conversions.as_ref().map(String::as_str)
+    }
+
+    /// Comma-separated list of the response objects to convert to input objects, as
+    /// `path = InputType`, where the path is dotted through the response names of the fields, like
+    /// `user.profile = ProfileInput`. Each input field takes the selected field with the same
+    /// name, and the optional input fields that are not selected are left out.
+    pub fn set_input_conversions(&mut self, input_conversions: String) {
+        self.input_conversions = Some(input_conversions);
+    }
+
+    /// The directory of the stored responses to test the deserialization of the responses with.
+    pub fn test_fixtures_path(&self) -> Option<&Path> {
+        self.test_fixtures_path.as_ref().map(PathBuf::as_path)
+    }
+
+    /// The directory of the stored responses to test the deserialization of the responses with.
+    /// Each operation gets a `#[cfg(test)]` test checking that every `.json` file in the
+    /// subdirectory named after the operation still deserializes to a `Response<ResponseData>`,
+    /// so the changes of the schema breaking stored responses are caught by the tests.
+    pub fn set_test_fixtures_path(&mut self, test_fixtures_path: PathBuf) {
+        self.test_fixtures_path = Some(test_fixtures_path);
+    }
+
+    /// Comma-separated list of the containers for the lists, as `Type = path::Container`.
+    pub fn collection_types(&self) -> Option<&str> {
+        self.collection_types.as_ref().map(String::as_str)
+    }
+
+    /// Comma-separated list of the containers to generate for the lists instead of `Vec`, by the
+    /// GraphQL type of their items, as `Type = path::Container`, e.g.
+    /// `"ID = std::collections::BTreeSet, * = im::Vector"`. The `*` entry applies to the lists of
+    /// all the other types. A container is used as `Container<Item>`: containers with other
+    /// parameters, like `SmallVec<[Item; 4]>`, need a type alias.
+    pub fn set_collection_types(&mut self, collection_types: String) {
+        self.collection_types = Some(collection_types);
+    }
+
+    /// Comma-separated list of the wire values of the enums, as `Enum = format` or
+    /// `Enum.VALUE = wire value`.
+    pub fn enum_wire_values(&self) -> Option<&str> {
+        self.enum_wire_values.as_ref().map(String::as_str)
+    }
+
+    /// Comma-separated list of the wire values of the enums the backend does not (de)serialize
+    /// like the schema, e.g. `"Episode = integer, Color = lowercase, Color.DARK_BLUE = navy"`.
+    /// The format of an enum is `integer`, numbering its values from 0 in the order of the schema,
+    /// or one of the `rename_all` cases of serde, like `lowercase` or `camelCase`. The
+    /// `Enum.VALUE` entries override the wire value of a single value. The Rust variants keep
+    /// their names.
+    pub fn set_enum_wire_values(&mut self, enum_wire_values: String) {
+        self.enum_wire_values = Some(enum_wire_values);
+    }
+
     /// Target module visibility.
     pub fn set_module_visibility(&mut self, visibility: Visibility) {
         self.module_visibility = Some(visibility);
     }
 
+    /// Visibility of the generated structs and enums inside the module.
+    ///
+    /// With the derive, this must not be more restrictive than the visibility of the struct the
+    /// `GraphQLQuery` trait is implemented for, since its `Variables` and `ResponseData` are part
+    /// of that implementation.
+    pub fn set_item_visibility(&mut self, visibility: Visibility) {
+        self.item_visibility = Some(visibility);
+    }
+
+    /// The module defining the custom scalar types, like `crate::scalars`. The generated modules
+    /// glob import it instead of aliasing each scalar to the type of the same name in the parent
+    /// module, so a single module can define the scalars for all the queries.
+    pub fn set_scalar_module(&mut self, scalar_module: syn::Path) {
+        self.scalar_module = Some(scalar_module);
+    }
+
     /// The name of implemention target struct.
     pub fn set_struct_name(&mut self, struct_name: String) {
         self.struct_name = Some(struct_name);
@@ -124,12 +410,24 @@ impl GraphQLClientCodegenOptions {
         self.schema_file.as_ref().map(PathBuf::as_path)
     }
 
+    /// A path to a file to include in the module to force Cargo to take into account changes in
+    /// the schema files when recompiling.
+    pub fn set_schema_file(&mut self, path: PathBuf) {
+        self.schema_file = Some(path);
+    }
+
     /// A path to a file to include in the module to force Cargo to take into account changes in
     /// the query files when recompiling.
     pub fn query_file(&self) -> Option<&Path> {
         self.query_file.as_ref().map(PathBuf::as_path)
     }
 
+    /// All the files the generated code depends on, to be included in the module so Cargo
+    /// recompiles it when any of them changes.
+    pub(crate) fn tracked_files(&self) -> impl Iterator<Item = &Path> {
+        self.query_file().into_iter().chain(self.schema_file())
+    }
+
     /// The identifier to use when referring to the struct implementing GraphQLQuery, if any.
     pub fn set_struct_ident(&mut self, ident: Ident) {
         self.struct_ident = Some(ident);
@@ -139,4 +437,491 @@ impl GraphQLClientCodegenOptions {
     pub fn struct_ident(&self) -> Option<&proc_macro2::Ident> {
         self.struct_ident.as_ref()
     }
+
+    /// Whether to include the query text in the generated module documentation.
+    pub fn include_query_in_docs(&self) -> bool {
+        self.include_query_in_docs
+    }
+
+    /// Whether to include the query text in the generated module documentation.
+    pub fn set_include_query_in_docs(&mut self, include_query_in_docs: bool) {
+        self.include_query_in_docs = include_query_in_docs;
+    }
+
+    /// Whether to generate a pretty `Display` implementation for the response types.
+    pub fn pretty_display(&self) -> bool {
+        self.pretty_display
+    }
+
+    /// Whether to generate a pretty `Display` implementation for the response types. It writes
+    /// the selected fields as an indented tree, and requires custom scalars to implement `Debug`.
+    pub fn set_pretty_display(&mut self, pretty_display: bool) {
+        self.pretty_display = pretty_display;
+    }
+
+    /// Whether to generate accessors on `ResponseData` flattening nested selections.
+    pub fn nested_accessors(&self) -> bool {
+        self.nested_accessors
+    }
+
+    /// Whether to generate accessors on `ResponseData` flattening nested selections. For every
+    /// path of nested object fields, an accessor named after the path returns an iterator over
+    /// the values at its end, skipping the `None`s and flattening the lists on the way.
+    pub fn set_nested_accessors(&mut self, nested_accessors: bool) {
+        self.nested_accessors = nested_accessors;
+    }
+
+    /// Whether to flatten the fields selecting a single field on an object.
+    pub fn flatten_wrappers(&self) -> bool {
+        self.flatten_wrappers
+    }
+
+    /// Whether to flatten the fields selecting a single field on an object. With
+    /// `payload { user { name } }`, the `payload` field of the generated struct holds the `user`
+    /// directly, with the list types of both fields combined and their nullability merged into a
+    /// single `Option`.
+    pub fn set_flatten_wrappers(&mut self, flatten_wrappers: bool) {
+        self.flatten_wrappers = flatten_wrappers;
+    }
+
+    /// The oldest Rust version the generated code has to compile with. By default, the generated
+    /// code can use anything stable in the toolchain building the crate.
+    pub fn rust_version(&self) -> Option<RustVersion> {
+        self.rust_version
+    }
+
+    /// The oldest Rust version the generated code has to compile with. Items needing a more
+    /// recent compiler, like the `TryFrom` implementation on `ResponseData`, are left out.
+    pub fn set_rust_version(&mut self, rust_version: RustVersion) {
+        self.rust_version = Some(rust_version);
+    }
+
+    /// Whether unused fragments and variables are errors rather than warnings.
+    pub fn deny_unused(&self) -> bool {
+        self.deny_unused
+    }
+
+    /// Whether unused fragments and variables are errors rather than warnings. By default, the
+    /// fragments of the query document that are never spread and the variables an operation never
+    /// uses are reported as compiler warnings on the generated module.
+    pub fn set_deny_unused(&mut self, deny_unused: bool) {
+        self.deny_unused = deny_unused;
+    }
+
+    /// Whether to generate enums with idiomatic variant names and a serde `rename_all` attribute.
+    pub fn enum_rename_all(&self) -> bool {
+        self.enum_rename_all
+    }
+
+    /// Whether to generate enums with idiomatic variant names and a serde `rename_all` attribute.
+    /// The variants are named in `CamelCase` and the enum derives `Serialize` and `Deserialize`
+    /// with `#[serde(rename_all = "SCREAMING_SNAKE_CASE")]`, so only the values not following that
+    /// convention get an explicit `#[serde(rename)]`. The unknown values are deserialized as a unit
+    /// `Other` variant, without the original value. Having no data, these enums also derive `Clone`
+    /// and `Copy`.
+    pub fn set_enum_rename_all(&mut self, enum_rename_all: bool) {
+        self.enum_rename_all = enum_rename_all;
+    }
+
+    /// Whether to (de)serialize the custom scalars through the `GraphQLScalar` trait.
+    pub fn scalar_trait(&self) -> bool {
+        self.scalar_trait
+    }
+
+    /// Whether to (de)serialize the custom scalars through the `GraphQLScalar` trait. The fields
+    /// and variables of custom scalar types, including in options and lists, are then parsed with
+    /// `GraphQLScalar::from_json` and serialized with `GraphQLScalar::to_json`, instead of the
+    /// `Deserialize` and `Serialize` implementations of the scalar types.
+    pub fn set_scalar_trait(&mut self, scalar_trait: bool) {
+        self.scalar_trait = scalar_trait;
+    }
+
+    /// Whether to check at compile time that the generated types are `Send + Sync + 'static`.
+    pub fn assert_send_sync(&self) -> bool {
+        self.assert_send_sync
+    }
+
+    /// Whether to check at compile time that the generated types are `Send + Sync + 'static`. The
+    /// generated code then fails to compile when a custom scalar, or any other type the response
+    /// data or the variables contain, cannot be sent to or shared with another thread, rather than
+    /// the code spawning the futures.
+    pub fn set_assert_send_sync(&mut self, assert_send_sync: bool) {
+        self.assert_send_sync = assert_send_sync;
+    }
+
+    /// Whether to generate the `JSON` scalars as `serde_json::Value`.
+    pub fn json_scalars(&self) -> bool {
+        self.json_scalars
+    }
+
+    /// Whether to generate the scalars named `JSON`, `JSONObject` or `Json` as `serde_json::Value`,
+    /// rather than aliasing them from the parent module like the other custom scalars. Defaults to
+    /// true with the `json_scalars` feature.
+    pub fn set_json_scalars(&mut self, json_scalars: bool) {
+        self.json_scalars = json_scalars;
+    }
+
+    /// Whether to keep the fields of the responses that are not selected in an `extra` map.
+    pub fn extra_fields(&self) -> bool {
+        self.extra_fields
+    }
+
+    /// Whether to generate an `extra` field on the response structs, with the fields of the
+    /// response that are not selected, as a `BTreeMap<String, serde_json::Value>`. Unexpected or
+    /// experimental fields returned by the server are then kept rather than dropped. The response
+    /// derives have to be implemented by `serde_json::Value`.
+    pub fn set_extra_fields(&mut self, extra_fields: bool) {
+        self.extra_fields = extra_fields;
+    }
+
+    /// Whether to generate typestate builders for the variables and the input objects.
+    pub fn typestate_builders(&self) -> bool {
+        self.typestate_builders
+    }
+
+    /// Whether to generate a typestate builder for the variables and each input object, started
+    /// with `builder()`. Each required field is a type parameter of the builder, so its `build()`
+    /// method only exists once all of them are set, and forgetting one is a compile error.
+    pub fn set_typestate_builders(&mut self, typestate_builders: bool) {
+        self.typestate_builders = typestate_builders;
+    }
+
+    /// Whether to register the operation in the `graphql_client::registry`.
+    pub fn register_operations(&self) -> bool {
+        self.register_operations
+    }
+
+    /// Whether to register the operation, with its name, type, query text and hash, in the registry
+    /// of `graphql_client` enumerating all the operations of the application, for example to upload
+    /// persisted queries. The `registry` feature of `graphql_client` has to be enabled. Defaults to
+    /// true with the `registry` feature.
+    pub fn set_register_operations(&mut self, register_operations: bool) {
+        self.register_operations = register_operations;
+    }
+
+    /// Whether to generate code for all the operations of the document, in derive mode.
+    pub fn all_operations(&self) -> bool {
+        self.all_operations
+    }
+
+    /// Whether to generate code for all the operations of the document in derive mode, rather than
+    /// for the operation named like the struct. The module named after the struct then contains a
+    /// module and a struct implementing `GraphQLQuery` for each operation, and a `build_query`
+    /// function building the request body of any of them from its name.
+    pub fn set_all_operations(&mut self, all_operations: bool) {
+        self.all_operations = all_operations;
+    }
+
+    /// Whether the operations declaring the same variables share one `Variables` struct.
+    pub fn share_variables(&self) -> bool {
+        self.share_variables
+    }
+
+    /// Whether the operations declaring the same variables, with the same types and defaults,
+    /// share one `Variables` struct, so the functions taking variables serve all of them. The
+    /// first operation of the document with these variables defines the struct, and the others
+    /// alias it. This applies to the operations generated together by a derive with
+    /// `all_operations`. Defaults to false.
+    pub fn set_share_variables(&mut self, share_variables: bool) {
+        self.share_variables = share_variables;
+    }
+
+    /// Whether to group the generated items in `types`, `enums`, `inputs` and `operations`
+    /// submodules, for the CLI to write them to separate files.
+    pub fn split_files(&self) -> bool {
+        self.split_files
+    }
+
+    /// Whether to group the generated items in `types`, `enums`, `inputs` and `operations`
+    /// submodules, for the CLI to write them to separate files. The items are re-exported from the
+    /// generated module, so the paths to them do not change. Defaults to false.
+    pub fn set_split_files(&mut self, split_files: bool) {
+        self.split_files = split_files;
+    }
+
+    /// Whether to also derive `Serialize` on the response types.
+    pub fn serialize_responses(&self) -> bool {
+        self.serialize_responses
+    }
+
+    /// Whether to also derive `Serialize` on the response types, so responses can be cached,
+    /// forwarded or snapshotted in the same JSON shape as they were received, with the fields
+    /// renamed back and the null fields kept. It cannot be combined with `flatten_wrappers`.
+    /// Defaults to false.
+    pub fn set_serialize_responses(&mut self, serialize_responses: bool) {
+        self.serialize_responses = serialize_responses;
+    }
+
+    /// The deepest nesting of fields allowed in the operations.
+    pub fn max_depth(&self) -> Option<usize> {
+        self.max_depth
+    }
+
+    /// The deepest nesting of fields allowed in the operations, like the depth limit of the server:
+    /// code generation fails for the deeper operations, so they never ship. Unlimited by default.
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = Some(max_depth);
+    }
+
+    /// The highest complexity score allowed for the operations.
+    pub fn max_complexity(&self) -> Option<u64> {
+        self.max_complexity
+    }
+
+    /// The highest complexity score allowed for the operations, computed with the complexity
+    /// options: code generation fails for the more complex operations. Unlimited by default.
+    pub fn set_max_complexity(&mut self, max_complexity: u64) {
+        self.max_complexity = Some(max_complexity);
+    }
+
+    /// How the complexity of the operations is computed, for the max complexity.
+    pub fn complexity_options(&self) -> &ComplexityOptions {
+        &self.complexity_options
+    }
+
+    /// How the complexity of the operations is computed, for the max complexity, with the sizes
+    /// of the lists in particular.
+    pub fn set_complexity_options(&mut self, complexity_options: ComplexityOptions) {
+        self.complexity_options = complexity_options;
+    }
+
+    /// The callback run on each generated struct and enum.
+    pub(crate) fn type_hook(&self) -> Option<&TypeHook> {
+        self.type_hook.as_ref().map(|hook| &**hook)
+    }
+
+    /// A callback run on each generated struct and enum, with its path in the generated code, like
+    /// `star_wars_query::ResponseData`, and the tokens of its definition, including its attributes.
+    /// The callback can add attributes to the definition, or impls and other items after it.
+    pub fn set_type_hook(&mut self, type_hook: impl Fn(&str, &mut TokenStream) + 'static) {
+        self.type_hook = Some(Box::new(type_hook));
+    }
+
+    /// The callback run on the whole generated code.
+    pub(crate) fn post_process(&self) -> Option<&PostProcess> {
+        self.post_process
+            .as_ref()
+            .map(|post_process| &**post_process)
+    }
+
+    /// A callback run on the whole generated code, after the type hook, so the embedders of the
+    /// code generation can inject their own items programmatically.
+    pub fn set_post_process(&mut self, post_process: impl Fn(&mut TokenStream) + 'static) {
+        self.post_process = Some(Box::new(post_process));
+    }
+
+    /// Whether to generate a `validate` method on the variables.
+    pub fn validate_variables(&self) -> bool {
+        self.validate_variables
+    }
+
+    /// Whether to generate a `validate` method on the variables, checking the constraints of the
+    /// schema their types cannot express before they are sent: the non-null custom scalars
+    /// serializing to null, the enum values unknown to the schema, and the format of the custom
+    /// scalars implementing `GraphQLScalar` with the `scalar_trait` option. It returns the list of
+    /// the invalid values, rather than the error of the server. Defaults to false.
+    pub fn set_validate_variables(&mut self, validate_variables: bool) {
+        self.validate_variables = validate_variables;
+    }
+
+    /// Whether to generate serde code working with the formats that are not self-describing.
+    pub fn format_agnostic_serde(&self) -> bool {
+        self.format_agnostic_serde
+    }
+
+    /// Whether to generate serde code working with the formats that are not self-describing, like
+    /// bincode, so the typed responses can be stored in binary caches or sent through message
+    /// queues. The optional fields are always serialized, rather than skipped when `None`, and the
+    /// union enums are tagged with `__typename` in human-readable formats like JSON and with the
+    /// index of their variant in the others. The code relying on `#[serde(flatten)]` or on the JSON
+    /// values cannot work with these formats, so fragment spreads in objects, the `__typename`
+    /// selections on interfaces, and the `extra_fields`, `json_scalars`, `scalar_trait` and
+    /// `flatten_wrappers` options are then rejected. Defaults to false.
+    pub fn set_format_agnostic_serde(&mut self, format_agnostic_serde: bool) {
+        self.format_agnostic_serde = format_agnostic_serde;
+    }
+
+    /// How the enums of the selections on unions and interfaces are represented for serde.
+    pub fn union_tagging(&self) -> &UnionTagging {
+        &self.union_tagging
+    }
+
+    /// How the enums of the selections on unions and interfaces are represented for serde, for the
+    /// servers and caches laying them out differently than GraphQL responses. Defaults to
+    /// [UnionTagging::Internal].
+    pub fn set_union_tagging(&mut self, union_tagging: UnionTagging) {
+        self.union_tagging = union_tagging;
+    }
+
+    /// What the enums of the selections on unions and interfaces fall back to without `__typename`.
+    pub fn typename_fallback(&self) -> Option<&TypenameFallback> {
+        self.typename_fallback.as_ref()
+    }
+
+    /// What the enums of the selections on unions and interfaces fall back to when a response
+    /// object has no `__typename`, for the servers omitting it unless requested, instead of failing
+    /// the deserialization. The `__typename` selection is then not required on unions. Cannot be
+    /// used with the `untagged` union tagging or the `format_agnostic_serde` option.
+    pub fn set_typename_fallback(&mut self, typename_fallback: TypenameFallback) {
+        self.typename_fallback = Some(typename_fallback);
+    }
+
+    /// Whether to print the time spent in each stage of the code generation.
+    pub fn timings(&self) -> bool {
+        self.timings
+    }
+
+    /// Whether to print the time spent in each stage of the code generation to stderr: the parsing
+    /// of the query document and of the schema, the building of the schema model, then the binding
+    /// of the selections to the schema and the generation of the tokens for each operation, so slow
+    /// builds can be blamed on the schema or on the operations. Defaults to false.
+    pub fn set_timings(&mut self, timings: bool) {
+        self.timings = timings;
+    }
+
+    /// Whether the generated code may use what was stabilized in the given Rust version.
+    pub(crate) fn supports(&self, version: RustVersion) -> bool {
+        self.rust_version
+            .map(|target| target >= version)
+            .unwrap_or(true)
+    }
+}
+
+/// A Rust compiler version, like `1.31`. The generated code needs at least Rust 1.31, the first
+/// release supporting the 2018 edition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct RustVersion {
+    /// The major version.
+    pub major: u32,
+    /// The minor version.
+    pub minor: u32,
+}
+
+impl RustVersion {
+    /// The oldest version the generated code can target.
+    pub const MINIMUM: RustVersion = RustVersion::new(1, 31);
+    /// `std::convert::TryFrom` was stabilized in Rust 1.34.
+    pub(crate) const TRY_FROM: RustVersion = RustVersion::new(1, 34);
+
+    /// A Rust version from its major and minor numbers.
+    pub const fn new(major: u32, minor: u32) -> RustVersion {
+        RustVersion { major, minor }
+    }
+}
+
+impl std::str::FromStr for RustVersion {
+    type Err = failure::Error;
+
+    fn from_str(s: &str) -> Result<Self, failure::Error> {
+        let mut parts = s.trim().split('.');
+        let version = match (parts.next(), parts.next()) {
+            (Some(major), Some(minor)) => RustVersion::new(major.parse()?, minor.parse()?),
+            _ => {
+                return Err(format_err!(
+                    "Invalid Rust version `{}`, expected e.g. `1.31`",
+                    s
+                ))
+            }
+        };
+
+        if version < RustVersion::MINIMUM {
+            return Err(format_err!(
+                "The generated code needs at least Rust {}.{}",
+                RustVersion::MINIMUM.major,
+                RustVersion::MINIMUM.minor
+            ));
+        }
+
+        Ok(version)
+    }
+}
+
+/// How the enums of the selections on unions and interfaces are represented for serde, with a
+/// `Droid` value for example.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UnionTagging {
+    /// `{"__typename": "Droid", "name": "R2-D2"}`, the layout of GraphQL responses. Parsed from
+    /// `internal`.
+    Internal,
+    /// `{"__typename": "Droid", "content": {"name": "R2-D2"}}`, with the fields under the given
+    /// key. Parsed from `adjacent`, for the `content` key, or `adjacent:<key>`.
+    Adjacent {
+        /// The key of the fields.
+        content: String,
+    },
+    /// `{"name": "R2-D2"}`, deserialized to the first variant matching its shape, without a
+    /// `__typename`. The variants without selected fields only match `null`. Parsed from
+    /// `untagged`.
+    Untagged,
+}
+
+impl Default for UnionTagging {
+    fn default() -> Self {
+        UnionTagging::Internal
+    }
+}
+
+impl std::str::FromStr for UnionTagging {
+    type Err = failure::Error;
+
+    fn from_str(s: &str) -> Result<Self, failure::Error> {
+        let mut parts = s.trim().splitn(2, ':').map(str::trim);
+        match (parts.next(), parts.next()) {
+            (Some("internal"), None) => Ok(UnionTagging::Internal),
+            (Some("adjacent"), None) => Ok(UnionTagging::Adjacent {
+                content: "content".to_owned(),
+            }),
+            (Some("adjacent"), Some(content)) if !content.is_empty() => Ok(UnionTagging::Adjacent {
+                content: content.to_owned(),
+            }),
+            (Some("untagged"), None) => Ok(UnionTagging::Untagged),
+            _ => Err(format_err!(
+                "Invalid union tagging `{}`, expected `internal`, `adjacent`, `adjacent:<key>` or `untagged`",
+                s
+            )),
+        }
+    }
+}
+
+/// What the enums of the selections on unions and interfaces are deserialized to when a response
+/// object has no `__typename`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypenameFallback {
+    /// The first variant matching the shape of the object, as with the untagged enums. Parsed from
+    /// `untagged`.
+    Untagged,
+    /// The variant of the given type, which must be a possible type of every union and interface
+    /// with a selection. Parsed from the name of the type.
+    Variant(String),
+}
+
+impl std::str::FromStr for TypenameFallback {
+    type Err = failure::Error;
+
+    fn from_str(s: &str) -> Result<Self, failure::Error> {
+        let s = s.trim();
+        let mut chars = s.chars();
+        let is_name = chars
+            .next()
+            .map(|first| first.is_ascii_alphabetic() || first == '_')
+            .unwrap_or(false)
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+        match s {
+            "untagged" => Ok(TypenameFallback::Untagged),
+            _ if is_name => Ok(TypenameFallback::Variant(s.to_owned())),
+            _ => Err(format_err!(
+                "Invalid typename fallback `{}`, expected `untagged` or the name of a type",
+                s
+            )),
+        }
+    }
+}
+
+/// The `pub` visibility.
+pub(crate) fn public_visibility() -> Visibility {
+    VisPublic {
+        pub_token: <Token![pub]>::default(),
+    }
+    .into()
 }
@@ -0,0 +1,420 @@
+//! The depth, field count and complexity of operations, to keep them under the limits servers put
+//! on the queries they execute.
+
+use crate::field_type::FieldType;
+use crate::objects::GqlObjectField;
+use crate::schema::Schema;
+use crate::unused::name_of;
+use failure::format_err;
+use graphql_parser::query::{
+    Definition, Document, FragmentDefinition, OperationDefinition, Selection, SelectionSet,
+    TypeCondition, Value,
+};
+use std::collections::BTreeMap;
+
+/// The schema directive giving the assumed size of the lists returned by a field, as in the
+/// [IBM cost specification](https://ibm.github.io/graphql-specs/cost-spec.html):
+/// `@listSize(assumedSize: 50)`.
+const LIST_SIZE_DIRECTIVE: &str = "listSize";
+
+/// The arguments limiting the size of the lists, or connections, returned by a field.
+const SLICING_ARGUMENTS: &[&str] = &["first", "last"];
+
+/// How the complexity of the operations is computed.
+///
+/// Each selected field costs one, plus the cost of its own selection multiplied by the size of the
+/// lists it returns. That size is, in order of precedence:
+///
+/// - the value of its `first` or `last` argument, for the paginated fields,
+/// - the multiplier configured for the field,
+/// - the `assumedSize` of its `@listSize` directive in the schema,
+/// - the default list multiplier, for the fields returning lists, and one for the others.
+#[derive(Debug, Clone)]
+pub struct ComplexityOptions {
+    list_multipliers: BTreeMap<String, u64>,
+    default_list_multiplier: u64,
+}
+
+impl Default for ComplexityOptions {
+    fn default() -> Self {
+        ComplexityOptions {
+            list_multipliers: BTreeMap::new(),
+            default_list_multiplier: 10,
+        }
+    }
+}
+
+impl ComplexityOptions {
+    /// The default options: a multiplier of 10 for the lists of unknown size.
+    pub fn new() -> ComplexityOptions {
+        ComplexityOptions::default()
+    }
+
+    /// The size of the lists returned by the field, by schema coordinate like `Query.users`.
+    pub fn set_list_multiplier(&mut self, coordinate: impl Into<String>, multiplier: u64) {
+        self.list_multipliers.insert(coordinate.into(), multiplier);
+    }
+
+    /// Sets the list multipliers from a comma-separated list of `Type.field = multiplier`, as
+    /// given on the command line.
+    pub fn set_list_multipliers(&mut self, multipliers: &str) -> Result<(), failure::Error> {
+        for multiplier in multipliers
+            .split(',')
+            .map(str::trim)
+            .filter(|multiplier| !multiplier.is_empty())
+        {
+            let mut parts = multiplier.splitn(2, '=').map(str::trim);
+            match (parts.next(), parts.next().map(str::parse)) {
+                (Some(coordinate), Some(Ok(size))) if coordinate.contains('.') => {
+                    self.set_list_multiplier(coordinate, size)
+                }
+                _ => {
+                    return Err(format_err!(
+                        "Invalid list multiplier `{}`, expected `Type.field = multiplier`",
+                        multiplier
+                    ))
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The configured size of the lists returned by the field, by schema coordinate.
+    pub fn list_multiplier(&self, coordinate: &str) -> Option<u64> {
+        self.list_multipliers.get(coordinate).cloned()
+    }
+
+    /// The size of the lists without a known size. Default value is 10.
+    pub fn set_default_list_multiplier(&mut self, multiplier: u64) {
+        self.default_list_multiplier = multiplier;
+    }
+
+    /// The size of the lists without a known size.
+    pub fn default_list_multiplier(&self) -> u64 {
+        self.default_list_multiplier
+    }
+}
+
+/// The cost of an operation, with the fragments it spreads expanded. `__typename` is free.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OperationComplexity {
+    /// The name of the operation, empty for an anonymous operation.
+    pub name: String,
+    /// The deepest nesting of fields, one for an operation selecting only root fields.
+    pub depth: usize,
+    /// The number of selected fields, not multiplied by the size of the lists.
+    pub field_count: usize,
+    /// The number of aliased fields, which servers limit to prevent the same field being resolved
+    /// many times under different names.
+    pub alias_count: usize,
+    /// The complexity score, with the list multipliers of the [ComplexityOptions].
+    pub complexity: u64,
+}
+
+/// The cost of every operation of the document, in the order they are defined.
+pub(crate) fn operations_complexity(
+    schema: &Schema<'_>,
+    document: &Document,
+    options: &ComplexityOptions,
+) -> Vec<OperationComplexity> {
+    let fragments: BTreeMap<&str, &FragmentDefinition> = document
+        .definitions
+        .iter()
+        .filter_map(|definition| match definition {
+            Definition::Fragment(fragment) => Some((fragment.name.as_str(), fragment)),
+            Definition::Operation(_) => None,
+        })
+        .collect();
+
+    document
+        .definitions
+        .iter()
+        .filter_map(|definition| match definition {
+            Definition::Operation(operation) => Some(operation),
+            Definition::Fragment(_) => None,
+        })
+        .map(|operation| {
+            let (root_name, selection_set, variable_definitions) = match operation {
+                OperationDefinition::SelectionSet(selection_set) => {
+                    (schema.query_type.unwrap_or("Query"), selection_set, &[][..])
+                }
+                OperationDefinition::Query(query) => (
+                    schema.query_type.unwrap_or("Query"),
+                    &query.selection_set,
+                    &query.variable_definitions[..],
+                ),
+                OperationDefinition::Mutation(mutation) => (
+                    schema.mutation_type.unwrap_or("Mutation"),
+                    &mutation.selection_set,
+                    &mutation.variable_definitions[..],
+                ),
+                OperationDefinition::Subscription(subscription) => (
+                    schema.subscription_type.unwrap_or("Subscription"),
+                    &subscription.selection_set,
+                    &subscription.variable_definitions[..],
+                ),
+            };
+            let analysis = Analysis {
+                schema,
+                fragments: &fragments,
+                options,
+                variable_defaults: variable_definitions
+                    .iter()
+                    .filter_map(|variable| {
+                        variable
+                            .default_value
+                            .as_ref()
+                            .map(|value| (variable.name.as_str(), value))
+                    })
+                    .collect(),
+            };
+            let cost = analysis.selection_cost(root_name, selection_set, &mut Vec::new());
+
+            OperationComplexity {
+                name: name_of(operation).unwrap_or("").to_owned(),
+                depth: cost.depth,
+                field_count: cost.field_count,
+                alias_count: cost.alias_count,
+                complexity: cost.complexity,
+            }
+        })
+        .collect()
+}
+
+struct Analysis<'a, 'schema> {
+    schema: &'a Schema<'schema>,
+    fragments: &'a BTreeMap<&'a str, &'a FragmentDefinition>,
+    options: &'a ComplexityOptions,
+    /// The default values of the variables of the operation, for the slicing arguments.
+    variable_defaults: BTreeMap<&'a str, &'a Value>,
+}
+
+#[derive(Default)]
+struct Cost {
+    depth: usize,
+    field_count: usize,
+    alias_count: usize,
+    complexity: u64,
+}
+
+impl Cost {
+    fn add(&mut self, other: Cost) {
+        self.depth = self.depth.max(other.depth);
+        self.field_count += other.field_count;
+        self.alias_count += other.alias_count;
+        self.complexity = self.complexity.saturating_add(other.complexity);
+    }
+}
+
+impl<'a, 'schema> Analysis<'a, 'schema> {
+    /// The cost of the selection on the type, with the names of the fragments being spread to
+    /// stop at the fragments spreading themselves.
+    fn selection_cost(
+        &self,
+        type_name: &str,
+        selection_set: &'a SelectionSet,
+        spread_fragments: &mut Vec<&'a str>,
+    ) -> Cost {
+        let mut cost = Cost::default();
+
+        for item in &selection_set.items {
+            match item {
+                Selection::Field(field) if field.name == "__typename" => (),
+                Selection::Field(field) => {
+                    let schema_field = self.schema_field(type_name, &field.name);
+                    let field_type_name = schema_field
+                        .map(|schema_field| schema_field.type_.inner_name_str())
+                        .unwrap_or("");
+                    let selection = self.selection_cost(
+                        field_type_name,
+                        &field.selection_set,
+                        spread_fragments,
+                    );
+                    let multiplier = self.multiplier(type_name, field, schema_field);
+
+                    cost.add(Cost {
+                        depth: selection.depth + 1,
+                        field_count: selection.field_count + 1,
+                        alias_count: selection.alias_count + field.alias.is_some() as usize,
+                        complexity: selection
+                            .complexity
+                            .saturating_mul(multiplier)
+                            .saturating_add(1),
+                    });
+                }
+                Selection::FragmentSpread(spread) => {
+                    let name = spread.fragment_name.as_str();
+                    if spread_fragments.contains(&name) {
+                        continue;
+                    }
+                    if let Some(fragment) = self.fragments.get(name) {
+                        let TypeCondition::On(on) = &fragment.type_condition;
+                        spread_fragments.push(name);
+                        cost.add(self.selection_cost(
+                            on,
+                            &fragment.selection_set,
+                            spread_fragments,
+                        ));
+                        spread_fragments.pop();
+                    }
+                }
+                Selection::InlineFragment(inline_fragment) => {
+                    let on = match &inline_fragment.type_condition {
+                        Some(TypeCondition::On(on)) => on.as_str(),
+                        None => type_name,
+                    };
+                    cost.add(self.selection_cost(
+                        on,
+                        &inline_fragment.selection_set,
+                        spread_fragments,
+                    ));
+                }
+            }
+        }
+
+        cost
+    }
+
+    fn schema_field(
+        &self,
+        type_name: &str,
+        field_name: &str,
+    ) -> Option<&'a GqlObjectField<'schema>> {
+        self.schema
+            .objects
+            .get(type_name)
+            .map(|object| &object.fields)
+            .or_else(|| {
+                self.schema
+                    .interfaces
+                    .get(type_name)
+                    .map(|interface| &interface.fields)
+            })
+            .and_then(|fields| fields.iter().find(|field| field.name == field_name))
+    }
+
+    /// The size of the lists returned by the field.
+    fn multiplier(
+        &self,
+        type_name: &str,
+        field: &graphql_parser::query::Field,
+        schema_field: Option<&GqlObjectField<'_>>,
+    ) -> u64 {
+        let slicing_argument = field
+            .arguments
+            .iter()
+            .filter(|(name, _)| SLICING_ARGUMENTS.contains(&name.as_str()))
+            .filter_map(|(_, value)| match value {
+                Value::Variable(variable) => self.variable_defaults.get(variable.as_str()).cloned(),
+                value => Some(value),
+            })
+            .filter_map(|value| match value {
+                Value::Int(size) => size.as_i64(),
+                _ => None,
+            })
+            .map(|size| size.max(0) as u64)
+            .max();
+        if let Some(size) = slicing_argument {
+            return size;
+        }
+
+        let coordinate = format!("{}.{}", type_name, field.name);
+        if let Some(size) = self.options.list_multiplier(&coordinate) {
+            return size;
+        }
+
+        let schema_field = match schema_field {
+            Some(schema_field) => schema_field,
+            None => return 1,
+        };
+        let assumed_size = schema_field
+            .directives
+            .iter()
+            .filter(|directive| directive.name == LIST_SIZE_DIRECTIVE)
+            .flat_map(|directive| &directive.arguments)
+            .filter(|(name, _)| name == "assumedSize")
+            .filter_map(|(_, value)| value.parse().ok())
+            .next();
+
+        match (assumed_size, is_list(&schema_field.type_)) {
+            (Some(size), _) => size,
+            (None, true) => self.options.default_list_multiplier(),
+            (None, false) => 1,
+        }
+    }
+}
+
+fn is_list(field_type: &FieldType<'_>) -> bool {
+    match field_type {
+        FieldType::Optional(inner) => is_list(inner),
+        FieldType::Vector(_) => true,
+        FieldType::Named(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = r#"
+        type Query {
+            viewer: User
+            users(first: Int): [User!]!
+            search: [User!] @listSize(assumedSize: 3)
+        }
+
+        type User {
+            name: String!
+            friends: [User!]!
+        }
+    "#;
+
+    fn complexity(query: &str, options: &ComplexityOptions) -> Vec<OperationComplexity> {
+        let schema = graphql_parser::schema::parse_schema(SCHEMA).unwrap();
+        let schema = Schema::from(&schema);
+        let document = graphql_parser::parse_query(query).unwrap();
+
+        operations_complexity(&schema, &document, options)
+    }
+
+    #[test]
+    fn depth_and_field_count_expand_the_fragments() {
+        let operations = complexity(
+            "query Viewer { viewer { __typename ...Friends } } fragment Friends on User { friends { fullName: name } }",
+            &ComplexityOptions::new(),
+        );
+
+        assert_eq!(operations.len(), 1);
+        assert_eq!(operations[0].name, "Viewer");
+        assert_eq!(operations[0].depth, 3);
+        assert_eq!(operations[0].field_count, 3);
+        assert_eq!(operations[0].alias_count, 1);
+        // viewer: 1 + friends: (1 + 10 × name: 1)
+        assert_eq!(operations[0].complexity, 12);
+    }
+
+    #[test]
+    fn list_multipliers_come_from_arguments_configuration_and_directives() {
+        let mut options = ComplexityOptions::new();
+        options
+            .set_list_multipliers("User.friends = 2, Query.users = 100")
+            .unwrap();
+
+        let operations = complexity(
+            "query Q($count: Int = 4) { users(first: $count) { friends { name } } search { name } }",
+            &options,
+        );
+
+        // users: 1 + 4 × friends: (1 + 2 × name: 1), search: 1 + 3 × name: 1
+        assert_eq!(operations[0].complexity, 13 + 4);
+    }
+
+    #[test]
+    fn invalid_list_multipliers_are_rejected() {
+        let mut options = ComplexityOptions::new();
+
+        assert!(options.set_list_multipliers("friends = 2").is_err());
+        assert!(options.set_list_multipliers("User.friends = many").is_err());
+    }
+}
@@ -21,6 +21,7 @@ pub(crate) fn typename_field() -> GqlObjectField<'static> {
         /// https://github.com/facebook/graphql/blob/master/spec/Section%204%20--%20Introspection.md
         type_: FieldType::Named(string_type()),
         deprecation: DeprecationStatus::Current,
+        directives: Vec::new(),
     }
 }
 
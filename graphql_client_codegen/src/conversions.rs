@@ -0,0 +1,338 @@
+//! Conversions from the response objects to the input objects of the same shape, for the forms
+//! fetching an entity, modifying it and sending it back.
+
+use crate::deprecation::{DeprecationStatus, DeprecationStrategy};
+use crate::field_type::FieldType;
+use crate::inputs::GqlInput;
+use crate::objects::{GqlObject, GqlObjectField};
+use crate::operations::Operation;
+use crate::query::QueryContext;
+use crate::relay::response_path;
+use crate::selection::{Selection, SelectionField, SelectionItem};
+use crate::shared::{field_name, field_prefix, flatten_field, selected_field_type};
+use failure::format_err;
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::quote;
+use std::collections::BTreeSet;
+
+/// The `From` implementations converting the response objects to input objects, configured as a
+/// comma-separated list of `path = InputType`.
+pub(crate) fn input_conversions(
+    context: &QueryContext<'_, '_>,
+    operation: &Operation<'_>,
+    conversions: &str,
+) -> Result<TokenStream, failure::Error> {
+    let root = &context.schema.objects[operation.root_name(&context.schema)];
+    let mut generator = Conversions {
+        context,
+        converted: BTreeSet::new(),
+        impls: Vec::new(),
+    };
+
+    for conversion in conversions
+        .split(',')
+        .map(str::trim)
+        .filter(|conversion| !conversion.is_empty())
+    {
+        let mut parts = conversion.splitn(2, '=').map(str::trim);
+        let (path, input_name) = match (parts.next(), parts.next()) {
+            (Some(path), Some(input_name)) if !path.is_empty() && !input_name.is_empty() => {
+                (path, input_name)
+            }
+            _ => {
+                return Err(format_err!(
+                    "Invalid input conversion `{}`, expected `path = InputType`",
+                    conversion
+                ))
+            }
+        };
+
+        let field = response_path(context, root, &operation.selection, &operation.name, path)?;
+        let object = context
+            .schema
+            .objects
+            .get(field.field_type.inner_name_str())
+            .ok_or_else(|| format_err!("The field at `{}` is not an object.", path))?;
+        let input = context.schema.inputs.get(input_name).ok_or_else(|| {
+            format_err!(
+                "Unknown input object `{}`, in the conversion of `{}`.",
+                input_name,
+                path
+            )
+        })?;
+
+        generator.object_conversion(object, field.selection, &field.prefix, input, path)?;
+    }
+
+    let impls = generator.impls;
+    Ok(quote!(#(#impls)*))
+}
+
+struct Conversions<'c, 'query, 'schema> {
+    context: &'c QueryContext<'query, 'schema>,
+    /// The conversions already generated, by prefix of the response struct and input object.
+    converted: BTreeSet<(String, String)>,
+    impls: Vec<TokenStream>,
+}
+
+impl<'c, 'query, 'schema> Conversions<'c, 'query, 'schema> {
+    /// Generates the conversion from the struct of the selection on the object, named `prefix`, to
+    /// the input object, and the conversions of the nested objects it needs.
+    fn object_conversion(
+        &mut self,
+        object: &GqlObject<'_>,
+        selection: &Selection<'_>,
+        prefix: &str,
+        input: &GqlInput<'_>,
+        path: &str,
+    ) -> Result<(), failure::Error> {
+        if !self
+            .converted
+            .insert((prefix.to_owned(), input.name.to_owned()))
+        {
+            return Ok(());
+        }
+        let context = self.context;
+        context.schema.require(input.name);
+
+        let mut input_fields: Vec<&GqlObjectField<'_>> = input.fields.values().collect();
+        input_fields.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+
+        let siblings = selection.response_names();
+        let mut assignments = Vec::with_capacity(input_fields.len());
+        for input_field in input_fields {
+            // The denied optional input fields are not generated.
+            if let (DeprecationStatus::Deprecated(_), DeprecationStrategy::Deny, true) = (
+                &input_field.deprecation,
+                context.field_deprecation_strategy(input.name, input_field.name),
+                input_field.type_.is_optional(),
+            ) {
+                continue;
+            }
+
+            let name = Ident::new(
+                &input.rust_field_name(context, input_field.name),
+                Span::call_site(),
+            );
+            let value = match selected_field(context, object, selection, input_field.name) {
+                Some((field, schema_field)) => self.field_conversion(
+                    object,
+                    field,
+                    &siblings,
+                    schema_field,
+                    prefix,
+                    input,
+                    input_field,
+                    path,
+                )?,
+                None if input_field.type_.is_optional() => quote!(None),
+                None => {
+                    return Err(format_err!(
+                        "Cannot convert `{}` to `{}`: `{}` is required but not selected.",
+                        path,
+                        input.name,
+                        input_field.name
+                    ))
+                }
+            };
+            assignments.push(quote!(#name: #value));
+        }
+
+        let struct_name = FieldType::Named(object.name).to_rust(context, prefix);
+        let input_name = FieldType::Named(input.name).to_rust(context, "");
+
+        self.impls.push(quote! {
+            #[allow(deprecated)]
+            impl From<#struct_name> for #input_name {
+                #[allow(unused_variables)]
+                fn from(value: #struct_name) -> Self {
+                    #input_name {
+                        #(#assignments,)*
+                    }
+                }
+            }
+        });
+
+        Ok(())
+    }
+
+    /// The value of the input field, converted from the selected field of the same name.
+    #[allow(clippy::too_many_arguments)]
+    fn field_conversion(
+        &mut self,
+        object: &GqlObject<'_>,
+        field: &SelectionField<'_>,
+        siblings: &[&str],
+        schema_field: &GqlObjectField<'_>,
+        prefix: &str,
+        input: &GqlInput<'_>,
+        input_field: &GqlObjectField<'_>,
+        path: &str,
+    ) -> Result<TokenStream, failure::Error> {
+        let alias = field.alias.unwrap_or(field.name);
+        let field_path = format!("{}.{}", path, alias);
+        let field_prefix = field_prefix(self.context, prefix, alias, siblings.iter().cloned());
+        let field_type = selected_field_type(field, &schema_field.type_);
+        let flattened = flatten_field(self.context, &field_type, &field.fields, field_prefix);
+        if flattened.unwrapped.is_some() {
+            return Err(format_err!(
+                "Cannot convert `{}` to `{}`: the field `{}` is flattened.",
+                path,
+                input.name,
+                alias
+            ));
+        }
+
+        let mismatch = || {
+            format_err!(
+                "Cannot convert `{}` to `{}`: the field `{}` is `{}` on `{}`, but `{}` in the input.",
+                path,
+                input.name,
+                alias,
+                graphql_type(&field_type),
+                object.name,
+                graphql_type(&input_field.type_),
+            )
+        };
+        let field_name = Ident::new(
+            &field_name(self.context, alias, siblings.iter().cloned()),
+            Span::call_site(),
+        );
+        let value = self.value_conversion(
+            &flattened.type_,
+            &input_field.type_,
+            quote!(value.#field_name),
+            &field.fields,
+            &flattened.prefix,
+            &field_path,
+            &mismatch,
+        )?;
+
+        // The fields of recursive input objects are boxed, inside their `Option`.
+        let is_boxed = self
+            .context
+            .schema
+            .inputs
+            .get(input_field.type_.inner_name_str())
+            .map(|input| input.is_recursive_without_indirection(self.context))
+            .unwrap_or(false);
+
+        Ok(match (is_boxed, &input_field.type_) {
+            (false, _) => value,
+            (true, FieldType::Optional(_)) => quote!(#value.map(Box::new)),
+            (true, _) => quote!(Box::new(#value)),
+        })
+    }
+
+    /// Converts `expr`, of the response type `from`, to the input type `to`, through their
+    /// `Option` and `Vec` layers.
+    #[allow(clippy::too_many_arguments)]
+    fn value_conversion(
+        &mut self,
+        from: &FieldType<'_>,
+        to: &FieldType<'_>,
+        expr: TokenStream,
+        selection: &Selection<'_>,
+        prefix: &str,
+        path: &str,
+        mismatch: &dyn Fn() -> failure::Error,
+    ) -> Result<TokenStream, failure::Error> {
+        match (from, to) {
+            (FieldType::Optional(from), FieldType::Optional(to)) => {
+                let value = self.value_conversion(
+                    from,
+                    to,
+                    quote!(value),
+                    selection,
+                    prefix,
+                    path,
+                    mismatch,
+                )?;
+                Ok(quote!(#expr.map(|value| #value)))
+            }
+            (from, FieldType::Optional(to)) => {
+                let value =
+                    self.value_conversion(from, to, expr, selection, prefix, path, mismatch)?;
+                Ok(quote!(Some(#value)))
+            }
+            (FieldType::Vector(from), FieldType::Vector(to)) => {
+                let value = self.value_conversion(
+                    from,
+                    to,
+                    quote!(value),
+                    selection,
+                    prefix,
+                    path,
+                    mismatch,
+                )?;
+                Ok(quote!(#expr.into_iter().map(|value| #value).collect()))
+            }
+            (FieldType::Named(from), FieldType::Named(to)) => {
+                let context = self.context;
+                match (
+                    context.schema.objects.get(from),
+                    context.schema.inputs.get(to),
+                ) {
+                    (Some(object), Some(input)) => {
+                        self.object_conversion(object, selection, prefix, input, path)?;
+                        Ok(quote!(#expr.into()))
+                    }
+                    (None, None) if from == to => Ok(expr),
+                    _ => Err(mismatch()),
+                }
+            }
+            _ => Err(mismatch()),
+        }
+    }
+}
+
+/// The first selection of the field with the given response name, unless it is not generated
+/// because of its deprecation.
+fn selected_field<'a, 'query, 'o, 'schema>(
+    context: &QueryContext<'_, '_>,
+    object: &'o GqlObject<'schema>,
+    selection: &'a Selection<'query>,
+    response_name: &str,
+) -> Option<(&'a SelectionField<'query>, &'o GqlObjectField<'schema>)> {
+    let field = selection
+        .into_iter()
+        .filter_map(|item| match item {
+            SelectionItem::Field(field) if field.alias.unwrap_or(field.name) == response_name => {
+                Some(field)
+            }
+            _ => None,
+        })
+        .next()?;
+    let schema_field = object.fields.iter().find(|f| f.name == field.name)?;
+
+    match (
+        &schema_field.deprecation,
+        context.field_deprecation_strategy(object.name, field.name),
+    ) {
+        (DeprecationStatus::Deprecated(_), DeprecationStrategy::Deny) => None,
+        _ => Some((field, schema_field)),
+    }
+}
+
+/// The type as it is written in GraphQL, like `[String!]`.
+pub(crate) fn graphql_type(field_type: &FieldType<'_>) -> String {
+    match field_type {
+        FieldType::Named(name) => format!("{}!", name),
+        FieldType::Vector(inner) => format!("[{}]!", graphql_type(inner)),
+        FieldType::Optional(inner) => graphql_type(inner).trim_end_matches('!').to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn graphql_types_are_printed() {
+        let list = FieldType::Optional(Box::new(FieldType::Vector(Box::new(FieldType::Named(
+            "String",
+        )))));
+        assert_eq!(graphql_type(&list), "[String!]");
+        assert_eq!(graphql_type(&FieldType::Named("ID")), "ID!");
+    }
+}
@@ -1,13 +1,87 @@
-use proc_macro2::{Ident, Span, TokenStream};
+use heck::{CamelCase, KebabCase, MixedCase, ShoutySnakeCase, SnakeCase};
+use proc_macro2::{Ident, Literal, Span, TokenStream};
 use quote::quote;
 use std::cell::Cell;
+use std::collections::{BTreeMap, BTreeSet};
 
 pub const ENUMS_PREFIX: &str = "";
 
+/// How the values of an enum are represented on the wire, when a backend does not follow the
+/// schema.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct WireValues {
+    /// The conversion of all the values of the enum.
+    pub(crate) format: WireFormat,
+    /// The wire values of single values of the enum, by their name in the schema.
+    pub(crate) overrides: BTreeMap<String, WireValue>,
+}
+
+/// The conversion of the values of an enum to their wire values.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum WireFormat {
+    /// The values keep their name in the schema.
+    Schema,
+    /// The values are converted to the case named like the `rename_all` cases of serde.
+    Case(&'static str),
+    /// The values are integers, numbered from 0 in the order of the schema.
+    Integer,
+}
+
+impl Default for WireFormat {
+    fn default() -> WireFormat {
+        WireFormat::Schema
+    }
+}
+
+/// The cases the values of an enum can be converted to, named like the `rename_all` cases of serde.
+pub(crate) const WIRE_CASES: &[&str] = &[
+    "lowercase",
+    "UPPERCASE",
+    "PascalCase",
+    "camelCase",
+    "snake_case",
+    "SCREAMING_SNAKE_CASE",
+    "kebab-case",
+    "SCREAMING-KEBAB-CASE",
+];
+
+/// The representation of a value of an enum on the wire.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum WireValue {
+    String(String),
+    Integer(i64),
+}
+
+impl WireValues {
+    /// The wire value of the value of the enum with the given name and position in the schema.
+    fn wire_value(&self, name: &str, index: usize) -> WireValue {
+        if let Some(value) = self.overrides.get(name) {
+            return value.clone();
+        }
+
+        match self.format {
+            WireFormat::Schema => WireValue::String(name.to_owned()),
+            WireFormat::Integer => WireValue::Integer(index as i64),
+            WireFormat::Case(case) => WireValue::String(match case {
+                "lowercase" => name.to_lowercase(),
+                "UPPERCASE" => name.to_uppercase(),
+                "PascalCase" => name.to_camel_case(),
+                "camelCase" => name.to_mixed_case(),
+                "snake_case" => name.to_snake_case(),
+                "SCREAMING_SNAKE_CASE" => name.to_shouty_snake_case(),
+                "kebab-case" => name.to_kebab_case(),
+                _ => name.to_kebab_case().to_uppercase(),
+            }),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct EnumVariant<'schema> {
     pub description: Option<&'schema str>,
     pub name: &'schema str,
+    /// The directives applied to the value in the schema.
+    pub directives: Vec<crate::schema::AppliedDirective>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -30,7 +104,15 @@ impl<'schema> GqlEnum<'schema> {
         &self,
         query_context: &crate::query::QueryContext<'_, '_>,
     ) -> TokenStream {
+        let wire_values = query_context.enum_wire_values(self.name);
+        if query_context.enum_rename_all && wire_values.is_none() {
+            if let Some(tokens) = self.to_rust_with_rename_all(query_context) {
+                return tokens;
+            }
+        }
+
         let derives = query_context.response_enum_derives();
+        let vis = &query_context.item_visibility;
         let variant_names: Vec<TokenStream> = self
             .variants
             .iter()
@@ -58,34 +140,397 @@ impl<'schema> GqlEnum<'schema> {
         let variant_str = &variant_str;
 
         let name = name_ident.clone();
+        let constants = self.constants(vis, &name, constructors);
+        let validation = if query_context.validate_variables {
+            crate::validation::enum_impl(
+                &name,
+                self.name,
+                quote!(#name::Other(other)),
+                quote!(other),
+            )
+        } else {
+            quote!()
+        };
+
+        let display = if query_context.pretty_display {
+            quote! {
+                impl ::core::fmt::Display for #name {
+                    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                        f.write_str(match *self {
+                            #(#constructors => #variant_str,)*
+                            #name::Other(ref s) => &s,
+                        })
+                    }
+                }
+            }
+        } else {
+            quote!()
+        };
+
+        let serde_impls = match wire_values {
+            Some(wire_values) => self.wire_serde_impls(&name, constructors, wire_values),
+            None => quote! {
+                impl ::serde::Serialize for #name {
+                    fn serialize<S: serde::Serializer>(&self, ser: S) -> ::core::result::Result<S::Ok, S::Error> {
+                        ser.serialize_str(match *self {
+                            #(#constructors => #variant_str,)*
+                            #name::Other(ref s) => &s,
+                        })
+                    }
+                }
+
+                impl<'de> ::serde::Deserialize<'de> for #name {
+                    fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> ::core::result::Result<Self, D::Error> {
+                        let s = <String>::deserialize(deserializer)?;
+
+                        match s.as_str() {
+                            #(#variant_str => Ok(#constructors),)*
+                            _ => Ok(#name::Other(s)),
+                        }
+                    }
+                }
+            },
+        };
 
         quote! {
             #derives
             #[allow(non_camel_case_types)]
-            pub enum #name {
+            #vis enum #name {
                 #(#variant_names,)*
                 Other(String),
             }
 
+            #serde_impls
+
+            #display
+
+            #constants
+
+            #validation
+        }
+    }
+
+    /// The `Serialize` and `Deserialize` impls with the values converted to their wire values. The
+    /// unknown integers are kept in `Other` as decimal text.
+    fn wire_serde_impls(
+        &self,
+        name: &Ident,
+        constructors: &[TokenStream],
+        wire_values: &WireValues,
+    ) -> TokenStream {
+        let values: Vec<WireValue> = self
+            .variants
+            .iter()
+            .enumerate()
+            .map(|(index, variant)| wire_values.wire_value(variant.name, index))
+            .collect();
+
+        if wire_values.format == WireFormat::Integer {
+            let integers: Vec<Literal> = values
+                .iter()
+                .map(|value| match value {
+                    WireValue::Integer(integer) => Literal::i64_unsuffixed(*integer),
+                    WireValue::String(_) => unreachable!("integer enums only have integer values"),
+                })
+                .collect();
+            let integers = &integers;
+
+            return quote! {
+                impl ::serde::Serialize for #name {
+                    fn serialize<S: serde::Serializer>(&self, ser: S) -> ::core::result::Result<S::Ok, S::Error> {
+                        match *self {
+                            #(#constructors => ser.serialize_i64(#integers),)*
+                            #name::Other(ref s) => match s.parse::<i64>() {
+                                Ok(integer) => ser.serialize_i64(integer),
+                                Err(_) => ser.serialize_str(&s),
+                            },
+                        }
+                    }
+                }
+
+                impl<'de> ::serde::Deserialize<'de> for #name {
+                    fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> ::core::result::Result<Self, D::Error> {
+                        let integer = <i64>::deserialize(deserializer)?;
+
+                        match integer {
+                            #(#integers => Ok(#constructors),)*
+                            _ => Ok(#name::Other(integer.to_string())),
+                        }
+                    }
+                }
+            };
+        }
+
+        let strings: Vec<&str> = values
+            .iter()
+            .map(|value| match value {
+                WireValue::String(string) => string.as_str(),
+                WireValue::Integer(_) => unreachable!("string enums only have string values"),
+            })
+            .collect();
+        let strings = &strings;
+
+        quote! {
             impl ::serde::Serialize for #name {
-                fn serialize<S: serde::Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+                fn serialize<S: serde::Serializer>(&self, ser: S) -> ::core::result::Result<S::Ok, S::Error> {
                     ser.serialize_str(match *self {
-                        #(#constructors => #variant_str,)*
+                        #(#constructors => #strings,)*
                         #name::Other(ref s) => &s,
                     })
                 }
             }
 
             impl<'de> ::serde::Deserialize<'de> for #name {
-                fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> ::core::result::Result<Self, D::Error> {
                     let s = <String>::deserialize(deserializer)?;
 
                     match s.as_str() {
-                        #(#variant_str => Ok(#constructors),)*
+                        #(#strings => Ok(#constructors),)*
                         _ => Ok(#name::Other(s)),
                     }
                 }
             }
         }
     }
+
+    /// The `ALL` and `VARIANTS` constants listing the values of the enum, given the expressions
+    /// for its variants.
+    fn constants(
+        &self,
+        vis: &syn::Visibility,
+        name: &Ident,
+        constructors: &[TokenStream],
+    ) -> TokenStream {
+        let variant_str = self.variants.iter().map(|v| v.name);
+
+        quote! {
+            impl #name {
+                /// All the values of the enum defined in the schema.
+                #vis const ALL: &'static [#name] = &[#(#constructors,)*];
+                /// The names of all the values of the enum defined in the schema, as they appear
+                /// in queries and responses.
+                #vis const VARIANTS: &'static [&'static str] = &[#(#variant_str,)*];
+            }
+        }
+    }
+
+    /// The enum with `CamelCase` variants, deriving `Serialize` and `Deserialize` with
+    /// `#[serde(rename_all = "SCREAMING_SNAKE_CASE")]`. Only the values that do not follow that
+    /// convention are renamed individually. Having no data, the enum is also `Copy`. Returns `None`
+    /// when two values would get the same variant name, or a value has no valid variant name.
+    fn to_rust_with_rename_all(
+        &self,
+        query_context: &crate::query::QueryContext<'_, '_>,
+    ) -> Option<TokenStream> {
+        let derives = query_context.fieldless_enum_derives();
+        let vis = &query_context.item_visibility;
+        let name = Ident::new(&format!("{}{}", ENUMS_PREFIX, self.name), Span::call_site());
+
+        let mut seen = BTreeSet::new();
+        seen.insert("Other".to_string());
+        let mut variants = Vec::with_capacity(self.variants.len());
+        let mut display_arms = Vec::with_capacity(self.variants.len());
+        let mut constructors = Vec::with_capacity(self.variants.len());
+
+        for variant in &self.variants {
+            let rust_name = match variant.name.to_camel_case() {
+                ref camel if camel == "Self" => "Self_".to_string(),
+                // Nothing is left of `_`, and `_2XL` would start with a digit.
+                ref camel
+                    if camel.is_empty() || camel.starts_with(|c: char| c.is_ascii_digit()) =>
+                {
+                    return None
+                }
+                camel => camel,
+            };
+            if !seen.insert(rust_name.clone()) {
+                return None;
+            }
+
+            let ident = Ident::new(&rust_name, Span::call_site());
+            let description = variant.description.map(|d| quote!(#[doc = #d]));
+            let rename = if screaming_snake_case(&rust_name) == variant.name {
+                quote!()
+            } else {
+                let value = variant.name;
+                quote!(#[serde(rename = #value)])
+            };
+            let value = variant.name;
+
+            variants.push(quote!(#description #rename #ident));
+            display_arms.push(quote!(#name::#ident => #value));
+            constructors.push(quote!(#name::#ident));
+        }
+
+        let display = if query_context.pretty_display {
+            quote! {
+                impl ::core::fmt::Display for #name {
+                    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                        f.write_str(match *self {
+                            #(#display_arms,)*
+                            #name::Other => "Other",
+                        })
+                    }
+                }
+            }
+        } else {
+            quote!()
+        };
+
+        let constants = self.constants(vis, &name, &constructors);
+        let validation = if query_context.validate_variables {
+            crate::validation::enum_impl(&name, self.name, quote!(#name::Other), quote!("Other"))
+        } else {
+            quote!()
+        };
+
+        Some(quote! {
+            #derives
+            #[derive(Serialize, Deserialize)]
+            #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+            #vis enum #name {
+                #(#variants,)*
+                #[serde(other)]
+                Other,
+            }
+
+            #display
+
+            #constants
+
+            #validation
+        })
+    }
+}
+
+/// The name serde gives to a variant with `rename_all = "SCREAMING_SNAKE_CASE"`.
+fn screaming_snake_case(variant: &str) -> String {
+    let mut renamed = String::with_capacity(variant.len() + 4);
+
+    for (i, ch) in variant.char_indices() {
+        if i > 0 && ch.is_uppercase() {
+            renamed.push('_');
+        }
+        renamed.push(ch.to_ascii_uppercase());
+    }
+
+    renamed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn screaming_snake_case_matches_serde() {
+        assert_eq!(screaming_snake_case("NewHope"), "NEW_HOPE");
+        assert_eq!(screaming_snake_case("Jedi"), "JEDI");
+        assert_eq!(screaming_snake_case("Http2"), "HTTP2");
+    }
+
+    #[test]
+    fn rename_all_only_renames_the_deviations() {
+        let schema = crate::schema::Schema::new();
+        let mut context = crate::query::QueryContext::new_empty(&schema);
+        context.enum_rename_all = true;
+        let enm = GqlEnum {
+            description: None,
+            name: "Episode",
+            variants: vec![
+                EnumVariant {
+                    description: None,
+                    name: "NEW_HOPE",
+                    directives: Vec::new(),
+                },
+                EnumVariant {
+                    description: None,
+                    name: "empireStrikesBack",
+                    directives: Vec::new(),
+                },
+            ],
+            is_required: false.into(),
+        };
+
+        let generated = enm.to_rust(&context).to_string();
+
+        assert!(generated.starts_with(
+            "# [ derive ( Clone , Copy , Eq , PartialEq ) ] \
+             # [ derive ( Serialize , Deserialize ) ] \
+             # [ serde ( rename_all = \"SCREAMING_SNAKE_CASE\" ) ] \
+             pub enum Episode { \
+             NewHope , \
+             # [ serde ( rename = \"empireStrikesBack\" ) ] EmpireStrikesBack , \
+             # [ serde ( other ) ] Other , \
+             }"
+        ));
+        assert!(generated.contains(
+            "pub const ALL : & 'static [ Episode ] = \
+             & [ Episode :: NewHope , Episode :: EmpireStrikesBack , ] ;"
+        ));
+        assert!(generated.contains(
+            "pub const VARIANTS : & 'static [ & 'static str ] = \
+             & [ \"NEW_HOPE\" , \"empireStrikesBack\" , ] ;"
+        ));
+    }
+    fn episode() -> GqlEnum<'static> {
+        GqlEnum {
+            description: None,
+            name: "Episode",
+            variants: ["NEWHOPE", "EMPIRE", "JEDI"]
+                .iter()
+                .map(|name| EnumVariant {
+                    description: None,
+                    name: *name,
+                    directives: Vec::new(),
+                })
+                .collect(),
+            is_required: false.into(),
+        }
+    }
+
+    #[test]
+    fn wire_values_keep_the_schema_names_in_rust() {
+        let mut schema = crate::schema::Schema::new();
+        schema.enums.insert("Episode", episode());
+        let mut context = crate::query::QueryContext::new_empty(&schema);
+        context
+            .ingest_enum_wire_values("Episode = lowercase, Episode.EMPIRE = empire_strikes_back")
+            .unwrap();
+
+        let generated = episode().to_rust(&context).to_string();
+
+        assert!(generated.contains(
+            "ser . serialize_str ( match * self { \
+             Episode :: NEWHOPE => \"newhope\" , \
+             Episode :: EMPIRE => \"empire_strikes_back\" , \
+             Episode :: JEDI => \"jedi\" , \
+             Episode :: Other ( ref s ) => & s , } )"
+        ));
+        assert!(generated.contains(
+            "pub const VARIANTS : & 'static [ & 'static str ] = \
+             & [ \"NEWHOPE\" , \"EMPIRE\" , \"JEDI\" , ] ;"
+        ));
+    }
+
+    #[test]
+    fn integer_wire_values_follow_the_schema_order() {
+        let mut schema = crate::schema::Schema::new();
+        schema.enums.insert("Episode", episode());
+        let mut context = crate::query::QueryContext::new_empty(&schema);
+        context.enum_rename_all = true;
+        context
+            .ingest_enum_wire_values("Episode = integer, Episode.JEDI = 6")
+            .unwrap();
+
+        let generated = episode().to_rust(&context).to_string();
+
+        assert!(generated.contains(
+            "match * self { \
+             Episode :: NEWHOPE => ser . serialize_i64 ( 0 ) , \
+             Episode :: EMPIRE => ser . serialize_i64 ( 1 ) , \
+             Episode :: JEDI => ser . serialize_i64 ( 6 ) ,"
+        ));
+        assert!(generated.contains("let integer = < i64 > :: deserialize ( deserializer ) ? ;"));
+        assert!(generated.contains("_ => Ok ( Episode :: Other ( integer . to_string ( ) ) ) ,"));
+    }
 }
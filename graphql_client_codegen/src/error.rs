@@ -0,0 +1,199 @@
+use std::fmt;
+use std::path::PathBuf;
+
+/// A position in a query or schema document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Location {
+    /// The line, starting from 1.
+    pub line: usize,
+    /// The column, starting from 1.
+    pub column: usize,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+impl From<graphql_parser::Pos> for Location {
+    fn from(position: graphql_parser::Pos) -> Location {
+        Location {
+            line: position.line,
+            column: position.column,
+        }
+    }
+}
+
+/// An error at a known position of the query, raised while generating its code. The entry points
+/// turn it into a `CodegenError::Query` with that location.
+#[derive(Debug)]
+pub(crate) struct PositionedError {
+    message: String,
+    location: Location,
+}
+
+impl PositionedError {
+    pub(crate) fn new(position: graphql_parser::Pos, message: impl fmt::Display) -> failure::Error {
+        PositionedError {
+            message: message.to_string(),
+            location: position.into(),
+        }
+        .into()
+    }
+}
+
+impl fmt::Display for PositionedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for PositionedError {}
+
+/// All the ways code generation can fail.
+#[derive(Debug)]
+pub enum CodegenError {
+    /// A query or schema file could not be read.
+    Io {
+        /// The path of the file.
+        path: PathBuf,
+        /// The underlying error.
+        source: std::io::Error,
+    },
+    /// The schema could not be parsed, or is not supported.
+    Schema {
+        /// What went wrong.
+        message: String,
+        /// Where it went wrong in the schema, when known.
+        location: Option<Location>,
+    },
+    /// The query could not be parsed, or does not match the schema.
+    Query {
+        /// What went wrong.
+        message: String,
+        /// Where it went wrong in the query, when known.
+        location: Option<Location>,
+    },
+}
+
+impl CodegenError {
+    /// An error in the query, without a known location.
+    pub(crate) fn query(message: impl fmt::Display) -> CodegenError {
+        CodegenError::Query {
+            message: message.to_string(),
+            location: None,
+        }
+    }
+
+    /// An error generating the code of a query, at the location of its selection when known.
+    pub(crate) fn query_generation(err: failure::Error) -> CodegenError {
+        match err.downcast::<PositionedError>() {
+            Ok(err) => CodegenError::Query {
+                message: err.message,
+                location: Some(err.location),
+            },
+            Err(err) => CodegenError::query(err),
+        }
+    }
+
+    /// An error parsing the query document.
+    pub(crate) fn query_parse(err: graphql_parser::query::ParseError) -> CodegenError {
+        let message = err.to_string();
+        CodegenError::Query {
+            location: parse_error_location(&message),
+            message,
+        }
+    }
+
+    /// An error parsing a schema in the GraphQL schema language.
+    pub(crate) fn schema_parse(err: graphql_parser::schema::ParseError) -> CodegenError {
+        let message = err.to_string();
+        CodegenError::Schema {
+            location: parse_error_location(&message),
+            message,
+        }
+    }
+
+    /// An error parsing a schema in the introspection JSON format.
+    pub(crate) fn schema_json(err: serde_json::Error) -> CodegenError {
+        CodegenError::Schema {
+            location: Some(Location {
+                line: err.line(),
+                column: err.column(),
+            }),
+            message: err.to_string(),
+        }
+    }
+
+    /// Where the error is in the query or schema, when known.
+    pub fn location(&self) -> Option<Location> {
+        match self {
+            CodegenError::Io { .. } => None,
+            CodegenError::Schema { location, .. } | CodegenError::Query { location, .. } => {
+                *location
+            }
+        }
+    }
+}
+
+impl fmt::Display for CodegenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodegenError::Io { path, source } => write!(
+                f,
+                "Could not read file with path: {}: {}\nHint: file paths in the GraphQLQuery attribute are relative to the project root (location of the Cargo.toml). Example: query_path = \"src/my_query.graphql\".",
+                path.display(),
+                source
+            ),
+            CodegenError::Schema { message, .. } => write!(f, "Invalid schema: {}", message),
+            CodegenError::Query { message, .. } => write!(f, "Invalid query: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for CodegenError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CodegenError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// Extracts the location from the message of a `graphql_parser` error, like
+/// `Parse error at 3:5`.
+fn parse_error_location(message: &str) -> Option<Location> {
+    let position = message.split("error at ").nth(1)?;
+    let position = position.split_whitespace().next()?;
+    let mut parts = position.split(':');
+    let line = parts.next()?.parse().ok()?;
+    let column = parts.next()?.parse().ok()?;
+
+    Some(Location { line, column })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_parse_errors_have_a_location() {
+        let err = graphql_parser::parse_query("query { a b }\n}").unwrap_err();
+        let err = CodegenError::query_parse(err);
+
+        assert_eq!(err.location(), Some(Location { line: 2, column: 1 }));
+    }
+
+    #[test]
+    fn parse_error_location_works() {
+        assert_eq!(
+            parse_error_location("query parse error: Parse error at 3:15\nUnexpected `}`"),
+            Some(Location {
+                line: 3,
+                column: 15
+            })
+        );
+        assert_eq!(parse_error_location("Unexpected end of input"), None);
+    }
+}
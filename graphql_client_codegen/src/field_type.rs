@@ -56,8 +56,9 @@ impl<'a> FieldType<'a> {
                 quote!( Option<#inner>)
             }
             FieldType::Vector(inner) => {
+                let container = context.collection_type(inner.inner_name_str());
                 let inner = inner.to_rust(context, &prefix);
-                quote!( Vec<#inner>)
+                quote!( #container<#inner>)
             }
         }
     }
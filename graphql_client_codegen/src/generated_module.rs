@@ -1,4 +1,6 @@
 use crate::codegen_options::*;
+use crate::operations::OperationType;
+use failure::format_err;
 use heck::*;
 use proc_macro2::{Ident, Span, TokenStream};
 use quote::quote;
@@ -10,6 +12,11 @@ pub(crate) struct GeneratedModule<'a> {
     pub query_document: &'a graphql_parser::query::Document,
     pub schema: &'a crate::schema::Schema<'a>,
     pub options: &'a crate::GraphQLClientCodegenOptions,
+    /// The timings to record the binding and the token generation of the operation in.
+    pub timings: Option<&'a crate::timings::Timings>,
+    /// Whether the module carries the warnings about the whole query document, like its unused
+    /// fragments. Only the first module generated from a document does.
+    pub document_warnings: bool,
 }
 
 impl<'a> GeneratedModule<'a> {
@@ -20,42 +27,302 @@ impl<'a> GeneratedModule<'a> {
             &self.query_document,
             &self.operation,
             &self.options,
+            self.shared_variables_module().as_ref().map(String::as_str),
         )?)
     }
 
+    /// The module of the first operation of the document declaring the same variables, when the
+    /// operations share their `Variables` struct and it is not this one.
+    fn shared_variables_module(&self) -> Option<String> {
+        if let CodegenMode::Cli = self.options.mode {
+            return None;
+        }
+        if !self.options.share_variables()
+            || !self.options.all_operations()
+            || self.operation.variables.is_empty()
+        {
+            return None;
+        }
+
+        crate::codegen::all_operations(self.query_document)
+            .into_iter()
+            .find(|operation| operation.has_same_variables(self.operation))
+            .filter(|operation| operation.name != self.operation.name)
+            .map(|operation| operation.name.to_snake_case())
+    }
+
+    /// Items producing a compiler warning for each fragment of the query document that is never
+    /// spread, in the module carrying the document warnings, and each variable the operation never
+    /// uses, or an error listing them when they are denied, and a warning for each suspicious but
+    /// legal construct.
+    fn validation_warnings(&self) -> Result<TokenStream, failure::Error> {
+        let mut messages: Vec<String> = if self.document_warnings {
+            crate::unused::unused_fragments(self.query_document)
+                .into_iter()
+                .map(|name| format!("The fragment `{}` is never used.", name))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        messages.extend(
+            crate::unused::unused_variables(self.query_document, &self.operation.name)
+                .into_iter()
+                .map(|name| {
+                    format!(
+                        "The variable `${}` of the `{}` operation is never used.",
+                        name, self.operation.name
+                    )
+                }),
+        );
+
+        if !messages.is_empty() && self.options.deny_unused() {
+            return Err(format_err!("{}", messages.join("\n")));
+        }
+
+        // A default value can never be used for a variable that must be provided.
+        messages.extend(
+            self.operation
+                .variables
+                .iter()
+                .filter(|variable| variable.default.is_some() && !variable.ty.is_optional())
+                .map(|variable| {
+                    format!(
+                        "The variable `${}` of the `{}` operation is non-null, so its default value is never used.",
+                        variable.name, self.operation.name
+                    )
+                }),
+        );
+
+        Ok(crate::shared::warning_items("Validation", &messages))
+    }
+
+    /// Fails when the operation is deeper or more complex than the configured limits.
+    fn check_limits(&self) -> Result<(), failure::Error> {
+        let (max_depth, max_complexity) = (self.options.max_depth(), self.options.max_complexity());
+        if max_depth.is_none() && max_complexity.is_none() {
+            return Ok(());
+        }
+
+        let complexity = crate::complexity::operations_complexity(
+            self.schema,
+            self.query_document,
+            self.options.complexity_options(),
+        )
+        .into_iter()
+        .find(|operation| operation.name == self.operation.name);
+        let complexity = match complexity {
+            Some(complexity) => complexity,
+            None => return Ok(()),
+        };
+
+        let mut messages = Vec::new();
+        if let Some(max_depth) = max_depth.filter(|max| complexity.depth > *max) {
+            messages.push(format!(
+                "The `{}` operation has a depth of {}, over the max depth of {}.",
+                self.operation.name, complexity.depth, max_depth
+            ));
+        }
+        if let Some(max_complexity) = max_complexity.filter(|max| complexity.complexity > *max) {
+            messages.push(format!(
+                "The `{}` operation has a complexity of {}, over the max complexity of {}.",
+                self.operation.name, complexity.complexity, max_complexity
+            ));
+        }
+
+        if messages.is_empty() {
+            Ok(())
+        } else {
+            Err(format_err!("{}", messages.join("\n")))
+        }
+    }
+
+    /// The `//!` documentation for the generated module: the operation it was generated from, its source file and optionally the query text.
+    fn module_docs(&self) -> TokenStream {
+        let operation_type = match self.operation.operation_type {
+            OperationType::Query => "query",
+            OperationType::Mutation => "mutation",
+            OperationType::Subscription => "subscription",
+        };
+        let mut lines = vec![format!(
+            " Generated code for the `{}` GraphQL {}.",
+            self.operation.name, operation_type
+        )];
+
+        if let Some(file_name) = self
+            .options
+            .query_file()
+            .and_then(std::path::Path::file_name)
+        {
+            lines.push(String::new());
+            lines.push(format!(" Source file: `{}`", file_name.to_string_lossy()));
+        }
+
+        if self.options.include_query_in_docs() {
+            lines.push(String::new());
+            lines.push(" ```graphql".to_string());
+            lines.extend(self.query_string.trim().lines().map(|line| format!(" {}", line)));
+            lines.push(" ```".to_string());
+        }
+
+        quote!(#(#![doc = #lines])*)
+    }
+
+    /// The registration of the operation in the `graphql_client::registry`.
+    fn registration(&self) -> TokenStream {
+        if !self.options.register_operations() {
+            return quote!();
+        }
+
+        let kind = match self.operation.operation_type {
+            OperationType::Query => quote!(Query),
+            OperationType::Mutation => quote!(Mutation),
+            OperationType::Subscription => quote!(Subscription),
+        };
+
+        quote!(::graphql_client::inventory::submit! {
+            #![crate = ::graphql_client::inventory]
+            ::graphql_client::registry::RegisteredOperation {
+                name: OPERATION_NAME,
+                kind: ::graphql_client::registry::OperationKind::#kind,
+                query: QUERY,
+                hash: QUERY_HASH,
+            }
+        })
+    }
+
+    /// The test checking that the stored responses of the operation still deserialize.
+    fn fixture_tests(&self) -> TokenStream {
+        let fixtures_path = match self.options.test_fixtures_path() {
+            Some(path) => path.join(&self.operation.name),
+            None => return quote!(),
+        };
+        let fixtures_path = fixtures_path.to_string_lossy();
+
+        quote! {
+            #[cfg(test)]
+            mod fixture_tests {
+                #[test]
+                fn fixtures_deserialize() {
+                    let fixtures = match ::std::fs::read_dir(#fixtures_path) {
+                        Ok(fixtures) => fixtures,
+                        // No responses are stored for the operation.
+                        Err(_) => return,
+                    };
+
+                    for fixture in fixtures {
+                        let path = fixture.unwrap().path();
+                        if path.extension().and_then(|extension| extension.to_str()) != Some("json") {
+                            continue;
+                        }
+
+                        let json = ::std::fs::read_to_string(&path).unwrap();
+                        if let Err(err) = ::graphql_client::serde_json::from_str::<
+                            ::graphql_client::Response<super::ResponseData>,
+                        >(&json)
+                        {
+                            panic!("{} does not deserialize: {}", path.display(), err);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// A constant with the text of each fragment the operation spreads, directly or not, as it is
+    /// sent to the server, and the list of their names.
+    fn fragment_consts(&self) -> TokenStream {
+        let fragments =
+            crate::normalized::used_fragments(self.query_document, &self.operation.name);
+        let names = fragments.iter().map(|fragment| fragment.name.as_str());
+        let consts = fragments.iter().map(|fragment| {
+            let name = Ident::new(
+                &format!("FRAGMENT_{}", fragment.name.to_shouty_snake_case()),
+                Span::call_site(),
+            );
+            let doc = format!("The text of the `{}` fragment.", fragment.name);
+            let text = crate::normalized::normalized_fragment(fragment);
+
+            quote! {
+                #[doc = #doc]
+                pub const #name: &'static str = #text;
+            }
+        });
+
+        quote! {
+            /// The names of the fragments the operation spreads, directly or not, sorted. The text
+            /// of each fragment is in its `FRAGMENT_<NAME>` constant.
+            pub const FRAGMENTS: &'static [&'static str] = &[#(#names),*];
+
+            #(#consts)*
+        }
+    }
+
     /// Generate the module and all the code inside.
     pub(crate) fn to_token_stream(&self) -> Result<TokenStream, failure::Error> {
+        let started = std::time::Instant::now();
         let module_name = Ident::new(&self.operation.name.to_snake_case(), Span::call_site());
-        let module_visibility = &self.options.module_visibility();
+        // With all the operations of the document, the modules are nested in the module named
+        // after the struct, which has its visibility.
+        let module_visibility: syn::Visibility = if self.options.all_operations() {
+            syn::parse_quote!(pub)
+        } else {
+            self.options.module_visibility().clone()
+        };
         let operation_name_literal = &self.operation.name;
         let operation_name_ident =
             Ident::new(&self.operation.name.to_camel_case(), Span::call_site());
 
-        // Force cargo to refresh the generated code when the query file changes.
-        let query_include = self
+        // Force cargo to refresh the generated code when the query or schema files change.
+        let tracked_files: Vec<&str> = self
             .options
-            .query_file()
-            .map(|path| {
-                let path = path.to_str();
-                quote!(
-                    const __QUERY_WORKAROUND: &str = include_str!(#path);
-                )
-            })
-            .unwrap_or_else(|| quote! {});
+            .tracked_files()
+            .filter_map(std::path::Path::to_str)
+            .collect();
+        let query_include = if tracked_files.is_empty() {
+            quote!()
+        } else {
+            quote!(
+                const __QUERY_WORKAROUND: &[&str] = &[#(include_str!(#tracked_files)),*];
+            )
+        };
 
         let query_string = &self.query_string;
+        let query_hash = query_hash(query_string);
+        let normalized_query =
+            crate::normalized::normalized_operation(self.query_document, &self.operation.name)
+                .unwrap_or_default();
+        let module_docs = self.module_docs();
+        let fragment_consts = self.fragment_consts();
+        let registration = self.registration();
+        let fixture_tests = self.fixture_tests();
+        let binding_started = std::time::Instant::now();
         let mut impls = self.build_impls()?;
+        let binding = binding_started.elapsed();
+        let validation_warnings = self.validation_warnings()?;
+        self.check_limits()?;
 
         let build_query_impl = match self.options.mode {
             CodegenMode::Cli => {
-                let context = crate::query::QueryContext::new(&self.schema, self.options.deprecation_strategy());
+                let mut context = crate::query::QueryContext::new(&self.schema, self.options.deprecation_strategy());
+                context.item_visibility = self.options.item_visibility();
+                if let Some(collection_types) = self.options.collection_types() {
+                    context.ingest_collection_types(collection_types)?;
+                }
+                let item_visibility = &context.item_visibility;
                 let (variables_derives, variables, _) = self.operation.expand_variables(&context);
-                impls = quote!(
-                    #impls
-
+                let builder = if self.options.typestate_builders() {
+                    crate::builders::typestate_builder(
+                        item_visibility,
+                        &operation_name_ident,
+                        &self.operation.variable_builder_fields(&context),
+                    )
+                } else {
+                    quote!()
+                };
+                let operation = quote!(
                     #[allow(dead_code)]
                     #variables_derives
-                    pub struct #operation_name_ident {
+                    #item_visibility struct #operation_name_ident {
                         #(#variables,)*
                     }
                     impl graphql_client::GraphQLQueryCLI for #operation_name_ident {
@@ -69,6 +336,18 @@ impl<'a> GeneratedModule<'a> {
                             }
                         }
                     }
+
+                    #builder
+                );
+                let operation = if self.options.split_files() {
+                    crate::codegen::submodule("operations", operation)
+                } else {
+                    operation
+                };
+                impls = quote!(
+                    #impls
+
+                    #operation
                 );
                 // No build_query_impl for CLI
                 quote!()
@@ -85,6 +364,9 @@ impl<'a> GeneratedModule<'a> {
                         type Variables = #variables_type;
                         type ResponseData = #module_name::ResponseData;
 
+                        const CLIENT_FIELDS: &'static [::graphql_client::local::ClientField] =
+                            #module_name::CLIENT_FIELDS;
+
                         fn build_query(variables: Self::Variables) -> ::graphql_client::QueryBody<Self::Variables> {
                             graphql_client::QueryBody {
                                 variables,
@@ -97,19 +379,57 @@ impl<'a> GeneratedModule<'a> {
             }
         };
 
-        Ok(quote!(
+        let module = quote!(
             #module_visibility mod #module_name {
+                #module_docs
                 #![allow(dead_code)]
 
                 pub const OPERATION_NAME: &'static str = #operation_name_literal;
                 pub const QUERY: &'static str = #query_string;
+                /// The hex-encoded SHA-256 hash of `QUERY`, as used by automatic persisted queries.
+                pub const QUERY_HASH: &'static str = #query_hash;
+
+                /// The operation and the fragments it uses, printed in a stable format, for
+                /// logging, persisted queries and cache keys.
+                pub fn normalized_query() -> &'static str {
+                    #normalized_query
+                }
+
+                #fragment_consts
 
                 #query_include
 
+                #registration
+
+                #fixture_tests
+
+                #validation_warnings
+
                 #impls
             }
 
             #build_query_impl
-        ))
+        );
+
+        if let Some(timings) = self.timings {
+            let name = &self.operation.name;
+            timings.record(format!("{}: query binding", name), binding);
+            timings.record(
+                format!("{}: token generation", name),
+                started.elapsed() - binding,
+            );
+        }
+
+        Ok(module)
     }
 }
+
+/// The hex-encoded SHA-256 hash of a query document.
+fn query_hash(query: &str) -> String {
+    use sha2::Digest;
+
+    sha2::Sha256::digest(query.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
@@ -5,7 +5,6 @@ use crate::query::QueryContext;
 use crate::schema::Schema;
 use failure;
 use graphql_parser;
-use heck::SnakeCase;
 use proc_macro2::{Ident, Span, TokenStream};
 use quote::quote;
 use std::cell::Cell;
@@ -61,17 +60,34 @@ impl<'schema> GqlInput<'schema> {
         })
     }
 
-    fn is_recursive_without_indirection(&self, context: &QueryContext<'_, '_>) -> bool {
+    pub(crate) fn is_recursive_without_indirection(&self, context: &QueryContext<'_, '_>) -> bool {
         self.contains_type_without_indirection(context, &self.name)
     }
 
+    /// The name of the Rust field for the input field `name`.
+    pub(crate) fn rust_field_name(&self, context: &QueryContext<'_, '_>, name: &str) -> String {
+        crate::shared::field_name(context, name, self.fields.keys().cloned())
+    }
+
     fn map_field(
         &self,
         context: &QueryContext<'_, '_>,
         field: &GqlObjectField<'_>,
         required_fields: &mut Vec<TokenStream>,
         struct_field_assignments: &mut Vec<TokenStream>,
+        builder_fields: &mut Vec<crate::builders::BuilderField>,
     ) -> TokenStream {
+        // Only optional input fields can be deprecated, so denying one leaves it out of the
+        // struct and of `new()` alike.
+        let deprecation = match crate::shared::deprecation_annotation(
+            &field.deprecation,
+            context.field_deprecation_strategy(self.name, field.name),
+        ) {
+            Some(deprecation) => deprecation,
+            None if field.type_.is_optional() => return quote!(),
+            None => quote!(),
+        };
+
         let is_recursive =
             if let Some(input) = context.schema.inputs.get(field.type_.inner_name_str()) {
                 input.is_recursive_without_indirection(context)
@@ -98,24 +114,42 @@ impl<'schema> GqlInput<'schema> {
         };
 
         context.schema.require(&field.type_.inner_name_str());
-        let rust_safe_field_name = crate::shared::keyword_replace(&field.name.to_snake_case());
+        let rust_safe_field_name = self.rust_field_name(context, field.name);
         let mut rename = crate::shared::field_rename_annotation(&field.name, &rust_safe_field_name);
         let name = Ident::new(&rust_safe_field_name, Span::call_site());
 
+        let optional = match &field.type_ {
+            crate::field_type::FieldType::Optional(inner) if is_recursive => {
+                let ty = inner.to_rust(&context, "");
+                Some(quote!(Box<#ty>))
+            }
+            crate::field_type::FieldType::Optional(inner) => Some(inner.to_rust(&context, "")),
+            _ => None,
+        };
+        builder_fields.push(crate::builders::BuilderField {
+            name: name.clone(),
+            ty: ty.clone(),
+            optional,
+        });
+
         match &field.type_ {
             crate::field_type::FieldType::Optional(_) => {
                 struct_field_assignments.push(quote!(#name: None));
-                rename = quote!(
-                    #[serde(skip_serializing_if = "Option::is_none")]
-                    #rename
-                )
+                if !context.format_agnostic_serde {
+                    rename = quote!(
+                        #[serde(skip_serializing_if = "Option::is_none")]
+                        #rename
+                    )
+                }
             }
             _ => {
                 required_fields.push(quote!(#name: #ty));
                 struct_field_assignments.push(quote!(#name: #name));
             }
         };
-        quote!(#rename pub #name: #ty)
+        let scalar_trait = crate::shared::scalar_trait_annotation(&field.type_, context);
+
+        quote!(#deprecation #rename #scalar_trait pub #name: #ty)
     }
 
     pub(crate) fn to_rust(
@@ -128,27 +162,62 @@ impl<'schema> GqlInput<'schema> {
         let mut fields: Vec<TokenStream> = vec![];
         let mut required_fields: Vec<TokenStream> = vec![];
         let mut struct_field_assignments: Vec<TokenStream> = vec![];
+        let mut builder_fields: Vec<crate::builders::BuilderField> = vec![];
+        let mut validated_fields: Vec<crate::validation::ValidatedField<'_>> = vec![];
 
         for field in obj_fields.iter() {
-            fields.push(self.map_field(
+            let mapped = self.map_field(
                 context,
                 field,
                 &mut required_fields,
                 &mut struct_field_assignments,
-            ));
+                &mut builder_fields,
+            );
+            if !mapped.is_empty() {
+                fields.push(mapped);
+                validated_fields.push(crate::validation::ValidatedField {
+                    name: Ident::new(
+                        &self.rust_field_name(context, field.name),
+                        Span::call_site(),
+                    ),
+                    graphql_name: field.name,
+                    type_: &field.type_,
+                });
+            }
         }
         let variables_derives = context.variables_derives();
+        let vis = &context.item_visibility;
+        // `new()` sets the deprecated optional fields to `None`.
+        let allow_deprecated = if obj_fields.iter().any(|field| match field.deprecation {
+            DeprecationStatus::Deprecated(_) => true,
+            DeprecationStatus::Current => false,
+        }) {
+            quote!(#[allow(deprecated)])
+        } else {
+            quote!()
+        };
 
         // Prevent generated code like "pub struct crate" for a schema input like "input crate { ... }"
         // This works in tandem with renamed struct Variables field types, eg: pub struct Variables { pub criteria : crate_ , }
         let rust_safe_field_name = crate::shared::keyword_replace(&self.name);
         let name = Ident::new(&rust_safe_field_name, Span::call_site());
+        let builder = if context.typestate_builders {
+            crate::builders::typestate_builder(vis, &name, &builder_fields)
+        } else {
+            quote!()
+        };
+        let validation = if context.validate_variables {
+            crate::validation::struct_impl(context, &name, &validated_fields)
+        } else {
+            quote!()
+        };
 
         Ok(quote! {
             #variables_derives
-            pub struct #name {
+            #vis struct #name {
                 #(#fields,)*
             }
+            #allow_deprecated
             impl #name {
                 pub fn new(#(#required_fields),*) -> Self {
                     Self {
@@ -156,6 +225,10 @@ impl<'schema> GqlInput<'schema> {
                     }
                 }
             }
+
+            #builder
+
+            #validation
         })
     }
 }
@@ -176,7 +249,10 @@ impl<'schema> ::std::convert::From<&'schema graphql_parser::schema::InputObjectT
                         description: None,
                         name: &field.name,
                         type_: crate::field_type::FieldType::from(&field.value_type),
-                        deprecation: DeprecationStatus::Current,
+                        deprecation: crate::objects::parse_deprecation_directives(
+                            &field.directives,
+                        ),
+                        directives: crate::schema::applied_directives(&field.directives),
                     };
                     (name, field)
                 })
@@ -219,7 +295,12 @@ impl<'schema> ::std::convert::From<&'schema introspection_response::FullType>
                             .as_ref()
                             .map(|s| s.into())
                             .expect("type on input object field"),
-                        deprecation: DeprecationStatus::Current,
+                        deprecation: if f.input_value.is_deprecated.unwrap_or(false) {
+                            DeprecationStatus::Deprecated(f.input_value.deprecation_reason.clone())
+                        } else {
+                            DeprecationStatus::Current
+                        },
+                        directives: Vec::new(),
                     };
                     (name, field)
                 })
@@ -248,6 +329,7 @@ mod tests {
                         name: "pawsCount",
                         type_: FieldType::Named(float_type()),
                         deprecation: DeprecationStatus::Current,
+                        directives: Vec::new(),
                     },
                 ),
                 (
@@ -257,6 +339,7 @@ mod tests {
                         name: "offsprings",
                         type_: FieldType::Vector(Box::new(FieldType::Named("Cat"))),
                         deprecation: DeprecationStatus::Current,
+                        directives: Vec::new(),
                     },
                 ),
                 (
@@ -266,6 +349,7 @@ mod tests {
                         name: "requirements",
                         type_: FieldType::Optional(Box::new(FieldType::Named("CatRequirements"))),
                         deprecation: DeprecationStatus::Current,
+                        directives: Vec::new(),
                     },
                 ),
             ]
@@ -288,4 +372,47 @@ mod tests {
             expected
         );
     }
+
+    #[test]
+    fn deprecated_input_fields() {
+        let document = graphql_parser::parse_schema(
+            r#"
+            input Filter {
+                name: String
+                login: String @deprecated(reason: "Use name")
+            }
+            "#,
+        )
+        .unwrap();
+        let input = match &document.definitions[0] {
+            graphql_parser::schema::Definition::TypeDefinition(
+                graphql_parser::schema::TypeDefinition::InputObject(input),
+            ) => GqlInput::from(input),
+            _ => unreachable!(),
+        };
+        assert_eq!(
+            input.fields["login"].deprecation,
+            DeprecationStatus::Deprecated(Some("Use name".to_string()))
+        );
+
+        let mut schema = crate::schema::Schema::new();
+        schema.inputs.insert(input.name, input);
+        let mut context = QueryContext::new_empty(&schema);
+
+        context.deprecation_strategy = crate::deprecation::DeprecationStrategy::Warn;
+        let generated = context.schema.inputs["Filter"]
+            .to_rust(&context)
+            .unwrap()
+            .to_string();
+        assert!(generated.contains("# [ deprecated ( note = \"Use name\" ) ]"));
+        assert!(generated.contains("pub login"));
+
+        context.deprecation_strategy = crate::deprecation::DeprecationStrategy::Deny;
+        let generated = context.schema.inputs["Filter"]
+            .to_rust(&context)
+            .unwrap()
+            .to_string();
+        assert!(!generated.contains("login"));
+        assert!(generated.contains("pub name"));
+    }
 }
@@ -8,7 +8,7 @@ use failure::*;
 use proc_macro2::{Ident, Span, TokenStream};
 use quote::quote;
 use std::cell::Cell;
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashSet};
 
 /// A GraphQL interface (simplified schema representation).
 ///
@@ -40,7 +40,7 @@ impl<'schema> GqlInterface<'schema> {
             // Only keep what we can handle
             .filter(|f| match f {
                 SelectionItem::Field(f) => f.name != TYPENAME_FIELD,
-                SelectionItem::FragmentSpread(SelectionFragmentSpread { fragment_name }) => {
+                SelectionItem::FragmentSpread(SelectionFragmentSpread { fragment_name, .. }) => {
                     // only if the fragment refers to the interface’s own fields (to take into account type-refining fragments)
                     let fragment = query_context
                         .fragments
@@ -67,7 +67,7 @@ impl<'schema> GqlInterface<'schema> {
             // Only keep what we can handle
             .filter(|f| match f {
                 SelectionItem::InlineFragment(_) => true,
-                SelectionItem::FragmentSpread(SelectionFragmentSpread { fragment_name }) => {
+                SelectionItem::FragmentSpread(SelectionFragmentSpread { fragment_name, .. }) => {
                     let fragment = query_context
                         .fragments
                         .get(fragment_name)
@@ -138,14 +138,18 @@ impl<'schema> GqlInterface<'schema> {
     ) -> Result<TokenStream, failure::Error> {
         let name = Ident::new(&prefix, Span::call_site());
         let derives = query_context.response_derives();
+        let vis = &query_context.item_visibility;
 
-        selection.extract_typename(query_context).ok_or_else(|| {
-            format_err!(
-                "Missing __typename in selection for the {} interface (type: {})",
-                prefix,
-                self.name
-            )
-        })?;
+        // The enums with a typename fallback are deserialized without their tag.
+        if query_context.typename_fallback.is_none() {
+            selection.extract_typename(query_context).ok_or_else(|| {
+                format_err!(
+                    "Missing __typename in selection for the {} interface (type: {})",
+                    prefix,
+                    self.name
+                )
+            })?;
+        }
 
         let object_fields =
             self.response_fields_for_selection(query_context, &selection, prefix)?;
@@ -157,24 +161,53 @@ impl<'schema> GqlInterface<'schema> {
         let (mut union_variants, union_children, used_variants) =
             union_variants(&union_selection, query_context, prefix, &self.name)?;
 
+        let mut other_variants: Vec<&str> = self
+            .implemented_by
+            .iter()
+            .filter(|obj| used_variants.iter().find(|v| v == obj).is_none())
+            .cloned()
+            .collect();
+        other_variants.sort();
+
         // Add the non-selected variants to the generated enum's variants.
-        union_variants.extend(
-            self.implemented_by
-                .iter()
-                .filter(|obj| used_variants.iter().find(|v| v == obj).is_none())
-                .map(|v| {
-                    let v = Ident::new(v, Span::call_site());
-                    quote!(#v)
-                }),
-        );
+        union_variants.extend(other_variants.iter().map(|v| {
+            let v = Ident::new(v, Span::call_site());
+            quote!(#v)
+        }));
+
+        // The enum of the `__typename` selections is flattened into the struct.
+        if query_context.format_agnostic_serde
+            && selection.extract_typename(query_context).is_some()
+        {
+            return Err(format_err!(
+                "The `__typename` selection on the `{}` interface would be flattened, which the format_agnostic_serde option does not support.",
+                self.name
+            ));
+        }
+
+        let selected_variants: BTreeSet<&str> = used_variants.iter().cloned().collect();
+        let selected_variants: Vec<&str> = selected_variants.into_iter().collect();
 
         let attached_enum_name = Ident::new(&format!("{}On", name), Span::call_site());
         let (attached_enum, last_object_field) =
-            if selection.extract_typename(query_context).is_some() {
+            if let Some(fallback) = &query_context.typename_fallback {
+                let attached_enum = crate::unions::typename_fallback_enum(
+                    query_context,
+                    fallback,
+                    &attached_enum_name,
+                    prefix,
+                    &selected_variants,
+                    &other_variants,
+                    &self.name,
+                )?;
+                let last_object_field = quote!(#[serde(flatten)] pub on: #attached_enum_name,);
+                (attached_enum, last_object_field)
+            } else if selection.extract_typename(query_context).is_some() {
+                let tagging = query_context.union_tagging_attribute();
                 let attached_enum = quote! {
                     #derives
-                    #[serde(tag = "__typename")]
-                    pub enum #attached_enum_name {
+                    #tagging
+                    #vis enum #attached_enum_name {
                         #(#union_variants,)*
                     }
                 };
@@ -183,6 +216,31 @@ impl<'schema> GqlInterface<'schema> {
             } else {
                 (quote!(), quote!())
             };
+        let accessors = crate::unions::variant_accessors(
+            &query_context.item_visibility,
+            &attached_enum_name,
+            prefix,
+            &selected_variants,
+            &other_variants,
+        );
+
+        let display = if query_context.pretty_display {
+            let variants_body =
+                pretty_variants(&attached_enum_name, &selected_variants, &other_variants);
+            let mut body = pretty_fields_for_selection(
+                self.name,
+                &self.fields,
+                query_context,
+                &self.object_selection(selection, query_context),
+            )?;
+            body.push(quote!(self.on.fmt_pretty(f, indent)?;));
+            let vis = &query_context.item_visibility;
+            let enum_display = pretty_display_impl(vis, &attached_enum_name, &[variants_body]);
+            let struct_display = pretty_display_impl(vis, &name, &body);
+            quote!(#enum_display #struct_display)
+        } else {
+            quote!()
+        };
 
         Ok(quote! {
 
@@ -193,10 +251,14 @@ impl<'schema> GqlInterface<'schema> {
             #attached_enum
 
             #derives
-            pub struct #name {
+            #vis struct #name {
                 #(#object_fields,)*
                 #last_object_field
             }
+
+            #accessors
+
+            #display
         })
     }
 }
@@ -223,6 +285,10 @@ mod tests {
             crate::selection::SelectionItem::Field(crate::selection::SelectionField {
                 alias: None,
                 name: "__typename",
+                deserialize_with: None,
+                client: false,
+                arguments: None,
+                position: graphql_parser::Pos::default(),
                 fields: Selection::new_empty(),
             });
         let selection = Selection::from_vec(vec![typename_field.clone()]);
@@ -251,6 +317,10 @@ mod tests {
             crate::selection::SelectionItem::Field(crate::selection::SelectionField {
                 alias: None,
                 name: "__typename",
+                deserialize_with: None,
+                client: false,
+                arguments: None,
+                position: graphql_parser::Pos::default(),
                 fields: Selection::new_empty(),
             });
         let selection: Selection<'_> = vec![typename_field].into_iter().collect();
@@ -13,6 +13,7 @@ pub enum __DirectiveLocation {
     FRAGMENT_DEFINITION,
     FRAGMENT_SPREAD,
     INLINE_FRAGMENT,
+    VARIABLE_DEFINITION,
     SCHEMA,
     SCALAR,
     OBJECT,
@@ -37,6 +38,7 @@ impl ::serde::Serialize for __DirectiveLocation {
             __DirectiveLocation::FRAGMENT_DEFINITION => "FRAGMENT_DEFINITION",
             __DirectiveLocation::FRAGMENT_SPREAD => "FRAGMENT_SPREAD",
             __DirectiveLocation::INLINE_FRAGMENT => "INLINE_FRAGMENT",
+            __DirectiveLocation::VARIABLE_DEFINITION => "VARIABLE_DEFINITION",
             __DirectiveLocation::SCHEMA => "SCHEMA",
             __DirectiveLocation::SCALAR => "SCALAR",
             __DirectiveLocation::OBJECT => "OBJECT",
@@ -64,6 +66,7 @@ impl<'de> ::serde::Deserialize<'de> for __DirectiveLocation {
             "FRAGMENT_DEFINITION" => Ok(__DirectiveLocation::FRAGMENT_DEFINITION),
             "FRAGMENT_SPREAD" => Ok(__DirectiveLocation::FRAGMENT_SPREAD),
             "INLINE_FRAGMENT" => Ok(__DirectiveLocation::INLINE_FRAGMENT),
+            "VARIABLE_DEFINITION" => Ok(__DirectiveLocation::VARIABLE_DEFINITION),
             "SCHEMA" => Ok(__DirectiveLocation::SCHEMA),
             "SCALAR" => Ok(__DirectiveLocation::SCALAR),
             "OBJECT" => Ok(__DirectiveLocation::OBJECT),
@@ -137,6 +140,9 @@ pub struct FullType {
     pub interfaces: Option<Vec<Option<FullTypeInterfaces>>>,
     pub enum_values: Option<Vec<Option<FullTypeEnumValues>>>,
     pub possible_types: Option<Vec<Option<FullTypePossibleTypes>>>,
+    /// Only in the responses to the extended introspection query.
+    #[serde(rename = "specifiedByURL", default)]
+    pub specified_by_url: Option<String>,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -203,6 +209,11 @@ pub struct InputValue {
     #[serde(rename = "type")]
     pub type_: Option<InputValueType>,
     pub default_value: Option<String>,
+    /// Only in the responses of servers supporting the deprecation of input values.
+    #[serde(default)]
+    pub is_deprecated: Option<Boolean>,
+    #[serde(default)]
+    pub deprecation_reason: Option<String>,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -304,7 +315,7 @@ pub struct RustIntrospectionQuerySchemaTypes {
 #[serde(rename_all = "camelCase")]
 pub struct RustIntrospectionQuerySchemaDirectivesArgs {
     #[serde(flatten)]
-    input_value: InputValue,
+    pub input_value: InputValue,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -314,16 +325,22 @@ pub struct RustIntrospectionQuerySchemaDirectives {
     pub description: Option<String>,
     pub locations: Option<Vec<Option<__DirectiveLocation>>>,
     pub args: Option<Vec<Option<RustIntrospectionQuerySchemaDirectivesArgs>>>,
+    /// Only in the responses to the extended introspection query.
+    #[serde(default)]
+    pub is_repeatable: Option<Boolean>,
 }
 
 #[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RustIntrospectionQuerySchema {
+    /// Only in the responses to the extended introspection query.
+    #[serde(default)]
+    pub description: Option<String>,
     pub query_type: Option<RustIntrospectionQuerySchemaQueryType>,
     pub mutation_type: Option<RustIntrospectionQuerySchemaMutationType>,
     pub subscription_type: Option<RustIntrospectionQuerySchemaSubscriptionType>,
     pub types: Option<Vec<Option<RustIntrospectionQuerySchemaTypes>>>,
-    directives: Option<Vec<Option<RustIntrospectionQuerySchemaDirectives>>>,
+    pub directives: Option<Vec<Option<RustIntrospectionQuerySchemaDirectives>>>,
 }
 
 #[derive(Clone, Debug, Deserialize)]
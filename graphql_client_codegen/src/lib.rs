@@ -2,170 +2,805 @@
 #![deny(missing_docs)]
 #![deny(rust_2018_idioms)]
 #![deny(warnings)]
+#![cfg_attr(feature = "nightly_diagnostics", feature(proc_macro_diagnostic))]
 
-//! Crate for internal use by other graphql-client crates, for code generation.
+//! Code generation for graphql-client, used by the `GraphQLQuery` derive and the CLI.
 //!
-//! It is not meant to be used directly by users of the library.
+//! It can also be embedded in build tools, editor plugins and other frontends. The supported API
+//! is:
+//!
+//! - [GraphQLClientCodegenOptions] to configure the generated code,
+//!   and [GraphQLClientCodegenOptions::set_type_hook] and
+//!   [GraphQLClientCodegenOptions::set_post_process] to extend it programmatically,
+//! - [generate_module_token_stream] to generate code from query and schema files,
+//! - [generate_module_token_stream_from_str] and [generate_module_string_from_str] to generate
+//!   code from the text of a query and a schema, in one of the [SchemaFormat]s,
+//! - [SchemaDocument] to parse a schema once and generate the code for several query documents,
+//!   and to get its [schema::SchemaModel] with [SchemaDocument::model],
+//! - [normalized_operation] to print an operation and the fragments it uses in a stable format,
+//! - [schema_directives] to list the directives applied in a schema,
+//! - [SchemaDocument::complexity] to compute the depth and the complexity of the operations, with
+//!   [ComplexityOptions],
+//! - [SchemaDocument::typescript] to declare the variables and the responses of the operations in
+//!   TypeScript,
+//! - [SchemaDocument::visit] to walk the operations with the schema types of their selections,
+//!   with a [QueryVisitor],
+//! - [SchemaDocument::type_bindings] to bind the fields of a query document to the generated
+//!   types, with their [TypeBinding]s, for the editors,
+//! - [CodegenError] for the errors, with their location in the query or schema when known.
 
-use failure::*;
 use graphql_parser;
 use lazy_static::*;
 use proc_macro2;
 use proc_macro2::TokenStream;
 use quote::*;
 
+#[cfg(feature = "nightly_diagnostics")]
+extern crate proc_macro;
+
+mod accessors;
+mod builders;
 mod codegen;
 mod codegen_options;
 /// Deprecation-related code
 pub mod deprecation;
+/// The errors returned by code generation.
+pub mod error;
 mod introspection_response;
+mod model;
 mod query;
-/// Contains the [Schema] type and its implementation.
+/// The model of the schemas, [schema::SchemaModel], and the directives applied in them.
 pub mod schema;
 
+mod client_directives;
+mod complexity;
 mod constants;
+mod conversions;
 mod enums;
 mod field_type;
 mod fragments;
 mod generated_module;
 mod inputs;
 mod interfaces;
+mod normalized;
 mod objects;
 mod operations;
+mod post_process;
+mod relay;
 mod scalars;
 mod selection;
 mod shared;
+mod suggestions;
+mod timings;
+mod type_bindings;
+mod typescript;
 mod unions;
+mod unused;
+mod validation;
 mod variables;
+mod visit;
 
 #[cfg(test)]
 mod tests;
 
-pub use crate::codegen_options::{CodegenMode, GraphQLClientCodegenOptions};
+pub use crate::codegen_options::{
+    CodegenMode, GraphQLClientCodegenOptions, PostProcess, RustVersion, TypeHook, TypenameFallback,
+    UnionTagging,
+};
+pub use crate::complexity::{ComplexityOptions, OperationComplexity};
+pub use crate::error::CodegenError;
+pub use crate::type_bindings::TypeBinding;
+pub use crate::visit::{
+    OperationKind, QueryVisitor, VisitedField, VisitedFragmentSpread, VisitedInlineFragment,
+    VisitedOperation, VisitedVariable,
+};
 
 use std::collections::HashMap;
 
 type CacheMap<T> = ::std::sync::Mutex<HashMap<::std::path::PathBuf, T>>;
 
 lazy_static! {
-    static ref SCHEMA_CACHE: CacheMap<String> = CacheMap::default();
     static ref QUERY_CACHE: CacheMap<(String, graphql_parser::query::Document)> =
         CacheMap::default();
 }
 
+/// The formats a schema can be provided in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaFormat {
+    /// The GraphQL schema definition language (`.graphql` or `.gql` files).
+    GraphQL,
+    /// The JSON result of an introspection query (`.json` files).
+    Json,
+}
+
+impl SchemaFormat {
+    /// The format of a schema file, from its extension.
+    pub fn from_path(path: &std::path::Path) -> Result<SchemaFormat, CodegenError> {
+        match path.extension().and_then(std::ffi::OsStr::to_str) {
+            Some("graphql") | Some("gql") => Ok(SchemaFormat::GraphQL),
+            Some("json") => Ok(SchemaFormat::Json),
+            extension => Err(CodegenError::Schema {
+                message: format!(
+                    "Unsupported extension for the GraphQL schema: {} (only .json and .graphql are supported)",
+                    extension.unwrap_or("INVALID")
+                ),
+                location: None,
+            }),
+        }
+    }
+}
+
 /// Generates Rust code given a query document, a schema and options.
 pub fn generate_module_token_stream(
     query_path: std::path::PathBuf,
     schema_path: &std::path::Path,
     options: GraphQLClientCodegenOptions,
-) -> Result<TokenStream, failure::Error> {
-    use std::collections::hash_map;
+) -> Result<TokenStream, CodegenError> {
+    let timings = timings::Timings::for_options(&options);
+    let title = query_path.display().to_string();
+
     // We need to qualify the query with the path to the crate it is part of
-    let (query_string, query) = {
-        let mut lock = QUERY_CACHE.lock().expect("query cache is poisoned");
-        match lock.entry(query_path) {
-            hash_map::Entry::Occupied(o) => o.get().clone(),
-            hash_map::Entry::Vacant(v) => {
-                let query_string = read_file(v.key())?;
-                let query = graphql_parser::parse_query(&query_string)?;
-                v.insert((query_string, query)).clone()
-            }
+    let (query_string, query) = timings::time(timings.as_ref(), "query parsing", || {
+        cached_query(query_path)
+    })?;
+
+    let schema_format = SchemaFormat::from_path(schema_path)?;
+
+    let parsed_schema = timings::time(timings.as_ref(), "schema parsing", || {
+        parse_schema_file(schema_path, schema_format)
+    })?;
+
+    let generated = generate(
+        &query_string,
+        &query,
+        &parsed_schema,
+        &options,
+        timings.as_ref(),
+    );
+    if let Some(timings) = timings {
+        timings.print(&title);
+    }
+    generated
+}
+
+/// The text and the document of a query file, parsed once per file.
+fn cached_query(
+    query_path: std::path::PathBuf,
+) -> Result<(String, graphql_parser::query::Document), CodegenError> {
+    use std::collections::hash_map;
+
+    let mut lock = QUERY_CACHE.lock().expect("query cache is poisoned");
+    match lock.entry(query_path) {
+        hash_map::Entry::Occupied(o) => Ok(o.get().clone()),
+        hash_map::Entry::Vacant(v) => {
+            let query_string = read_file(v.key())?;
+            let query =
+                graphql_parser::parse_query(&query_string).map_err(CodegenError::query_parse)?;
+            Ok(v.insert((query_string, query)).clone())
         }
+    }
+}
+
+/// Parses a schema file from a memory mapping of the file rather than from a copy of its text, so
+/// the peak memory of the expansion of the derive stays low with large schemas. The mapping is
+/// dropped once the schema is parsed.
+fn parse_schema_file(
+    schema_path: &std::path::Path,
+    schema_format: SchemaFormat,
+) -> Result<schema::ParsedSchema, CodegenError> {
+    let io_error = |source| CodegenError::Io {
+        path: schema_path.to_path_buf(),
+        source,
     };
 
+    let file = std::fs::File::open(schema_path).map_err(io_error)?;
+    // Empty files cannot be mapped on all platforms.
+    if file.metadata().map_err(io_error)?.len() == 0 {
+        return parse_schema("", schema_format);
+    }
+
+    // Safety: the mapping is only read while the schema is parsed. As with any mapped file, a
+    // schema truncated by another process in the meantime would crash the compiler rather than
+    // fail the parsing.
+    let mapping = unsafe { memmap2::Mmap::map(&file) }.map_err(io_error)?;
+    let schema_string = std::str::from_utf8(&mapping)
+        .map_err(|err| io_error(std::io::Error::new(std::io::ErrorKind::InvalidData, err)))?;
+
+    parse_schema(schema_string, schema_format)
+}
+
+/// Generates Rust code given the text of a query document and of a schema, without reading any
+/// file. This is the entry point for build tools, editor plugins and other frontends embedding
+/// the code generation.
+///
+/// ```
+/// use graphql_client_codegen::{
+///     generate_module_token_stream_from_str, CodegenMode, GraphQLClientCodegenOptions,
+///     SchemaFormat,
+/// };
+///
+/// # fn main() -> Result<(), graphql_client_codegen::CodegenError> {
+/// let schema = "type Query { greeting: String }";
+/// let query = "query Greeting { greeting }";
+/// let options = GraphQLClientCodegenOptions::new(CodegenMode::Cli);
+///
+/// let code = generate_module_token_stream_from_str(query, schema, SchemaFormat::GraphQL, &options)?;
+///
+/// assert!(code.to_string().contains("mod greeting"));
+/// # Ok(())
+/// # }
+/// ```
+pub fn generate_module_token_stream_from_str(
+    query_string: &str,
+    schema_string: &str,
+    schema_format: SchemaFormat,
+    options: &GraphQLClientCodegenOptions,
+) -> Result<TokenStream, CodegenError> {
+    SchemaDocument::parse(schema_string, schema_format)?
+        .generate_module_token_stream_from_str(query_string, options)
+}
+
+/// Like [generate_module_token_stream_from_str], but returns the generated code as a string.
+/// It is not formatted: run it through `rustfmt` to make it readable.
+pub fn generate_module_string_from_str(
+    query_string: &str,
+    schema_string: &str,
+    schema_format: SchemaFormat,
+    options: &GraphQLClientCodegenOptions,
+) -> Result<String, CodegenError> {
+    generate_module_token_stream_from_str(query_string, schema_string, schema_format, options)
+        .map(|tokens| tokens.to_string())
+}
+
+/// The operation with the given name in a query document, followed by only the fragments it uses,
+/// sorted by name and printed in a stable format. Neither the formatting and comments of the
+/// document nor its other definitions change it, which makes it suitable for logging, persisted
+/// queries and cache keys. The generated modules return the same text from `normalized_query()`.
+///
+/// ```
+/// # fn main() -> Result<(), graphql_client_codegen::CodegenError> {
+/// let query = "
+///     fragment Unused on Query { id }
+///     query Greeting { ...Greeting }
+///     fragment Greeting on Query { greeting }
+/// ";
+///
+/// let normalized = graphql_client_codegen::normalized_operation(query, "Greeting")?;
+///
+/// assert!(normalized.starts_with("query Greeting"));
+/// assert!(normalized.contains("fragment Greeting on Query"));
+/// assert!(!normalized.contains("Unused"));
+/// # Ok(())
+/// # }
+/// ```
+pub fn normalized_operation(
+    query_string: &str,
+    operation_name: &str,
+) -> Result<String, CodegenError> {
+    let query = graphql_parser::parse_query(query_string).map_err(CodegenError::query_parse)?;
+
+    normalized::normalized_operation(&query, operation_name).ok_or_else(|| {
+        let operation_names = query
+            .definitions
+            .iter()
+            .filter_map(|definition| match definition {
+                graphql_parser::query::Definition::Operation(operation) => {
+                    unused::name_of(operation)
+                }
+                graphql_parser::query::Definition::Fragment(_) => None,
+            });
+
+        CodegenError::query(format_args!(
+            "Unknown operation: {}.{}",
+            operation_name,
+            suggestions::did_you_mean(operation_name, operation_names)
+        ))
+    })
+}
+
+/// The directives applied in a schema in the GraphQL format, by schema coordinate: `Type` for the
+/// types, `Type.field` for their fields and input fields and `Enum.VALUE` for the enum values.
+///
+/// ```
+/// # fn main() -> Result<(), graphql_client_codegen::CodegenError> {
+/// let schema = r#"
+///     type User @key(fields: "id") {
+///         id: ID!
+///         email: String @private
+///     }
+/// "#;
+///
+/// let directives = graphql_client_codegen::schema_directives(schema)?;
+///
+/// assert_eq!(directives["User"][0].name, "key");
+/// assert_eq!(
+///     directives["User"][0].arguments,
+///     vec![("fields".to_string(), "\"id\"".to_string())]
+/// );
+/// assert_eq!(directives["User.email"][0].name, "private");
+/// assert!(!directives.contains_key("User.id"));
+/// # Ok(())
+/// # }
+/// ```
+pub fn schema_directives(
+    schema_string: &str,
+) -> Result<std::collections::BTreeMap<String, Vec<schema::AppliedDirective>>, CodegenError> {
+    let document =
+        graphql_parser::schema::parse_schema(schema_string).map_err(CodegenError::schema_parse)?;
+    let schema = schema::Schema::from(&document);
+
+    let mut directives: std::collections::BTreeMap<String, Vec<schema::AppliedDirective>> = schema
+        .type_directives
+        .iter()
+        .map(|(name, directives)| (name.to_string(), directives.clone()))
+        .collect();
+
+    let fields = schema
+        .objects
+        .values()
+        .flat_map(|object| object.fields.iter().map(move |field| (object.name, field)))
+        .chain(schema.interfaces.values().flat_map(|interface| {
+            interface
+                .fields
+                .iter()
+                .map(move |field| (interface.name, field))
+        }))
+        .chain(
+            schema
+                .inputs
+                .values()
+                .flat_map(|input| input.fields.values().map(move |field| (input.name, field))),
+        )
+        .map(|(type_name, field)| (type_name, field.name, &field.directives));
+    let enum_values = schema.enums.values().flat_map(|enm| {
+        enm.variants
+            .iter()
+            .map(move |variant| (enm.name, variant.name, &variant.directives))
+    });
+
+    for (type_name, name, applied) in fields.chain(enum_values) {
+        if !applied.is_empty() {
+            directives.insert(format!("{}.{}", type_name, name), applied.clone());
+        }
+    }
+
+    Ok(directives)
+}
+
+/// A schema read and parsed once, to generate the code for several query documents.
+///
+/// ```
+/// use graphql_client_codegen::{
+///     CodegenMode, GraphQLClientCodegenOptions, SchemaDocument, SchemaFormat,
+/// };
+///
+/// # fn main() -> Result<(), graphql_client_codegen::CodegenError> {
+/// let schema = SchemaDocument::parse("type Query { greeting: String }", SchemaFormat::GraphQL)?;
+/// let options = GraphQLClientCodegenOptions::new(CodegenMode::Cli);
+///
+/// for query in &["query Greeting { greeting }", "query Other { greeting }"] {
+///     let code = schema.generate_module_token_stream_from_str(query, &options)?;
+///     assert!(code.to_string().contains("greeting"));
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct SchemaDocument {
+    parsed: schema::ParsedSchema,
+    /// The time spent parsing the schema, reported with the `timings` option.
+    parsing: std::time::Duration,
+}
+
+impl SchemaDocument {
+    /// Reads and parses the schema file at the given path, in the format given by its extension.
+    pub fn read(schema_path: &std::path::Path) -> Result<SchemaDocument, CodegenError> {
+        let schema_format = SchemaFormat::from_path(schema_path)?;
+        let started = std::time::Instant::now();
+        let parsed = parse_schema_file(schema_path, schema_format)?;
+
+        Ok(SchemaDocument {
+            parsed,
+            parsing: started.elapsed(),
+        })
+    }
+
+    /// Parses the text of a schema.
+    pub fn parse(
+        schema_string: &str,
+        schema_format: SchemaFormat,
+    ) -> Result<SchemaDocument, CodegenError> {
+        let started = std::time::Instant::now();
+        parse_schema(schema_string, schema_format).map(|parsed| SchemaDocument {
+            parsed,
+            parsing: started.elapsed(),
+        })
+    }
+
+    /// The model of this schema: its types, their fields and the directives applied to them, for
+    /// the tools using this crate as a GraphQL schema model.
+    ///
+    /// ```
+    /// use graphql_client_codegen::{SchemaDocument, SchemaFormat};
+    ///
+    /// # fn main() -> Result<(), graphql_client_codegen::CodegenError> {
+    /// let schema = SchemaDocument::parse(
+    ///     "type Query { users: [User!]! } type User { name: String }",
+    ///     SchemaFormat::GraphQL,
+    /// )?;
+    ///
+    /// let model = schema.model();
+    /// let users = &model.objects["Query"].fields[0];
+    ///
+    /// assert_eq!(users.type_.to_string(), "[User!]!");
+    /// assert_eq!(users.type_.name(), "User");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn model(&self) -> schema::SchemaModel<'_> {
+        schema::SchemaModel::from_schema(&schema::Schema::from(&self.parsed))
+    }
+
+    /// Like [generate_module_token_stream], with this schema.
+    pub fn generate_module_token_stream(
+        &self,
+        query_path: &std::path::Path,
+        options: &GraphQLClientCodegenOptions,
+    ) -> Result<TokenStream, CodegenError> {
+        let query_string = read_file(query_path)?;
+
+        self.generate_titled(&query_string, options, &query_path.display().to_string())
+    }
+
+    /// Like [generate_module_token_stream_from_str], with this schema.
+    pub fn generate_module_token_stream_from_str(
+        &self,
+        query_string: &str,
+        options: &GraphQLClientCodegenOptions,
+    ) -> Result<TokenStream, CodegenError> {
+        self.generate_titled(query_string, options, "the query document")
+    }
+
+    /// Generates the code of a query document, with the given title for its timings.
+    fn generate_titled(
+        &self,
+        query_string: &str,
+        options: &GraphQLClientCodegenOptions,
+        title: &str,
+    ) -> Result<TokenStream, CodegenError> {
+        let timings = timings::Timings::for_options(options);
+        if let Some(timings) = &timings {
+            // The schema is parsed once for all the query documents generated with it.
+            timings.record("schema parsing (shared)".to_owned(), self.parsing);
+        }
+        let query = timings::time(timings.as_ref(), "query parsing", || {
+            graphql_parser::parse_query(query_string).map_err(CodegenError::query_parse)
+        })?;
+
+        let generated = generate(
+            query_string,
+            &query,
+            &self.parsed,
+            options,
+            timings.as_ref(),
+        );
+        if let Some(timings) = timings {
+            timings.print(title);
+        }
+        generated
+    }
+
+    /// The depth, field count and complexity of the operations of a query document, with this
+    /// schema.
+    ///
+    /// ```
+    /// use graphql_client_codegen::{ComplexityOptions, SchemaDocument, SchemaFormat};
+    ///
+    /// # fn main() -> Result<(), graphql_client_codegen::CodegenError> {
+    /// let schema = SchemaDocument::parse(
+    ///     "type Query { users: [User!]! } type User { name: String! }",
+    ///     SchemaFormat::GraphQL,
+    /// )?;
+    ///
+    /// let options = ComplexityOptions::new();
+    /// let operations = schema.complexity("query Users { users { name } }", &options)?;
+    ///
+    /// assert_eq!(operations[0].name, "Users");
+    /// assert_eq!(operations[0].depth, 2);
+    /// assert_eq!(operations[0].complexity, 11);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn complexity(
+        &self,
+        query_string: &str,
+        options: &ComplexityOptions,
+    ) -> Result<Vec<OperationComplexity>, CodegenError> {
+        let query = graphql_parser::parse_query(query_string).map_err(CodegenError::query_parse)?;
+        let schema = schema::Schema::from(&self.parsed);
+
+        Ok(complexity::operations_complexity(&schema, &query, options))
+    }
+
+    /// TypeScript declarations of the variables and the response data of the operations of a
+    /// query document, with this schema, for the JavaScript code calling the same operations.
+    ///
+    /// ```
+    /// use graphql_client_codegen::{SchemaDocument, SchemaFormat};
+    ///
+    /// # fn main() -> Result<(), graphql_client_codegen::CodegenError> {
+    /// let schema = SchemaDocument::parse(
+    ///     "type Query { greeting(name: String!): String }",
+    ///     SchemaFormat::GraphQL,
+    /// )?;
+    ///
+    /// let declarations =
+    ///     schema.typescript("query Greet($name: String!) { greeting(name: $name) }")?;
+    ///
+    /// assert!(declarations.contains("export interface GreetVariables {\n  name: string;\n}"));
+    /// assert!(declarations.contains("greeting: string | null;"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn typescript(&self, query_string: &str) -> Result<String, CodegenError> {
+        let query = graphql_parser::parse_query(query_string).map_err(CodegenError::query_parse)?;
+        let schema = schema::Schema::from(&self.parsed);
+
+        Ok(typescript::operations_typescript(&schema, &query))
+    }
+
+    /// The fields selected in the operations and fragments of a query document, with the
+    /// generated structs, fields and types they are bound to, sorted by their position in the
+    /// document. Editor plugins serialize them to JSON to go from a field of a `.graphql` file to
+    /// its generated type, and to show the type on hover.
+    ///
+    /// ```
+    /// use graphql_client_codegen::{
+    ///     CodegenMode, GraphQLClientCodegenOptions, SchemaDocument, SchemaFormat,
+    /// };
+    ///
+    /// # fn main() -> Result<(), graphql_client_codegen::CodegenError> {
+    /// let schema = SchemaDocument::parse(
+    ///     "type Query { users: [User!]! } type User { name: String }",
+    ///     SchemaFormat::GraphQL,
+    /// )?;
+    /// let options = GraphQLClientCodegenOptions::new(CodegenMode::Cli);
+    ///
+    /// let bindings = schema.type_bindings("query Users {\n  users { name }\n}", &options)?;
+    ///
+    /// assert_eq!((bindings[0].line, bindings[0].column), (2, 3));
+    /// assert_eq!(bindings[0].parent_path, "users::ResponseData");
+    /// assert_eq!(bindings[0].rust_type, "Vec<UsersUsers>");
+    /// assert_eq!(bindings[0].selection_path, Some("users::UsersUsers".to_owned()));
+    /// assert_eq!(bindings[1].rust_type, "Option<String>");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn type_bindings(
+        &self,
+        query_string: &str,
+        options: &GraphQLClientCodegenOptions,
+    ) -> Result<Vec<TypeBinding>, CodegenError> {
+        use heck::SnakeCase;
+
+        let query = graphql_parser::parse_query(query_string).map_err(CodegenError::query_parse)?;
+        let schema = schema::Schema::from(&self.parsed);
+
+        let mut bindings = Vec::new();
+        for operation in codegen::all_operations(&query) {
+            let module = operation.name.to_snake_case();
+            let (_, operation_bindings) =
+                codegen::response_and_type_bindings(&schema, &query, &operation, options, None)
+                    .map_err(CodegenError::query_generation)?;
+            bindings.extend(
+                operation_bindings
+                    .into_iter()
+                    .map(|binding| binding.in_module(&module)),
+            );
+        }
+        bindings.sort();
+
+        Ok(bindings)
+    }
+
+    /// Walks the operations of a query document bound to this schema, calling the visitor for
+    /// each operation, variable, field and fragment, in the order of the document. The fields come
+    /// with their type and definition in the schema, so tools like linters and doc generators build
+    /// on the same binding as the generated code.
+    ///
+    /// ```
+    /// use graphql_client_codegen::{QueryVisitor, SchemaDocument, SchemaFormat, VisitedField};
+    ///
+    /// struct FieldTypes(Vec<String>);
+    ///
+    /// impl QueryVisitor for FieldTypes {
+    ///     fn enter_field(&mut self, field: &VisitedField<'_>) {
+    ///         let type_ = field.type_.clone().unwrap_or_default();
+    ///         self.0.push(format!("{}.{}: {}", field.parent_type, field.name, type_));
+    ///     }
+    /// }
+    ///
+    /// # fn main() -> Result<(), graphql_client_codegen::CodegenError> {
+    /// let schema = SchemaDocument::parse(
+    ///     "type Query { users: [User!]! } type User { name: String }",
+    ///     SchemaFormat::GraphQL,
+    /// )?;
+    ///
+    /// let mut field_types = FieldTypes(Vec::new());
+    /// schema.visit("query Users { users { name } }", &mut field_types)?;
+    ///
+    /// assert_eq!(field_types.0, vec!["Query.users: [User!]!", "User.name: String"]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn visit<V: QueryVisitor>(
+        &self,
+        query_string: &str,
+        visitor: &mut V,
+    ) -> Result<(), CodegenError> {
+        let query = graphql_parser::parse_query(query_string).map_err(CodegenError::query_parse)?;
+        let schema = schema::Schema::from(&self.parsed);
+
+        visit::visit_operations(&schema, &query, visitor);
+        Ok(())
+    }
+}
+
+fn parse_schema(
+    schema_string: &str,
+    schema_format: SchemaFormat,
+) -> Result<schema::ParsedSchema, CodegenError> {
+    match schema_format {
+        SchemaFormat::GraphQL => {
+            let s = graphql_parser::schema::parse_schema(schema_string)
+                .map_err(CodegenError::schema_parse)?;
+            Ok(schema::ParsedSchema::GraphQLParser(s))
+        }
+        SchemaFormat::Json => {
+            let parsed: introspection_response::IntrospectionResponse =
+                ::serde_json::from_str(schema_string).map_err(CodegenError::schema_json)?;
+            Ok(schema::ParsedSchema::Json(parsed))
+        }
+    }
+}
+
+fn generate(
+    query_string: &str,
+    query: &graphql_parser::query::Document,
+    parsed_schema: &schema::ParsedSchema,
+    options: &GraphQLClientCodegenOptions,
+    timings: Option<&timings::Timings>,
+) -> Result<TokenStream, CodegenError> {
     // Determine which operation we are generating code for. This will be used in operationName.
     let operations = options
         .operation_name
         .as_ref()
-        .and_then(|operation_name| codegen::select_operation(&query, &operation_name))
+        .and_then(|operation_name| codegen::select_operation(query, &operation_name))
         .map(|op| vec![op]);
 
     let operations = match (operations, &options.mode) {
+        (_, &CodegenMode::Derive) if options.all_operations() => codegen::all_operations(query),
         (Some(ops), _) => ops,
-        (None, &CodegenMode::Cli) => codegen::all_operations(&query),
+        (None, &CodegenMode::Cli) => codegen::all_operations(query),
         (None, &CodegenMode::Derive) => {
             return Err(derive_operation_not_found_error(
                 options.struct_ident(),
-                &query,
+                query,
             ));
         }
     };
 
-    let schema_extension = schema_path
-        .extension()
-        .and_then(std::ffi::OsStr::to_str)
-        .unwrap_or("INVALID");
-
-    // Check the schema cache.
-    let schema_string: String = {
-        let mut lock = SCHEMA_CACHE.lock().expect("schema cache is poisoned");
-        match lock.entry(schema_path.to_path_buf()) {
-            hash_map::Entry::Occupied(o) => o.get().clone(),
-            hash_map::Entry::Vacant(v) => {
-                let schema_string = read_file(v.key())?;
-                v.insert(schema_string).to_string()
-            }
-        }
-    };
-
-    let parsed_schema = match schema_extension {
-                        "graphql" | "gql" => {
-                            let s = graphql_parser::schema::parse_schema(&schema_string)?;
-                            schema::ParsedSchema::GraphQLParser(s)
-                        }
-                        "json" => {
-                            let parsed: introspection_response::IntrospectionResponse = ::serde_json::from_str(&schema_string)?;
-                            schema::ParsedSchema::Json(parsed)
-                        }
-                        extension => panic!("Unsupported extension for the GraphQL schema: {} (only .json and .graphql are supported)", extension)
-                    };
+    let schema = timings::time(timings, "schema model", || {
+        schema::Schema::from(parsed_schema)
+    });
 
-    let schema = schema::Schema::from(&parsed_schema);
+    // The client directives are not sent to the server.
+    let sent_query = client_directives::sent_query(query)?;
+    let query_string = sent_query.as_ref().map(String::as_str).unwrap_or(query_string);
 
     // The generated modules.
     let mut modules = Vec::with_capacity(operations.len());
 
-    for operation in &operations {
+    for (index, operation) in operations.iter().enumerate() {
         let generated = generated_module::GeneratedModule {
-            query_string: query_string.as_str(),
+            query_string,
             schema: &schema,
-            query_document: &query,
+            query_document: query,
             operation,
-            options: &options,
+            options,
+            timings,
+            document_warnings: index == 0,
         }
-        .to_token_stream()?;
+        .to_token_stream()
+        .map_err(CodegenError::query_generation)?;
         modules.push(generated);
     }
 
     let modules = quote! { #(#modules)* };
 
-    Ok(modules)
+    let generated = match options.mode {
+        CodegenMode::Derive if options.all_operations() => {
+            all_operations_module(query_string, &operations, modules, options)
+        }
+        _ => modules,
+    };
+
+    timings::time(timings, "post processing", || {
+        post_process::post_process(generated, options)
+    })
 }
 
-fn read_file(path: &::std::path::Path) -> Result<String, failure::Error> {
-    use std::fs;
-    use std::io::prelude::*;
+/// The module named after the struct under derive, with the modules generated for all the
+/// operations of the document, a struct implementing `GraphQLQuery` for each of them, and the
+/// function building the request body of an operation from its name.
+fn all_operations_module(
+    query_string: &str,
+    operations: &[operations::Operation<'_>],
+    modules: TokenStream,
+    options: &GraphQLClientCodegenOptions,
+) -> TokenStream {
+    use heck::{CamelCase, SnakeCase};
+    use proc_macro2::{Ident, Span};
 
-    let mut out = String::new();
-    let mut file = fs::File::open(path).map_err(|io_err| {
-        let err: failure::Error = io_err.into();
-        err.context(format!(
-            r#"
-            Could not find file with path: {}
-            Hint: file paths in the GraphQLQuery attribute are relative to the project root (location of the Cargo.toml). Example: query_path = "src/my_query.graphql".
-            "#,
-            path.display()
-        ))
-    })?;
-    file.read_to_string(&mut out)?;
-    Ok(out)
+    let module_name = options
+        .struct_ident()
+        .map(|ident| ident.to_string().to_snake_case())
+        .unwrap_or_default();
+    let module_name = Ident::new(&module_name, Span::call_site());
+    let module_visibility = options.module_visibility();
+    let operation_names: Vec<&str> = operations
+        .iter()
+        .map(|operation| operation.name.as_str())
+        .collect();
+    let operation_structs = operations
+        .iter()
+        .map(|operation| Ident::new(&operation.name.to_camel_case(), Span::call_site()));
+
+    quote! {
+        #module_visibility mod #module_name {
+            #![allow(dead_code)]
+
+            // The generated modules refer to the custom scalars in their parent module.
+            #[allow(unused_imports)]
+            use super::*;
+
+            pub const QUERY: &'static str = #query_string;
+            pub const OPERATION_NAMES: &'static [&'static str] = &[#(#operation_names),*];
+
+            /// The request body for the operation with the given name, or `None` when the document
+            /// does not define it.
+            pub fn build_query<V: ::serde::Serialize>(
+                operation_name: &str,
+                variables: V,
+            ) -> ::core::option::Option<::graphql_client::QueryBody<V>> {
+                OPERATION_NAMES
+                    .iter()
+                    .find(|name| **name == operation_name)
+                    .map(|name| ::graphql_client::QueryBody {
+                        variables,
+                        query: QUERY,
+                        operation_name: name,
+                    })
+            }
+
+            #(pub struct #operation_structs;)*
+
+            #modules
+        }
+    }
+}
+
+fn read_file(path: &::std::path::Path) -> Result<String, CodegenError> {
+    std::fs::read_to_string(path).map_err(|source| CodegenError::Io {
+        path: path.to_path_buf(),
+        source,
+    })
 }
 
 /// In derive mode, build an error when the operation with the same name as the struct is not found.
 fn derive_operation_not_found_error(
     ident: Option<&proc_macro2::Ident>,
     query: &graphql_parser::query::Document,
-) -> failure::Error {
+) -> CodegenError {
     use graphql_parser::query::*;
 
     let operation_name = ident.map(ToString::to_string);
@@ -193,9 +828,13 @@ fn derive_operation_not_found_error(
 
     let available_operations = available_operations.trim_end_matches(", ");
 
-    return format_err!(
-        "The struct name does not match any defined operation in the query file.\nStruct name: {}\nDefined operations: {}",
+    CodegenError::query(format_args!(
+        "The struct name does not match any defined operation in the query file.{}\nStruct name: {}\nDefined operations: {}",
+        suggestions::did_you_mean(
+            struct_ident,
+            available_operations.split(", ").filter(|name| !name.is_empty())
+        ),
         struct_ident,
         available_operations,
-    );
+    ))
 }
@@ -0,0 +1,394 @@
+//! The public model of a schema, for the tools using this crate as a GraphQL schema model. It is
+//! built from the schema used by the code generation, without its code generation state.
+
+use crate::deprecation::DeprecationStatus;
+use crate::field_type::FieldType;
+use crate::objects::GqlObjectField;
+use crate::schema::{AppliedDirective, Schema, DEFAULT_SCALARS};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// The types of a schema, by name. The introspection types, like `__Schema`, and the built-in
+/// scalars are left out.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaModel<'a> {
+    /// The root type of the queries.
+    pub query_type: Option<&'a str>,
+    /// The root type of the mutations.
+    pub mutation_type: Option<&'a str>,
+    /// The root type of the subscriptions.
+    pub subscription_type: Option<&'a str>,
+    /// The object types.
+    pub objects: BTreeMap<&'a str, ObjectType<'a>>,
+    /// The interfaces.
+    pub interfaces: BTreeMap<&'a str, InterfaceType<'a>>,
+    /// The unions.
+    pub unions: BTreeMap<&'a str, UnionType<'a>>,
+    /// The enums.
+    pub enums: BTreeMap<&'a str, EnumType<'a>>,
+    /// The input object types.
+    pub input_objects: BTreeMap<&'a str, InputObjectType<'a>>,
+    /// The custom scalars.
+    pub scalars: BTreeMap<&'a str, ScalarType<'a>>,
+}
+
+/// An object type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectType<'a> {
+    /// The name of the type.
+    pub name: &'a str,
+    /// The description of the type.
+    pub description: Option<&'a str>,
+    /// The fields of the type, in the order of the schema, without `__typename`.
+    pub fields: Vec<FieldDefinition<'a>>,
+    /// The interfaces the type implements, sorted by name.
+    pub interfaces: Vec<&'a str>,
+    /// The directives applied to the type.
+    pub directives: Vec<AppliedDirective>,
+}
+
+/// An interface.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InterfaceType<'a> {
+    /// The name of the interface.
+    pub name: &'a str,
+    /// The description of the interface.
+    pub description: Option<&'a str>,
+    /// The fields of the interface, in the order of the schema, without `__typename`.
+    pub fields: Vec<FieldDefinition<'a>>,
+    /// The object types implementing the interface, sorted by name.
+    pub possible_types: Vec<&'a str>,
+    /// The directives applied to the interface.
+    pub directives: Vec<AppliedDirective>,
+}
+
+/// A union.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnionType<'a> {
+    /// The name of the union.
+    pub name: &'a str,
+    /// The description of the union.
+    pub description: Option<&'a str>,
+    /// The members of the union, sorted by name.
+    pub possible_types: Vec<&'a str>,
+    /// The directives applied to the union.
+    pub directives: Vec<AppliedDirective>,
+}
+
+/// An enum.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnumType<'a> {
+    /// The name of the enum.
+    pub name: &'a str,
+    /// The description of the enum.
+    pub description: Option<&'a str>,
+    /// The values of the enum, in the order of the schema.
+    pub values: Vec<EnumValue<'a>>,
+    /// The directives applied to the enum.
+    pub directives: Vec<AppliedDirective>,
+}
+
+/// A value of an enum.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnumValue<'a> {
+    /// The name of the value.
+    pub name: &'a str,
+    /// The description of the value.
+    pub description: Option<&'a str>,
+    /// The directives applied to the value.
+    pub directives: Vec<AppliedDirective>,
+}
+
+/// An input object type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputObjectType<'a> {
+    /// The name of the type.
+    pub name: &'a str,
+    /// The description of the type.
+    pub description: Option<&'a str>,
+    /// The fields of the type, sorted by name.
+    pub fields: Vec<FieldDefinition<'a>>,
+    /// The directives applied to the type.
+    pub directives: Vec<AppliedDirective>,
+}
+
+/// A custom scalar.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScalarType<'a> {
+    /// The name of the scalar.
+    pub name: &'a str,
+    /// The description of the scalar.
+    pub description: Option<&'a str>,
+    /// The URL of the specification of the scalar, from `@specifiedBy` or `specifiedByURL`.
+    pub specified_by_url: Option<&'a str>,
+    /// The directives applied to the scalar.
+    pub directives: Vec<AppliedDirective>,
+}
+
+/// A field of an object type, an interface or an input object type. The arguments of the fields
+/// are not part of the model.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldDefinition<'a> {
+    /// The name of the field.
+    pub name: &'a str,
+    /// The description of the field.
+    pub description: Option<&'a str>,
+    /// The type of the field.
+    pub type_: TypeRef<'a>,
+    /// Whether the field is deprecated, with the reason.
+    pub deprecation: DeprecationStatus,
+    /// The directives applied to the field.
+    pub directives: Vec<AppliedDirective>,
+}
+
+/// A reference to a type, with its lists and non-null markers. It displays in the GraphQL syntax,
+/// like `[User!]!`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TypeRef<'a> {
+    /// A nullable named type.
+    Named(&'a str),
+    /// A nullable list.
+    List(Box<TypeRef<'a>>),
+    /// The non-null version of a type.
+    NonNull(Box<TypeRef<'a>>),
+}
+
+impl<'a> TypeRef<'a> {
+    /// The named type, without the lists and non-null markers.
+    pub fn name(&self) -> &'a str {
+        match self {
+            TypeRef::Named(name) => *name,
+            TypeRef::List(inner) | TypeRef::NonNull(inner) => inner.name(),
+        }
+    }
+
+    fn from_field_type(field_type: &FieldType<'a>) -> TypeRef<'a> {
+        match field_type {
+            FieldType::Optional(inner) => TypeRef::nullable(inner),
+            field_type => TypeRef::NonNull(Box::new(TypeRef::nullable(field_type))),
+        }
+    }
+
+    /// The nullable version of the field type.
+    fn nullable(field_type: &FieldType<'a>) -> TypeRef<'a> {
+        match field_type {
+            FieldType::Named(name) => TypeRef::Named(*name),
+            FieldType::Vector(inner) => TypeRef::List(Box::new(TypeRef::from_field_type(inner))),
+            FieldType::Optional(inner) => TypeRef::nullable(inner),
+        }
+    }
+}
+
+impl<'a> fmt::Display for TypeRef<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TypeRef::Named(name) => write!(f, "{}", name),
+            TypeRef::List(inner) => write!(f, "[{}]", inner),
+            TypeRef::NonNull(inner) => write!(f, "{}!", inner),
+        }
+    }
+}
+
+impl<'a> SchemaModel<'a> {
+    pub(crate) fn from_schema(schema: &Schema<'a>) -> SchemaModel<'a> {
+        let directives = |name: &str| {
+            schema
+                .type_directives
+                .get(name)
+                .cloned()
+                .unwrap_or_default()
+        };
+        let is_listed = |name: &&str| !name.starts_with("__");
+
+        SchemaModel {
+            query_type: schema.query_type,
+            mutation_type: schema.mutation_type,
+            subscription_type: schema.subscription_type,
+            objects: schema
+                .objects
+                .values()
+                .filter(|object| is_listed(&object.name))
+                .map(|object| {
+                    let mut interfaces: Vec<&str> = schema
+                        .interfaces
+                        .values()
+                        .filter(|interface| interface.implemented_by.contains(&object.name))
+                        .map(|interface| interface.name)
+                        .collect();
+                    interfaces.sort();
+
+                    let object_type = ObjectType {
+                        name: object.name,
+                        description: object.description,
+                        fields: field_definitions(&object.fields),
+                        interfaces,
+                        directives: directives(object.name),
+                    };
+                    (object.name, object_type)
+                })
+                .collect(),
+            interfaces: schema
+                .interfaces
+                .values()
+                .map(|interface| {
+                    let mut possible_types: Vec<&str> =
+                        interface.implemented_by.iter().cloned().collect();
+                    possible_types.sort();
+
+                    let interface_type = InterfaceType {
+                        name: interface.name,
+                        description: interface.description,
+                        fields: field_definitions(&interface.fields),
+                        possible_types,
+                        directives: directives(interface.name),
+                    };
+                    (interface.name, interface_type)
+                })
+                .collect(),
+            unions: schema
+                .unions
+                .values()
+                .map(|union| {
+                    let union_type = UnionType {
+                        name: union.name,
+                        description: union.description,
+                        possible_types: union.variants.iter().cloned().collect(),
+                        directives: directives(union.name),
+                    };
+                    (union.name, union_type)
+                })
+                .collect(),
+            enums: schema
+                .enums
+                .values()
+                .filter(|enm| is_listed(&enm.name))
+                .map(|enm| {
+                    let enum_type = EnumType {
+                        name: enm.name,
+                        description: enm.description,
+                        values: enm
+                            .variants
+                            .iter()
+                            .map(|variant| EnumValue {
+                                name: variant.name,
+                                description: variant.description,
+                                directives: variant.directives.clone(),
+                            })
+                            .collect(),
+                        directives: directives(enm.name),
+                    };
+                    (enm.name, enum_type)
+                })
+                .collect(),
+            input_objects: schema
+                .inputs
+                .values()
+                .map(|input| {
+                    let mut fields: Vec<&GqlObjectField<'a>> = input.fields.values().collect();
+                    fields.sort_by_key(|field| field.name);
+
+                    let input_type = InputObjectType {
+                        name: input.name,
+                        description: input.description,
+                        fields: fields.into_iter().map(field_definition).collect(),
+                        directives: directives(input.name),
+                    };
+                    (input.name, input_type)
+                })
+                .collect(),
+            scalars: schema
+                .scalars
+                .values()
+                .filter(|scalar| !DEFAULT_SCALARS.contains(&scalar.name))
+                .map(|scalar| {
+                    let scalar_type = ScalarType {
+                        name: scalar.name,
+                        description: scalar.description,
+                        specified_by_url: scalar.specified_by_url,
+                        directives: directives(scalar.name),
+                    };
+                    (scalar.name, scalar_type)
+                })
+                .collect(),
+        }
+    }
+}
+
+/// The fields of an object type or an interface, without `__typename`.
+fn field_definitions<'a>(fields: &[GqlObjectField<'a>]) -> Vec<FieldDefinition<'a>> {
+    fields
+        .iter()
+        .filter(|field| !field.name.starts_with("__"))
+        .map(field_definition)
+        .collect()
+}
+
+fn field_definition<'a>(field: &GqlObjectField<'a>) -> FieldDefinition<'a> {
+    FieldDefinition {
+        name: field.name,
+        description: field.description,
+        type_: TypeRef::from_field_type(&field.type_),
+        deprecation: field.deprecation.clone(),
+        directives: field.directives.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn schema_model_from_sdl() {
+        let schema = graphql_parser::parse_schema(
+            r#"
+            schema { query: Query }
+            scalar Date @specifiedBy(url: "https://example.com/date")
+            type Query { node(id: ID!): Node, search: [SearchResult!]! }
+            interface Node { id: ID! }
+            type User implements Node {
+              id: ID!
+              "The birthday of the user."
+              born: Date @deprecated(reason: "Private.")
+            }
+            union SearchResult = User
+            enum Role { ADMIN, GUEST }
+            input UserFilter { role: Role, name: String! }
+            "#,
+        )
+        .unwrap();
+        let schema = Schema::from(&schema);
+        let model = SchemaModel::from_schema(&schema);
+
+        assert_eq!(model.query_type, Some("Query"));
+        assert_eq!(model.objects["User"].interfaces, vec!["Node"]);
+        assert_eq!(model.interfaces["Node"].possible_types, vec!["User"]);
+        assert_eq!(model.unions["SearchResult"].possible_types, vec!["User"]);
+        assert_eq!(model.enums["Role"].values[1].name, "GUEST");
+        assert_eq!(
+            model.scalars["Date"].specified_by_url,
+            Some("https://example.com/date")
+        );
+
+        let fields: Vec<String> = model.objects["Query"]
+            .fields
+            .iter()
+            .map(|field| format!("{}: {}", field.name, field.type_))
+            .collect();
+        assert_eq!(fields, vec!["node: Node", "search: [SearchResult!]!"]);
+
+        let born = &model.objects["User"].fields[1];
+        assert_eq!(born.description, Some("The birthday of the user."));
+        assert_eq!(born.type_.name(), "Date");
+        assert_eq!(
+            born.deprecation,
+            DeprecationStatus::Deprecated(Some("Private.".to_owned()))
+        );
+
+        let input_fields: Vec<String> = model.input_objects["UserFilter"]
+            .fields
+            .iter()
+            .map(|field| format!("{}: {}", field.name, field.type_))
+            .collect();
+        assert_eq!(input_fields, vec!["name: String!", "role: Role"]);
+    }
+}
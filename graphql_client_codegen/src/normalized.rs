@@ -0,0 +1,170 @@
+//! The normalized text of an operation, for logging, persisted queries and cache keys.
+
+use crate::unused::{collect_spreads, name_of, operation_selection_set};
+use graphql_parser::query::{Definition, Document, FragmentDefinition, OperationDefinition};
+use std::collections::BTreeSet;
+
+/// The operation with the given name, followed by the fragments it spreads, directly or not,
+/// sorted by name and printed in the format of `graphql_parser`. The formatting, the comments and
+/// the other definitions of the document do not change it.
+pub(crate) fn normalized_operation(document: &Document, operation_name: &str) -> Option<String> {
+    let operation = operation(document, operation_name)?;
+
+    let mut definitions = vec![Definition::Operation(operation.clone())];
+    definitions.extend(
+        used_fragments(document, operation_name)
+            .into_iter()
+            .map(|fragment| Definition::Fragment(fragment.clone())),
+    );
+
+    // The client directives are not sent to the server.
+    let mut normalized = Document { definitions };
+    crate::client_directives::strip(&mut normalized);
+
+    Some(normalized.to_string())
+}
+
+/// The fragments the operation with the given name spreads, directly or not, sorted by name.
+pub(crate) fn used_fragments<'a>(
+    document: &'a Document,
+    operation_name: &str,
+) -> Vec<&'a FragmentDefinition> {
+    let operation = match operation(document, operation_name) {
+        Some(operation) => operation,
+        None => return Vec::new(),
+    };
+
+    let mut used_fragments = BTreeSet::new();
+    let mut pending = vec![operation_selection_set(operation)];
+
+    while let Some(selection_set) = pending.pop() {
+        let mut spread = BTreeSet::new();
+        collect_spreads(selection_set, &mut spread);
+
+        for fragment_name in spread {
+            if !used_fragments.insert(fragment_name) {
+                continue;
+            }
+
+            if let Some(fragment) = fragment(document, fragment_name) {
+                pending.push(&fragment.selection_set);
+            }
+        }
+    }
+
+    used_fragments
+        .into_iter()
+        .filter_map(|fragment_name| fragment(document, fragment_name))
+        .collect()
+}
+
+/// The fragment printed in the format of `graphql_parser`, as it is sent to the server.
+pub(crate) fn normalized_fragment(fragment: &FragmentDefinition) -> String {
+    let mut normalized = Document {
+        definitions: vec![Definition::Fragment(fragment.clone())],
+    };
+    crate::client_directives::strip(&mut normalized);
+
+    normalized.to_string()
+}
+
+fn operation<'a>(document: &'a Document, name: &str) -> Option<&'a OperationDefinition> {
+    document
+        .definitions
+        .iter()
+        .filter_map(|definition| match definition {
+            Definition::Operation(operation) => Some(operation),
+            Definition::Fragment(_) => None,
+        })
+        .find(|operation| name_of(operation) == Some(name))
+}
+
+fn fragment<'a>(document: &'a Document, name: &str) -> Option<&'a FragmentDefinition> {
+    document
+        .definitions
+        .iter()
+        .filter_map(|definition| match definition {
+            Definition::Fragment(fragment) if fragment.name == name => Some(fragment),
+            _ => None,
+        })
+        .next()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const QUERY: &str = r#"
+        fragment Orphan on Character { id }
+
+        # The fragments are spread in no particular order.
+        query Hero { hero { ...HeroName ...Appearances } }
+
+        fragment Appearances on Character { appearsIn ...HeroName }
+        fragment HeroName on Character { name }
+
+        query Other { hero { id } }
+    "#;
+
+    #[test]
+    fn normalized_operation_keeps_only_the_used_fragments() {
+        let document = graphql_parser::parse_query(QUERY).unwrap();
+        let normalized = normalized_operation(&document, "Hero").unwrap();
+
+        assert!(normalized.starts_with("query Hero"));
+        assert!(!normalized.contains("Orphan"));
+        assert!(!normalized.contains("query Other"));
+        assert!(!normalized.contains('#'));
+
+        let appearances = normalized.find("fragment Appearances").unwrap();
+        let hero_name = normalized.find("fragment HeroName").unwrap();
+        assert!(appearances < hero_name);
+    }
+
+    #[test]
+    fn used_fragments_include_the_nested_spreads() {
+        let document = graphql_parser::parse_query(QUERY).unwrap();
+        let names = |operation_name| -> Vec<String> {
+            used_fragments(&document, operation_name)
+                .into_iter()
+                .map(|fragment| fragment.name.clone())
+                .collect()
+        };
+
+        assert_eq!(names("Hero"), vec!["Appearances", "HeroName"]);
+        assert!(names("Other").is_empty());
+        assert!(normalized_fragment(used_fragments(&document, "Hero")[1])
+            .starts_with("fragment HeroName on Character {"));
+    }
+
+    #[test]
+    fn normalized_operation_ignores_formatting() {
+        let document = graphql_parser::parse_query(QUERY).unwrap();
+        let reformatted = graphql_parser::parse_query(
+            r#"
+            query Hero {
+              hero {
+                ...HeroName
+                ...Appearances
+              }
+            }
+
+            fragment HeroName on Character {
+              name
+            }
+
+            fragment Appearances on Character {
+              appearsIn
+              ...HeroName
+            }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            normalized_operation(&document, "Hero"),
+            normalized_operation(&reformatted, "Hero")
+        );
+        assert_eq!(normalized_operation(&document, "Villain"), None);
+    }
+}
@@ -4,7 +4,10 @@ use crate::field_type::FieldType;
 use crate::query::QueryContext;
 use crate::schema::Schema;
 use crate::selection::*;
-use crate::shared::{field_impls_for_selection, response_fields_for_selection};
+use crate::shared::{
+    field_impls_for_selection, pretty_display_impl, pretty_fields_for_selection,
+    response_fields_for_selection,
+};
 use failure;
 use graphql_parser::schema;
 use proc_macro2::{Ident, Span, TokenStream};
@@ -25,11 +28,17 @@ pub struct GqlObjectField<'schema> {
     pub name: &'schema str,
     pub type_: FieldType<'schema>,
     pub deprecation: DeprecationStatus,
+    /// The directives applied to the field in the schema.
+    pub directives: Vec<crate::schema::AppliedDirective>,
 }
 
-fn parse_deprecation_info(field: &schema::Field) -> DeprecationStatus {
-    let deprecated = field
-        .directives
+pub(crate) fn parse_deprecation_info(field: &schema::Field) -> DeprecationStatus {
+    parse_deprecation_directives(&field.directives)
+}
+
+/// The deprecation status of a field or an input field with the given directives.
+pub(crate) fn parse_deprecation_directives(directives: &[schema::Directive]) -> DeprecationStatus {
+    let deprecated = directives
         .iter()
         .filter(|x| x.name.to_lowercase() == "deprecated")
         .nth(0);
@@ -57,6 +66,17 @@ fn parse_deprecation_info(field: &schema::Field) -> DeprecationStatus {
     }
 }
 
+/// The deprecation status of a field from an introspection response.
+pub(crate) fn introspected_deprecation_info(
+    field: &crate::introspection_response::FullTypeFields,
+) -> DeprecationStatus {
+    if field.is_deprecated.unwrap_or(false) {
+        DeprecationStatus::Deprecated(field.deprecation_reason.clone())
+    } else {
+        DeprecationStatus::Current
+    }
+}
+
 impl<'schema> GqlObject<'schema> {
     pub fn new(name: &'schema str, description: Option<&'schema str>) -> GqlObject<'schema> {
         GqlObject {
@@ -77,6 +97,7 @@ impl<'schema> GqlObject<'schema> {
                 name: &f.name,
                 type_: FieldType::from(&f.field_type),
                 deprecation,
+                directives: crate::schema::applied_directives(&f.directives),
             }
         }));
         item
@@ -89,16 +110,13 @@ impl<'schema> GqlObject<'schema> {
         let mut item = GqlObject::new(obj.name.as_ref().expect("missing object name"), description);
         let fields = obj.fields.as_ref().unwrap().iter().filter_map(|t| {
             t.as_ref().map(|t| {
-                let deprecation = if t.is_deprecated.unwrap_or(false) {
-                    DeprecationStatus::Deprecated(t.deprecation_reason.clone())
-                } else {
-                    DeprecationStatus::Current
-                };
+                let deprecation = introspected_deprecation_info(t);
                 GqlObjectField {
                     description: t.description.as_ref().map(String::as_str),
                     name: t.name.as_ref().expect("field name"),
                     type_: FieldType::from(t.type_.as_ref().expect("field type")),
                     deprecation,
+                    directives: Vec::new(),
                 }
             })
         });
@@ -125,18 +143,33 @@ impl<'schema> GqlObject<'schema> {
         prefix: &str,
     ) -> Result<TokenStream, failure::Error> {
         let derives = query_context.response_derives();
+        let vis = &query_context.item_visibility;
         let name = Ident::new(prefix, Span::call_site());
         let fields = self.response_fields_for_selection(query_context, selection, prefix)?;
         let field_impls = self.field_impls_for_selection(query_context, selection, &prefix)?;
         let description = self.description.as_ref().map(|desc| quote!(#[doc = #desc]));
+        let display = if query_context.pretty_display {
+            let body =
+                pretty_fields_for_selection(self.name, &self.fields, query_context, selection)?;
+            pretty_display_impl(vis, &name, &body)
+        } else {
+            quote!()
+        };
+        let relay = crate::relay::relay_impls(query_context, self, selection, prefix);
+        let extra_field = crate::shared::extra_field(query_context);
         Ok(quote! {
             #(#field_impls)*
 
             #derives
             #description
-            pub struct #name {
+            #vis struct #name {
                 #(#fields,)*
+                #extra_field
             }
+
+            #display
+
+            #relay
         })
     }
 
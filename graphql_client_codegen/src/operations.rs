@@ -1,9 +1,9 @@
 use crate::constants::*;
+use crate::field_type::FieldType;
 use crate::query::QueryContext;
 use crate::selection::Selection;
 use crate::variables::Variable;
-use graphql_parser::query::OperationDefinition;
-use heck::SnakeCase;
+use graphql_parser::query::{OperationDefinition, Value};
 use proc_macro2::{Span, TokenStream};
 use quote::quote;
 use syn::Ident;
@@ -46,23 +46,96 @@ impl<'query> Operation<'query> {
     pub(crate) fn variable_fields(&self, context: &QueryContext<'_, '_>) -> Vec<TokenStream> {
         self.variables.iter().map(|variable| {
             let ty = variable.ty.to_rust(context, "");
-            let rust_safe_field_name =
-                crate::shared::keyword_replace(&variable.name.to_snake_case());
+            let rust_safe_field_name = self.variable_field_name(context, &variable.name);
             let mut rename =
                 crate::shared::field_rename_annotation(&variable.name, &rust_safe_field_name);
             let name = Ident::new(&rust_safe_field_name, Span::call_site());
 
-            if let crate::field_type::FieldType::Optional(_) = &variable.ty {
+            if variable.ty.is_optional() && !context.format_agnostic_serde {
                 rename = quote!(
                     #[serde(skip_serializing_if = "Option::is_none")]
                     #rename
                 )
             }
+            let scalar_trait = crate::shared::scalar_trait_annotation(&variable.ty, context);
 
-            quote!(#rename pub #name: #ty)
+            quote!(#rename #scalar_trait pub #name: #ty)
         }).collect()
     }
 
+    /// The fields of the Variables struct, for its typestate builder.
+    pub(crate) fn variable_builder_fields(
+        &self,
+        context: &QueryContext<'_, '_>,
+    ) -> Vec<crate::builders::BuilderField> {
+        self.variables
+            .iter()
+            .map(|variable| {
+                let name = Ident::new(
+                    &self.variable_field_name(context, &variable.name),
+                    Span::call_site(),
+                );
+                let optional = match &variable.ty {
+                    crate::field_type::FieldType::Optional(inner) => {
+                        Some(inner.to_rust(context, ""))
+                    }
+                    _ => None,
+                };
+
+                crate::builders::BuilderField {
+                    name,
+                    ty: variable.ty.to_rust(context, ""),
+                    optional,
+                }
+            })
+            .collect()
+    }
+
+    /// The variables, as fields checked by the generated `Variables::validate`.
+    pub(crate) fn variable_validated_fields(
+        &self,
+        context: &QueryContext<'_, '_>,
+    ) -> Vec<crate::validation::ValidatedField<'_>> {
+        self.variables
+            .iter()
+            .map(|variable| crate::validation::ValidatedField {
+                name: Ident::new(
+                    &self.variable_field_name(context, &variable.name),
+                    Span::call_site(),
+                ),
+                graphql_name: variable.name,
+                type_: &variable.ty,
+            })
+            .collect()
+    }
+
+    /// The name of the field of the Variables struct for the variable `name`.
+    fn variable_field_name(&self, context: &QueryContext<'_, '_>, name: &str) -> String {
+        crate::shared::field_name(
+            context,
+            name,
+            self.variables.iter().map(|variable| variable.name),
+        )
+    }
+
+    /// Whether the operations declare the same variables, with the same types and defaults, in any
+    /// order.
+    pub(crate) fn has_same_variables(&self, other: &Operation<'_>) -> bool {
+        fn signature<'a>(
+            operation: &'a Operation<'_>,
+        ) -> Vec<(&'a str, &'a FieldType<'a>, Option<&'a Value>)> {
+            let mut variables: Vec<_> = operation
+                .variables
+                .iter()
+                .map(|variable| (variable.name, &variable.ty, variable.default))
+                .collect();
+            variables.sort_by_key(|(name, _, _)| *name);
+            variables
+        }
+
+        signature(self) == signature(other)
+    }
+
     /// mark types of variables of this operation as required
     pub(crate) fn compute_variable_requirements(&self, context: &QueryContext<'_, '_>) {
         for variable in &self.variables {
@@ -0,0 +1,73 @@
+use crate::codegen_options::{GraphQLClientCodegenOptions, TypeHook};
+use crate::error::CodegenError;
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+
+/// Runs the type hook, then the post-process callback of the options on the generated code.
+pub(crate) fn post_process(
+    mut generated: TokenStream,
+    options: &GraphQLClientCodegenOptions,
+) -> Result<TokenStream, CodegenError> {
+    if let Some(type_hook) = options.type_hook() {
+        let file: syn::File = syn::parse2(generated).map_err(|err| {
+            CodegenError::query(format!(
+                "The generated code could not be parsed for the type hook: {}",
+                err
+            ))
+        })?;
+        generated = hook_items(file.items, "", type_hook);
+    }
+
+    if let Some(post_process) = options.post_process() {
+        post_process(&mut generated);
+    }
+
+    Ok(generated)
+}
+
+/// The items with the type hook run on their structs and enums, and on those of their modules.
+fn hook_items(items: Vec<syn::Item>, prefix: &str, type_hook: &TypeHook) -> TokenStream {
+    let path = |ident: &syn::Ident| {
+        if prefix.is_empty() {
+            ident.to_string()
+        } else {
+            format!("{}::{}", prefix, ident)
+        }
+    };
+
+    items
+        .into_iter()
+        .map(|item| match item {
+            syn::Item::Struct(definition) => {
+                let path = path(&definition.ident);
+                let mut tokens = definition.into_token_stream();
+                type_hook(&path, &mut tokens);
+                tokens
+            }
+            syn::Item::Enum(definition) => {
+                let path = path(&definition.ident);
+                let mut tokens = definition.into_token_stream();
+                type_hook(&path, &mut tokens);
+                tokens
+            }
+            syn::Item::Mod(syn::ItemMod {
+                attrs,
+                vis,
+                mod_token,
+                ident,
+                content: Some((_, items)),
+                ..
+            }) => {
+                let items = hook_items(items, &path(&ident), type_hook);
+                // The inner attributes, like `#![allow(dead_code)]`, stay inside the module.
+                let (inner, outer): (Vec<_>, Vec<_>) =
+                    attrs.into_iter().partition(|attr| match attr.style {
+                        syn::AttrStyle::Inner(_) => true,
+                        syn::AttrStyle::Outer => false,
+                    });
+                quote!(#(#outer)* #vis #mod_token #ident { #(#inner)* #items })
+            }
+            item => item.into_token_stream(),
+        })
+        .collect()
+}
@@ -1,21 +1,68 @@
-use crate::deprecation::DeprecationStrategy;
+use crate::codegen_options::{TypenameFallback, UnionTagging};
+use crate::deprecation::{DeprecationStatus, DeprecationStrategy};
+use crate::enums::{WireFormat, WireValue, WireValues, WIRE_CASES};
 use crate::fragments::GqlFragment;
 use crate::schema::Schema;
 use crate::selection::Selection;
+use crate::suggestions::did_you_mean;
+use crate::type_bindings::TypeBinding;
 use failure::*;
 use proc_macro2::Span;
 use proc_macro2::TokenStream;
 use quote::quote;
+use std::cell::RefCell;
 use std::collections::{BTreeMap, BTreeSet};
-use syn::Ident;
+use syn::{Ident, Visibility};
 
 /// This holds all the information we need during the code generation phase.
 pub(crate) struct QueryContext<'query, 'schema: 'query> {
     pub fragments: BTreeMap<&'query str, GqlFragment<'query>>,
     pub schema: &'schema Schema<'schema>,
     pub deprecation_strategy: DeprecationStrategy,
+    /// The deprecated fields exempted from the `deny` strategy, as `Type.field`.
+    allowed_deprecations: BTreeSet<String>,
+    /// The containers for the lists, by the GraphQL type of their items, or `*` for all the others.
+    collection_types: BTreeMap<String, syn::Path>,
+    /// The wire values of the enums the backend does not (de)serialize like the schema, by name.
+    enum_wire_values: BTreeMap<String, WireValues>,
+    /// The visibility of the generated structs and enums.
+    pub item_visibility: Visibility,
+    /// Whether to generate a pretty `Display` implementation for the response types.
+    pub pretty_display: bool,
+    /// Whether to flatten the fields selecting a single field on an object.
+    pub flatten_wrappers: bool,
+    /// Whether to generate enums with `CamelCase` variants and a serde `rename_all` attribute.
+    pub enum_rename_all: bool,
+    /// Whether to (de)serialize the custom scalars through the `GraphQLScalar` trait.
+    pub scalar_trait: bool,
+    /// Whether to generate the `JSON` scalars as `serde_json::Value`.
+    pub json_scalars: bool,
+    /// Whether to keep the fields of the responses that are not selected in an `extra` map.
+    pub extra_fields: bool,
+    /// Whether to generate typestate builders for the input objects.
+    pub typestate_builders: bool,
+    /// Whether to also derive `Serialize` on the response types.
+    pub serialize_responses: bool,
+    /// Whether to generate serde code working with the formats that are not self-describing.
+    pub format_agnostic_serde: bool,
+    /// How the enums of the selections on unions and interfaces are represented for serde.
+    pub union_tagging: UnionTagging,
+    /// What the enums of the selections on unions and interfaces fall back to without `__typename`.
+    pub typename_fallback: Option<TypenameFallback>,
+    /// Whether to implement `Validate` on the variables, the input objects and the enums.
+    pub validate_variables: bool,
     variables_derives: Vec<Ident>,
     response_derives: Vec<Ident>,
+    /// Derives for the response structs, replacing `response_derives` when set.
+    struct_derives: Option<Vec<Ident>>,
+    /// Derives for the enums, replacing `response_derives` when set.
+    enum_derives: Option<Vec<Ident>>,
+    /// The compiler warnings to emit for the generated code.
+    warnings: RefCell<BTreeSet<String>>,
+    /// The compiler warnings about the names numbered to avoid a collision.
+    name_collisions: RefCell<BTreeSet<String>>,
+    /// The response fields bound to the generated types, for the editors.
+    type_bindings: RefCell<BTreeSet<TypeBinding>>,
 }
 
 impl<'query, 'schema> QueryContext<'query, 'schema> {
@@ -28,11 +75,101 @@ impl<'query, 'schema> QueryContext<'query, 'schema> {
             fragments: BTreeMap::new(),
             schema,
             deprecation_strategy,
+            allowed_deprecations: BTreeSet::new(),
+            collection_types: BTreeMap::new(),
+            enum_wire_values: BTreeMap::new(),
+            item_visibility: crate::codegen_options::public_visibility(),
+            pretty_display: false,
+            flatten_wrappers: false,
+            enum_rename_all: false,
+            scalar_trait: false,
+            json_scalars: false,
+            extra_fields: false,
+            typestate_builders: false,
+            serialize_responses: false,
+            format_agnostic_serde: false,
+            union_tagging: UnionTagging::Internal,
+            typename_fallback: None,
+            validate_variables: false,
             variables_derives: vec![Ident::new("Serialize", Span::call_site())],
             response_derives: vec![Ident::new("Deserialize", Span::call_site())],
+            struct_derives: None,
+            enum_derives: None,
+            warnings: RefCell::new(BTreeSet::new()),
+            name_collisions: RefCell::new(BTreeSet::new()),
+            type_bindings: RefCell::new(BTreeSet::new()),
         }
     }
 
+    /// The deprecation strategy for a field of the given type: the strategy of the context,
+    /// unless the field is exempted from `deny`.
+    pub(crate) fn field_deprecation_strategy(
+        &self,
+        type_name: &str,
+        field_name: &str,
+    ) -> &DeprecationStrategy {
+        match self.deprecation_strategy {
+            DeprecationStrategy::Deny
+                if self
+                    .allowed_deprecations
+                    .contains(&format!("{}.{}", type_name, field_name)) =>
+            {
+                &DeprecationStrategy::Allow
+            }
+            _ => &self.deprecation_strategy,
+        }
+    }
+
+    /// Report a deprecated field selected under the `warn` deprecation strategy.
+    pub(crate) fn warn_deprecated_field(
+        &self,
+        type_name: &str,
+        field_name: &str,
+        status: &DeprecationStatus,
+    ) {
+        if let (DeprecationStatus::Deprecated(reason), DeprecationStrategy::Warn) = (
+            status,
+            self.field_deprecation_strategy(type_name, field_name),
+        ) {
+            let reason = reason
+                .as_ref()
+                .map(|reason| format!(": {}", reason))
+                .unwrap_or_default();
+            self.warnings.borrow_mut().insert(format!(
+                "The field `{}.{}` is deprecated{}",
+                type_name, field_name, reason
+            ));
+        }
+    }
+
+    /// The compiler warnings to emit for the generated code.
+    pub(crate) fn warnings(&self) -> Vec<String> {
+        self.warnings.borrow().iter().cloned().collect()
+    }
+
+    /// Report GraphQL names converted to the same Rust name, then numbered to tell them apart.
+    pub(crate) fn warn_name_collision(&self, graphql_names: &[&str], rust_name: &str) {
+        self.name_collisions.borrow_mut().insert(format!(
+            "The names `{}` are all converted to `{}`, so they are numbered in this order",
+            graphql_names.join("`, `"),
+            rust_name
+        ));
+    }
+
+    /// The compiler warnings about the names numbered to avoid a collision.
+    pub(crate) fn name_collision_warnings(&self) -> Vec<String> {
+        self.name_collisions.borrow().iter().cloned().collect()
+    }
+
+    /// Record the generated field and type a field of the query document is bound to.
+    pub(crate) fn bind_type(&self, binding: TypeBinding) {
+        self.type_bindings.borrow_mut().insert(binding);
+    }
+
+    pub(crate) fn type_bindings(&self) -> Vec<TypeBinding> {
+        self.type_bindings.borrow().iter().cloned().collect()
+    }
+
     /// Mark a fragment as required, so code is actually generated for it.
     pub(crate) fn require_fragment(&self, typename_: &str) {
         if let Some(fragment) = self.fragments.get(typename_) {
@@ -47,8 +184,29 @@ impl<'query, 'schema> QueryContext<'query, 'schema> {
             fragments: BTreeMap::new(),
             schema,
             deprecation_strategy: DeprecationStrategy::Allow,
+            allowed_deprecations: BTreeSet::new(),
+            collection_types: BTreeMap::new(),
+            enum_wire_values: BTreeMap::new(),
+            item_visibility: crate::codegen_options::public_visibility(),
+            pretty_display: false,
+            flatten_wrappers: false,
+            enum_rename_all: false,
+            scalar_trait: false,
+            json_scalars: false,
+            extra_fields: false,
+            typestate_builders: false,
+            serialize_responses: false,
+            format_agnostic_serde: false,
+            union_tagging: UnionTagging::Internal,
+            typename_fallback: None,
+            validate_variables: false,
             variables_derives: vec![Ident::new("Serialize", Span::call_site())],
             response_derives: vec![Ident::new("Deserialize", Span::call_site())],
+            struct_derives: None,
+            enum_derives: None,
+            warnings: RefCell::new(BTreeSet::new()),
+            name_collisions: RefCell::new(BTreeSet::new()),
+            type_bindings: RefCell::new(BTreeSet::new()),
         }
     }
 
@@ -88,12 +246,7 @@ impl<'query, 'schema> QueryContext<'query, 'schema> {
             ));
         }
 
-        self.variables_derives.extend(
-            attribute_value
-                .split(',')
-                .map(str::trim)
-                .map(|s| Ident::new(s, Span::call_site())),
-        );
+        self.variables_derives.extend(parse_derives(attribute_value));
         Ok(())
     }
 
@@ -107,15 +260,224 @@ impl<'query, 'schema> QueryContext<'query, 'schema> {
             ));
         }
 
-        self.response_derives.extend(
-            attribute_value
-                .split(',')
-                .map(str::trim)
-                .map(|s| Ident::new(s, Span::call_site())),
-        );
+        self.response_derives.extend(parse_derives(attribute_value));
         Ok(())
     }
 
+    /// Derives for the response structs, taking precedence over the response derives.
+    pub(crate) fn ingest_response_struct_derives(
+        &mut self,
+        attribute_value: &str,
+    ) -> Result<(), failure::Error> {
+        if self.struct_derives.is_some() {
+            return Err(format_err!(
+                "ingest_response_struct_derives should only be called once"
+            ));
+        }
+
+        let mut derives = vec![Ident::new("Deserialize", Span::call_site())];
+        derives.extend(parse_derives(attribute_value));
+        self.struct_derives = Some(derives);
+        Ok(())
+    }
+
+    /// Derives for the enums, taking precedence over the response derives.
+    pub(crate) fn ingest_response_enum_derives(
+        &mut self,
+        attribute_value: &str,
+    ) -> Result<(), failure::Error> {
+        if self.enum_derives.is_some() {
+            return Err(format_err!(
+                "ingest_response_enum_derives should only be called once"
+            ));
+        }
+
+        self.enum_derives = Some(parse_derives(attribute_value).collect());
+        Ok(())
+    }
+
+    /// The deprecated fields exempted from the `deny` strategy, as a comma-separated list of
+    /// `Type.field`.
+    pub(crate) fn ingest_allowed_deprecations(
+        &mut self,
+        attribute_value: &str,
+    ) -> Result<(), failure::Error> {
+        for field in attribute_value.split(',').map(str::trim) {
+            if field.split('.').count() != 2 {
+                return Err(format_err!(
+                    "Invalid allowed deprecation `{}`, expected `Type.field`",
+                    field
+                ));
+            }
+            self.allowed_deprecations.insert(field.to_owned());
+        }
+
+        Ok(())
+    }
+
+    /// The containers for the lists, as a comma-separated list of `Type = path::Container`.
+    pub(crate) fn ingest_collection_types(
+        &mut self,
+        attribute_value: &str,
+    ) -> Result<(), failure::Error> {
+        for entry in attribute_value
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+        {
+            let mut parts = entry.splitn(2, '=').map(str::trim);
+            let (type_name, container) = match (parts.next(), parts.next()) {
+                (Some(type_name), Some(container)) if !type_name.is_empty() => {
+                    (type_name, container)
+                }
+                _ => {
+                    return Err(format_err!(
+                        "Invalid collection type `{}`, expected `Type = path::Container`",
+                        entry
+                    ))
+                }
+            };
+            let container: syn::Path = syn::parse_str(container)
+                .map_err(|err| format_err!("Invalid collection type `{}`: {}", entry, err))?;
+            self.collection_types
+                .insert(type_name.to_owned(), container);
+        }
+
+        Ok(())
+    }
+
+    /// The wire values of the enums, as a comma-separated list of `Enum = format`, where the format
+    /// is `integer` or one of the `rename_all` cases of serde, and of `Enum.VALUE = wire value`.
+    pub(crate) fn ingest_enum_wire_values(
+        &mut self,
+        attribute_value: &str,
+    ) -> Result<(), failure::Error> {
+        let mut formats = Vec::new();
+        let mut overrides = Vec::new();
+
+        for entry in attribute_value
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+        {
+            let mut parts = entry.splitn(2, '=').map(str::trim);
+            match (parts.next(), parts.next()) {
+                (Some(target), Some(wire)) if !target.is_empty() && !wire.is_empty() => {
+                    let mut target = target.splitn(2, '.').map(str::trim);
+                    match (target.next(), target.next()) {
+                        (Some(enum_name), None) => formats.push((enum_name, wire, entry)),
+                        (Some(enum_name), Some(value)) => {
+                            overrides.push((enum_name, value, wire, entry))
+                        }
+                        _ => unreachable!("splitn always yields a first part"),
+                    }
+                }
+                _ => {
+                    return Err(format_err!(
+                        "Invalid enum wire value `{}`, expected `Enum = format` or `Enum.VALUE = wire value`",
+                        entry
+                    ))
+                }
+            };
+        }
+
+        for (enum_name, format, entry) in formats {
+            self.schema_enum(enum_name, entry)?;
+            let format = if format == "integer" {
+                WireFormat::Integer
+            } else {
+                let case = WIRE_CASES
+                    .iter()
+                    .find(|case| **case == format)
+                    .ok_or_else(|| {
+                        format_err!(
+                            "Invalid enum wire format in `{}`, expected `integer` or one of {}.{}",
+                            entry,
+                            WIRE_CASES.join(", "),
+                            did_you_mean(format, WIRE_CASES.iter().cloned())
+                        )
+                    })?;
+                WireFormat::Case(*case)
+            };
+            self.enum_wire_values
+                .entry(enum_name.to_owned())
+                .or_default()
+                .format = format;
+        }
+
+        for (enum_name, value, wire, entry) in overrides {
+            let schema_enum = self.schema_enum(enum_name, entry)?;
+            if !schema_enum
+                .variants
+                .iter()
+                .any(|variant| variant.name == value)
+            {
+                return Err(format_err!(
+                    "Unknown value `{}` of the enum `{}` in `{}`.{}",
+                    value,
+                    enum_name,
+                    entry,
+                    did_you_mean(
+                        value,
+                        schema_enum.variants.iter().map(|variant| variant.name)
+                    )
+                ));
+            }
+
+            let wire_values = self
+                .enum_wire_values
+                .entry(enum_name.to_owned())
+                .or_default();
+            let wire = if wire_values.format == WireFormat::Integer {
+                WireValue::Integer(wire.parse().map_err(|_| {
+                    format_err!(
+                        "Invalid enum wire value `{}`, the values of `{}` are integers",
+                        entry,
+                        enum_name
+                    )
+                })?)
+            } else {
+                WireValue::String(wire.to_owned())
+            };
+            wire_values.overrides.insert(value.to_owned(), wire);
+        }
+
+        Ok(())
+    }
+
+    /// The enum of the schema with the given name, for the configuration entry naming it.
+    fn schema_enum(
+        &self,
+        enum_name: &str,
+        entry: &str,
+    ) -> Result<&'schema crate::enums::GqlEnum<'schema>, failure::Error> {
+        self.schema.enums.get(enum_name).ok_or_else(|| {
+            format_err!(
+                "Unknown enum `{}` in `{}`.{}",
+                enum_name,
+                entry,
+                did_you_mean(enum_name, self.schema.enums.keys().cloned())
+            )
+        })
+    }
+
+    /// The wire values of the given enum, if they are not its values in the schema.
+    pub(crate) fn enum_wire_values(&self, enum_name: &str) -> Option<&WireValues> {
+        self.enum_wire_values.get(enum_name)
+    }
+
+    /// The container for the lists of the given GraphQL type: `Vec`, unless configured otherwise.
+    pub(crate) fn collection_type(&self, item_type: &str) -> TokenStream {
+        match self
+            .collection_types
+            .get(item_type)
+            .or_else(|| self.collection_types.get("*"))
+        {
+            Some(container) => quote!(#container),
+            None => quote!(Vec),
+        }
+    }
+
     pub(crate) fn variables_derives(&self) -> TokenStream {
         let derives: BTreeSet<&Ident> = self.variables_derives.iter().collect();
         let derives = derives.iter();
@@ -126,20 +488,68 @@ impl<'query, 'schema> QueryContext<'query, 'schema> {
     }
 
     pub(crate) fn response_derives(&self) -> TokenStream {
-        let derives: BTreeSet<&Ident> = self.response_derives.iter().collect();
+        let serialize = Ident::new("Serialize", Span::call_site());
+        let mut derives: BTreeSet<&Ident> = self
+            .struct_derives
+            .as_ref()
+            .unwrap_or(&self.response_derives)
+            .iter()
+            .collect();
+        if self.serialize_responses {
+            derives.insert(&serialize);
+        }
         let derives = derives.iter();
         quote! {
             #[derive( #(#derives),* )]
         }
     }
 
+    /// The serde attribute of the enums of the selections on unions and interfaces.
+    pub(crate) fn union_tagging_attribute(&self) -> TokenStream {
+        match &self.union_tagging {
+            UnionTagging::Internal => quote!(#[serde(tag = "__typename")]),
+            UnionTagging::Adjacent { content } => {
+                quote!(#[serde(tag = "__typename", content = #content)])
+            }
+            UnionTagging::Untagged => quote!(#[serde(untagged)]),
+        }
+    }
+
+    /// The response derives without `Serialize` and `Deserialize`, for the types implementing
+    /// them by hand, and whether `Serialize` is derived on the other response types.
+    pub(crate) fn response_derives_without_serde(&self) -> (TokenStream, bool) {
+        let derives = self
+            .struct_derives
+            .as_ref()
+            .unwrap_or(&self.response_derives);
+        let serialize = self.serialize_responses || derives.iter().any(|d| d == "Serialize");
+        let derives: BTreeSet<&Ident> = derives
+            .iter()
+            .filter(|d| *d != "Serialize" && *d != "Deserialize")
+            .collect();
+        let derives = derives.iter();
+
+        (quote!(#[derive( #(#derives),* )]), serialize)
+    }
+
     pub(crate) fn response_enum_derives(&self) -> TokenStream {
-        let always_derives = [
-            Ident::new("Eq", Span::call_site()),
-            Ident::new("PartialEq", Span::call_site()),
-        ];
+        self.enum_derives_with(&["Eq", "PartialEq"])
+    }
+
+    /// The derives for the enums without data-bearing variants, which are also `Copy`.
+    pub(crate) fn fieldless_enum_derives(&self) -> TokenStream {
+        self.enum_derives_with(&["Clone", "Copy", "Eq", "PartialEq"])
+    }
+
+    fn enum_derives_with(&self, always_derives: &[&str]) -> TokenStream {
+        let always_derives: Vec<Ident> = always_derives
+            .iter()
+            .map(|derive| Ident::new(derive, Span::call_site()))
+            .collect();
         let mut enum_derives: BTreeSet<_> = self
-            .response_derives
+            .enum_derives
+            .as_ref()
+            .unwrap_or(&self.response_derives)
             .iter()
             .filter(|derive| {
                 // Do not apply the "Default" derive to enums.
@@ -154,6 +564,15 @@ impl<'query, 'schema> QueryContext<'query, 'schema> {
     }
 }
 
+/// Parses a comma-separated list of derives.
+fn parse_derives(attribute_value: &str) -> impl Iterator<Item = Ident> + '_ {
+    attribute_value
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| Ident::new(s, Span::call_site()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -173,6 +592,64 @@ mod tests {
         );
     }
 
+    #[test]
+    fn collection_types_ingestion_works() {
+        let schema = crate::schema::Schema::new();
+        let mut context = QueryContext::new_empty(&schema);
+
+        context
+            .ingest_collection_types("ID = std::collections::BTreeSet, * = im::Vector")
+            .unwrap();
+
+        assert_eq!(
+            context.collection_type("ID").to_string(),
+            "std :: collections :: BTreeSet"
+        );
+        assert_eq!(context.collection_type("User").to_string(), "im :: Vector");
+        assert!(context.ingest_collection_types("ID").is_err());
+    }
+
+    #[test]
+    fn enum_wire_values_ingestion_validates_the_names() {
+        let mut schema = crate::schema::Schema::new();
+        schema.enums.insert(
+            "Episode",
+            crate::enums::GqlEnum {
+                description: None,
+                name: "Episode",
+                variants: vec![crate::enums::EnumVariant {
+                    description: None,
+                    name: "JEDI",
+                    directives: Vec::new(),
+                }],
+                is_required: false.into(),
+            },
+        );
+        let mut context = QueryContext::new_empty(&schema);
+
+        context
+            .ingest_enum_wire_values("Episode.JEDI = 6, Episode = integer")
+            .unwrap();
+        assert_eq!(
+            context.enum_wire_values("Episode").unwrap().overrides["JEDI"],
+            WireValue::Integer(6)
+        );
+
+        let err = context
+            .ingest_enum_wire_values("Episod = integer")
+            .unwrap_err();
+        assert!(err.to_string().ends_with("Did you mean `Episode`?"));
+        let err = context
+            .ingest_enum_wire_values("Episode = lowercas")
+            .unwrap_err();
+        assert!(err.to_string().ends_with("Did you mean `lowercase`?"));
+        assert!(context.ingest_enum_wire_values("Episode.SITH = 7").is_err());
+        assert!(context
+            .ingest_enum_wire_values("Episode.JEDI = jedi")
+            .is_err());
+        assert!(context.ingest_enum_wire_values("Episode").is_err());
+    }
+
     #[test]
     fn response_enum_derives_does_not_produce_empty_list() {
         let schema = crate::schema::Schema::new();
@@ -198,6 +675,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn response_struct_and_enum_derives_take_precedence() {
+        let schema = crate::schema::Schema::new();
+        let mut context = QueryContext::new_empty(&schema);
+
+        context.ingest_response_derives("Debug, Default").unwrap();
+        context
+            .ingest_response_struct_derives("Debug, PartialEq")
+            .unwrap();
+        context.ingest_response_enum_derives("Hash, Ord, PartialOrd").unwrap();
+
+        assert_eq!(
+            context.response_derives().to_string(),
+            "# [ derive ( Debug , Deserialize , PartialEq ) ]"
+        );
+        assert_eq!(
+            context.response_enum_derives().to_string(),
+            "# [ derive ( Eq , Hash , Ord , PartialEq , PartialOrd ) ]"
+        );
+    }
+
     #[test]
     fn response_derives_fails_when_called_twice() {
         let schema = crate::schema::Schema::new();
@@ -0,0 +1,669 @@
+//! Helpers for the [Relay-style connections](https://relay.dev/graphql/connections.htm) in the
+//! response types: an iterator over the nodes of the edges, and a conversion of the page info to
+//! the standard `graphql_client::PageInfo`.
+
+use crate::accessors::{accessor, PathSegment};
+use crate::deprecation::{DeprecationStatus, DeprecationStrategy};
+use crate::field_type::FieldType;
+use crate::objects::{GqlObject, GqlObjectField};
+use crate::operations::Operation;
+use crate::query::QueryContext;
+use crate::selection::{Selection, SelectionField, SelectionItem};
+use crate::shared::flatten_field;
+use failure::format_err;
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::quote;
+
+/// The impls for the struct generated for a selection on the object, named `prefix`:
+/// `iter_nodes()` when it selects the nodes of the edges of a connection, and a conversion to
+/// `graphql_client::PageInfo` when it selects all the fields of a page info.
+pub(crate) fn relay_impls(
+    context: &QueryContext<'_, '_>,
+    object: &GqlObject<'_>,
+    selection: &Selection<'_>,
+    prefix: &str,
+) -> TokenStream {
+    let name = Ident::new(prefix, Span::call_site());
+    let iter_nodes = iter_nodes(context, object, selection, prefix).map(|method| {
+        quote! {
+            #[allow(deprecated)]
+            impl #name {
+                /// The nodes of the edges of the connection, skipping the null edges and nodes.
+                #method
+            }
+        }
+    });
+    let page_info = page_info_conversion(context, object, selection, &name);
+
+    quote!(#iter_nodes #page_info)
+}
+
+fn iter_nodes(
+    context: &QueryContext<'_, '_>,
+    object: &GqlObject<'_>,
+    selection: &Selection<'_>,
+    prefix: &str,
+) -> Option<TokenStream> {
+    let (path, node_type) = nodes_path(context, object, selection, prefix)?;
+
+    Some(accessor(
+        &context.item_visibility,
+        "iter_nodes",
+        &path,
+        &node_type,
+    ))
+}
+
+/// The path from a connection to its nodes, through its edges, and the type of the nodes.
+fn nodes_path<'schema>(
+    context: &QueryContext<'_, 'schema>,
+    object: &GqlObject<'schema>,
+    selection: &Selection<'_>,
+    prefix: &str,
+) -> Option<([PathSegment<'schema>; 2], TokenStream)> {
+    let (edges, edges_schema_field) = selected_field(context, object, selection, "edges")?;
+    let edges_alias = edges.alias.unwrap_or(edges.name);
+    let edges_prefix = struct_prefix(context, selection, prefix, edges_alias);
+    let edges_flattened = flatten_field(
+        context,
+        &edges_schema_field.type_,
+        &edges.fields,
+        edges_prefix.clone(),
+    );
+    // With `flatten_wrappers`, the edges may have been replaced by the nodes, or by what is
+    // selected on them.
+    if edges_flattened.unwrapped.is_some() {
+        return None;
+    }
+
+    let edge_object = context
+        .schema
+        .objects
+        .get(edges_schema_field.type_.inner_name_str())?;
+    let (node, node_schema_field) = selected_field(context, edge_object, &edges.fields, "node")?;
+    let node_alias = node.alias.unwrap_or(node.name);
+    let node_prefix = struct_prefix(context, &edges.fields, &edges_prefix, node_alias);
+    let node_flattened =
+        flatten_field(context, &node_schema_field.type_, &node.fields, node_prefix);
+    let node_type = FieldType::Named(node_flattened.type_.inner_name_str())
+        .to_rust(context, &node_flattened.prefix);
+
+    let path = [
+        PathSegment {
+            field_name: field_ident(context, selection, edges_alias),
+            field_type: edges_flattened.type_,
+        },
+        PathSegment {
+            field_name: field_ident(context, &edges.fields, node_alias),
+            field_type: node_flattened.type_,
+        },
+    ];
+
+    Some((path, node_type))
+}
+
+fn page_info_conversion(
+    context: &QueryContext<'_, '_>,
+    object: &GqlObject<'_>,
+    selection: &Selection<'_>,
+    name: &Ident,
+) -> Option<TokenStream> {
+    let has_page = |field_name| {
+        let (field, schema_field) = selected_field(context, object, selection, field_name)?;
+        let field_name = field_ident(context, selection, field.alias.unwrap_or(field.name));
+
+        match &schema_field.type_ {
+            FieldType::Named("Boolean") => Some(quote!(page_info.#field_name)),
+            _ => None,
+        }
+    };
+    let cursor = |field_name| {
+        let (field, schema_field) = selected_field(context, object, selection, field_name)?;
+        let field_name = field_ident(context, selection, field.alias.unwrap_or(field.name));
+
+        match &schema_field.type_ {
+            FieldType::Named("String") => Some(quote!(Some(page_info.#field_name.clone()))),
+            FieldType::Optional(inner) if **inner == FieldType::Named("String") => {
+                Some(quote!(page_info.#field_name.clone()))
+            }
+            _ => None,
+        }
+    };
+
+    let has_next_page = has_page("hasNextPage")?;
+    let has_previous_page = has_page("hasPreviousPage")?;
+    let start_cursor = cursor("startCursor")?;
+    let end_cursor = cursor("endCursor")?;
+
+    Some(quote! {
+        #[allow(deprecated)]
+        impl<'a> From<&'a #name> for ::graphql_client::PageInfo {
+            fn from(page_info: &'a #name) -> Self {
+                ::graphql_client::PageInfo {
+                    has_next_page: #has_next_page,
+                    has_previous_page: #has_previous_page,
+                    start_cursor: #start_cursor,
+                    end_cursor: #end_cursor,
+                }
+            }
+        }
+    })
+}
+
+/// The implementation of `graphql_client::Paginated` for the struct under derive: as configured
+/// by the pagination options, or else when the operation has an optional `$after` string variable
+/// and selects a single connection, outside of any list, with its nodes and the four fields of its
+/// page info.
+pub(crate) fn paginated_impl(
+    context: &QueryContext<'_, '_>,
+    operation: &Operation<'_>,
+    struct_ident: &Ident,
+    options: &crate::GraphQLClientCodegenOptions,
+) -> Result<Option<TokenStream>, failure::Error> {
+    match options.pagination_cursor() {
+        Some(cursor) => {
+            configured_paginated_impl(context, operation, struct_ident, cursor, options).map(Some)
+        }
+        None => Ok(relay_paginated_impl(context, operation, struct_ident)),
+    }
+}
+
+fn relay_paginated_impl(
+    context: &QueryContext<'_, '_>,
+    operation: &Operation<'_>,
+    struct_ident: &Ident,
+) -> Option<TokenStream> {
+    let has_cursor_variable = operation.variables.iter().any(|variable| {
+        variable.name == "after"
+            && variable.ty == FieldType::Optional(Box::new(FieldType::Named("String")))
+    });
+    if !has_cursor_variable {
+        return None;
+    }
+
+    let root = context
+        .schema
+        .objects
+        .get(operation.root_name(&context.schema))?;
+    let mut connections = Vec::new();
+    find_connections(
+        context,
+        root,
+        &operation.selection,
+        &operation.name,
+        &mut Vec::new(),
+        &mut connections,
+    );
+    if connections.len() != 1 {
+        return None;
+    }
+    let Connection {
+        path,
+        nodes_path,
+        node_type,
+        page_info,
+    } = connections.pop()?;
+
+    let connection = values_at(quote!(data), &path, false);
+    let nodes = values_at(quote!(connection), &nodes_path, false);
+    let page_info_name = &page_info.field_name;
+    let page_info = match page_info.field_type {
+        FieldType::Optional(_) => quote! {
+            connection.#page_info_name.as_ref().map(::graphql_client::PageInfo::from)?
+        },
+        _ => quote!(::graphql_client::PageInfo::from(&connection.#page_info_name)),
+    };
+
+    Some(quote! {
+        #[allow(deprecated)]
+        impl ::graphql_client::Paginated for super::#struct_ident {
+            type Node = #node_type;
+
+            fn into_page(
+                data: ResponseData,
+            ) -> ::core::option::Option<(::graphql_client::__alloc::Vec<Self::Node>, ::graphql_client::PageInfo)> {
+                let connection = #connection.next()?;
+                let page_info = #page_info;
+                let nodes = #nodes.collect();
+
+                Some((nodes, page_info))
+            }
+        }
+    })
+}
+
+/// The implementation of `graphql_client::Paginated` configured by the pagination options.
+fn configured_paginated_impl(
+    context: &QueryContext<'_, '_>,
+    operation: &Operation<'_>,
+    struct_ident: &Ident,
+    cursor: &str,
+    options: &crate::GraphQLClientCodegenOptions,
+) -> Result<TokenStream, failure::Error> {
+    let variable = operation
+        .variables
+        .iter()
+        .find(|variable| variable.name == cursor)
+        .ok_or_else(|| {
+            format_err!(
+                "The pagination cursor `${}` is not a variable of the `{}` operation.",
+                cursor,
+                operation.name
+            )
+        })?;
+    let is_offset = variable.ty.inner_name_str() == "Int";
+
+    let root = &context.schema.objects[operation.root_name(&context.schema)];
+    let nodes_path = options
+        .pagination_nodes()
+        .ok_or_else(|| format_err!("`pagination_cursor` needs `pagination_nodes`."))?;
+    let nodes = response_path(
+        context,
+        root,
+        &operation.selection,
+        &operation.name,
+        nodes_path,
+    )?;
+    let node_type =
+        FieldType::Named(nodes.field_type.inner_name_str()).to_rust(context, &nodes.prefix);
+    let nodes = values_at(quote!(data), &nodes.segments, false);
+
+    let (cursor, page_info) = if is_offset {
+        (
+            quote!(::graphql_client::PaginationCursor::Offset(#cursor)),
+            quote!(::graphql_client::PageInfo::default()),
+        )
+    } else {
+        let page_info_path = options.pagination_page_info().ok_or_else(|| {
+            format_err!(
+                "`pagination_cursor` needs `pagination_page_info`, unless it is an `Int` offset."
+            )
+        })?;
+        let page_info = response_path(
+            context,
+            root,
+            &operation.selection,
+            &operation.name,
+            page_info_path,
+        )?;
+        let conversion = end_cursor_conversion(context, &page_info).ok_or_else(|| {
+            format_err!(
+                "The page info at `{}` has to select `hasNextPage: Boolean` and `endCursor: String`.",
+                page_info_path
+            )
+        })?;
+        let page_info = values_at(quote!(&data), &page_info.segments, true);
+
+        (
+            quote!(::graphql_client::PaginationCursor::EndCursor(#cursor)),
+            quote! {{
+                let page_info = #page_info.next()?;
+                #conversion
+            }},
+        )
+    };
+
+    Ok(quote! {
+        #[allow(deprecated)]
+        impl ::graphql_client::Paginated for super::#struct_ident {
+            type Node = #node_type;
+
+            const CURSOR: ::graphql_client::PaginationCursor = #cursor;
+
+            fn into_page(
+                data: ResponseData,
+            ) -> ::core::option::Option<(::graphql_client::__alloc::Vec<Self::Node>, ::graphql_client::PageInfo)> {
+                let page_info = #page_info;
+                let nodes = #nodes.collect();
+
+                Some((nodes, page_info))
+            }
+        }
+    })
+}
+
+/// A field reached from the response data through a dotted path of response names.
+pub(crate) struct ResponsePath<'schema, 'a, 'query> {
+    pub(crate) segments: Vec<PathSegment<'schema>>,
+    pub(crate) field_type: FieldType<'schema>,
+    /// The prefix of the type of the field.
+    pub(crate) prefix: String,
+    /// The selection on the field.
+    pub(crate) selection: &'a Selection<'query>,
+}
+
+/// The field at the end of a dotted path of response names, like the pagination paths. None of the
+/// fields on the way can be lists or flattened.
+pub(crate) fn response_path<'schema, 'a, 'query>(
+    context: &QueryContext<'_, 'schema>,
+    root: &GqlObject<'schema>,
+    selection: &'a Selection<'query>,
+    prefix: &str,
+    path: &str,
+) -> Result<ResponsePath<'schema, 'a, 'query>, failure::Error> {
+    let mut object = root;
+    let mut selection = selection;
+    let mut prefix = prefix.to_string();
+    let mut segments = Vec::new();
+    let mut names = path.split('.').peekable();
+
+    while let Some(name) = names.next() {
+        let field = selection
+            .into_iter()
+            .filter_map(|item| match item {
+                SelectionItem::Field(field) if field.alias.unwrap_or(field.name) == name => {
+                    Some(field)
+                }
+                _ => None,
+            })
+            .next()
+            .ok_or_else(|| {
+                format_err!(
+                    "No field `{}` is selected on `{}`, in the path `{}`.",
+                    name,
+                    object.name,
+                    path
+                )
+            })?;
+        let schema_field = object
+            .fields
+            .iter()
+            .find(|schema_field| schema_field.name == field.name)
+            .ok_or_else(|| format_err!("Unknown field `{}` on `{}`.", field.name, object.name))?;
+
+        let field_prefix = struct_prefix(context, selection, &prefix, name);
+        let flattened = flatten_field(
+            context,
+            &schema_field.type_,
+            &field.fields,
+            field_prefix.clone(),
+        );
+        if flattened.unwrapped.is_some() {
+            return Err(format_err!(
+                "The field `{}` of the path `{}` is flattened.",
+                name,
+                path
+            ));
+        }
+
+        segments.push(PathSegment {
+            field_name: field_ident(context, selection, name),
+            field_type: flattened.type_.clone(),
+        });
+
+        if names.peek().is_none() {
+            return Ok(ResponsePath {
+                segments,
+                field_type: flattened.type_,
+                prefix: flattened.prefix,
+                selection: &field.fields,
+            });
+        }
+
+        if is_list(&flattened.type_) {
+            return Err(format_err!(
+                "The field `{}` of the path `{}` is a list.",
+                name,
+                path
+            ));
+        }
+        object = context
+            .schema
+            .objects
+            .get(schema_field.type_.inner_name_str())
+            .ok_or_else(|| {
+                format_err!(
+                    "The field `{}` of the path `{}` is not an object.",
+                    name,
+                    path
+                )
+            })?;
+        selection = &field.fields;
+        prefix = field_prefix;
+    }
+
+    Err(format_err!("The path is empty."))
+}
+
+/// The conversion from a `page_info` selecting `hasNextPage` and `endCursor` to a
+/// `graphql_client::PageInfo`.
+fn end_cursor_conversion(
+    context: &QueryContext<'_, '_>,
+    page_info: &ResponsePath<'_, '_, '_>,
+) -> Option<TokenStream> {
+    if is_list(&page_info.field_type) {
+        return None;
+    }
+    let object = context
+        .schema
+        .objects
+        .get(page_info.field_type.inner_name_str())?;
+
+    let (field, schema_field) =
+        selected_field(context, object, page_info.selection, "hasNextPage")?;
+    let field_name = field_ident(
+        context,
+        page_info.selection,
+        field.alias.unwrap_or(field.name),
+    );
+    let has_next_page = match &schema_field.type_ {
+        FieldType::Named("Boolean") => quote!(page_info.#field_name),
+        FieldType::Optional(inner) if **inner == FieldType::Named("Boolean") => {
+            quote!(page_info.#field_name.unwrap_or(false))
+        }
+        _ => return None,
+    };
+
+    let (field, schema_field) = selected_field(context, object, page_info.selection, "endCursor")?;
+    let field_name = field_ident(
+        context,
+        page_info.selection,
+        field.alias.unwrap_or(field.name),
+    );
+    let end_cursor = match &schema_field.type_ {
+        FieldType::Named("String") | FieldType::Named("ID") => {
+            quote!(Some(page_info.#field_name.clone()))
+        }
+        FieldType::Optional(inner)
+            if **inner == FieldType::Named("String") || **inner == FieldType::Named("ID") =>
+        {
+            quote!(page_info.#field_name.clone())
+        }
+        _ => return None,
+    };
+
+    Some(quote! {
+        ::graphql_client::PageInfo {
+            has_next_page: #has_next_page,
+            end_cursor: #end_cursor,
+            ..::graphql_client::PageInfo::default()
+        }
+    })
+}
+
+/// A connection selected in an operation.
+struct Connection<'schema> {
+    /// The path from the response data to the connection.
+    path: Vec<PathSegment<'schema>>,
+    /// The path from the connection to its nodes.
+    nodes_path: [PathSegment<'schema>; 2],
+    node_type: TokenStream,
+    /// The page info field of the connection.
+    page_info: PathSegment<'schema>,
+}
+
+/// Collects the connections in the selection, with the nodes and the four fields of the page
+/// info selected, that are not inside a list.
+fn find_connections<'schema>(
+    context: &QueryContext<'_, 'schema>,
+    object: &GqlObject<'schema>,
+    selection: &Selection<'_>,
+    prefix: &str,
+    path: &mut Vec<PathSegment<'schema>>,
+    connections: &mut Vec<Connection<'schema>>,
+) {
+    for item in selection {
+        let field = match item {
+            SelectionItem::Field(field) => field,
+            _ => continue,
+        };
+        let (field, schema_field) = match selected_field(context, object, selection, field.name) {
+            Some(selected) if std::ptr::eq(selected.0, field) => selected,
+            _ => continue,
+        };
+        let child = match context
+            .schema
+            .objects
+            .get(schema_field.type_.inner_name_str())
+        {
+            Some(child) => child,
+            None => continue,
+        };
+        let alias = field.alias.unwrap_or(field.name);
+        let field_prefix = struct_prefix(context, selection, prefix, alias);
+        let flattened = flatten_field(
+            context,
+            &schema_field.type_,
+            &field.fields,
+            field_prefix.clone(),
+        );
+        if flattened.unwrapped.is_some() || is_list(&flattened.type_) {
+            continue;
+        }
+
+        path.push(PathSegment {
+            field_name: field_ident(context, selection, alias),
+            field_type: flattened.type_,
+        });
+
+        match (
+            nodes_path(context, child, &field.fields, &field_prefix),
+            page_info_field(context, child, &field.fields, &field_prefix),
+        ) {
+            (Some((nodes_path, node_type)), Some(page_info)) => connections.push(Connection {
+                path: path.iter().map(PathSegment::clone).collect(),
+                nodes_path,
+                node_type,
+                page_info,
+            }),
+            _ => find_connections(
+                context,
+                child,
+                &field.fields,
+                &field_prefix,
+                path,
+                connections,
+            ),
+        }
+
+        path.pop();
+    }
+}
+
+/// The page info field of the connection, if it selects the four fields of the standard page info.
+fn page_info_field<'schema>(
+    context: &QueryContext<'_, 'schema>,
+    connection: &GqlObject<'schema>,
+    selection: &Selection<'_>,
+    prefix: &str,
+) -> Option<PathSegment<'schema>> {
+    let (field, schema_field) = selected_field(context, connection, selection, "pageInfo")?;
+    let page_info = context
+        .schema
+        .objects
+        .get(schema_field.type_.inner_name_str())?;
+    let alias = field.alias.unwrap_or(field.name);
+    let name = Ident::new(
+        &struct_prefix(context, selection, prefix, alias),
+        Span::call_site(),
+    );
+    page_info_conversion(context, page_info, &field.fields, &name)?;
+
+    if is_list(&schema_field.type_) {
+        return None;
+    }
+
+    Some(PathSegment {
+        field_name: field_ident(context, selection, alias),
+        field_type: schema_field.type_.clone(),
+    })
+}
+
+fn is_list(field_type: &FieldType<'_>) -> bool {
+    match field_type {
+        FieldType::Named(_) => false,
+        FieldType::Optional(inner) => is_list(inner),
+        FieldType::Vector(_) => true,
+    }
+}
+
+/// An iterator over the values at the end of the path from `start`, through the `Option` and
+/// `Vec` layers of its fields, consuming `start` or borrowing from it.
+fn values_at(start: TokenStream, path: &[PathSegment<'_>], by_ref: bool) -> TokenStream {
+    path.iter()
+        .fold(quote!(::core::iter::once(#start)), |expr, segment| {
+            let field = field_values(&segment.field_type, &segment.field_name, by_ref);
+            quote!(#expr.flat_map(|value| #field))
+        })
+}
+
+/// An iterator over the values inside the `Option` and `Vec` layers of the field of `value`.
+fn field_values(field_type: &FieldType<'_>, field_name: &Ident, by_ref: bool) -> TokenStream {
+    let mut expr = if by_ref {
+        quote!(::core::iter::once(&value.#field_name))
+    } else {
+        quote!(::core::iter::once(value.#field_name))
+    };
+    let mut field_type = field_type;
+
+    while let FieldType::Optional(inner) | FieldType::Vector(inner) = field_type {
+        expr = quote!(#expr.flatten());
+        field_type = inner;
+    }
+
+    expr
+}
+
+/// The first selection of the field with the given name, unless it is not generated because of
+/// its deprecation.
+fn selected_field<'a, 'query, 'o, 'schema>(
+    context: &QueryContext<'_, '_>,
+    object: &'o GqlObject<'schema>,
+    selection: &'a Selection<'query>,
+    field_name: &str,
+) -> Option<(&'a SelectionField<'query>, &'o GqlObjectField<'schema>)> {
+    let field = selection
+        .into_iter()
+        .filter_map(|item| match item {
+            SelectionItem::Field(field) if field.name == field_name => Some(field),
+            _ => None,
+        })
+        .next()?;
+    let schema_field = object.fields.iter().find(|f| f.name == field_name)?;
+
+    match (
+        &schema_field.deprecation,
+        context.field_deprecation_strategy(object.name, field_name),
+    ) {
+        (DeprecationStatus::Deprecated(_), DeprecationStrategy::Deny) => None,
+        _ => Some((field, schema_field)),
+    }
+}
+
+/// The Rust field of the selected field `alias` in the struct of the `selection`.
+fn field_ident(context: &QueryContext<'_, '_>, selection: &Selection<'_>, alias: &str) -> Ident {
+    Ident::new(
+        &crate::shared::field_name(context, alias, selection.response_names()),
+        Span::call_site(),
+    )
+}
+
+/// The name of the struct for the selected field `alias`, in the struct named after `prefix`.
+fn struct_prefix(
+    context: &QueryContext<'_, '_>,
+    selection: &Selection<'_>,
+    prefix: &str,
+    alias: &str,
+) -> String {
+    crate::shared::field_prefix(context, prefix, alias, selection.response_names())
+}
@@ -1,10 +1,15 @@
 use quote::quote;
 use std::cell::Cell;
 
+/// The names of the scalars generated as `serde_json::Value` with the `json_scalars` option.
+pub(crate) const JSON_SCALARS: &[&str] = &["JSON", "JSONObject", "Json"];
+
 #[derive(Debug, Clone, PartialEq, PartialOrd, Ord, Eq)]
 pub struct Scalar<'schema> {
     pub name: &'schema str,
     pub description: Option<&'schema str>,
+    /// The URL of the specification of the scalar, from `@specifiedBy` or `specifiedByURL`.
+    pub specified_by_url: Option<&'schema str>,
     pub is_required: Cell<bool>,
 }
 
@@ -13,10 +18,32 @@ impl<'schema> Scalar<'schema> {
     pub fn to_rust(&self) -> proc_macro2::TokenStream {
         use proc_macro2::{Ident, Span};
         let ident = Ident::new(&self.name, Span::call_site());
-        let description = match &self.description {
-            Some(d) => quote!(#[doc = #d]),
-            None => quote!(),
-        };
+        let description = self.docs();
         quote!(#description type #ident = super::#ident;)
     }
+
+    /// The alias of the scalar to `serde_json::Value`, for the `json_scalars` option.
+    pub fn to_json_value(&self) -> proc_macro2::TokenStream {
+        use proc_macro2::{Ident, Span};
+        let ident = Ident::new(&self.name, Span::call_site());
+        let description = self.docs();
+        quote!(#description type #ident = ::graphql_client::serde_json::Value;)
+    }
+
+    /// The documentation of the scalar: its description, then the URL of its specification.
+    fn docs(&self) -> proc_macro2::TokenStream {
+        let description = self.description.map(|d| quote!(#[doc = #d]));
+        let specified_by = self.specified_by_url.map(|url| {
+            let separator = self.description.map(|_| quote!(#[doc = ""]));
+            let line = format!(" Specified by <{}>.", url);
+            quote!(#separator #[doc = #line])
+        });
+
+        quote!(#description #specified_by)
+    }
+
+    /// Whether the scalar is generated as `serde_json::Value` in the context.
+    pub(crate) fn is_json(&self, context: &crate::query::QueryContext<'_, '_>) -> bool {
+        context.json_scalars && JSON_SCALARS.contains(&self.name)
+    }
 }
@@ -1,4 +1,3 @@
-use crate::deprecation::DeprecationStatus;
 use crate::enums::{EnumVariant, GqlEnum};
 use crate::field_type::FieldType;
 use crate::inputs::GqlInput;
@@ -10,6 +9,21 @@ use failure::*;
 use graphql_parser::{self, schema};
 use std::collections::{BTreeMap, BTreeSet};
 
+pub use crate::model::{
+    EnumType, EnumValue, FieldDefinition, InputObjectType, InterfaceType, ObjectType, ScalarType,
+    SchemaModel, TypeRef, UnionType,
+};
+
+/// A directive applied in a schema, like `@key(fields: "id")`. Introspection does not return the
+/// applied directives, so they are only known for schemas in the GraphQL format.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AppliedDirective {
+    /// The name of the directive, without the `@`.
+    pub name: String,
+    /// The arguments of the directive, with their values printed in the GraphQL syntax.
+    pub arguments: Vec<(String, String)>,
+}
+
 pub(crate) const DEFAULT_SCALARS: &[&str] = &["ID", "String", "Int", "Float", "Boolean"];
 
 /// Intermediate representation for a parsed GraphQL schema used during code generation.
@@ -24,6 +38,8 @@ pub(crate) struct Schema<'schema> {
     pub(crate) query_type: Option<&'schema str>,
     pub(crate) mutation_type: Option<&'schema str>,
     pub(crate) subscription_type: Option<&'schema str>,
+    /// The directives applied to the types, by type name.
+    pub(crate) type_directives: BTreeMap<&'schema str, Vec<AppliedDirective>>,
 }
 
 impl<'schema> Schema<'schema> {
@@ -38,6 +54,7 @@ impl<'schema> Schema<'schema> {
             query_type: None,
             mutation_type: None,
             subscription_type: None,
+            type_directives: BTreeMap::new(),
         }
     }
 
@@ -114,6 +131,15 @@ impl<'schema> ::std::convert::From<&'schema graphql_parser::schema::Document> fo
         let mut interface_implementations: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
 
         for definition in &ast.definitions {
+            if let schema::Definition::TypeDefinition(ty_definition) = definition {
+                let (name, directives) = type_name_and_directives(ty_definition);
+                if !directives.is_empty() {
+                    schema
+                        .type_directives
+                        .insert(name, applied_directives(directives));
+                }
+            }
+
             match definition {
                 schema::Definition::TypeDefinition(ty_definition) => match ty_definition {
                     schema::TypeDefinition::Object(obj) => {
@@ -141,6 +167,7 @@ impl<'schema> ::std::convert::From<&'schema graphql_parser::schema::Document> fo
                                     .map(|v| EnumVariant {
                                         description: v.description.as_ref().map(String::as_str),
                                         name: &v.name,
+                                        directives: applied_directives(&v.directives),
                                     })
                                     .collect(),
                                 is_required: false.into(),
@@ -153,6 +180,7 @@ impl<'schema> ::std::convert::From<&'schema graphql_parser::schema::Document> fo
                             Scalar {
                                 name: &scalar.name,
                                 description: scalar.description.as_ref().map(String::as_str),
+                                specified_by_url: specified_by_url(&scalar.directives),
                                 is_required: false.into(),
                             },
                         );
@@ -181,7 +209,8 @@ impl<'schema> ::std::convert::From<&'schema graphql_parser::schema::Document> fo
                                 description: f.description.as_ref().map(String::as_str),
                                 name: f.name.as_str(),
                                 type_: FieldType::from(&f.field_type),
-                                deprecation: DeprecationStatus::Current,
+                                deprecation: crate::objects::parse_deprecation_info(f),
+                                directives: applied_directives(&f.directives),
                             }));
                         schema.interfaces.insert(&interface.name, iface);
                     }
@@ -268,6 +297,7 @@ impl<'schema> ::std::convert::From<&'schema crate::introspection_response::Intro
                                     .as_ref()
                                     .map(String::as_str)
                                     .expect("enum variant name"),
+                                directives: Vec::new(),
                             })
                         })
                         .filter_map(|t| t)
@@ -287,6 +317,7 @@ impl<'schema> ::std::convert::From<&'schema crate::introspection_response::Intro
                             Scalar {
                                 name,
                                 description: ty.description.as_ref().map(String::as_str),
+                                specified_by_url: ty.specified_by_url.as_ref().map(String::as_str),
                                 is_required: false.into(),
                             },
                         );
@@ -351,7 +382,8 @@ impl<'schema> ::std::convert::From<&'schema crate::introspection_response::Intro
                                 description: f.description.as_ref().map(String::as_str),
                                 name: f.name.as_ref().expect("field name").as_str(),
                                 type_: FieldType::from(f.type_.as_ref().expect("field type")),
-                                deprecation: DeprecationStatus::Current,
+                                deprecation: crate::objects::introspected_deprecation_info(f),
+                                directives: Vec::new(),
                             }),
                     );
                     schema.interfaces.insert(name, iface);
@@ -371,6 +403,81 @@ impl<'schema> ::std::convert::From<&'schema crate::introspection_response::Intro
     }
 }
 
+/// The directives applied to a field, a type or an enum value. `@deprecated` is left out, as it is
+/// already reflected in the deprecation status, like in introspection responses.
+pub(crate) fn applied_directives(directives: &[schema::Directive]) -> Vec<AppliedDirective> {
+    directives
+        .iter()
+        .filter(|directive| directive.name != "deprecated")
+        .map(|directive| AppliedDirective {
+            name: directive.name.clone(),
+            arguments: directive
+                .arguments
+                .iter()
+                .map(|(name, value)| (name.clone(), print_value(value)))
+                .collect(),
+        })
+        .collect()
+}
+
+fn type_name_and_directives(definition: &schema::TypeDefinition) -> (&str, &[schema::Directive]) {
+    match definition {
+        schema::TypeDefinition::Scalar(ty) => (&ty.name, &ty.directives),
+        schema::TypeDefinition::Object(ty) => (&ty.name, &ty.directives),
+        schema::TypeDefinition::Interface(ty) => (&ty.name, &ty.directives),
+        schema::TypeDefinition::Union(ty) => (&ty.name, &ty.directives),
+        schema::TypeDefinition::Enum(ty) => (&ty.name, &ty.directives),
+        schema::TypeDefinition::InputObject(ty) => (&ty.name, &ty.directives),
+    }
+}
+
+/// A value in the GraphQL syntax.
+pub(crate) fn print_value(value: &schema::Value) -> String {
+    match value {
+        schema::Value::Variable(name) => format!("${}", name),
+        schema::Value::Int(number) => number
+            .as_i64()
+            .map(|number| number.to_string())
+            .unwrap_or_default(),
+        schema::Value::Float(number) => number.to_string(),
+        schema::Value::String(string) => {
+            serde_json::to_string(string).expect("strings serialize to JSON")
+        }
+        schema::Value::Boolean(boolean) => boolean.to_string(),
+        schema::Value::Null => "null".to_string(),
+        schema::Value::Enum(name) => name.clone(),
+        schema::Value::List(values) => format!(
+            "[{}]",
+            values
+                .iter()
+                .map(print_value)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        schema::Value::Object(fields) => format!(
+            "{{{}}}",
+            fields
+                .iter()
+                .map(|(name, value)| format!("{}: {}", name, print_value(value)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
+
+/// The `url` argument of the `@specifiedBy` directive, if any.
+fn specified_by_url(directives: &[schema::Directive]) -> Option<&str> {
+    directives
+        .iter()
+        .filter(|directive| directive.name == "specifiedBy")
+        .flat_map(|directive| directive.arguments.iter())
+        .find(|(name, _)| name == "url")
+        .and_then(|(_, value)| match value {
+            schema::Value::String(url) => Some(url.as_str()),
+            _ => None,
+        })
+}
+
 pub(crate) enum ParsedSchema {
     GraphQLParser(graphql_parser::schema::Document),
     Json(crate::introspection_response::IntrospectionResponse),
@@ -389,6 +496,7 @@ impl<'schema> From<&'schema ParsedSchema> for Schema<'schema> {
 mod tests {
     use super::*;
     use crate::constants::*;
+    use crate::deprecation::DeprecationStatus;
 
     #[test]
     fn build_schema_works() {
@@ -406,18 +514,21 @@ mod tests {
                         name: TYPENAME_FIELD,
                         type_: FieldType::Named(string_type()),
                         deprecation: DeprecationStatus::Current,
+                        directives: Vec::new(),
                     },
                     GqlObjectField {
                         description: None,
                         name: "id",
                         type_: FieldType::Named("ID"),
                         deprecation: DeprecationStatus::Current,
+                        directives: Vec::new(),
                     },
                     GqlObjectField {
                         description: None,
                         name: "name",
                         type_: FieldType::Named("String"),
                         deprecation: DeprecationStatus::Current,
+                        directives: Vec::new(),
                     },
                     GqlObjectField {
                         description: None,
@@ -426,12 +537,14 @@ mod tests {
                             FieldType::Optional(Box::new(FieldType::Named("Character"))),
                         )))),
                         deprecation: DeprecationStatus::Current,
+                        directives: Vec::new(),
                     },
                     GqlObjectField {
                         description: None,
                         name: "friendsConnection",
                         type_: FieldType::Named("FriendsConnection"),
                         deprecation: DeprecationStatus::Current,
+                        directives: Vec::new(),
                     },
                     GqlObjectField {
                         description: None,
@@ -440,16 +553,96 @@ mod tests {
                             FieldType::Named("Episode"),
                         )))),
                         deprecation: DeprecationStatus::Current,
+                        directives: Vec::new(),
                     },
                     GqlObjectField {
                         description: None,
                         name: "primaryFunction",
                         type_: FieldType::Optional(Box::new(FieldType::Named("String"))),
                         deprecation: DeprecationStatus::Current,
+                        directives: Vec::new(),
                     },
                 ],
                 is_required: false.into(),
             })
         )
     }
+
+    #[test]
+    fn scalars_have_their_specification_url() {
+        let gql_schema = graphql_parser::parse_schema(
+            r#"scalar UUID @specifiedBy(url: "https://tools.ietf.org/html/rfc4122")"#,
+        )
+        .unwrap();
+        let built = Schema::from(&gql_schema);
+        assert_eq!(
+            built.scalars["UUID"].specified_by_url,
+            Some("https://tools.ietf.org/html/rfc4122")
+        );
+
+        let json_schema: crate::introspection_response::IntrospectionResponse =
+            serde_json::from_str(
+                r#"{
+                    "__schema": {
+                        "description": "The schema",
+                        "queryType": { "name": "Query" },
+                        "types": [{
+                            "kind": "SCALAR",
+                            "name": "UUID",
+                            "specifiedByURL": "https://tools.ietf.org/html/rfc4122"
+                        }],
+                        "directives": [{
+                            "name": "tag",
+                            "locations": ["FIELD_DEFINITION", "VARIABLE_DEFINITION"],
+                            "args": [],
+                            "isRepeatable": true
+                        }]
+                    }
+                }"#,
+            )
+            .unwrap();
+        let introspected = json_schema.as_schema().schema.as_ref().unwrap();
+        assert_eq!(introspected.description.as_ref().unwrap(), "The schema");
+        let directive = introspected.directives.as_ref().unwrap()[0]
+            .as_ref()
+            .unwrap();
+        assert_eq!(directive.is_repeatable, Some(true));
+
+        let built = Schema::from(&json_schema);
+        assert_eq!(
+            built.scalars["UUID"].specified_by_url,
+            Some("https://tools.ietf.org/html/rfc4122")
+        );
+    }
+
+    #[test]
+    fn applied_directives_are_kept() {
+        let gql_schema = graphql_parser::parse_schema(
+            r#"
+            enum Role @internal {
+                ADMIN @scope(names: ["admin", "root"], options: { strict: true, level: 2 })
+                USER
+            }
+            "#,
+        )
+        .unwrap();
+        let built = Schema::from(&gql_schema);
+
+        assert_eq!(built.type_directives["Role"][0].name, "internal");
+        let admin = &built.enums["Role"].variants[0];
+        assert_eq!(
+            admin.directives,
+            vec![AppliedDirective {
+                name: "scope".to_string(),
+                arguments: vec![
+                    ("names".to_string(), r#"["admin", "root"]"#.to_string()),
+                    (
+                        "options".to_string(),
+                        "{level: 2, strict: true}".to_string()
+                    ),
+                ],
+            }]
+        );
+        assert!(built.enums["Role"].variants[1].directives.is_empty());
+    }
 }
@@ -1,5 +1,4 @@
 use crate::constants::*;
-use failure::*;
 use graphql_parser::query::SelectionSet;
 use std::collections::BTreeMap;
 
@@ -8,6 +7,14 @@ use std::collections::BTreeMap;
 pub struct SelectionField<'query> {
     pub alias: Option<&'query str>,
     pub name: &'query str,
+    /// The function deserializing the field, from the `@rust(deserialize_with: ...)` directive.
+    pub deserialize_with: Option<&'query str>,
+    /// Whether the field is resolved locally, from the `@client` directive.
+    pub client: bool,
+    /// The arguments passed to the field, in the GraphQL syntax, like `first: 10, after: $cursor`.
+    pub arguments: Option<String>,
+    /// The position of the field in the query document.
+    pub position: graphql_parser::Pos,
     pub fields: Selection<'query>,
 }
 
@@ -15,6 +22,8 @@ pub struct SelectionField<'query> {
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct SelectionFragmentSpread<'query> {
     pub fragment_name: &'query str,
+    /// The position of the spread in the query document.
+    pub position: graphql_parser::Pos,
 }
 
 /// An inline fragment as part of a selection (e.g. `...on MyThing { name }`).
@@ -60,9 +69,9 @@ impl<'query> Selection<'query> {
         (&self)
             .into_iter()
             .filter_map(|f| match f {
-                SelectionItem::FragmentSpread(SelectionFragmentSpread { fragment_name }) => {
-                    Some(fragment_name)
-                }
+                SelectionItem::FragmentSpread(SelectionFragmentSpread {
+                    fragment_name, ..
+                }) => Some(fragment_name),
                 _ => None,
             })
             .filter_map(|fragment_name| {
@@ -94,11 +103,11 @@ impl<'query> Selection<'query> {
                             Selection(items)
                         });
                 }
-                SelectionItem::FragmentSpread(SelectionFragmentSpread { fragment_name }) => {
+                SelectionItem::FragmentSpread(spread) => {
                     let fragment = context
                         .fragments
-                        .get(fragment_name)
-                        .ok_or_else(|| format_err!("Unknown fragment: {}", &fragment_name))?;
+                        .get(spread.fragment_name)
+                        .ok_or_else(|| crate::shared::unknown_fragment_error(spread, context))?;
 
                     // The fragment can either be on the union/interface itself, or on one of its variants (type-refining fragment).
                     if fragment.on.name() == selection_on {
@@ -167,6 +176,21 @@ impl<'query> Selection<'query> {
     pub(crate) fn len(&self) -> usize {
         self.0.len()
     }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The names of the selected fields in the response, their alias or their name.
+    pub(crate) fn response_names(&self) -> Vec<&'query str> {
+        self.0
+            .iter()
+            .filter_map(|item| match item {
+                SelectionItem::Field(field) => Some(field.alias.unwrap_or(field.name)),
+                _ => None,
+            })
+            .collect()
+    }
 }
 
 impl<'query> ::std::convert::From<&'query SelectionSet> for Selection<'query> {
@@ -180,11 +204,16 @@ impl<'query> ::std::convert::From<&'query SelectionSet> for Selection<'query> {
                 Selection::Field(f) => SelectionItem::Field(SelectionField {
                     alias: f.alias.as_ref().map(String::as_str),
                     name: &f.name,
+                    deserialize_with: crate::client_directives::deserialize_with(&f.directives),
+                    client: crate::client_directives::is_client_field(&f.directives),
+                    arguments: print_arguments(&f.arguments),
+                    position: f.position,
                     fields: (&f.selection_set).into(),
                 }),
                 Selection::FragmentSpread(spread) => {
                     SelectionItem::FragmentSpread(SelectionFragmentSpread {
                         fragment_name: &spread.fragment_name,
+                        position: spread.position,
                     })
                 }
                 Selection::InlineFragment(inline) => {
@@ -205,6 +234,19 @@ impl<'query> ::std::convert::From<&'query SelectionSet> for Selection<'query> {
     }
 }
 
+/// The arguments of a field in the GraphQL syntax, if there are any.
+fn print_arguments(arguments: &[(String, graphql_parser::query::Value)]) -> Option<String> {
+    if arguments.is_empty() {
+        return None;
+    }
+
+    let arguments: Vec<String> = arguments
+        .iter()
+        .map(|(name, value)| format!("{}: {}", name, crate::schema::print_value(value)))
+        .collect();
+    Some(arguments.join(", "))
+}
+
 impl<'a, 'query> std::iter::IntoIterator for &'a Selection<'query> {
     type Item = &'a SelectionItem<'query>;
     type IntoIter = std::slice::Iter<'a, SelectionItem<'query>>;
@@ -224,6 +266,7 @@ impl<'a> std::iter::FromIterator<SelectionItem<'a>> for Selection<'a> {
 mod tests {
     use super::*;
     use graphql_parser;
+    use graphql_parser::Pos;
 
     #[test]
     fn selection_extract_typename_simple_case() {
@@ -241,6 +284,7 @@ mod tests {
             .0
             .push(SelectionItem::FragmentSpread(SelectionFragmentSpread {
                 fragment_name: "MyFragment",
+                position: Pos::default(),
             }));
 
         let mut fragment_selection = Selection::new_empty();
@@ -249,6 +293,10 @@ mod tests {
             .push(SelectionItem::Field(SelectionField {
                 alias: None,
                 name: "__typename",
+                deserialize_with: None,
+                client: false,
+                arguments: None,
+                position: Pos::default(),
                 fields: Selection::new_empty(),
             }));
 
@@ -309,23 +357,55 @@ mod tests {
             Selection(vec![SelectionItem::Field(SelectionField {
                 alias: None,
                 name: "animal",
+                deserialize_with: None,
+                client: false,
+                arguments: None,
+                position: Pos {
+                    line: 3,
+                    column: 11
+                },
                 fields: Selection(vec![
                     SelectionItem::Field(SelectionField {
                         alias: None,
                         name: "isCat",
+                        deserialize_with: None,
+                        client: false,
+                        arguments: None,
+                        position: Pos {
+                            line: 4,
+                            column: 13
+                        },
                         fields: Selection(Vec::new()),
                     }),
                     SelectionItem::Field(SelectionField {
                         alias: None,
                         name: "isHorse",
+                        deserialize_with: None,
+                        client: false,
+                        arguments: None,
+                        position: Pos {
+                            line: 5,
+                            column: 13
+                        },
                         fields: Selection(Vec::new()),
                     }),
                     SelectionItem::FragmentSpread(SelectionFragmentSpread {
                         fragment_name: "Timestamps",
+                        position: Pos {
+                            line: 6,
+                            column: 16
+                        },
                     }),
                     SelectionItem::Field(SelectionField {
                         alias: None,
                         name: "barks",
+                        deserialize_with: None,
+                        client: false,
+                        arguments: None,
+                        position: Pos {
+                            line: 7,
+                            column: 13
+                        },
                         fields: Selection(Vec::new()),
                     }),
                     SelectionItem::InlineFragment(SelectionInlineFragment {
@@ -333,17 +413,38 @@ mod tests {
                         fields: Selection(vec![SelectionItem::Field(SelectionField {
                             alias: None,
                             name: "rating",
+                            deserialize_with: None,
+                            client: false,
+                            arguments: None,
+                            position: Pos {
+                                line: 9,
+                                column: 17
+                            },
                             fields: Selection(Vec::new()),
                         })]),
                     }),
                     SelectionItem::Field(SelectionField {
                         alias: None,
                         name: "pawsCount",
+                        deserialize_with: None,
+                        client: false,
+                        arguments: None,
+                        position: Pos {
+                            line: 11,
+                            column: 13
+                        },
                         fields: Selection(Vec::new()),
                     }),
                     SelectionItem::Field(SelectionField {
                         alias: Some("aliased"),
                         name: "sillyName",
+                        deserialize_with: None,
+                        client: false,
+                        arguments: None,
+                        position: Pos {
+                            line: 12,
+                            column: 13
+                        },
                         fields: Selection(Vec::new()),
                     }),
                 ]),
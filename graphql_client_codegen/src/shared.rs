@@ -1,14 +1,21 @@
+use crate::constants::TYPENAME_FIELD;
 use crate::deprecation::{DeprecationStatus, DeprecationStrategy};
+use crate::error::PositionedError;
+use crate::field_type::FieldType;
 use crate::objects::GqlObjectField;
 use crate::query::QueryContext;
 use crate::selection::*;
+use crate::suggestions::did_you_mean;
+use crate::type_bindings::TypeBinding;
 use failure::*;
 use heck::{CamelCase, SnakeCase};
 use proc_macro2::{Ident, Span, TokenStream};
 use quote::quote;
 
 // List of keywords based on https://doc.rust-lang.org/grammar.html#keywords
+// The lone underscore is not a keyword, but it cannot be an identifier either.
 const RUST_KEYWORDS: &'static [&'static str] = &[
+    "_",
     "abstract",
     "alignof",
     "as",
@@ -23,17 +30,14 @@ const RUST_KEYWORDS: &'static [&'static str] = &[
     "do",
     "else",
     "enum",
-    "extern crate",
     "extern",
+    "extern crate",
     "false",
     "final",
     "fn",
     "for",
-    "for",
-    "if let",
     "if",
-    "if",
-    "impl",
+    "if let",
     "impl",
     "in",
     "let",
@@ -63,7 +67,6 @@ const RUST_KEYWORDS: &'static [&'static str] = &[
     "unsafe",
     "unsized",
     "use",
-    "use",
     "virtual",
     "where",
     "while",
@@ -77,6 +80,88 @@ pub(crate) fn keyword_replace(needle: &str) -> String {
     }
 }
 
+/// The introspection fields, which lose their leading underscores in Rust, like `__typename`
+/// becoming the `typename` field.
+const INTROSPECTION_FIELDS: &[&str] = &[TYPENAME_FIELD, "__schema", "__type"];
+
+/// The snake case name of a field or variable, safe to use as a Rust identifier. The leading
+/// underscores dropped by `to_snake_case` are kept, so `_id` does not become `id`, and `_` or
+/// `_1st` still give valid identifiers, except for the introspection fields.
+pub(crate) fn snake_case_name(name: &str) -> String {
+    let rest = name.trim_start_matches('_');
+    let underscores = if INTROSPECTION_FIELDS.contains(&name) {
+        ""
+    } else {
+        &name[..name.len() - rest.len()]
+    };
+
+    keyword_replace(&format!("{}{}", underscores, rest.to_snake_case()))
+}
+
+/// The Rust name of the field `name` of a struct, in snake case. The `siblings` are the GraphQL
+/// names of all the fields of the struct, to number the ones converted to the same name, like
+/// `userInput` and `UserInput` becoming `user_input` and `user_input_2`.
+pub(crate) fn field_name<'a>(
+    context: &QueryContext<'_, '_>,
+    name: &str,
+    siblings: impl IntoIterator<Item = &'a str>,
+) -> String {
+    disambiguate(context, name, siblings, "_", snake_case_name)
+}
+
+/// The name of the struct for the sub-selection of the field `alias`, in the struct named after
+/// `prefix`. The `siblings` are the names of the fields selected next to it, as in [field_name].
+pub(crate) fn field_prefix<'a>(
+    context: &QueryContext<'_, '_>,
+    prefix: &str,
+    alias: &str,
+    siblings: impl IntoIterator<Item = &'a str>,
+) -> String {
+    let field = disambiguate(context, alias, siblings, "", |name| name.to_camel_case());
+    format!("{}{}", prefix.to_camel_case(), field)
+}
+
+/// Converts `name` with `convert`, numbering the names among `siblings` converted to the same
+/// name in the order of their GraphQL names. The first one keeps its name.
+fn disambiguate<'a>(
+    context: &QueryContext<'_, '_>,
+    name: &str,
+    siblings: impl IntoIterator<Item = &'a str>,
+    separator: &str,
+    convert: impl Fn(&str) -> String,
+) -> String {
+    let converted = convert(name);
+    let siblings: std::collections::BTreeSet<&str> = siblings.into_iter().collect();
+    let colliding: Vec<&str> = siblings
+        .iter()
+        .cloned()
+        .filter(|sibling| convert(*sibling) == converted)
+        .collect();
+    if colliding.len() < 2 || !colliding.contains(&name) {
+        return converted;
+    }
+
+    context.warn_name_collision(&colliding, &converted);
+    // The numbers giving the converted name of another sibling are skipped.
+    let taken: std::collections::BTreeSet<String> =
+        siblings.iter().map(|sibling| convert(*sibling)).collect();
+    let mut number = 1;
+    for sibling in colliding.iter().skip(1) {
+        let numbered = loop {
+            number += 1;
+            let numbered = format!("{}{}{}", converted, separator, number);
+            if !taken.contains(&numbered) {
+                break numbered;
+            }
+        };
+        if *sibling == name {
+            return numbered;
+        }
+    }
+
+    converted
+}
+
 mod tests {
     #[test]
     fn keyword_replace() {
@@ -85,44 +170,87 @@ mod tests {
         assert_eq!("in_", keyword_replace("in"));
         assert_eq!("fn_", keyword_replace("fn"));
         assert_eq!("struct_", keyword_replace("struct"));
+        assert_eq!("__", keyword_replace("_"));
+        assert_eq!("extern_", keyword_replace("extern"));
+        assert_eq!("if_", keyword_replace("if"));
+    }
+
+    #[test]
+    fn keywords_are_sorted() {
+        use super::RUST_KEYWORDS;
+        assert!(RUST_KEYWORDS.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn snake_case_name_keeps_leading_underscores() {
+        use super::snake_case_name;
+        assert_eq!("_id", snake_case_name("_id"));
+        assert_eq!("__internal_state", snake_case_name("__internalState"));
+        assert_eq!("_1st", snake_case_name("_1st"));
+        assert_eq!("__", snake_case_name("_"));
+        assert_eq!("typename", snake_case_name("__typename"));
+        assert_eq!("type_", snake_case_name("__type"));
+        assert_eq!("user_input", snake_case_name("UserInput"));
+    }
+
+    #[test]
+    fn field_names_only_differing_by_case_are_numbered() {
+        use super::{field_name, field_prefix};
+        use crate::query::QueryContext;
+        use crate::schema::Schema;
+
+        let schema = Schema::new();
+        let context = QueryContext::new_empty(&schema);
+        let siblings = ["userInput", "UserInput", "user_input_2", "type"];
+
+        assert_eq!(
+            field_name(&context, "UserInput", siblings.iter().cloned()),
+            "user_input"
+        );
+        assert_eq!(
+            field_name(&context, "userInput", siblings.iter().cloned()),
+            "user_input_3"
+        );
+        assert_eq!(
+            field_name(&context, "type", siblings.iter().cloned()),
+            "type_"
+        );
+        assert_eq!(
+            field_prefix(&context, "Query", "userInput", siblings.iter().cloned()),
+            "QueryUserInput3"
+        );
+        assert_eq!(
+            context.name_collision_warnings(),
+            vec![
+                "The names `UserInput`, `userInput` are all converted to `UserInput`, so they are numbered in this order".to_owned(),
+                "The names `UserInput`, `userInput` are all converted to `user_input`, so they are numbered in this order".to_owned(),
+            ]
+        );
     }
 }
 
 pub(crate) fn render_object_field(
     field_name: &str,
+    rust_safe_field_name: &str,
     field_type: &TokenStream,
-    field_is_optional: bool,
+    skip_serializing_none: bool,
     description: Option<&str>,
     status: &DeprecationStatus,
     strategy: &DeprecationStrategy,
 ) -> TokenStream {
-    #[allow(unused_assignments)]
-    let mut deprecation = quote!();
-    match (status, strategy) {
-        // If the field is deprecated and we are denying usage, don't generate the
-        // field in rust at all and short-circuit.
-        (DeprecationStatus::Deprecated(_), DeprecationStrategy::Deny) => return quote!(),
-        // Everything is allowed so there is nothing to do.
-        (_, DeprecationStrategy::Allow) => deprecation = quote!(),
-        // Current so there is nothing to do.
-        (DeprecationStatus::Current, _) => deprecation = quote!(),
-        // A reason was provided, translate it to a note.
-        (DeprecationStatus::Deprecated(Some(reason)), DeprecationStrategy::Warn) => {
-            deprecation = quote!(#[deprecated(note = #reason)])
-        }
-        // No reason provided, just mark as deprecated.
-        (DeprecationStatus::Deprecated(None), DeprecationStrategy::Warn) => {
-            deprecation = quote!(#[deprecated])
-        }
+    // If the field is deprecated and we are denying usage, don't generate the
+    // field in rust at all and short-circuit.
+    let deprecation = match deprecation_annotation(status, strategy) {
+        Some(deprecation) => deprecation,
+        None => return quote!(),
     };
 
     let description = description.map(|s| quote!(#[doc = #s]));
 
-    let rust_safe_field_name = keyword_replace(&field_name.to_snake_case());
-    let name_ident = Ident::new(&rust_safe_field_name, Span::call_site());
+    let name_ident = Ident::new(rust_safe_field_name, Span::call_site());
     let mut rename = crate::shared::field_rename_annotation(&field_name, &rust_safe_field_name);
 
-    if field_is_optional {
+    if skip_serializing_none {
         rename = quote!(
             #[serde(skip_serializing_if = "Option::is_none")]
             #rename
@@ -131,12 +259,121 @@ pub(crate) fn render_object_field(
     quote!(#description #deprecation #rename pub #name_ident: #field_type)
 }
 
+/// The GraphQL signature of a selected field, with the arguments it was selected with, like
+/// `` `issues(first: 10): [Issue!]!` ``. It is part of the docs of the generated field.
+fn field_signature(name: &str, arguments: Option<&str>, field_type: &FieldType<'_>) -> String {
+    let arguments = arguments
+        .map(|arguments| format!("({})", arguments))
+        .unwrap_or_default();
+    format!(
+        "`{}{}: {}`",
+        name,
+        arguments,
+        crate::conversions::graphql_type(field_type)
+    )
+}
+
+/// Items producing a compiler warning for each message. On stable Rust, using a deprecated item is
+/// the only way to emit a warning from generated code; with the `nightly_diagnostics` feature,
+/// the warnings are emitted as proper diagnostics when running in a procedural macro.
+pub(crate) fn warning_items(kind: &str, messages: &[String]) -> TokenStream {
+    if messages.is_empty() {
+        return quote!();
+    }
+
+    #[cfg(feature = "nightly_diagnostics")]
+    {
+        if proc_macro::is_available() {
+            for message in messages {
+                proc_macro::Diagnostic::new(proc_macro::Level::Warning, message.as_str()).emit();
+            }
+            return quote!();
+        }
+    }
+
+    let markers: Vec<Ident> = (0..messages.len())
+        .map(|i| Ident::new(&format!("__{}Warning{}", kind, i), Span::call_site()))
+        .collect();
+    let function = Ident::new(
+        &format!("__{}_warnings", kind.to_snake_case()),
+        Span::call_site(),
+    );
+
+    quote!(
+        #(
+            #[deprecated(note = #messages)]
+            struct #markers;
+        )*
+
+        fn #function() {
+            #(let _ = #markers;)*
+        }
+    )
+}
+
+/// The annotation of a field with the given deprecation status under the strategy, or `None` when
+/// the field is denied and should not be generated.
+pub(crate) fn deprecation_annotation(
+    status: &DeprecationStatus,
+    strategy: &DeprecationStrategy,
+) -> Option<TokenStream> {
+    match (status, strategy) {
+        (DeprecationStatus::Deprecated(_), DeprecationStrategy::Deny) => None,
+        // Everything is allowed, but keep the reason in the documentation.
+        (DeprecationStatus::Deprecated(Some(reason)), DeprecationStrategy::Allow) => {
+            let note = format!(" Deprecated: {}", reason);
+            Some(quote!(#[doc = #note]))
+        }
+        // Everything is allowed so there is nothing to do.
+        (_, DeprecationStrategy::Allow) => Some(quote!()),
+        // Current so there is nothing to do.
+        (DeprecationStatus::Current, _) => Some(quote!()),
+        // A reason was provided, translate it to a note.
+        (DeprecationStatus::Deprecated(Some(reason)), DeprecationStrategy::Warn) => {
+            Some(quote!(#[deprecated(note = #reason)]))
+        }
+        // No reason provided, just mark as deprecated.
+        (DeprecationStatus::Deprecated(None), DeprecationStrategy::Warn) => {
+            Some(quote!(#[deprecated]))
+        }
+    }
+}
+
+fn unknown_field_error(
+    field: &SelectionField<'_>,
+    fields: &[GqlObjectField<'_>],
+) -> failure::Error {
+    PositionedError::new(
+        field.position,
+        format_args!(
+            "Could not find field `{}`.{}",
+            field.name,
+            did_you_mean(field.name, fields.iter().map(|field| field.name))
+        ),
+    )
+}
+
+pub(crate) fn unknown_fragment_error(
+    spread: &SelectionFragmentSpread<'_>,
+    context: &QueryContext<'_, '_>,
+) -> failure::Error {
+    PositionedError::new(
+        spread.position,
+        format_args!(
+            "Unknown fragment: {}.{}",
+            spread.fragment_name,
+            did_you_mean(spread.fragment_name, context.fragments.keys().cloned())
+        ),
+    )
+}
+
 pub(crate) fn field_impls_for_selection(
     fields: &[GqlObjectField<'_>],
     context: &QueryContext<'_, '_>,
     selection: &Selection<'_>,
     prefix: &str,
 ) -> Result<Vec<TokenStream>, failure::Error> {
+    let siblings = selection.response_names();
     (&selection)
         .into_iter()
         .map(|selected| {
@@ -144,14 +381,24 @@ pub(crate) fn field_impls_for_selection(
                 let name = &selected.name;
                 let alias = selected.alias.as_ref().unwrap_or(name);
 
-                let ty = fields
-                    .iter()
-                    .find(|f| &f.name == name)
-                    .ok_or_else(|| format_err!("could not find field `{}`", name))?
-                    .type_
-                    .inner_name_str();
-                let prefix = format!("{}{}", prefix.to_camel_case(), alias.to_camel_case());
-                context.maybe_expand_field(&ty, &selected.fields, &prefix)
+                let field_type = &selected_field_type(
+                    selected,
+                    &fields
+                        .iter()
+                        .find(|f| &f.name == name)
+                        .ok_or_else(|| unknown_field_error(selected, fields))?
+                        .type_,
+                );
+                let prefix = field_prefix(context, prefix, alias, siblings.iter().cloned());
+                let expanded = context.maybe_expand_field(
+                    field_type.inner_name_str(),
+                    &selected.fields,
+                    &prefix,
+                )?;
+                let flattened =
+                    flatten_field(context, field_type, &selected.fields, prefix.clone());
+                let unwrap = unwrap_fn(context, field_type, &flattened, &prefix);
+                Ok(quote!(#expanded #unwrap))
             } else {
                 Ok(quote!())
             }
@@ -166,6 +413,7 @@ pub(crate) fn response_fields_for_selection(
     selection: &Selection<'_>,
     prefix: &str,
 ) -> Result<Vec<TokenStream>, failure::Error> {
+    let siblings = selection.response_names();
     (&selection)
         .into_iter()
         .map(|item| match item {
@@ -177,46 +425,123 @@ pub(crate) fn response_fields_for_selection(
                     .iter()
                     .find(|field| &field.name == name)
                     .ok_or_else(|| {
-                        format_err!(
-                            "Could not find field `{}` on `{}`. Available fields: `{}`.",
-                            *name,
-                            type_name,
-                            schema_fields
-                                .iter()
-                                .map(|ref field| &field.name)
-                                .fold(String::new(), |mut acc, item| {
-                                    acc.push_str(item);
-                                    acc.push_str(", ");
-                                    acc
-                                })
-                                .trim_end_matches(", ")
+                        PositionedError::new(
+                            f.position,
+                            format_args!(
+                                "Could not find field `{}` on `{}`.{} Available fields: `{}`.",
+                                *name,
+                                type_name,
+                                did_you_mean(name, schema_fields.iter().map(|field| field.name)),
+                                schema_fields
+                                    .iter()
+                                    .map(|ref field| &field.name)
+                                    .fold(String::new(), |mut acc, item| {
+                                        acc.push_str(item);
+                                        acc.push_str(", ");
+                                        acc
+                                    })
+                                    .trim_end_matches(", ")
+                            ),
                         )
                     })?;
-                let ty = schema_field.type_.to_rust(
+                context.warn_deprecated_field(type_name, name, &schema_field.deprecation);
+
+                let field_prefix = field_prefix(context, prefix, alias, siblings.iter().cloned());
+                let rust_name = field_name(context, alias, siblings.iter().cloned());
+                let flattened = flatten_field(
                     context,
-                    &format!("{}{}", prefix.to_camel_case(), alias.to_camel_case()),
+                    &selected_field_type(f, &schema_field.type_),
+                    &f.fields,
+                    field_prefix.clone(),
+                );
+                let ty = flattened.type_.to_rust(context, &flattened.prefix);
+
+                let signature = field_signature(
+                    name,
+                    f.arguments.as_ref().map(String::as_str),
+                    &schema_field.type_,
                 );
+                let docs = match schema_field.description {
+                    Some(description) => format!("{}\n\n{}", description, signature),
+                    None => signature,
+                };
 
-                Ok(render_object_field(
+                // Serialized responses keep their null fields.
+                let field = render_object_field(
                     alias,
+                    &rust_name,
                     &ty,
-                    match &schema_field.type_ {
-                        crate::field_type::FieldType::Optional(_) => true,
-                        _ => false,
-                    },
-                    schema_field.description.as_ref().cloned(),
+                    flattened.type_.is_optional()
+                        && !context.serialize_responses
+                        && !context.format_agnostic_serde,
+                    Some(&docs),
                     &schema_field.deprecation,
-                    &context.deprecation_strategy,
+                    context.field_deprecation_strategy(type_name, name),
+                );
+
+                if !field.is_empty() {
+                    context.bind_type(TypeBinding {
+                        line: f.position.line,
+                        column: f.position.column,
+                        response_name: (*alias).to_owned(),
+                        parent_path: prefix.to_owned(),
+                        rust_field: rust_name.clone(),
+                        rust_type: ty.to_string().replace(' ', ""),
+                        selection_path: if flattened.selection.is_empty() {
+                            None
+                        } else {
+                            Some(flattened.prefix.clone())
+                        },
+                    });
+                }
+
+                if field.is_empty() {
+                    Ok(field)
+                } else if let Some(deserialize_with) = f.deserialize_with {
+                    if flattened.unwrapped.is_some() {
+                        return Err(format_err!(
+                            "The field `{}` on `{}` is flattened, so it cannot have a custom deserializer.",
+                            alias,
+                            type_name
+                        ));
+                    }
+
+                    // Like the scalar trait, the function is not called for missing fields.
+                    let default = if flattened.type_.is_optional() {
+                        quote!(#[serde(default)])
+                    } else {
+                        quote!()
+                    };
+                    Ok(quote!(#[serde(deserialize_with = #deserialize_with)] #default #field))
+                } else if flattened.unwrapped.is_some() {
+                    let unwrap_fn_name = unwrap_fn_name(&field_prefix);
+                    // The fields resolved locally are missing from the responses of the server.
+                    let default = if f.client {
+                        quote!(#[serde(default)])
+                    } else {
+                        quote!()
+                    };
+                    Ok(quote!(#[serde(deserialize_with = #unwrap_fn_name)] #default #field))
+                } else {
+                    let scalar_trait = scalar_trait_annotation(&flattened.type_, context);
+                    Ok(quote!(#scalar_trait #field))
+                }
+            }
+            SelectionItem::FragmentSpread(fragment) if context.format_agnostic_serde => {
+                Err(format_err!(
+                    "The fragment `{}` spread on `{}` would be flattened, which the format_agnostic_serde option does not support.",
+                    fragment.fragment_name,
+                    type_name
                 ))
             }
             SelectionItem::FragmentSpread(fragment) => {
                 let field_name =
-                    Ident::new(&fragment.fragment_name.to_snake_case(), Span::call_site());
+                    Ident::new(&snake_case_name(fragment.fragment_name), Span::call_site());
                 context.require_fragment(&fragment.fragment_name);
                 let fragment_from_context = context
                     .fragments
                     .get(&fragment.fragment_name)
-                    .ok_or_else(|| format_err!("Unknown fragment: {}", &fragment.fragment_name))?;
+                    .ok_or_else(|| unknown_fragment_error(fragment, context))?;
                 let type_name = Ident::new(&fragment.fragment_name, Span::call_site());
                 let type_name = if fragment_from_context.is_recursive() {
                     quote!(Box<#type_name>)
@@ -241,6 +566,21 @@ pub(crate) fn response_fields_for_selection(
         .collect()
 }
 
+/// With the `extra_fields` option, the field of a response struct keeping the fields of the
+/// response that are not selected, otherwise an empty TokenStream. It has to come last, after the
+/// flattened fragments.
+pub(crate) fn extra_field(context: &QueryContext<'_, '_>) -> TokenStream {
+    if !context.extra_fields {
+        return quote!();
+    }
+
+    quote! {
+        /// The fields of the response that are not selected.
+        #[serde(flatten)]
+        pub extra: ::graphql_client::__alloc::BTreeMap<String, ::graphql_client::serde_json::Value>
+    }
+}
+
 /// Given the GraphQL schema name for an object/interface/input object field and
 /// the equivalent rust name, produces a serde annotation to map them during
 /// (de)serialization if it is necessary, otherwise an empty TokenStream.
@@ -251,3 +591,372 @@ pub(crate) fn field_rename_annotation(graphql_name: &str, rust_name: &str) -> To
         quote!()
     }
 }
+
+/// With the `scalar_trait` option, produces the serde annotation (de)serializing a field of a
+/// custom scalar type through the `GraphQLScalar` trait, otherwise an empty TokenStream.
+pub(crate) fn scalar_trait_annotation(
+    field_type: &FieldType<'_>,
+    context: &QueryContext<'_, '_>,
+) -> TokenStream {
+    // The JSON scalars generated as `serde_json::Value` do not implement the trait.
+    let is_custom_scalar = context
+        .schema
+        .scalars
+        .get(field_type.inner_name_str())
+        .map(|scalar| !scalar.is_json(context))
+        .unwrap_or(false);
+
+    if !context.scalar_trait || !is_custom_scalar {
+        return quote!();
+    }
+
+    // Unlike the `Deserialize` implementation of `Option`, the `with` functions are not called
+    // for missing fields.
+    let default = if field_type.is_optional() {
+        quote!(#[serde(default)])
+    } else {
+        quote!()
+    };
+
+    quote!(#[serde(with = "::graphql_client::__scalar")] #default)
+}
+
+/// The type of the selected field in the response. The fields resolved locally, with the
+/// `@client` directive, are optional: they are missing from the responses of the server.
+pub(crate) fn selected_field_type<'t>(
+    field: &SelectionField<'_>,
+    field_type: &FieldType<'t>,
+) -> FieldType<'t> {
+    if field.client && !field_type.is_optional() {
+        FieldType::Optional(Box::new(field_type.clone()))
+    } else {
+        field_type.clone()
+    }
+}
+
+/// A selected field as it is generated in a response struct. With the `flatten_wrappers` option,
+/// a field selecting exactly one field on an object is replaced by that inner field.
+pub(crate) struct FlattenedField<'t, 'a, 'query> {
+    /// The type of the field, with the types of the flattened wrappers folded in.
+    pub type_: FieldType<'t>,
+    /// The selection on the innermost field.
+    pub selection: &'a Selection<'query>,
+    /// The prefix for the type of the innermost field.
+    pub prefix: String,
+    /// The response name and type of the field unwrapped from the object, if it was flattened.
+    pub unwrapped: Option<(&'a str, FieldType<'t>)>,
+}
+
+/// Flattens the selected field of the given type and prefix, if it is a single-field wrapper.
+pub(crate) fn flatten_field<'t, 'schema: 't, 'a, 'query>(
+    context: &QueryContext<'_, 'schema>,
+    field_type: &FieldType<'t>,
+    selection: &'a Selection<'query>,
+    prefix: String,
+) -> FlattenedField<'t, 'a, 'query> {
+    let object = if context.flatten_wrappers {
+        context.schema.objects.get(field_type.inner_name_str())
+    } else {
+        None
+    };
+    let mut items = selection.into_iter();
+    let inner = match (object, items.next(), items.next()) {
+        (Some(object), Some(SelectionItem::Field(inner)), None) => object
+            .fields
+            .iter()
+            .find(|field| field.name == inner.name)
+            .filter(|field| {
+                match (
+                    &field.deprecation,
+                    context.field_deprecation_strategy(object.name, field.name),
+                ) {
+                    // Denied fields are not generated at all, so there is nothing to unwrap.
+                    (DeprecationStatus::Deprecated(_), DeprecationStrategy::Deny) => false,
+                    _ => true,
+                }
+            })
+            .map(|schema_field| (schema_field, inner)),
+        _ => None,
+    };
+
+    match inner {
+        Some((schema_field, inner)) => {
+            let alias = inner.alias.unwrap_or(inner.name);
+            let inner_prefix = format!("{}{}", prefix, alias.to_camel_case());
+            let flattened = flatten_field(
+                context,
+                &selected_field_type(inner, &schema_field.type_),
+                &inner.fields,
+                inner_prefix,
+            );
+            FlattenedField {
+                type_: wrap_field_type(field_type, flattened.type_.clone()),
+                selection: flattened.selection,
+                prefix: flattened.prefix,
+                unwrapped: Some((alias, flattened.type_)),
+            }
+        }
+        _ => FlattenedField {
+            type_: field_type.clone(),
+            selection,
+            prefix,
+            unwrapped: None,
+        },
+    }
+}
+
+/// Replaces the named type inside the `Option` and `Vec` layers of `outer` with `inner`.
+fn wrap_field_type<'a>(outer: &FieldType<'a>, inner: FieldType<'a>) -> FieldType<'a> {
+    match outer {
+        FieldType::Named(_) => inner,
+        FieldType::Optional(outer) => match wrap_field_type(outer, inner) {
+            // A null wrapper and a null inner field both end up as `None`.
+            wrapped @ FieldType::Optional(_) => wrapped,
+            wrapped => FieldType::Optional(Box::new(wrapped)),
+        },
+        FieldType::Vector(outer) => FieldType::Vector(Box::new(wrap_field_type(outer, inner))),
+    }
+}
+
+/// The name of the function deserializing a flattened field through its wrapper.
+fn unwrap_fn_name(prefix: &str) -> String {
+    format!("deserialize_{}", prefix.to_snake_case())
+}
+
+/// The function deserializing a flattened field, with the given prefix, through its wrapper
+/// object and taking the unwrapped field out of it.
+fn unwrap_fn(
+    context: &QueryContext<'_, '_>,
+    field_type: &FieldType<'_>,
+    flattened: &FlattenedField<'_, '_, '_>,
+    prefix: &str,
+) -> TokenStream {
+    let (unwrapped, unwrapped_type) = match &flattened.unwrapped {
+        Some((name, unwrapped_type)) => (
+            Ident::new(&snake_case_name(name), Span::call_site()),
+            unwrapped_type,
+        ),
+        None => return quote!(),
+    };
+    let fn_name = Ident::new(&unwrap_fn_name(prefix), Span::call_site());
+    let wrapper_type = field_type.to_rust(context, prefix);
+    let flattened_type = flattened.type_.to_rust(context, &flattened.prefix);
+    let unwrap = unwrap_expr(
+        context,
+        field_type,
+        unwrapped_type,
+        quote!(wrapper),
+        &unwrapped,
+    );
+
+    quote! {
+        fn #fn_name<'de, D>(deserializer: D) -> ::core::result::Result<#flattened_type, D::Error>
+        where
+            D: ::serde::Deserializer<'de>,
+        {
+            let wrapper = <#wrapper_type as ::serde::Deserialize>::deserialize(deserializer)?;
+            Ok(#unwrap)
+        }
+    }
+}
+
+/// Takes the `unwrapped` field, of type `unwrapped_type`, out of the wrapper objects inside the
+/// `Option` and `Vec` layers of `expr`.
+fn unwrap_expr(
+    context: &QueryContext<'_, '_>,
+    field_type: &FieldType<'_>,
+    unwrapped_type: &FieldType<'_>,
+    expr: TokenStream,
+    unwrapped: &Ident,
+) -> TokenStream {
+    match field_type {
+        FieldType::Named(_) => quote!(#expr.#unwrapped),
+        FieldType::Optional(inner) => {
+            let collapsed = wrap_field_type(inner, unwrapped_type.clone()).is_optional();
+            let inner = unwrap_expr(context, inner, unwrapped_type, quote!(value), unwrapped);
+            if collapsed {
+                quote!(#expr.and_then(|value| #inner))
+            } else {
+                quote!(#expr.map(|value| #inner))
+            }
+        }
+        FieldType::Vector(inner) => {
+            let inner = unwrap_expr(context, inner, unwrapped_type, quote!(value), unwrapped);
+            let container = context.collection_type(unwrapped_type.inner_name_str());
+            quote!(#expr.into_iter().map(|value| #inner).collect::<#container<_>>())
+        }
+    }
+}
+
+/// Produces the statements writing the selected fields in the pretty `Display` implementation
+/// of a generated response struct. Each field is written on its own line, prefixed with its name
+/// in the response and indented according to the `indent` variable in scope.
+pub(crate) fn pretty_fields_for_selection(
+    type_name: &str,
+    schema_fields: &[GqlObjectField<'_>],
+    context: &QueryContext<'_, '_>,
+    selection: &Selection<'_>,
+) -> Result<Vec<TokenStream>, failure::Error> {
+    let mut statements = Vec::new();
+    let siblings = selection.response_names();
+
+    for item in selection {
+        match item {
+            SelectionItem::Field(f) => {
+                let name = &f.name;
+                let alias = f.alias.as_ref().unwrap_or(name);
+                let schema_field = schema_fields
+                    .iter()
+                    .find(|field| &field.name == name)
+                    .ok_or_else(|| unknown_field_error(f, schema_fields))?;
+
+                // Denied fields are not generated at all.
+                if let (DeprecationStatus::Deprecated(_), DeprecationStrategy::Deny) = (
+                    &schema_field.deprecation,
+                    context.field_deprecation_strategy(type_name, name),
+                ) {
+                    continue;
+                }
+
+                let rust_name = Ident::new(
+                    &field_name(context, alias, siblings.iter().cloned()),
+                    Span::call_site(),
+                );
+                // Only the type matters here, not the names of the generated structs.
+                let flattened = flatten_field(
+                    context,
+                    &selected_field_type(f, &schema_field.type_),
+                    &f.fields,
+                    String::new(),
+                );
+                let value = pretty_value(&flattened.type_, context, quote!(indent));
+                statements.push(quote! {
+                    write!(f, "{:width$}{}:", "", #alias, width = indent * 2)?;
+                    {
+                        let value = &self.#rust_name;
+                        #value
+                    }
+                });
+            }
+            SelectionItem::FragmentSpread(fragment) => {
+                let field_name =
+                    Ident::new(&snake_case_name(fragment.fragment_name), Span::call_site());
+                statements.push(quote!(self.#field_name.fmt_pretty(f, indent)?;));
+            }
+            SelectionItem::InlineFragment(_) => Err(format_err!(
+                "unimplemented: inline fragment on object field"
+            ))?,
+        }
+    }
+
+    Ok(statements)
+}
+
+/// Writes the `value` reference in scope, of the given type, for the pretty `Display`
+/// implementation. Nested objects are written on the following lines, one level deeper.
+fn pretty_value(
+    field_type: &FieldType<'_>,
+    context: &QueryContext<'_, '_>,
+    indent: TokenStream,
+) -> TokenStream {
+    match field_type {
+        FieldType::Optional(inner) => {
+            let inner = pretty_value(inner, context, indent);
+            quote! {
+                match value {
+                    Some(value) => { #inner }
+                    None => writeln!(f, " null")?,
+                }
+            }
+        }
+        FieldType::Vector(inner) => {
+            let inner = pretty_value(inner, context, quote!((#indent + 1)));
+            quote! {
+                writeln!(f)?;
+                for value in value.iter() {
+                    write!(f, "{:width$}-", "", width = (#indent + 1) * 2)?;
+                    #inner
+                }
+            }
+        }
+        FieldType::Named(name) => {
+            if context.schema.enums.contains_key(name) {
+                quote!(writeln!(f, " {}", value)?;)
+            } else if context.schema.contains_scalar(name) {
+                quote!(writeln!(f, " {:?}", value)?;)
+            } else {
+                quote! {
+                    writeln!(f)?;
+                    value.fmt_pretty(f, #indent + 1)?;
+                }
+            }
+        }
+    }
+}
+
+/// The pretty `Display` implementation for a generated response type, given the statements
+/// writing its contents.
+pub(crate) fn pretty_display_impl(
+    vis: &syn::Visibility,
+    name: &Ident,
+    body: &[TokenStream],
+) -> TokenStream {
+    quote! {
+        impl #name {
+            #[doc(hidden)]
+            #[allow(deprecated, unused_variables)]
+            #vis fn fmt_pretty(&self, f: &mut ::core::fmt::Formatter<'_>, indent: usize) -> ::core::fmt::Result {
+                #(#body)*
+                Ok(())
+            }
+        }
+
+        impl ::core::fmt::Display for #name {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                self.fmt_pretty(f, 0)
+            }
+        }
+    }
+}
+
+/// The statements writing a generated union or interface enum in the pretty `Display`
+/// implementation: the `__typename` of the variant, followed by its selected fields.
+pub(crate) fn pretty_variants(
+    enum_name: &Ident,
+    selected_variants: &[&str],
+    other_variants: &[&str],
+) -> TokenStream {
+    let typename_line = quote!(writeln!(
+        f,
+        "{:width$}__typename: {}",
+        "",
+        typename,
+        width = indent * 2
+    )?);
+    let selected = selected_variants.iter().map(|variant| {
+        let ident = Ident::new(variant, Span::call_site());
+        quote! {
+            #enum_name::#ident(inner) => {
+                let typename = #variant;
+                #typename_line;
+                inner.fmt_pretty(f, indent)?;
+            }
+        }
+    });
+    let other = other_variants.iter().map(|variant| {
+        let ident = Ident::new(variant, Span::call_site());
+        quote! {
+            #enum_name::#ident => {
+                let typename = #variant;
+                #typename_line;
+            }
+        }
+    });
+
+    quote! {
+        match self {
+            #(#selected)*
+            #(#other)*
+        }
+    }
+}
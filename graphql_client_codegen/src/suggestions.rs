@@ -0,0 +1,72 @@
+//! "Did you mean" suggestions for the names in a query that do not exist in the schema.
+
+/// Formats a suggestion for the candidate closest to `name`, like `` Did you mean `createdAt`?``,
+/// to append to an error message. It is empty when no candidate is close enough.
+pub(crate) fn did_you_mean<'a>(
+    name: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> String {
+    closest_match(name, candidates)
+        .map(|candidate| format!(" Did you mean `{}`?", candidate))
+        .unwrap_or_default()
+}
+
+/// The candidate with the smallest edit distance to `name`, ignoring case, if that distance is at
+/// most a third of the length of `name`.
+fn closest_match<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let name = name.to_lowercase();
+    let threshold = std::cmp::max(1, name.chars().count() / 3);
+
+    candidates
+        .into_iter()
+        .map(|candidate| (edit_distance(&name, &candidate.to_lowercase()), candidate))
+        .filter(|(distance, _)| *distance <= threshold)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// The Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, a_char) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + if a_char == *b_char { 0 } else { 1 };
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edit_distance_works() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("createdat", "createdat"), 0);
+        assert_eq!(edit_distance("name", ""), 4);
+    }
+
+    #[test]
+    fn did_you_mean_suggests_the_closest_candidate() {
+        let fields = ["id", "createdAt", "updatedAt", "name"];
+
+        assert_eq!(
+            did_you_mean("createAt", fields.iter().cloned()),
+            " Did you mean `createdAt`?"
+        );
+        assert_eq!(
+            did_you_mean("created_at", fields.iter().cloned()),
+            " Did you mean `createdAt`?"
+        );
+        assert_eq!(did_you_mean("email", fields.iter().cloned()), "");
+    }
+}
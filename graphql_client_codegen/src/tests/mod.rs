@@ -1,5 +1,18 @@
 mod github;
 
+use crate::{CodegenError, GraphQLClientCodegenOptions};
+
+/// Generates the module of a query against the star wars schema.
+fn generate(query: &str, options: &GraphQLClientCodegenOptions) -> Result<String, CodegenError> {
+    crate::generate_module_token_stream_from_str(
+        query,
+        include_str!("star_wars_schema.graphql"),
+        crate::SchemaFormat::GraphQL,
+        options,
+    )
+    .map(|tokens| tokens.to_string())
+}
+
 #[test]
 fn schema_with_keywords_works() {
     use crate::{
@@ -22,6 +35,8 @@ fn schema_with_keywords_works() {
             query_document: &query,
             operation,
             options: &options,
+            timings: None,
+            document_warnings: true,
         }
         .to_token_stream()
         .expect("Generate keywords module");
@@ -41,3 +56,576 @@ fn schema_with_keywords_works() {
         };
     }
 }
+
+#[test]
+fn generated_module_docs_describe_the_operation() {
+    use crate::{
+        codegen, generated_module, schema::Schema, CodegenMode, GraphQLClientCodegenOptions,
+    };
+    use graphql_parser;
+
+    let query_string = include_str!("star_wars_query.graphql");
+    let query = graphql_parser::parse_query(query_string).expect("Parse star wars query");
+    let schema = graphql_parser::parse_schema(include_str!("star_wars_schema.graphql"))
+        .expect("Parse star wars schema");
+    let schema = Schema::from(&schema);
+
+    let mut options = GraphQLClientCodegenOptions::new(CodegenMode::Cli);
+    options.set_query_file("src/tests/star_wars_query.graphql".into());
+    options.set_include_query_in_docs(true);
+    let operations = codegen::all_operations(&query);
+    let generated_code = generated_module::GeneratedModule {
+        query_string,
+        schema: &schema,
+        query_document: &query,
+        operation: &operations[0],
+        options: &options,
+        timings: None,
+        document_warnings: true,
+    }
+    .to_token_stream()
+    .expect("Generate star wars module")
+    .to_string();
+
+    assert!(generated_code.contains("Generated code for the `StarWarsQuery` GraphQL query."));
+    assert!(generated_code.contains("Source file: `star_wars_query.graphql`"));
+    assert!(generated_code.contains("```graphql"));
+}
+
+#[test]
+fn builtin_scalars_are_imported_from_the_runtime() {
+    use crate::CodegenMode;
+
+    let generated = generate(
+        include_str!("star_wars_query.graphql"),
+        &GraphQLClientCodegenOptions::new(CodegenMode::Cli),
+    )
+    .unwrap();
+
+    assert!(
+        generated.contains("use :: graphql_client :: scalars :: { Boolean , Float , Int , ID } ;")
+    );
+    assert!(!generated.contains("type Boolean"));
+}
+
+#[test]
+fn helper_methods_take_the_item_visibility() {
+    use crate::CodegenMode;
+
+    let mut options = GraphQLClientCodegenOptions::new(CodegenMode::Cli);
+    options.set_item_visibility(syn::parse_str("pub(crate)").unwrap());
+    options.set_pretty_display(true);
+
+    let generated = generate(
+        "query Search($episode: Episode) {
+            hero(episode: $episode) { __typename name }
+            search(text: \"droid\") { __typename ... on Droid { name } }
+        }",
+        &options,
+    )
+    .unwrap();
+    let generated: String = generated.split_whitespace().collect();
+
+    for helper in &[
+        "fnfrom_json",
+        "fnfmt_pretty",
+        "fntypename",
+        "fnis_droid",
+        "fnas_droid",
+        "fninto_droid",
+        "constDROID_TYPENAME",
+        "constALL",
+        "constVARIANTS",
+    ] {
+        assert!(generated.contains(&format!("pub(crate){}", helper)));
+        assert!(!generated.contains(&format!("pub{}", helper)));
+    }
+}
+
+#[test]
+fn type_hook_and_post_process_extend_the_generated_code() {
+    use crate::CodegenMode;
+    use quote::quote;
+
+    let mut options = GraphQLClientCodegenOptions::new(CodegenMode::Cli);
+    options.set_type_hook(|path, tokens| {
+        if path == "star_wars_query::ResponseData" {
+            tokens.extend(quote!(impl Marker for ResponseData {}));
+        }
+    });
+    options.set_post_process(|tokens| {
+        tokens.extend(quote!(
+            pub struct Extra;
+        ))
+    });
+
+    let generated = generate(include_str!("star_wars_query.graphql"), &options).unwrap();
+
+    assert!(generated.contains("pub struct ResponseData"));
+    assert!(generated.contains("impl Marker for ResponseData { }"));
+    assert!(generated.ends_with("pub struct Extra ;"));
+    assert!(generated.contains("# ! [ allow ( dead_code ) ]"));
+}
+
+#[test]
+fn rust_version_leaves_out_newer_items() {
+    use crate::{
+        codegen, generated_module, schema::Schema, CodegenMode, GraphQLClientCodegenOptions,
+        RustVersion,
+    };
+    use graphql_parser;
+
+    let query_string = include_str!("star_wars_query.graphql");
+    let query = graphql_parser::parse_query(query_string).expect("Parse star wars query");
+    let schema = graphql_parser::parse_schema(include_str!("star_wars_schema.graphql"))
+        .expect("Parse star wars schema");
+    let schema = Schema::from(&schema);
+    let operations = codegen::all_operations(&query);
+
+    let generate = |options: &GraphQLClientCodegenOptions| {
+        generated_module::GeneratedModule {
+            query_string,
+            schema: &schema,
+            query_document: &query,
+            operation: &operations[0],
+            options,
+            timings: None,
+            document_warnings: true,
+        }
+        .to_token_stream()
+        .expect("Generate star wars module")
+        .to_string()
+    };
+
+    let mut options = GraphQLClientCodegenOptions::new(CodegenMode::Cli);
+    assert!(generate(&options).contains("TryFrom"));
+
+    options.set_rust_version("1.33".parse().unwrap());
+    assert!(!generate(&options).contains("TryFrom"));
+
+    options.set_rust_version("1.34".parse().unwrap());
+    assert!(generate(&options).contains("TryFrom"));
+
+    assert!("1.30".parse::<RustVersion>().is_err());
+    assert!("latest".parse::<RustVersion>().is_err());
+}
+
+#[test]
+fn unknown_fields_get_suggestions() {
+    use crate::{error::Location, CodegenMode};
+
+    let query_string = "query StarWarsQuery { hero(episode: NEWHOPE) { __typename nme } }";
+    let options = GraphQLClientCodegenOptions::new(CodegenMode::Cli);
+
+    let err = generate(query_string, &options).unwrap_err();
+
+    assert!(
+        err.to_string().contains("Did you mean `name`?"),
+        "unexpected error: {}",
+        err
+    );
+    assert_eq!(
+        err.location(),
+        Some(Location {
+            line: 1,
+            column: 59
+        })
+    );
+
+    let query_string = "query StarWarsQuery { droid(id: \"2001\") { ...DroidNme } } fragment DroidName on Droid { name }";
+    let err = generate(query_string, &options).unwrap_err();
+
+    assert!(err.to_string().contains("Did you mean `DroidName`?"));
+    assert_eq!(
+        err.location(),
+        Some(Location {
+            line: 1,
+            column: 46
+        })
+    );
+
+    let query_string = "query StarWarsQuery { hero { ...HeroName } }\nfragment HeroName on Charakter { __typename name }";
+    let err = generate(query_string, &options).unwrap_err();
+
+    assert!(err.to_string().contains("Did you mean `Character`?"));
+    assert_eq!(err.location(), Some(Location { line: 2, column: 1 }));
+}
+
+#[test]
+fn unused_fragments_and_variables_are_reported() {
+    use crate::CodegenMode;
+
+    let query_string = r#"
+        query StarWarsQuery($episode: Episode, $unused: String) {
+          hero(episode: $episode) { __typename name }
+        }
+
+        fragment Orphan on Character { id }
+    "#;
+
+    let mut options = GraphQLClientCodegenOptions::new(CodegenMode::Cli);
+    let generated = generate(query_string, &options).unwrap();
+    assert!(generated.contains("The fragment `Orphan` is never used."));
+    assert!(generated
+        .contains("The variable `$unused` of the `StarWarsQuery` operation is never used."));
+
+    // The fragments are reported once for the whole document.
+    let generated = generate(
+        &format!(
+            "{}\nquery OtherQuery {{ hero {{ __typename }} }}",
+            query_string
+        ),
+        &options,
+    )
+    .unwrap();
+    assert_eq!(
+        generated
+            .matches("The fragment `Orphan` is never used.")
+            .count(),
+        1
+    );
+
+    options.set_deny_unused(true);
+    let err = generate(query_string, &options).unwrap_err().to_string();
+    assert!(err.contains("The fragment `Orphan` is never used."));
+}
+
+#[test]
+fn non_null_variables_with_defaults_are_reported() {
+    use crate::CodegenMode;
+
+    let generated = generate(
+        "query StarWarsQuery($episode: Episode! = NEWHOPE) { hero(episode: $episode) { __typename name } }",
+        &GraphQLClientCodegenOptions::new(CodegenMode::Cli),
+    )
+    .unwrap();
+
+    assert!(generated.contains(
+        "The variable `$episode` of the `StarWarsQuery` operation is non-null, so its default value is never used."
+    ));
+}
+
+#[test]
+fn deprecation_reasons_are_carried_over() {
+    use crate::{
+        deprecation::DeprecationStrategy, generate_module_token_stream_from_str, CodegenMode,
+        GraphQLClientCodegenOptions, SchemaFormat,
+    };
+
+    let schema_string = r#"
+        schema { query: Query }
+
+        type Query { node: Node }
+
+        interface Node {
+          id: ID!
+          oldId: ID @deprecated(reason: "Use `id`.")
+        }
+
+        type User implements Node {
+          id: ID!
+          oldId: ID @deprecated(reason: "Use `id`.")
+        }
+    "#;
+    let query_string = "query NodeQuery { node { __typename oldId } }";
+    let generate = |options: &GraphQLClientCodegenOptions| {
+        generate_module_token_stream_from_str(
+            query_string,
+            schema_string,
+            SchemaFormat::GraphQL,
+            options,
+        )
+        .unwrap()
+        .to_string()
+    };
+
+    let mut options = GraphQLClientCodegenOptions::new(CodegenMode::Cli);
+    let generated = generate(&options);
+    assert!(generated.contains("# [ deprecated ( note = \"Use `id`.\" ) ]"));
+    assert!(generated.contains("The field `Node.oldId` is deprecated: Use `id`."));
+
+    options.set_deprecation_strategy(DeprecationStrategy::Allow);
+    let generated = generate(&options);
+    assert!(generated.contains("# [ doc = \" Deprecated: Use `id`.\" ]"));
+    assert!(!generated.contains("is deprecated"));
+}
+
+#[test]
+fn field_docs_include_the_graphql_signature() {
+    use crate::{
+        generate_module_token_stream_from_str, CodegenMode, GraphQLClientCodegenOptions,
+        SchemaFormat,
+    };
+
+    let schema_string = r#"
+        schema { query: Query }
+
+        type Query { issues(first: Int, after: String): [Issue!]! }
+
+        type Issue {
+          "The title of the issue."
+          title: String
+        }
+    "#;
+    let query_string = r#"
+        query IssuesQuery($cursor: String) {
+          issues(first: 10, after: $cursor) { title }
+        }
+    "#;
+    let generated = generate_module_token_stream_from_str(
+        query_string,
+        schema_string,
+        SchemaFormat::GraphQL,
+        &GraphQLClientCodegenOptions::new(CodegenMode::Cli),
+    )
+    .unwrap()
+    .to_string();
+
+    assert!(generated.contains("# [ doc = \"`issues(first: 10, after: $cursor): [Issue!]!`\" ]"));
+    assert!(generated.contains("The title of the issue.\\n\\n`title: String`"));
+}
+
+#[test]
+fn diverging_input_conversions_are_reported() {
+    use crate::{
+        generate_module_token_stream_from_str, CodegenMode, GraphQLClientCodegenOptions,
+        SchemaFormat,
+    };
+
+    let schema_string = r#"
+        schema { query: Query }
+        type User { name: String, age: Int! }
+        input UserInput { name: String!, age: Int, email: String }
+        type Query { user: User! }
+    "#;
+    let generate = |query_string: &str| {
+        let mut options = GraphQLClientCodegenOptions::new(CodegenMode::Cli);
+        options.set_input_conversions("user = UserInput".to_string());
+        generate_module_token_stream_from_str(
+            query_string,
+            schema_string,
+            SchemaFormat::GraphQL,
+            &options,
+        )
+        .unwrap_err()
+        .to_string()
+    };
+
+    assert!(generate("query User { user { age } }")
+        .contains("Cannot convert `user` to `UserInput`: `name` is required but not selected."));
+    assert!(generate("query User { user { name age } }")
+        .contains("the field `name` is `String` on `User`, but `String!` in the input."));
+}
+
+#[test]
+fn operations_over_the_limits_are_rejected() {
+    use crate::{CodegenMode, ComplexityOptions};
+
+    let query_string =
+        "query Friends { hero { __typename friends { __typename friends { __typename name } } } }";
+
+    let mut options = GraphQLClientCodegenOptions::new(CodegenMode::Cli);
+    options.set_max_depth(4);
+    options.set_max_complexity(112);
+    assert!(generate(query_string, &options).is_ok());
+
+    options.set_max_depth(3);
+    let err = generate(query_string, &options).unwrap_err().to_string();
+    assert!(err.contains("The `Friends` operation has a depth of 4, over the max depth of 3."));
+
+    let mut complexity_options = ComplexityOptions::new();
+    complexity_options.set_list_multiplier("Character.friends", 20);
+    options.set_complexity_options(complexity_options);
+    let err = generate(query_string, &options).unwrap_err().to_string();
+    assert!(err.contains(
+        "The `Friends` operation has a complexity of 422, over the max complexity of 112."
+    ));
+}
+
+#[test]
+fn format_agnostic_serde_rejects_flattened_selections() {
+    use crate::CodegenMode;
+
+    let mut options = GraphQLClientCodegenOptions::new(CodegenMode::Cli);
+    options.set_format_agnostic_serde(true);
+
+    let generated = generate(
+        "query Droid($id: ID!) { droid(id: $id) { name primaryFunction } }",
+        &options,
+    )
+    .unwrap();
+    assert!(!generated.contains("skip_serializing_if"));
+
+    let err = generate(
+        "query Hero { hero { ...Name } } fragment Name on Character { __typename name }",
+        &options,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("The fragment `Name` spread on `Character` would be flattened"));
+
+    let err = generate("query Hero { hero { __typename name } }", &options)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("The `__typename` selection on the `Character` interface"));
+
+    options.set_extra_fields(true);
+    let err = generate("query Hero { hero { name } }", &options)
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.contains("The format_agnostic_serde and extra_fields options cannot be used together.")
+    );
+}
+
+#[test]
+fn typename_fallback_must_be_a_possible_type() {
+    use crate::CodegenMode;
+
+    let mut options = GraphQLClientCodegenOptions::new(CodegenMode::Cli);
+    options.set_typename_fallback("Starship".parse().unwrap());
+
+    // The `__typename` selection is not required on unions with a fallback.
+    generate(
+        "query Search { search(text: \"R2\") { ... on Starship { name } } }",
+        &options,
+    )
+    .unwrap();
+
+    let err = generate("query Hero { hero { __typename name } }", &options)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("The typename fallback `Starship` is not a possible type of `Character`."));
+
+    options.set_format_agnostic_serde(true);
+    let err = generate("query Hero { hero { name } }", &options)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains(
+        "The format_agnostic_serde and typename_fallback options cannot be used together."
+    ));
+}
+
+#[test]
+fn type_bindings_follow_aliases_and_fragments() {
+    use crate::{CodegenMode, GraphQLClientCodegenOptions, SchemaDocument, SchemaFormat};
+
+    let schema = SchemaDocument::parse(
+        include_str!("star_wars_schema.graphql"),
+        SchemaFormat::GraphQL,
+    )
+    .unwrap();
+    let options = GraphQLClientCodegenOptions::new(CodegenMode::Cli);
+    let query = r#"query Hero {
+  hero {
+    ...HeroName
+    buddies: friends { __typename name }
+  }
+}
+
+fragment HeroName on Character {
+  name __typename
+}
+"#;
+
+    let bindings = schema.type_bindings(query, &options).unwrap();
+
+    let buddies = bindings
+        .iter()
+        .find(|binding| (binding.line, binding.column) == (4, 5))
+        .unwrap();
+    assert_eq!(buddies.response_name, "buddies");
+    assert_eq!(buddies.rust_field, "buddies");
+    assert_eq!(buddies.parent_path, "hero::HeroHero");
+    assert_eq!(
+        buddies.selection_path,
+        Some("hero::HeroHeroBuddies".to_owned())
+    );
+
+    let name = bindings
+        .iter()
+        .find(|binding| (binding.line, binding.column) == (9, 3))
+        .unwrap();
+    assert_eq!(name.parent_path, "hero::HeroName");
+    assert_eq!(name.rust_type, "String");
+}
+
+#[test]
+fn schema_files_are_parsed_from_a_mapping() {
+    use crate::{CodegenError, SchemaDocument};
+
+    let schema =
+        SchemaDocument::read(std::path::Path::new("src/tests/github_schema.json")).unwrap();
+    assert!(schema.model().objects.contains_key("Repository"));
+
+    let path = std::env::temp_dir().join("graphql_client_codegen_invalid_schema.graphql");
+    std::fs::write(&path, b"type Query { name: String } \xff").unwrap();
+    let err = SchemaDocument::read(&path).err().unwrap();
+    std::fs::remove_file(&path).unwrap();
+    match err {
+        CodegenError::Io { source, .. } => {
+            assert_eq!(source.kind(), std::io::ErrorKind::InvalidData)
+        }
+        other => panic!("expected an I/O error, got {}", other),
+    }
+}
+
+#[test]
+fn rust_version_1_31_leaves_out_all_newer_items() {
+    use crate::CodegenMode;
+
+    let query_string = r#"
+        query Hero($episode: Episode) {
+          hero(episode: $episode) {
+            __typename
+            ...HeroName
+            ... on Droid { primaryFunction }
+          }
+          search(text: "R2") {
+            __typename
+            ... on Starship { name }
+            ... on Human { name height }
+          }
+        }
+
+        fragment HeroName on Character { __typename name }
+
+        mutation CreateReview($episode: Episode, $review: ReviewInput!) {
+          createReview(episode: $episode, review: $review) { stars commentary }
+        }
+    "#;
+
+    let mut options = GraphQLClientCodegenOptions::new(CodegenMode::Cli);
+    options.set_all_operations(true);
+    options.set_response_derives("PartialEq".to_string());
+    options.set_pretty_display(true);
+    options.set_nested_accessors(true);
+    options.set_enum_rename_all(true);
+    options.set_assert_send_sync(true);
+    options.set_typestate_builders(true);
+    options.set_validate_variables(true);
+    options.set_serialize_responses(true);
+    assert!(generate(query_string, &options)
+        .unwrap()
+        .contains("TryFrom"));
+
+    options.set_rust_version("1.31".parse().unwrap());
+    let generated = generate(query_string, &options).unwrap().replace(' ', "");
+    for newer in &[
+        "TryFrom",
+        "const_:",
+        "matches!",
+        "as_deref",
+        "non_exhaustive",
+        "todo!",
+        ".copied()",
+        "strip_prefix",
+        "split_once",
+    ] {
+        assert!(
+            !generated.contains(newer),
+            "`{}` is newer than Rust 1.31",
+            newer
+        );
+    }
+}
@@ -0,0 +1,92 @@
+//! The durations of the stages of the code generation, printed with the `timings` option to tell
+//! whether the schema or the operations make a build slow.
+
+use std::cell::RefCell;
+use std::time::{Duration, Instant};
+
+/// The durations of the stages of one code generation, in the order they ran.
+#[derive(Debug, Default)]
+pub(crate) struct Timings {
+    stages: RefCell<Vec<(String, Duration)>>,
+}
+
+impl Timings {
+    /// The timings to record, if the options enable them.
+    pub(crate) fn for_options(options: &crate::GraphQLClientCodegenOptions) -> Option<Timings> {
+        if options.timings() {
+            Some(Timings::default())
+        } else {
+            None
+        }
+    }
+
+    /// Records the duration of a stage.
+    pub(crate) fn record(&self, stage: String, duration: Duration) {
+        self.stages.borrow_mut().push((stage, duration));
+    }
+
+    /// The report of the stages, one per line, followed by their total.
+    pub(crate) fn report(&self, title: &str) -> String {
+        let stages = self.stages.borrow();
+        let width = stages
+            .iter()
+            .map(|(stage, _)| stage.len())
+            .max()
+            .unwrap_or(0)
+            .max("total".len());
+        let total: (String, Duration) = (
+            "total".to_owned(),
+            stages.iter().map(|(_, duration)| *duration).sum(),
+        );
+
+        let mut report = format!("graphql_client codegen timings for {}:", title);
+        for (stage, duration) in stages.iter().chain(std::iter::once(&total)) {
+            report.push_str(&format!(
+                "\n  {:<width$}  {:>9.3} ms",
+                stage,
+                milliseconds(*duration),
+                width = width
+            ));
+        }
+        report
+    }
+
+    /// Prints the report to stderr, where both cargo and the CLI show it.
+    pub(crate) fn print(&self, title: &str) {
+        eprintln!("{}", self.report(title));
+    }
+}
+
+/// Runs a stage, recording its duration if there are timings.
+pub(crate) fn time<T>(timings: Option<&Timings>, stage: &str, run: impl FnOnce() -> T) -> T {
+    let started = Instant::now();
+    let result = run();
+    if let Some(timings) = timings {
+        timings.record(stage.to_owned(), started.elapsed());
+    }
+    result
+}
+
+fn milliseconds(duration: Duration) -> f64 {
+    duration.as_micros() as f64 / 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_aligns_the_stages_and_sums_them() {
+        let timings = Timings::default();
+        timings.record("schema parsing".to_owned(), Duration::from_micros(12_500));
+        timings.record("Q: query binding".to_owned(), Duration::from_micros(1_250));
+
+        assert_eq!(
+            timings.report("query.graphql"),
+            "graphql_client codegen timings for query.graphql:\n  \
+             schema parsing       12.500 ms\n  \
+             Q: query binding      1.250 ms\n  \
+             total                13.750 ms"
+        );
+    }
+}
@@ -0,0 +1,40 @@
+//! The bindings of the fields of query documents to the generated code, for the editor plugins
+//! offering to go to the generated type of a field, and hover information, in `.graphql` files.
+
+use serde::Serialize;
+
+/// A field selected in a query document, and the generated code it is bound to.
+///
+/// The paths are relative to the module the code is generated in, and start with the module of
+/// the operation, like `star_wars_query::ResponseDataHero`. The fields of a fragment are bound in
+/// the module of each operation.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct TypeBinding {
+    /// The one-based line of the field in the query document.
+    pub line: usize,
+    /// The one-based column of the field in the query document, at its alias when it has one.
+    pub column: usize,
+    /// The name of the field in the response: its alias, or its name.
+    pub response_name: String,
+    /// The path of the generated struct with the field.
+    pub parent_path: String,
+    /// The name of the generated field.
+    pub rust_field: String,
+    /// The Rust type of the generated field, like `Option<Vec<ResponseDataHeroFriends>>`.
+    pub rust_type: String,
+    /// The path of the type generated for the selection of the field, if it has one.
+    pub selection_path: Option<String>,
+}
+
+impl TypeBinding {
+    /// The binding with its paths in the module of an operation.
+    pub(crate) fn in_module(self, module: &str) -> TypeBinding {
+        TypeBinding {
+            parent_path: format!("{}::{}", module, self.parent_path),
+            selection_path: self
+                .selection_path
+                .map(|path| format!("{}::{}", module, path)),
+            ..self
+        }
+    }
+}
@@ -0,0 +1,517 @@
+//! TypeScript declarations of the variables and the responses of the operations of a query
+//! document, for the JavaScript callers of the same operations.
+
+use crate::field_type::FieldType;
+use crate::schema::Schema;
+use crate::unused::name_of;
+use graphql_parser::query::{
+    Definition, Document, Field, FragmentDefinition, OperationDefinition, Selection, SelectionSet,
+    TypeCondition,
+};
+use heck::CamelCase;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The indentation of the nested declarations.
+const INDENT: &str = "  ";
+
+/// The declarations of the variables and the response data of every operation of the document,
+/// as `<Operation>Variables` and `<Operation>ResponseData` interfaces, preceded by the enums, input
+/// objects and custom scalars they use.
+pub(crate) fn operations_typescript(schema: &Schema<'_>, document: &Document) -> String {
+    let fragments: BTreeMap<&str, &FragmentDefinition> = document
+        .definitions
+        .iter()
+        .filter_map(|definition| match definition {
+            Definition::Fragment(fragment) => Some((fragment.name.as_str(), fragment)),
+            Definition::Operation(_) => None,
+        })
+        .collect();
+    let mut declarations = Declarations {
+        schema,
+        fragments: &fragments,
+        scalars: BTreeSet::new(),
+        enums: BTreeSet::new(),
+        inputs: BTreeSet::new(),
+    };
+
+    let mut operations = String::new();
+    for operation in document
+        .definitions
+        .iter()
+        .filter_map(|definition| match definition {
+            Definition::Operation(operation) => Some(operation),
+            Definition::Fragment(_) => None,
+        })
+    {
+        let (root_name, selection_set, variable_definitions) = match operation {
+            OperationDefinition::SelectionSet(selection_set) => {
+                (schema.query_type.unwrap_or("Query"), selection_set, &[][..])
+            }
+            OperationDefinition::Query(query) => (
+                schema.query_type.unwrap_or("Query"),
+                &query.selection_set,
+                &query.variable_definitions[..],
+            ),
+            OperationDefinition::Mutation(mutation) => (
+                schema.mutation_type.unwrap_or("Mutation"),
+                &mutation.selection_set,
+                &mutation.variable_definitions[..],
+            ),
+            OperationDefinition::Subscription(subscription) => (
+                schema.subscription_type.unwrap_or("Subscription"),
+                &subscription.selection_set,
+                &subscription.variable_definitions[..],
+            ),
+        };
+        let name = name_of(operation).unwrap_or("Anonymous").to_camel_case();
+
+        let variables: Vec<String> = variable_definitions
+            .iter()
+            .map(|variable| {
+                let ty = FieldType::from(&variable.var_type);
+                declarations.input_field(&variable.name, &ty)
+            })
+            .collect();
+        operations.push_str(&format!(
+            "export interface {}Variables {}\n\n",
+            name,
+            block(&variables, 0)
+        ));
+
+        let response = declarations.selection(root_name, &[selection_set], 0);
+        operations.push_str(&format!(
+            "export interface {}ResponseData {}\n\n",
+            name, response
+        ));
+    }
+
+    let mut output = String::from("// Generated by graphql-client. Do not edit.\n\n");
+    output.push_str(&declarations.schema_types());
+    output.push_str(&operations);
+    output.truncate(output.trim_end().len());
+    output.push('\n');
+    output
+}
+
+/// The `{ ... }` block of the given members, one per line, at the given indentation level.
+fn block(members: &[String], level: usize) -> String {
+    if members.is_empty() {
+        return "{}".to_owned();
+    }
+
+    let mut block = String::from("{\n");
+    for member in members {
+        block.push_str(&INDENT.repeat(level + 1));
+        block.push_str(member);
+        block.push_str(";\n");
+    }
+    block.push_str(&INDENT.repeat(level));
+    block.push('}');
+    block
+}
+
+/// A string literal type.
+fn literal(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+struct Declarations<'a, 'schema> {
+    schema: &'a Schema<'schema>,
+    fragments: &'a BTreeMap<&'a str, &'a FragmentDefinition>,
+    /// The custom scalars, enums and input objects used by the operations, declared before them.
+    scalars: BTreeSet<&'schema str>,
+    enums: BTreeSet<&'schema str>,
+    inputs: BTreeSet<&'schema str>,
+}
+
+impl<'a, 'schema> Declarations<'a, 'schema> {
+    /// The declarations of the custom scalars, enums and input objects used by the operations.
+    /// The custom scalars are `unknown`, as their JSON representation is not in the schema.
+    fn schema_types(&mut self) -> String {
+        // The input objects were collected with the types of their fields, so declaring them
+        // requires no other type.
+        let mut inputs = String::new();
+        for name in self.inputs.clone() {
+            let mut fields: Vec<_> = self.schema.inputs[name].fields.values().collect();
+            fields.sort_by(|a, b| a.name.cmp(&b.name));
+            let fields: Vec<String> = fields
+                .iter()
+                .map(|field| self.input_field(field.name, &field.type_))
+                .collect();
+            inputs.push_str(&format!(
+                "export interface {} {}\n\n",
+                name,
+                block(&fields, 0)
+            ));
+        }
+
+        let mut declarations = String::new();
+        for scalar in &self.scalars {
+            declarations.push_str(&format!("export type {} = unknown;\n\n", scalar));
+        }
+        for name in &self.enums {
+            let values: Vec<String> = self.schema.enums[name]
+                .variants
+                .iter()
+                .map(|variant| literal(variant.name))
+                .collect();
+            declarations.push_str(&format!(
+                "export type {} = {};\n\n",
+                name,
+                values.join(" | ")
+            ));
+        }
+        declarations.push_str(&inputs);
+
+        declarations
+    }
+
+    /// A field of the variables or of an input object: optional when nullable, as GraphQL treats
+    /// the missing nullable values as null.
+    fn input_field(&mut self, name: &str, field_type: &FieldType<'_>) -> String {
+        let optional = if field_type.is_optional() { "?" } else { "" };
+        format!("{}{}: {}", name, optional, self.input_type(field_type))
+    }
+
+    fn input_type(&mut self, field_type: &FieldType<'_>) -> String {
+        match field_type {
+            FieldType::Optional(inner) => format!("{} | null", self.input_type(inner)),
+            FieldType::Vector(inner) => format!("Array<{}>", self.input_type(inner)),
+            FieldType::Named(name) => self.named_type(name),
+        }
+    }
+
+    /// The TypeScript type of a scalar, an enum or an input object, declaring the ones from the
+    /// schema.
+    fn named_type(&mut self, name: &str) -> String {
+        match name {
+            "Int" | "Float" => return "number".to_owned(),
+            "String" | "ID" => return "string".to_owned(),
+            "Boolean" => return "boolean".to_owned(),
+            _ => (),
+        }
+
+        if let Some(enum_) = self.schema.enums.get(name) {
+            self.enums.insert(enum_.name);
+        } else if let Some(input) = self.schema.inputs.get(name) {
+            if self.inputs.insert(input.name) {
+                for field in input.fields.values() {
+                    self.input_type(&field.type_);
+                }
+            }
+        } else if let Some(scalar) = self.schema.scalars.get(name) {
+            self.scalars.insert(scalar.name);
+        } else {
+            return "unknown".to_owned();
+        }
+
+        name.to_owned()
+    }
+
+    /// The type of the response to a field returning the given type, with the given selections.
+    fn response_type(
+        &mut self,
+        field_type: &FieldType<'_>,
+        selection_sets: &[&'a SelectionSet],
+        level: usize,
+    ) -> String {
+        match field_type {
+            FieldType::Optional(inner) => {
+                format!(
+                    "{} | null",
+                    self.response_type(inner, selection_sets, level)
+                )
+            }
+            FieldType::Vector(inner) => format!(
+                "Array<{}>",
+                self.response_type(inner, selection_sets, level)
+            ),
+            FieldType::Named(name) if selection_sets.iter().all(|set| set.items.is_empty()) => {
+                self.named_type(name)
+            }
+            FieldType::Named(name) => self.selection(name, selection_sets, level),
+        }
+    }
+
+    /// The object type of the selections on the given type. The selections of an interface or a
+    /// union refined by fragments on the types implementing it give a union, with one member for
+    /// each of these types, and one for the others.
+    fn selection(
+        &mut self,
+        type_name: &str,
+        selection_sets: &[&'a SelectionSet],
+        level: usize,
+    ) -> String {
+        let possible_types = self.possible_types(type_name);
+        let refined: BTreeSet<&str> = possible_types
+            .iter()
+            .cloned()
+            .filter(|possible_type| {
+                let mut fields = Vec::new();
+                self.collect_fields(type_name, selection_sets, Some(*possible_type), &mut fields);
+                let mut base_fields = Vec::new();
+                self.collect_fields(type_name, selection_sets, None, &mut base_fields);
+                fields.len() != base_fields.len()
+            })
+            .collect();
+
+        if refined.is_empty() {
+            let mut fields = Vec::new();
+            self.collect_fields(type_name, selection_sets, None, &mut fields);
+            return self.object(type_name, &fields, &possible_types, level);
+        }
+
+        let mut members = Vec::new();
+        for possible_type in &refined {
+            let mut fields = Vec::new();
+            self.collect_fields(type_name, selection_sets, Some(*possible_type), &mut fields);
+            members.push(self.object(type_name, &fields, &[*possible_type], level));
+        }
+        let others: Vec<&str> = possible_types
+            .iter()
+            .cloned()
+            .filter(|possible_type| !refined.contains(possible_type))
+            .collect();
+        if !others.is_empty() {
+            let mut fields = Vec::new();
+            self.collect_fields(type_name, selection_sets, None, &mut fields);
+            members.push(self.object(type_name, &fields, &others, level));
+        }
+
+        members.join(" | ")
+    }
+
+    /// The object with the given fields, grouped by response key, of a type that is one of the
+    /// given object types.
+    fn object(
+        &mut self,
+        type_name: &str,
+        fields: &[(&'a str, Vec<&'a Field>)],
+        object_types: &[&str],
+        level: usize,
+    ) -> String {
+        let members: Vec<String> = fields
+            .iter()
+            .map(|(key, fields)| {
+                let field = fields[0];
+                if field.name == "__typename" {
+                    let names: Vec<String> =
+                        object_types.iter().map(|name| literal(name)).collect();
+                    let names = if names.is_empty() {
+                        "string".to_owned()
+                    } else {
+                        names.join(" | ")
+                    };
+                    return format!("{}: {}", key, names);
+                }
+
+                let selection_sets: Vec<&SelectionSet> =
+                    fields.iter().map(|field| &field.selection_set).collect();
+                let field_type = object_types
+                    .iter()
+                    .chain(Some(&type_name))
+                    .filter_map(|name| self.schema_field_type(name, &field.name))
+                    .next();
+                let ty = match field_type {
+                    Some(field_type) => self.response_type(&field_type, &selection_sets, level + 1),
+                    None => "unknown".to_owned(),
+                };
+                format!("{}: {}", key, ty)
+            })
+            .collect();
+
+        block(&members, level)
+    }
+
+    /// The fields selected on the type, grouped by response key in the order of the query, with
+    /// the fragments refining the type to the given object type when there is one.
+    fn collect_fields(
+        &self,
+        type_name: &str,
+        selection_sets: &[&'a SelectionSet],
+        object_type: Option<&str>,
+        fields: &mut Vec<(&'a str, Vec<&'a Field>)>,
+    ) {
+        for selection_set in selection_sets {
+            for item in &selection_set.items {
+                match item {
+                    Selection::Field(field) => {
+                        let key = field.alias.as_ref().unwrap_or(&field.name).as_str();
+                        match fields.iter_mut().find(|(other, _)| *other == key) {
+                            Some((_, same_key)) => same_key.push(field),
+                            None => fields.push((key, vec![field])),
+                        }
+                    }
+                    Selection::FragmentSpread(spread) => {
+                        if let Some(fragment) = self.fragments.get(spread.fragment_name.as_str()) {
+                            let TypeCondition::On(on) = &fragment.type_condition;
+                            if self.applies(on, type_name, object_type) {
+                                self.collect_fields(
+                                    type_name,
+                                    &[&fragment.selection_set],
+                                    object_type,
+                                    fields,
+                                );
+                            }
+                        }
+                    }
+                    Selection::InlineFragment(inline_fragment) => {
+                        let applies = match &inline_fragment.type_condition {
+                            Some(TypeCondition::On(on)) => self.applies(on, type_name, object_type),
+                            None => true,
+                        };
+                        if applies {
+                            self.collect_fields(
+                                type_name,
+                                &[&inline_fragment.selection_set],
+                                object_type,
+                                fields,
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Whether a fragment on the `on` type always applies to a value of `type_name`, or applies
+    /// when it is of the given object type.
+    fn applies(&self, on: &str, type_name: &str, object_type: Option<&str>) -> bool {
+        if on == type_name || self.possible_types(type_name) == [type_name] {
+            return true;
+        }
+
+        match object_type {
+            Some(object_type) => self.possible_types(on).contains(&object_type),
+            None => false,
+        }
+    }
+
+    /// The object types a value of the given type can have.
+    fn possible_types(&self, type_name: &str) -> Vec<&'schema str> {
+        if let Some(union) = self.schema.unions.get(type_name) {
+            union.variants.iter().cloned().collect()
+        } else if let Some(interface) = self.schema.interfaces.get(type_name) {
+            let mut implementations: Vec<&str> = interface.implemented_by.iter().cloned().collect();
+            implementations.sort();
+            implementations
+        } else {
+            self.schema
+                .objects
+                .get(type_name)
+                .map(|object| vec![object.name])
+                .unwrap_or_default()
+        }
+    }
+
+    fn schema_field_type(&self, type_name: &str, field_name: &str) -> Option<FieldType<'schema>> {
+        self.schema
+            .objects
+            .get(type_name)
+            .map(|object| &object.fields)
+            .or_else(|| {
+                self.schema
+                    .interfaces
+                    .get(type_name)
+                    .map(|interface| &interface.fields)
+            })
+            .and_then(|fields| fields.iter().find(|field| field.name == field_name))
+            .map(|field| field.type_.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn typescript(schema: &str, query: &str) -> String {
+        let schema = graphql_parser::schema::parse_schema(schema).unwrap();
+        let schema = Schema::from(&schema);
+        let query = graphql_parser::parse_query(query).unwrap();
+
+        operations_typescript(&schema, &query)
+    }
+
+    #[test]
+    fn variables_and_responses() {
+        let declarations = typescript(
+            "
+            scalar Date
+            enum Status { ACTIVE ARCHIVED }
+            input Filter { status: Status! since: Date }
+            type User { name: String! friends: [User!]! status: Status }
+            type Query { users(filter: Filter): [User] }
+            ",
+            "
+            query Users($filter: Filter) {
+                users(filter: $filter) { name buddies: friends { name } status }
+            }
+            ",
+        );
+
+        assert_eq!(
+            declarations,
+            r#"// Generated by graphql-client. Do not edit.
+
+export type Date = unknown;
+
+export type Status = "ACTIVE" | "ARCHIVED";
+
+export interface Filter {
+  since?: Date | null;
+  status: Status;
+}
+
+export interface UsersVariables {
+  filter?: Filter | null;
+}
+
+export interface UsersResponseData {
+  users: Array<{
+    name: string;
+    buddies: Array<{
+      name: string;
+    }>;
+    status: Status | null;
+  } | null> | null;
+}
+"#
+        );
+    }
+
+    #[test]
+    fn refined_selections_are_unions() {
+        let declarations = typescript(
+            "
+            interface Node { id: ID! }
+            type User implements Node { id: ID! name: String! }
+            type Post implements Node { id: ID! title: String! }
+            type Tag implements Node { id: ID! }
+            type Query { node: Node! }
+            ",
+            "
+            query Node {
+                node { __typename id ... on User { name } ...Post }
+            }
+            fragment Post on Post { title }
+            ",
+        );
+
+        assert!(declarations.contains(
+            r#"export interface NodeResponseData {
+  node: {
+    __typename: "Post";
+    id: string;
+    title: string;
+  } | {
+    __typename: "User";
+    id: string;
+    name: string;
+  } | {
+    __typename: "Tag";
+    id: string;
+  };
+}"#
+        ));
+    }
+}
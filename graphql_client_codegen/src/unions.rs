@@ -1,6 +1,8 @@
+use crate::codegen_options::{TypenameFallback, UnionTagging};
 use crate::query::QueryContext;
 use crate::selection::Selection;
 use failure::*;
+use heck::{ShoutySnakeCase, SnakeCase};
 use proc_macro2::{Ident, Span, TokenStream};
 use quote::quote;
 use std::cell::Cell;
@@ -82,6 +84,357 @@ pub(crate) fn union_variants<'selection>(
     Ok((variants, children_definitions, used_variants))
 }
 
+/// Generates `is_*`, `as_*` and `into_*` helpers on the enum for a union or interface selection, so
+/// a single variant can be accessed without a full match. Variants without selected fields only
+/// get an `is_*` helper. The `__typename` of every variant is available as a `*_TYPENAME`
+/// constant, and through the `typename` method.
+pub(crate) fn variant_accessors(
+    vis: &syn::Visibility,
+    enum_name: &Ident,
+    prefix: &str,
+    selected_variants: &[&str],
+    other_variants: &[&str],
+) -> TokenStream {
+    let selected = selected_variants.iter().map(|variant| {
+        let snake_name = variant.to_snake_case();
+        let ident = Ident::new(variant, Span::call_site());
+        let variant_type = Ident::new(&format!("{}On{}", prefix, variant), Span::call_site());
+        let is_fn = Ident::new(&format!("is_{}", snake_name), Span::call_site());
+        let as_fn = Ident::new(&format!("as_{}", snake_name), Span::call_site());
+        let into_fn = Ident::new(&format!("into_{}", snake_name), Span::call_site());
+        quote! {
+            #vis fn #is_fn(&self) -> bool {
+                match self {
+                    #enum_name::#ident(_) => true,
+                    _ => false,
+                }
+            }
+
+            #vis fn #as_fn(&self) -> ::core::option::Option<&#variant_type> {
+                match self {
+                    #enum_name::#ident(inner) => Some(inner),
+                    _ => None,
+                }
+            }
+
+            #vis fn #into_fn(self) -> ::core::option::Option<#variant_type> {
+                match self {
+                    #enum_name::#ident(inner) => Some(inner),
+                    _ => None,
+                }
+            }
+        }
+    });
+    let typename_const = |variant: &str| {
+        Ident::new(
+            &format!("{}_TYPENAME", variant.to_shouty_snake_case()),
+            Span::call_site(),
+        )
+    };
+    let typename_consts = selected_variants
+        .iter()
+        .chain(other_variants.iter())
+        .map(|variant| {
+            let const_name = typename_const(variant);
+            quote!(#vis const #const_name: &str = #variant;)
+        });
+    let typename_arms = selected_variants
+        .iter()
+        .map(|variant| {
+            let ident = Ident::new(variant, Span::call_site());
+            let const_name = typename_const(variant);
+            quote!(#enum_name::#ident(_) => #enum_name::#const_name,)
+        })
+        .chain(other_variants.iter().map(|variant| {
+            let ident = Ident::new(variant, Span::call_site());
+            let const_name = typename_const(variant);
+            quote!(#enum_name::#ident => #enum_name::#const_name,)
+        }));
+    let other = other_variants.iter().map(|variant| {
+        let ident = Ident::new(variant, Span::call_site());
+        let is_fn = Ident::new(
+            &format!("is_{}", variant.to_snake_case()),
+            Span::call_site(),
+        );
+        quote! {
+            #vis fn #is_fn(&self) -> bool {
+                match self {
+                    #enum_name::#ident => true,
+                    _ => false,
+                }
+            }
+        }
+    });
+
+    quote! {
+        #[allow(unreachable_patterns)]
+        impl #enum_name {
+            #(#typename_consts)*
+
+            #vis fn typename(&self) -> &'static str {
+                match self {
+                    #(#typename_arms)*
+                }
+            }
+
+            #(#selected)*
+            #(#other)*
+        }
+    }
+}
+
+/// The variants of the enum for a union or interface selection, for the mirror enums through which
+/// the hand-written serde implementations go.
+struct MirrorVariants {
+    /// The variants with selected fields, with the types of these fields.
+    selected: Vec<(Ident, Ident)>,
+    others: Vec<Ident>,
+}
+
+impl MirrorVariants {
+    fn new(prefix: &str, selected_variants: &[&str], other_variants: &[&str]) -> Self {
+        let selected = selected_variants
+            .iter()
+            .map(|variant| {
+                (
+                    Ident::new(variant, Span::call_site()),
+                    Ident::new(&format!("{}On{}", prefix, variant), Span::call_site()),
+                )
+            })
+            .collect();
+        let others = other_variants
+            .iter()
+            .map(|variant| Ident::new(variant, Span::call_site()))
+            .collect();
+
+        MirrorVariants { selected, others }
+    }
+
+    /// The variants to declare in an enum, borrowing their fields for serialization.
+    fn declaration(&self, borrowed: bool) -> TokenStream {
+        let reference = if borrowed { quote!(&'a) } else { quote!() };
+        let selected = self
+            .selected
+            .iter()
+            .map(|(variant, variant_type)| quote!(#variant(#reference #variant_type)));
+        let others = self.others.iter().map(|variant| quote!(#variant));
+        quote!(#(#selected,)* #(#others,)*)
+    }
+
+    /// The lifetime and variants of a mirror enum borrowing from the enum to serialize it. The
+    /// variants without selected fields borrow nothing.
+    fn borrowed_declaration(&self) -> (TokenStream, TokenStream) {
+        if self.selected.is_empty() {
+            (quote!(), self.declaration(false))
+        } else {
+            (quote!(<'a>), self.declaration(true))
+        }
+    }
+
+    /// Converts `value` between the enum and a mirror enum.
+    fn convert(&self, from: &TokenStream, to: &TokenStream) -> TokenStream {
+        let selected = self
+            .selected
+            .iter()
+            .map(|(variant, _)| quote!(#from::#variant(inner) => #to::#variant(inner),));
+        let others = self
+            .others
+            .iter()
+            .map(|variant| quote!(#from::#variant => #to::#variant,));
+        quote! {
+            match value {
+                #(#selected)*
+                #(#others)*
+            }
+        }
+    }
+}
+
+/// The enum of a union selection with the `format_agnostic_serde` option, (de)serialized with its
+/// `__typename` tag in the human-readable formats, like the JSON responses, and with the index of
+/// its variant in the others, which cannot deserialize internally tagged enums.
+fn format_agnostic_enum(
+    context: &QueryContext<'_, '_>,
+    enum_name: &Ident,
+    prefix: &str,
+    selected_variants: &[&str],
+    other_variants: &[&str],
+) -> TokenStream {
+    let (derives, serialize) = context.response_derives_without_serde();
+    let vis = &context.item_visibility;
+    let variants = MirrorVariants::new(prefix, selected_variants, other_variants);
+
+    let owned_variants = variants.declaration(false);
+    let tagged = quote!(Tagged);
+    let indexed = quote!(Indexed);
+    let this = quote!(#enum_name);
+    let from_tagged = variants.convert(&tagged, &this);
+    let from_indexed = variants.convert(&indexed, &this);
+    let tagging = context.union_tagging_attribute();
+    let deserialize = quote! {
+        impl<'de> ::serde::Deserialize<'de> for #enum_name {
+            fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> ::core::result::Result<Self, D::Error> {
+                #[derive(Deserialize)]
+                #tagging
+                enum Tagged { #owned_variants }
+
+                #[derive(Deserialize)]
+                enum Indexed { #owned_variants }
+
+                if deserializer.is_human_readable() {
+                    let value = Tagged::deserialize(deserializer)?;
+                    Ok(#from_tagged)
+                } else {
+                    let value = Indexed::deserialize(deserializer)?;
+                    Ok(#from_indexed)
+                }
+            }
+        }
+    };
+
+    let serialize = if serialize {
+        let (lifetime, borrowed_variants) = variants.borrowed_declaration();
+        let to_tagged = variants.convert(&this, &tagged);
+        let to_indexed = variants.convert(&this, &indexed);
+        quote! {
+            impl ::serde::Serialize for #enum_name {
+                fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> ::core::result::Result<S::Ok, S::Error> {
+                    #[derive(Serialize)]
+                    #tagging
+                    enum Tagged #lifetime { #borrowed_variants }
+
+                    #[derive(Serialize)]
+                    enum Indexed #lifetime { #borrowed_variants }
+
+                    let value = self;
+                    if serializer.is_human_readable() {
+                        ::serde::Serialize::serialize(&#to_tagged, serializer)
+                    } else {
+                        ::serde::Serialize::serialize(&#to_indexed, serializer)
+                    }
+                }
+            }
+        }
+    } else {
+        quote!()
+    };
+
+    quote! {
+        #derives
+        #vis enum #enum_name {
+            #owned_variants
+        }
+
+        #deserialize
+
+        #serialize
+    }
+}
+
+/// The enum of a union or interface selection with the `typename_fallback` option. The objects are
+/// buffered as JSON values, deserialized with their tag when they have a `__typename`, and by
+/// their shape or as the default variant otherwise.
+pub(crate) fn typename_fallback_enum(
+    context: &QueryContext<'_, '_>,
+    fallback: &TypenameFallback,
+    enum_name: &Ident,
+    prefix: &str,
+    selected_variants: &[&str],
+    other_variants: &[&str],
+    type_name: &str,
+) -> Result<TokenStream, failure::Error> {
+    let (derives, serialize) = context.response_derives_without_serde();
+    let vis = &context.item_visibility;
+    let variants = MirrorVariants::new(prefix, selected_variants, other_variants);
+
+    let owned_variants = variants.declaration(false);
+    let tagged = quote!(Tagged);
+    let this = quote!(#enum_name);
+    let from_tagged = variants.convert(&tagged, &this);
+    let tagging = context.union_tagging_attribute();
+
+    let fallback = match fallback {
+        TypenameFallback::Untagged => {
+            let untagged = quote!(Untagged);
+            let from_untagged = variants.convert(&untagged, &this);
+            quote! {
+                #[derive(Deserialize)]
+                #[serde(untagged)]
+                enum Untagged { #owned_variants }
+
+                let value = Untagged::deserialize(value).map_err(D::Error::custom)?;
+                Ok(#from_untagged)
+            }
+        }
+        TypenameFallback::Variant(name) if selected_variants.contains(&name.as_str()) => {
+            let variant = Ident::new(name, Span::call_site());
+            quote! {
+                let inner = ::serde::Deserialize::deserialize(value).map_err(D::Error::custom)?;
+                Ok(#enum_name::#variant(inner))
+            }
+        }
+        TypenameFallback::Variant(name) if other_variants.contains(&name.as_str()) => {
+            let variant = Ident::new(name, Span::call_site());
+            quote!(Ok(#enum_name::#variant))
+        }
+        TypenameFallback::Variant(name) => Err(format_err!(
+            "The typename fallback `{}` is not a possible type of `{}`.",
+            name,
+            type_name
+        ))?,
+    };
+
+    let deserialize = quote! {
+        impl<'de> ::serde::Deserialize<'de> for #enum_name {
+            fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> ::core::result::Result<Self, D::Error> {
+                use ::serde::de::Error;
+
+                #[derive(Deserialize)]
+                #tagging
+                enum Tagged { #owned_variants }
+
+                let value = <::graphql_client::serde_json::Value as ::serde::Deserialize>::deserialize(deserializer)?;
+                if value.get("__typename").is_some() {
+                    let value = Tagged::deserialize(value).map_err(D::Error::custom)?;
+                    return Ok(#from_tagged);
+                }
+
+                #fallback
+            }
+        }
+    };
+
+    let serialize = if serialize {
+        let (lifetime, borrowed_variants) = variants.borrowed_declaration();
+        let to_tagged = variants.convert(&this, &tagged);
+        quote! {
+            impl ::serde::Serialize for #enum_name {
+                fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> ::core::result::Result<S::Ok, S::Error> {
+                    #[derive(Serialize)]
+                    #tagging
+                    enum Tagged #lifetime { #borrowed_variants }
+
+                    let value = self;
+                    ::serde::Serialize::serialize(&#to_tagged, serializer)
+                }
+            }
+        }
+    } else {
+        quote!()
+    };
+
+    Ok(quote! {
+        #derives
+        #vis enum #enum_name {
+            #owned_variants
+        }
+
+        #deserialize
+
+        #serialize
+    })
+}
+
 impl<'schema> GqlUnion<'schema> {
     /// Returns the code to deserialize this union in the response given the query selection.
     pub(crate) fn response_for_selection(
@@ -92,7 +445,12 @@ impl<'schema> GqlUnion<'schema> {
     ) -> Result<TokenStream, failure::Error> {
         let typename_field = selection.extract_typename(query_context);
 
-        if typename_field.is_none() {
+        // The untagged enums are deserialized by the shape of their variants, and the enums with a
+        // typename fallback without their tag.
+        if typename_field.is_none()
+            && query_context.union_tagging != UnionTagging::Untagged
+            && query_context.typename_fallback.is_none()
+        {
             Err(UnionError::MissingTypename {
                 union_name: prefix.into(),
             })?;
@@ -100,28 +458,82 @@ impl<'schema> GqlUnion<'schema> {
 
         let struct_name = Ident::new(prefix, Span::call_site());
         let derives = query_context.response_derives();
+        let vis = &query_context.item_visibility;
 
         let (mut variants, children_definitions, used_variants) =
             union_variants(selection, query_context, prefix, &self.name)?;
 
-        variants.extend(
-            self.variants
-                .iter()
-                .filter(|v| used_variants.iter().find(|a| a == v).is_none())
-                .map(|v| {
-                    let v = Ident::new(v, Span::call_site());
-                    quote!(#v)
-                }),
+        let other_variants: Vec<&str> = self
+            .variants
+            .iter()
+            .filter(|v| used_variants.iter().find(|a| a == v).is_none())
+            .cloned()
+            .collect();
+
+        variants.extend(other_variants.iter().map(|v| {
+            let v = Ident::new(v, Span::call_site());
+            quote!(#v)
+        }));
+
+        let selected_variants: BTreeSet<&str> = used_variants.iter().cloned().collect();
+        let selected_variants: Vec<&str> = selected_variants.into_iter().collect();
+        let accessors = variant_accessors(
+            &query_context.item_visibility,
+            &struct_name,
+            prefix,
+            &selected_variants,
+            &other_variants,
         );
 
+        let display = if query_context.pretty_display {
+            let body =
+                crate::shared::pretty_variants(&struct_name, &selected_variants, &other_variants);
+            crate::shared::pretty_display_impl(
+                &query_context.item_visibility,
+                &struct_name,
+                &[body],
+            )
+        } else {
+            quote!()
+        };
+
+        let enum_definition = if let Some(fallback) = &query_context.typename_fallback {
+            typename_fallback_enum(
+                query_context,
+                fallback,
+                &struct_name,
+                prefix,
+                &selected_variants,
+                &other_variants,
+                &self.name,
+            )?
+        } else if query_context.format_agnostic_serde {
+            format_agnostic_enum(
+                query_context,
+                &struct_name,
+                prefix,
+                &selected_variants,
+                &other_variants,
+            )
+        } else {
+            let tagging = query_context.union_tagging_attribute();
+            quote! {
+                #derives
+                #tagging
+                #vis enum #struct_name {
+                    #(#variants),*
+                }
+            }
+        };
+
         Ok(quote! {
             #(#children_definitions)*
 
-            #derives
-            #[serde(tag = "__typename")]
-            pub enum #struct_name {
-                #(#variants),*
-            }
+            #enum_definition
+
+            #accessors
+
+            #display
         })
     }
 }
@@ -143,6 +555,10 @@ mod tests {
                 fields: Selection::from_vec(vec![SelectionItem::Field(SelectionField {
                     alias: None,
                     name: "firstName",
+                    deserialize_with: None,
+                    client: false,
+                    arguments: None,
+                    position: graphql_parser::Pos::default(),
                     fields: Selection::new_empty(),
                 })]),
             }),
@@ -151,6 +567,10 @@ mod tests {
                 fields: Selection::from_vec(vec![SelectionItem::Field(SelectionField {
                     alias: None,
                     name: "title",
+                    deserialize_with: None,
+                    client: false,
+                    arguments: None,
+                    position: graphql_parser::Pos::default(),
                     fields: Selection::new_empty(),
                 })]),
             }),
@@ -177,6 +597,7 @@ mod tests {
                         name: "firstName",
                         type_: FieldType::Named("String"),
                         deprecation: DeprecationStatus::Current,
+                        directives: Vec::new(),
                     },
                     GqlObjectField {
                         description: None,
@@ -184,12 +605,14 @@ mod tests {
                         type_: FieldType::Named("String"),
 
                         deprecation: DeprecationStatus::Current,
+                        directives: Vec::new(),
                     },
                     GqlObjectField {
                         description: None,
                         name: "createdAt",
                         type_: FieldType::Named("Date"),
                         deprecation: DeprecationStatus::Current,
+                        directives: Vec::new(),
                     },
                 ],
                 is_required: false.into(),
@@ -207,12 +630,14 @@ mod tests {
                         name: "title",
                         type_: FieldType::Named("String"),
                         deprecation: DeprecationStatus::Current,
+                        directives: Vec::new(),
                     },
                     GqlObjectField {
                         description: None,
                         name: "created_at",
                         type_: FieldType::Named("Date"),
                         deprecation: DeprecationStatus::Current,
+                        directives: Vec::new(),
                     },
                 ],
                 is_required: false.into(),
@@ -236,6 +661,10 @@ mod tests {
             SelectionItem::Field(SelectionField {
                 alias: None,
                 name: "__typename",
+                deserialize_with: None,
+                client: false,
+                arguments: None,
+                position: graphql_parser::Pos::default(),
                 fields: Selection::new_empty(),
             }),
             SelectionItem::InlineFragment(SelectionInlineFragment {
@@ -243,6 +672,10 @@ mod tests {
                 fields: Selection::from_vec(vec![SelectionItem::Field(SelectionField {
                     alias: None,
                     name: "firstName",
+                    deserialize_with: None,
+                    client: false,
+                    arguments: None,
+                    position: graphql_parser::Pos::default(),
                     fields: Selection::new_empty(),
                 })]),
             }),
@@ -251,6 +684,10 @@ mod tests {
                 fields: Selection::from_vec(vec![SelectionItem::Field(SelectionField {
                     alias: None,
                     name: "title",
+                    deserialize_with: None,
+                    client: false,
+                    arguments: None,
+                    position: graphql_parser::Pos::default(),
                     fields: Selection::new_empty(),
                 })]),
             }),
@@ -282,24 +719,28 @@ mod tests {
                         name: "__typename",
                         type_: FieldType::Named(string_type()),
                         deprecation: DeprecationStatus::Current,
+                        directives: Vec::new(),
                     },
                     GqlObjectField {
                         description: None,
                         name: "firstName",
                         type_: FieldType::Named(string_type()),
                         deprecation: DeprecationStatus::Current,
+                        directives: Vec::new(),
                     },
                     GqlObjectField {
                         description: None,
                         name: "lastName",
                         type_: FieldType::Named(string_type()),
                         deprecation: DeprecationStatus::Current,
+                        directives: Vec::new(),
                     },
                     GqlObjectField {
                         description: None,
                         name: "createdAt",
                         type_: FieldType::Named("Date"),
                         deprecation: DeprecationStatus::Current,
+                        directives: Vec::new(),
                     },
                 ],
                 is_required: false.into(),
@@ -317,18 +758,21 @@ mod tests {
                         name: "__typename",
                         type_: FieldType::Named(string_type()),
                         deprecation: DeprecationStatus::Current,
+                        directives: Vec::new(),
                     },
                     GqlObjectField {
                         description: None,
                         name: "title",
                         type_: FieldType::Named("String"),
                         deprecation: DeprecationStatus::Current,
+                        directives: Vec::new(),
                     },
                     GqlObjectField {
                         description: None,
                         name: "createdAt",
                         type_: FieldType::Named("Date"),
                         deprecation: DeprecationStatus::Current,
+                        directives: Vec::new(),
                     },
                 ],
                 is_required: false.into(),
@@ -347,12 +791,26 @@ mod tests {
             result.unwrap().to_string(),
             vec![
                 "# [ derive ( Deserialize ) ] ",
-                "pub struct MeowOnOrganization { pub title : String , } ",
+                "pub struct MeowOnOrganization { # [ doc = \"`title: String!`\" ] pub title : String , } ",
                 "# [ derive ( Deserialize ) ] ",
-                "pub struct MeowOnUser { # [ serde ( rename = \"firstName\" ) ] pub first_name : String , } ",
+                "pub struct MeowOnUser { # [ doc = \"`firstName: String!`\" ] # [ serde ( rename = \"firstName\" ) ] pub first_name : String , } ",
                 "# [ derive ( Deserialize ) ] ",
                 "# [ serde ( tag = \"__typename\" ) ] ",
-                "pub enum Meow { Organization ( MeowOnOrganization ) , User ( MeowOnUser ) }",
+                "pub enum Meow { Organization ( MeowOnOrganization ) , User ( MeowOnUser ) } ",
+                "# [ allow ( unreachable_patterns ) ] impl Meow { ",
+                "pub const ORGANIZATION_TYPENAME : & str = \"Organization\" ; ",
+                "pub const USER_TYPENAME : & str = \"User\" ; ",
+                "pub fn typename ( & self ) -> & 'static str { match self { ",
+                "Meow :: Organization ( _ ) => Meow :: ORGANIZATION_TYPENAME , ",
+                "Meow :: User ( _ ) => Meow :: USER_TYPENAME , ",
+                "} } ",
+                "pub fn is_organization ( & self ) -> bool { match self { Meow :: Organization ( _ ) => true , _ => false , } } ",
+                "pub fn as_organization ( & self ) -> :: core :: option :: Option < & MeowOnOrganization > { match self { Meow :: Organization ( inner ) => Some ( inner ) , _ => None , } } ",
+                "pub fn into_organization ( self ) -> :: core :: option :: Option < MeowOnOrganization > { match self { Meow :: Organization ( inner ) => Some ( inner ) , _ => None , } } ",
+                "pub fn is_user ( & self ) -> bool { match self { Meow :: User ( _ ) => true , _ => false , } } ",
+                "pub fn as_user ( & self ) -> :: core :: option :: Option < & MeowOnUser > { match self { Meow :: User ( inner ) => Some ( inner ) , _ => None , } } ",
+                "pub fn into_user ( self ) -> :: core :: option :: Option < MeowOnUser > { match self { Meow :: User ( inner ) => Some ( inner ) , _ => None , } } ",
+                "}",
             ].into_iter()
                 .collect::<String>(),
         );
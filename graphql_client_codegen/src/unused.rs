@@ -0,0 +1,233 @@
+//! Detection of the fragments and variables a query document declares but never uses.
+
+use graphql_parser::query::{
+    Definition, Directive, Document, OperationDefinition, Selection, SelectionSet, Value,
+};
+use std::collections::BTreeSet;
+
+/// The names of the fragments that are not spread anywhere in the document.
+pub(crate) fn unused_fragments(document: &Document) -> Vec<&str> {
+    let mut spread = BTreeSet::new();
+
+    for definition in &document.definitions {
+        let selection_set = match definition {
+            Definition::Operation(operation) => operation_selection_set(operation),
+            Definition::Fragment(fragment) => &fragment.selection_set,
+        };
+        collect_spreads(selection_set, &mut spread);
+    }
+
+    document
+        .definitions
+        .iter()
+        .filter_map(|definition| match definition {
+            Definition::Fragment(fragment) if !spread.contains(fragment.name.as_str()) => {
+                Some(fragment.name.as_str())
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// The names of the variables declared by the operation that are used neither in its selection
+/// nor in the fragments it spreads, directly or not.
+pub(crate) fn unused_variables<'a>(document: &'a Document, operation_name: &str) -> Vec<&'a str> {
+    let operation = document
+        .definitions
+        .iter()
+        .filter_map(|definition| match definition {
+            Definition::Operation(operation) => Some(operation),
+            Definition::Fragment(_) => None,
+        })
+        .find(|operation| name_of(operation) == Some(operation_name));
+
+    let (variable_definitions, directives, selection_set) = match operation {
+        Some(OperationDefinition::Query(query)) => (
+            &query.variable_definitions,
+            &query.directives,
+            &query.selection_set,
+        ),
+        Some(OperationDefinition::Mutation(mutation)) => (
+            &mutation.variable_definitions,
+            &mutation.directives,
+            &mutation.selection_set,
+        ),
+        Some(OperationDefinition::Subscription(subscription)) => (
+            &subscription.variable_definitions,
+            &subscription.directives,
+            &subscription.selection_set,
+        ),
+        Some(OperationDefinition::SelectionSet(_)) | None => return Vec::new(),
+    };
+
+    let mut used = BTreeSet::new();
+    collect_directive_variables(directives, &mut used);
+
+    // The selection sets to go through: the operation's, then those of the fragments it spreads.
+    let mut pending = vec![selection_set];
+    let mut visited_fragments = BTreeSet::new();
+
+    while let Some(selection_set) = pending.pop() {
+        let mut spread = BTreeSet::new();
+        collect_spreads(selection_set, &mut spread);
+        collect_variables(selection_set, &mut used);
+
+        for fragment_name in spread {
+            if !visited_fragments.insert(fragment_name) {
+                continue;
+            }
+
+            let fragment = document
+                .definitions
+                .iter()
+                .filter_map(|definition| match definition {
+                    Definition::Fragment(fragment) if fragment.name == fragment_name => {
+                        Some(fragment)
+                    }
+                    _ => None,
+                })
+                .next();
+
+            if let Some(fragment) = fragment {
+                collect_directive_variables(&fragment.directives, &mut used);
+                pending.push(&fragment.selection_set);
+            }
+        }
+    }
+
+    variable_definitions
+        .iter()
+        .map(|variable| variable.name.as_str())
+        .filter(|name| !used.contains(name))
+        .collect()
+}
+
+pub(crate) fn name_of(operation: &OperationDefinition) -> Option<&str> {
+    match operation {
+        OperationDefinition::Query(query) => query.name.as_ref(),
+        OperationDefinition::Mutation(mutation) => mutation.name.as_ref(),
+        OperationDefinition::Subscription(subscription) => subscription.name.as_ref(),
+        OperationDefinition::SelectionSet(_) => None,
+    }
+    .map(String::as_str)
+}
+
+pub(crate) fn operation_selection_set(operation: &OperationDefinition) -> &SelectionSet {
+    match operation {
+        OperationDefinition::Query(query) => &query.selection_set,
+        OperationDefinition::Mutation(mutation) => &mutation.selection_set,
+        OperationDefinition::Subscription(subscription) => &subscription.selection_set,
+        OperationDefinition::SelectionSet(selection_set) => selection_set,
+    }
+}
+
+/// Collects the names of the fragments spread in the selection set, including in its inline
+/// fragments, but not in the fragments it spreads.
+pub(crate) fn collect_spreads<'a>(selection_set: &'a SelectionSet, spread: &mut BTreeSet<&'a str>) {
+    for item in &selection_set.items {
+        match item {
+            Selection::Field(field) => collect_spreads(&field.selection_set, spread),
+            Selection::FragmentSpread(fragment_spread) => {
+                spread.insert(fragment_spread.fragment_name.as_str());
+            }
+            Selection::InlineFragment(inline_fragment) => {
+                collect_spreads(&inline_fragment.selection_set, spread)
+            }
+        }
+    }
+}
+
+/// Collects the variables used in the arguments and directives of the selection set, including
+/// in its inline fragments, but not in the fragments it spreads.
+fn collect_variables<'a>(selection_set: &'a SelectionSet, used: &mut BTreeSet<&'a str>) {
+    for item in &selection_set.items {
+        match item {
+            Selection::Field(field) => {
+                for (_, value) in &field.arguments {
+                    collect_value_variables(value, used);
+                }
+                collect_directive_variables(&field.directives, used);
+                collect_variables(&field.selection_set, used);
+            }
+            Selection::FragmentSpread(fragment_spread) => {
+                collect_directive_variables(&fragment_spread.directives, used)
+            }
+            Selection::InlineFragment(inline_fragment) => {
+                collect_directive_variables(&inline_fragment.directives, used);
+                collect_variables(&inline_fragment.selection_set, used);
+            }
+        }
+    }
+}
+
+fn collect_directive_variables<'a>(directives: &'a [Directive], used: &mut BTreeSet<&'a str>) {
+    for directive in directives {
+        for (_, value) in &directive.arguments {
+            collect_value_variables(value, used);
+        }
+    }
+}
+
+fn collect_value_variables<'a>(value: &'a Value, used: &mut BTreeSet<&'a str>) {
+    match value {
+        Value::Variable(name) => {
+            used.insert(name.as_str());
+        }
+        Value::List(values) => {
+            for value in values {
+                collect_value_variables(value, used);
+            }
+        }
+        Value::Object(fields) => {
+            for value in fields.values() {
+                collect_value_variables(value, used);
+            }
+        }
+        _ => (),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const QUERY: &str = r#"
+        query Hero($episode: Episode, $withFriends: Boolean!, $unused: String, $first: Int) {
+          hero(episode: $episode) {
+            ...HeroName
+            friends @include(if: $withFriends) {
+              name
+            }
+          }
+        }
+
+        fragment HeroName on Character {
+          name
+          ...HeroFriends
+        }
+
+        fragment HeroFriends on Character {
+          friendsConnection(first: $first) {
+            totalCount
+          }
+        }
+
+        fragment Orphan on Character {
+          id
+        }
+    "#;
+
+    #[test]
+    fn unused_fragments_works() {
+        let document = graphql_parser::parse_query(QUERY).unwrap();
+
+        assert_eq!(unused_fragments(&document), vec!["Orphan"]);
+    }
+
+    #[test]
+    fn unused_variables_works() {
+        let document = graphql_parser::parse_query(QUERY).unwrap();
+
+        assert_eq!(unused_variables(&document, "Hero"), vec!["unused"]);
+    }
+}
@@ -0,0 +1,212 @@
+//! The implementations of `graphql_client::validation::Validate` on the variables, the input
+//! objects and the enums, generated with the `validate_variables` option.
+
+use crate::field_type::FieldType;
+use crate::query::QueryContext;
+use proc_macro2::{Ident, TokenStream};
+use quote::quote;
+
+/// One field of a struct to validate.
+pub(crate) struct ValidatedField<'a> {
+    /// The name of the field in the struct.
+    pub name: Ident,
+    /// The name of the variable or the input field in GraphQL, for the error paths.
+    pub graphql_name: &'a str,
+    pub type_: &'a FieldType<'a>,
+}
+
+/// The `Validate` implementation of the variables or an input object, named `name`.
+pub(crate) fn struct_impl(
+    context: &QueryContext<'_, '_>,
+    name: &Ident,
+    fields: &[ValidatedField<'_>],
+) -> TokenStream {
+    let checks = fields.iter().map(|field| {
+        let name = &field.name;
+        let graphql_name = field.graphql_name;
+        let checks = value_checks(context, field.type_, quote!(value), false);
+        if checks.is_empty() {
+            return quote!();
+        }
+
+        quote! {
+            {
+                let value = &self.#name;
+                let path = ::graphql_client::validation::field_path(path, #graphql_name);
+                let path = path.as_str();
+                #checks
+            }
+        }
+    });
+
+    quote! {
+        impl ::graphql_client::validation::Validate for #name {
+            #[allow(unused_variables)]
+            fn validate_at(
+                &self,
+                path: &str,
+                errors: &mut ::graphql_client::__alloc::Vec<::graphql_client::validation::ValidationError>,
+            ) {
+                #(#checks)*
+            }
+        }
+    }
+}
+
+/// The `Validate` implementation of an enum, reporting the values matched by the `other` pattern,
+/// with the unknown value given by the `other_value` expression.
+pub(crate) fn enum_impl(
+    name: &Ident,
+    graphql_name: &str,
+    other: TokenStream,
+    other_value: TokenStream,
+) -> TokenStream {
+    quote! {
+        impl ::graphql_client::validation::Validate for #name {
+            fn validate_at(
+                &self,
+                path: &str,
+                errors: &mut ::graphql_client::__alloc::Vec<::graphql_client::validation::ValidationError>,
+            ) {
+                if let #other = self {
+                    ::graphql_client::validation::unknown_enum_value(
+                        #other_value,
+                        #graphql_name,
+                        path,
+                        errors,
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// The checks of `value`, a reference to a value of the given type found at `path`. Empty when
+/// there is nothing to check.
+fn value_checks(
+    context: &QueryContext<'_, '_>,
+    field_type: &FieldType<'_>,
+    value: TokenStream,
+    nullable: bool,
+) -> TokenStream {
+    match field_type {
+        FieldType::Optional(inner) => {
+            let checks = value_checks(context, inner, quote!(value), true);
+            if checks.is_empty() {
+                return checks;
+            }
+
+            quote! {
+                if let Some(value) = #value {
+                    #checks
+                }
+            }
+        }
+        FieldType::Vector(inner) => {
+            let checks = value_checks(context, inner, quote!(value), false);
+            if checks.is_empty() {
+                return checks;
+            }
+
+            quote! {
+                for (index, value) in #value.iter().enumerate() {
+                    let path = format!("{}[{}]", path, index);
+                    let path = path.as_str();
+                    #checks
+                }
+            }
+        }
+        FieldType::Named(name) => {
+            let schema = context.schema;
+            if schema.enums.contains_key(name) || schema.inputs.contains_key(name) {
+                return quote! {
+                    ::graphql_client::validation::Validate::validate_at(#value, path, errors);
+                };
+            }
+
+            let scalar = match schema.scalars.get(name) {
+                Some(scalar) => scalar,
+                // The built-in scalars are valid whatever their value.
+                None => return quote!(),
+            };
+            // The JSON scalars generated as `serde_json::Value` do not implement the trait, and the
+            // other scalars only implement `Serialize` without it.
+            let through_trait = context.scalar_trait && !scalar.is_json(context);
+            let non_null = match (nullable, through_trait) {
+                (true, _) => quote!(),
+                (false, true) => quote! {
+                    ::graphql_client::validation::check_non_null(
+                        &::graphql_client::GraphQLScalar::to_json(#value),
+                        path,
+                        errors,
+                    );
+                },
+                (false, false) => {
+                    quote!(::graphql_client::validation::check_non_null(#value, path, errors);)
+                }
+            };
+            let format = if through_trait {
+                quote!(::graphql_client::validation::check_scalar(#value, path, errors);)
+            } else {
+                quote!()
+            };
+
+            quote!(#non_null #format)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proc_macro2::Span;
+
+    #[test]
+    fn only_the_custom_scalars_enums_and_inputs_are_checked() {
+        let schema = graphql_parser::schema::parse_schema(
+            "scalar Date enum Color { RED } type Query { a: String }",
+        )
+        .unwrap();
+        let schema = crate::schema::Schema::from(&schema);
+        let context = QueryContext::new_empty(&schema);
+        let string = FieldType::Optional(Box::new(FieldType::Named("String")));
+        let dates = FieldType::Vector(Box::new(FieldType::Named("Date")));
+        let color = FieldType::Optional(Box::new(FieldType::Named("Color")));
+        let fields = [
+            ValidatedField {
+                name: Ident::new("name", Span::call_site()),
+                graphql_name: "name",
+                type_: &string,
+            },
+            ValidatedField {
+                name: Ident::new("dates", Span::call_site()),
+                graphql_name: "dates",
+                type_: &dates,
+            },
+            ValidatedField {
+                name: Ident::new("color", Span::call_site()),
+                graphql_name: "color",
+                type_: &color,
+            },
+        ];
+
+        let generated = struct_impl(
+            &context,
+            &Ident::new("Variables", Span::call_site()),
+            &fields,
+        )
+        .to_string();
+
+        assert!(!generated.contains("self . name"));
+        assert!(generated.contains(
+            "for ( index , value ) in value . iter ( ) . enumerate ( ) { \
+             let path = format ! ( \"{}[{}]\" , path , index ) ; \
+             let path = path . as_str ( ) ; \
+             :: graphql_client :: validation :: check_non_null ( value , path , errors ) ; }"
+        ));
+        assert!(generated.contains(
+            "if let Some ( value ) = value { \
+             :: graphql_client :: validation :: Validate :: validate_at ( value , path , errors ) ; }"
+        ));
+    }
+}
@@ -27,8 +27,9 @@ impl<'query> Variable<'query> {
                     &self.ty,
                     self.ty.is_optional(),
                 );
+                let vis = &context.item_visibility;
                 quote! {
-                    pub fn #fn_name() -> #ty {
+                    #vis fn #fn_name() -> #ty {
                         #value
                     }
 
@@ -0,0 +1,405 @@
+//! A visitor over the operations of a query document bound to the schema, for the tools building
+//! on the same binding as the generated code, like linters, doc generators and analyzers.
+
+use crate::conversions::graphql_type;
+use crate::deprecation::DeprecationStatus;
+use crate::field_type::FieldType;
+use crate::objects::GqlObjectField;
+use crate::schema::{print_value, Schema};
+use crate::unused::name_of;
+use graphql_parser::query::{
+    Definition, Document, FragmentDefinition, OperationDefinition, Selection, SelectionSet,
+    TypeCondition,
+};
+use std::collections::BTreeMap;
+
+/// The kind of an operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperationKind {
+    /// A query, including the anonymous `{ ... }` shorthand.
+    Query,
+    /// A mutation.
+    Mutation,
+    /// A subscription.
+    Subscription,
+}
+
+/// An operation of the query document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VisitedOperation<'a> {
+    /// The name of the operation, empty for an anonymous operation.
+    pub name: &'a str,
+    /// Whether the operation is a query, a mutation or a subscription.
+    pub kind: OperationKind,
+    /// The type its selection is on, like `Query`.
+    pub root_type: &'a str,
+}
+
+/// A variable of an operation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VisitedVariable<'a> {
+    /// The name of the variable, without the `$`.
+    pub name: &'a str,
+    /// The type of the variable in the GraphQL syntax, like `[ID!]!`.
+    pub type_: String,
+    /// The default value of the variable in the GraphQL syntax.
+    pub default_value: Option<String>,
+}
+
+/// A selected field, with its definition in the schema.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VisitedField<'a> {
+    /// The type the field is selected on.
+    pub parent_type: &'a str,
+    /// The name of the field in the schema.
+    pub name: &'a str,
+    /// The alias of the field, which names it in the response instead.
+    pub alias: Option<&'a str>,
+    /// The arguments passed to the field, with their values in the GraphQL syntax.
+    pub arguments: Vec<(&'a str, String)>,
+    /// The number of fields the field is nested in, zero for the root fields.
+    pub depth: usize,
+    /// The type of the field in the GraphQL syntax, like `[Issue!]!`, or `None` when the schema
+    /// does not define the field.
+    pub type_: Option<String>,
+    /// The type of the field without its lists and non-null markers, like `Issue`.
+    pub type_name: Option<&'a str>,
+    /// The description of the field in the schema.
+    pub description: Option<&'a str>,
+    /// Whether the field is deprecated in the schema, or `None` when the schema does not define it.
+    pub deprecation: Option<&'a DeprecationStatus>,
+}
+
+/// A fragment spread. The selection of the fragment is visited after it, except when the fragment
+/// is already being spread, so a fragment spreading itself is only visited once.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VisitedFragmentSpread<'a> {
+    /// The type the fragment is spread on.
+    pub parent_type: &'a str,
+    /// The name of the fragment.
+    pub name: &'a str,
+    /// The type condition of the fragment, or `None` when the document does not define it.
+    pub on: Option<&'a str>,
+}
+
+/// An inline fragment. Its selection is visited after it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VisitedInlineFragment<'a> {
+    /// The type the fragment is selected on.
+    pub parent_type: &'a str,
+    /// The type condition of the fragment, if it has one.
+    pub on: Option<&'a str>,
+}
+
+/// The callbacks of [SchemaDocument::visit](crate::SchemaDocument::visit), called in the order
+/// of the document. The selections are visited between the `enter_` and `leave_` calls of their
+/// field or fragment. All the methods do nothing by default.
+pub trait QueryVisitor {
+    /// An operation, before its variables and its selection.
+    fn enter_operation(&mut self, _operation: &VisitedOperation<'_>) {}
+
+    /// An operation, after its selection.
+    fn leave_operation(&mut self, _operation: &VisitedOperation<'_>) {}
+
+    /// A variable of the current operation.
+    fn visit_variable(&mut self, _variable: &VisitedVariable<'_>) {}
+
+    /// A field, before its selection.
+    fn enter_field(&mut self, _field: &VisitedField<'_>) {}
+
+    /// A field, after its selection.
+    fn leave_field(&mut self, _field: &VisitedField<'_>) {}
+
+    /// A fragment spread, before the selection of the fragment.
+    fn enter_fragment_spread(&mut self, _spread: &VisitedFragmentSpread<'_>) {}
+
+    /// A fragment spread, after the selection of the fragment.
+    fn leave_fragment_spread(&mut self, _spread: &VisitedFragmentSpread<'_>) {}
+
+    /// An inline fragment, before its selection.
+    fn enter_inline_fragment(&mut self, _fragment: &VisitedInlineFragment<'_>) {}
+
+    /// An inline fragment, after its selection.
+    fn leave_inline_fragment(&mut self, _fragment: &VisitedInlineFragment<'_>) {}
+}
+
+/// Visits every operation of the document, in the order they are defined.
+pub(crate) fn visit_operations<V: QueryVisitor + ?Sized>(
+    schema: &Schema<'_>,
+    document: &Document,
+    visitor: &mut V,
+) {
+    let walker = Walker {
+        schema,
+        fragments: document
+            .definitions
+            .iter()
+            .filter_map(|definition| match definition {
+                Definition::Fragment(fragment) => Some((fragment.name.as_str(), fragment)),
+                Definition::Operation(_) => None,
+            })
+            .collect(),
+    };
+
+    for operation in document
+        .definitions
+        .iter()
+        .filter_map(|definition| match definition {
+            Definition::Operation(operation) => Some(operation),
+            Definition::Fragment(_) => None,
+        })
+    {
+        let (kind, root_type, selection_set, variable_definitions) = match operation {
+            OperationDefinition::SelectionSet(selection_set) => (
+                OperationKind::Query,
+                schema.query_type.unwrap_or("Query"),
+                selection_set,
+                &[][..],
+            ),
+            OperationDefinition::Query(query) => (
+                OperationKind::Query,
+                schema.query_type.unwrap_or("Query"),
+                &query.selection_set,
+                &query.variable_definitions[..],
+            ),
+            OperationDefinition::Mutation(mutation) => (
+                OperationKind::Mutation,
+                schema.mutation_type.unwrap_or("Mutation"),
+                &mutation.selection_set,
+                &mutation.variable_definitions[..],
+            ),
+            OperationDefinition::Subscription(subscription) => (
+                OperationKind::Subscription,
+                schema.subscription_type.unwrap_or("Subscription"),
+                &subscription.selection_set,
+                &subscription.variable_definitions[..],
+            ),
+        };
+        let visited = VisitedOperation {
+            name: name_of(operation).unwrap_or(""),
+            kind,
+            root_type,
+        };
+
+        visitor.enter_operation(&visited);
+        for variable in variable_definitions {
+            visitor.visit_variable(&VisitedVariable {
+                name: &variable.name,
+                type_: graphql_type(&FieldType::from(&variable.var_type)),
+                default_value: variable.default_value.as_ref().map(print_value),
+            });
+        }
+        walker.selection(visitor, root_type, selection_set, 0, &mut Vec::new());
+        visitor.leave_operation(&visited);
+    }
+}
+
+struct Walker<'a, 'schema> {
+    schema: &'a Schema<'schema>,
+    fragments: BTreeMap<&'a str, &'a FragmentDefinition>,
+}
+
+impl<'a, 'schema> Walker<'a, 'schema> {
+    /// Visits the selection on the type, with the names of the fragments being spread to stop at
+    /// the fragments spreading themselves.
+    fn selection<V: QueryVisitor + ?Sized>(
+        &self,
+        visitor: &mut V,
+        type_name: &'a str,
+        selection_set: &'a SelectionSet,
+        depth: usize,
+        spread_fragments: &mut Vec<&'a str>,
+    ) {
+        for item in &selection_set.items {
+            match item {
+                Selection::Field(field) => {
+                    let schema_field = self.schema_field(type_name, &field.name);
+                    let (type_, type_name_of_field) = if field.name == "__typename" {
+                        (Some("String!".to_owned()), Some("String"))
+                    } else {
+                        (
+                            schema_field.map(|schema_field| graphql_type(&schema_field.type_)),
+                            schema_field.map(|schema_field| schema_field.type_.inner_name_str()),
+                        )
+                    };
+                    let visited = VisitedField {
+                        parent_type: type_name,
+                        name: &field.name,
+                        alias: field.alias.as_ref().map(String::as_str),
+                        arguments: field
+                            .arguments
+                            .iter()
+                            .map(|(name, value)| (name.as_str(), print_value(value)))
+                            .collect(),
+                        depth,
+                        type_,
+                        type_name: type_name_of_field,
+                        description: schema_field.and_then(|schema_field| schema_field.description),
+                        deprecation: schema_field.map(|schema_field| &schema_field.deprecation),
+                    };
+
+                    visitor.enter_field(&visited);
+                    self.selection(
+                        visitor,
+                        type_name_of_field.unwrap_or(""),
+                        &field.selection_set,
+                        depth + 1,
+                        spread_fragments,
+                    );
+                    visitor.leave_field(&visited);
+                }
+                Selection::FragmentSpread(spread) => {
+                    let name = spread.fragment_name.as_str();
+                    let fragment = self.fragments.get(name).cloned();
+                    let visited = VisitedFragmentSpread {
+                        parent_type: type_name,
+                        name,
+                        on: fragment.map(|fragment| {
+                            let TypeCondition::On(on) = &fragment.type_condition;
+                            on.as_str()
+                        }),
+                    };
+
+                    visitor.enter_fragment_spread(&visited);
+                    if let (Some(fragment), Some(on)) = (fragment, visited.on) {
+                        if !spread_fragments.contains(&name) {
+                            spread_fragments.push(name);
+                            self.selection(
+                                visitor,
+                                on,
+                                &fragment.selection_set,
+                                depth,
+                                spread_fragments,
+                            );
+                            spread_fragments.pop();
+                        }
+                    }
+                    visitor.leave_fragment_spread(&visited);
+                }
+                Selection::InlineFragment(inline_fragment) => {
+                    let on = inline_fragment
+                        .type_condition
+                        .as_ref()
+                        .map(|TypeCondition::On(on)| on.as_str());
+                    let visited = VisitedInlineFragment {
+                        parent_type: type_name,
+                        on,
+                    };
+
+                    visitor.enter_inline_fragment(&visited);
+                    self.selection(
+                        visitor,
+                        on.unwrap_or(type_name),
+                        &inline_fragment.selection_set,
+                        depth,
+                        spread_fragments,
+                    );
+                    visitor.leave_inline_fragment(&visited);
+                }
+            }
+        }
+    }
+
+    fn schema_field(
+        &self,
+        type_name: &str,
+        field_name: &str,
+    ) -> Option<&'a GqlObjectField<'schema>> {
+        self.schema
+            .objects
+            .get(type_name)
+            .map(|object| &object.fields)
+            .or_else(|| {
+                self.schema
+                    .interfaces
+                    .get(type_name)
+                    .map(|interface| &interface.fields)
+            })
+            .and_then(|fields| fields.iter().find(|field| field.name == field_name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Records the visited items as lines.
+    #[derive(Default)]
+    struct Recorder(Vec<String>);
+
+    impl QueryVisitor for Recorder {
+        fn enter_operation(&mut self, operation: &VisitedOperation<'_>) {
+            self.0.push(format!(
+                "{:?} {} on {}",
+                operation.kind, operation.name, operation.root_type
+            ));
+        }
+
+        fn visit_variable(&mut self, variable: &VisitedVariable<'_>) {
+            self.0.push(format!(
+                "${}: {} = {:?}",
+                variable.name, variable.type_, variable.default_value
+            ));
+        }
+
+        fn enter_field(&mut self, field: &VisitedField<'_>) {
+            self.0.push(format!(
+                "{}{}.{}({:?}): {:?}",
+                "  ".repeat(field.depth),
+                field.parent_type,
+                field.name,
+                field.arguments,
+                field.type_
+            ));
+        }
+
+        fn enter_fragment_spread(&mut self, spread: &VisitedFragmentSpread<'_>) {
+            self.0
+                .push(format!("...{} on {:?}", spread.name, spread.on));
+        }
+
+        fn enter_inline_fragment(&mut self, fragment: &VisitedInlineFragment<'_>) {
+            self.0.push(format!("... on {:?}", fragment.on));
+        }
+    }
+
+    #[test]
+    fn operations_are_visited_with_their_schema_types() {
+        let schema = graphql_parser::parse_schema(
+            r#"
+            schema { query: Query }
+            type Query { node(id: ID!): Node }
+            interface Node { id: ID! }
+            type User implements Node { id: ID!, friends(first: Int): [User!] }
+            "#,
+        )
+        .unwrap();
+        let schema = Schema::from(&schema);
+        let document = graphql_parser::parse_query(
+            r#"
+            query Friends($id: ID! = "1") {
+              node(id: $id) { __typename ...Friends }
+            }
+            fragment Friends on Node { ... on User { friends(first: 2) { id } } }
+            "#,
+        )
+        .unwrap();
+
+        let mut recorder = Recorder::default();
+        visit_operations(&schema, &document, &mut recorder);
+
+        assert_eq!(
+            recorder.0,
+            vec![
+                "Query Friends on Query",
+                "$id: ID! = Some(\"\\\"1\\\"\")",
+                "Query.node([(\"id\", \"$id\")]): Some(\"Node\")",
+                "  Node.__typename([]): Some(\"String!\")",
+                "...Friends on Some(\"Node\")",
+                "... on Some(\"User\")",
+                "  User.friends([(\"first\", \"2\")]): Some(\"[User!]\")",
+                "    User.id([]): Some(\"ID!\")",
+            ]
+        );
+    }
+}
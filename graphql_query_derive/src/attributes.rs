@@ -35,6 +35,22 @@ pub fn extract_attr(ast: &syn::DeriveInput, attr: &str) -> Result<String, failur
     Err(format_err!("attribute not found"))?
 }
 
+/// Extract a boolean configuration parameter (`"true"` or `"false"`) specified in the `graphql`
+/// attribute. Returns `None` when the parameter is absent.
+pub fn extract_bool_attr(
+    ast: &syn::DeriveInput,
+    attr: &str,
+) -> Result<Option<bool>, failure::Error> {
+    extract_attr(ast, attr)
+        .ok()
+        .map(|value| {
+            value
+                .parse()
+                .map_err(|_| format_err!("{} must be \"true\" or \"false\"", attr))
+        })
+        .transpose()
+}
+
 /// Get the deprecation from a struct attribute in the derive case.
 pub fn extract_deprecation_strategy(
     ast: &syn::DeriveInput,
@@ -86,6 +102,27 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_bool_attr() {
+        let input = "
+        #[derive(GraphQLQuery)]
+        #[graphql(
+            schema_path = \"x\",
+            query_path = \"x\",
+            pretty_display = \"true\",
+            other = \"yes\",
+        )]
+        struct MyQuery;
+        ";
+        let parsed = syn::parse_str(input).unwrap();
+        assert_eq!(
+            extract_bool_attr(&parsed, "pretty_display").unwrap(),
+            Some(true)
+        );
+        assert_eq!(extract_bool_attr(&parsed, "missing").unwrap(), None);
+        assert!(extract_bool_attr(&parsed, "other").is_err());
+    }
+
     #[test]
     fn test_invalid_deprecation_strategy() {
         let input = "
@@ -2,10 +2,12 @@ extern crate proc_macro;
 
 /// Derive-related code. This will be moved into graphql_query_derive.
 mod attributes;
+mod paths;
 
-use failure::ResultExt;
+use failure::{format_err, ResultExt};
 use graphql_client_codegen::{
-    generate_module_token_stream, CodegenMode, GraphQLClientCodegenOptions,
+    generate_module_token_stream, CodegenMode, GraphQLClientCodegenOptions, SchemaDocument,
+    SchemaFormat,
 };
 use std::path::{Path, PathBuf};
 
@@ -32,40 +34,139 @@ fn graphql_query_derive_inner(
 ) -> Result<proc_macro::TokenStream, failure::Error> {
     let input = TokenStream::from(input);
     let ast = syn::parse2(input).context("Derive input parsing.")?;
-    let (query_path, schema_path) = build_query_and_schema_path(&ast)?;
-    let options = build_graphql_client_derive_options(&ast, query_path.to_path_buf())?;
-    Ok(
-        generate_module_token_stream(query_path, &schema_path, options)
-            .map(Into::into)
-            .context("Code generation failed.")?,
-    )
+    let (query, schema) = build_query_and_schema_sources(&ast)?;
+    let options = build_graphql_client_derive_options(&ast, query.path(), schema.path())?;
+
+    let generated = match (query, schema) {
+        (Source::Path(query_path), Source::Path(schema_path)) => {
+            generate_module_token_stream(query_path, &schema_path, options)
+        }
+        (query, schema) => {
+            let schema = match schema {
+                Source::Path(schema_path) => SchemaDocument::read(&schema_path),
+                Source::String(schema_string) => {
+                    SchemaDocument::parse(&schema_string, schema_string_format(&schema_string))
+                }
+            };
+            schema.and_then(|schema| match query {
+                Source::Path(query_path) => {
+                    schema.generate_module_token_stream(&query_path, &options)
+                }
+                Source::String(query_string) => {
+                    schema.generate_module_token_stream_from_str(&query_string, &options)
+                }
+            })
+        }
+    };
+
+    Ok(generated
+        .map(Into::into)
+        .context("Code generation failed.")?)
+}
+
+/// A query or schema document of the derive: a file, or the text of an attribute.
+enum Source {
+    Path(PathBuf),
+    String(String),
+}
+
+impl Source {
+    fn path(&self) -> Option<PathBuf> {
+        match self {
+            Source::Path(path) => Some(path.clone()),
+            Source::String(_) => None,
+        }
+    }
+}
+
+/// The format of an inline schema: an introspection response if it is a JSON object, the GraphQL
+/// schema language otherwise.
+fn schema_string_format(schema_string: &str) -> SchemaFormat {
+    if schema_string.trim_start().starts_with('{') {
+        SchemaFormat::Json
+    } else {
+        SchemaFormat::GraphQL
+    }
 }
 
-fn build_query_and_schema_path(
+/// The file named by the `{name}_path` attribute (see [paths::resolve_path]), or the text of the
+/// `{name}_string` attribute.
+fn build_source(
     input: &syn::DeriveInput,
-) -> Result<(PathBuf, PathBuf), failure::Error> {
+    cargo_manifest_dir: &Path,
+    name: &str,
+) -> Result<Source, failure::Error> {
+    let path_attr = format!("{}_path", name);
+    let string_attr = format!("{}_string", name);
+
+    match attributes::extract_attr(input, &string_attr).ok() {
+        Some(string) => {
+            if attributes::extract_attr(input, &path_attr).is_ok() {
+                return Err(format_err!(
+                    "The {} and {} attributes cannot be used together.",
+                    path_attr,
+                    string_attr
+                ));
+            }
+            Ok(Source::String(string))
+        }
+        None => {
+            let path = attributes::extract_attr(input, &path_attr)
+                .with_context(|_| format!("Extracting {} path.", name))?;
+            Ok(Source::Path(paths::resolve_path(
+                cargo_manifest_dir,
+                &path,
+            )?))
+        }
+    }
+}
+
+fn build_query_and_schema_sources(
+    input: &syn::DeriveInput,
+) -> Result<(Source, Source), failure::Error> {
     let cargo_manifest_dir = ::std::env::var("CARGO_MANIFEST_DIR")
         .context("Checking that the CARGO_MANIFEST_DIR env variable is defined.")?;
 
-    let query_path =
-        attributes::extract_attr(input, "query_path").context("Extracting query path.")?;
-    let query_path = format!("{}/{}", cargo_manifest_dir, query_path);
-    let query_path = Path::new(&query_path).to_path_buf();
-    let schema_path =
-        attributes::extract_attr(input, "schema_path").context("Extracting schema path.")?;
-    let schema_path = Path::new(&cargo_manifest_dir).join(schema_path);
-    Ok((query_path, schema_path))
+    let cargo_manifest_dir = Path::new(&cargo_manifest_dir);
+
+    let query = build_source(input, cargo_manifest_dir, "query")?;
+    let schema = build_source(input, cargo_manifest_dir, "schema")?;
+    Ok((query, schema))
 }
 
 fn build_graphql_client_derive_options(
     input: &syn::DeriveInput,
-    query_path: PathBuf,
+    query_path: Option<PathBuf>,
+    schema_path: Option<PathBuf>,
 ) -> Result<GraphQLClientCodegenOptions, failure::Error> {
     let response_derives = attributes::extract_attr(input, "response_derives").ok();
     let input_derives = attributes::extract_attr(input, "input_derives").ok();
+    let response_struct_derives = attributes::extract_attr(input, "response_struct_derives").ok();
+    let response_enum_derives = attributes::extract_attr(input, "response_enum_derives").ok();
+    let allowed_deprecations = attributes::extract_attr(input, "allowed_deprecations").ok();
+    let collection_types = attributes::extract_attr(input, "collection_types").ok();
+    let enum_wire_values = attributes::extract_attr(input, "enum_wire_values").ok();
+    let input_conversions = attributes::extract_attr(input, "input_conversions").ok();
+    let test_fixtures_path = attributes::extract_attr(input, "test_fixtures_path").ok();
+    let item_visibility = attributes::extract_attr(input, "item_visibility").ok();
+    let scalar_module = attributes::extract_attr(input, "scalar_module").ok();
+    let rust_version = attributes::extract_attr(input, "rust_version").ok();
+    let union_tagging = attributes::extract_attr(input, "union_tagging").ok();
+    let typename_fallback = attributes::extract_attr(input, "typename_fallback").ok();
+    let pagination_cursor = attributes::extract_attr(input, "pagination_cursor").ok();
+    let pagination_nodes = attributes::extract_attr(input, "pagination_nodes").ok();
+    let pagination_page_info = attributes::extract_attr(input, "pagination_page_info").ok();
+    let max_depth = attributes::extract_attr(input, "max_depth").ok();
+    let max_complexity = attributes::extract_attr(input, "max_complexity").ok();
+    let list_multipliers = attributes::extract_attr(input, "list_multipliers").ok();
 
     let mut options = GraphQLClientCodegenOptions::new(CodegenMode::Derive);
-    options.set_query_file(query_path);
+    if let Some(query_path) = query_path {
+        options.set_query_file(query_path);
+    }
+    if let Some(schema_path) = schema_path {
+        options.set_schema_file(schema_path);
+    }
 
     if let Some(input_derives) = input_derives {
         options.set_input_derives(input_derives);
@@ -75,11 +176,167 @@ fn build_graphql_client_derive_options(
         options.set_response_derives(response_derives);
     };
 
+    if let Some(response_struct_derives) = response_struct_derives {
+        options.set_response_struct_derives(response_struct_derives);
+    };
+
+    if let Some(response_enum_derives) = response_enum_derives {
+        options.set_response_enum_derives(response_enum_derives);
+    };
+
     // The user can determine what to do about deprecations.
     if let Ok(deprecation_strategy) = attributes::extract_deprecation_strategy(input) {
         options.set_deprecation_strategy(deprecation_strategy);
     };
 
+    if let Some(allowed_deprecations) = allowed_deprecations {
+        options.set_allowed_deprecations(allowed_deprecations);
+    };
+
+    if let Some(collection_types) = collection_types {
+        options.set_collection_types(collection_types);
+    };
+
+    if let Some(enum_wire_values) = enum_wire_values {
+        options.set_enum_wire_values(enum_wire_values);
+    };
+
+    if let Some(input_conversions) = input_conversions {
+        options.set_input_conversions(input_conversions);
+    };
+
+    if let Some(test_fixtures_path) = test_fixtures_path {
+        let cargo_manifest_dir = ::std::env::var("CARGO_MANIFEST_DIR")
+            .context("Checking that the CARGO_MANIFEST_DIR env variable is defined.")?;
+        options.set_test_fixtures_path(paths::resolve_path(
+            Path::new(&cargo_manifest_dir),
+            &test_fixtures_path,
+        )?);
+    };
+
+    if let Some(item_visibility) = item_visibility {
+        let item_visibility: syn::Visibility = syn::parse_str(&item_visibility)
+            .map_err(|err| format_err!("Invalid item_visibility: {}", err))?;
+        options.set_item_visibility(item_visibility);
+    };
+
+    if let Some(scalar_module) = scalar_module {
+        let scalar_module: syn::Path = syn::parse_str(&scalar_module)
+            .map_err(|err| format_err!("Invalid scalar_module: {}", err))?;
+        options.set_scalar_module(scalar_module);
+    };
+
+    if let Some(pagination_cursor) = pagination_cursor {
+        options.set_pagination_cursor(pagination_cursor);
+    };
+
+    if let Some(pagination_nodes) = pagination_nodes {
+        options.set_pagination_nodes(pagination_nodes);
+    };
+
+    if let Some(pagination_page_info) = pagination_page_info {
+        options.set_pagination_page_info(pagination_page_info);
+    };
+
+    if let Some(pretty_display) = attributes::extract_bool_attr(input, "pretty_display")? {
+        options.set_pretty_display(pretty_display);
+    };
+
+    if let Some(nested_accessors) = attributes::extract_bool_attr(input, "nested_accessors")? {
+        options.set_nested_accessors(nested_accessors);
+    };
+
+    if let Some(flatten_wrappers) = attributes::extract_bool_attr(input, "flatten_wrappers")? {
+        options.set_flatten_wrappers(flatten_wrappers);
+    };
+
+    if let Some(rust_version) = rust_version {
+        options.set_rust_version(rust_version.parse()?);
+    };
+
+    if let Some(union_tagging) = union_tagging {
+        options.set_union_tagging(union_tagging.parse()?);
+    };
+
+    if let Some(typename_fallback) = typename_fallback {
+        options.set_typename_fallback(typename_fallback.parse()?);
+    };
+
+    if let Some(deny_unused) = attributes::extract_bool_attr(input, "deny_unused")? {
+        options.set_deny_unused(deny_unused);
+    };
+
+    if let Some(enum_rename_all) = attributes::extract_bool_attr(input, "enum_rename_all")? {
+        options.set_enum_rename_all(enum_rename_all);
+    };
+
+    if let Some(scalar_trait) = attributes::extract_bool_attr(input, "scalar_trait")? {
+        options.set_scalar_trait(scalar_trait);
+    };
+
+    if let Some(assert_send_sync) = attributes::extract_bool_attr(input, "assert_send_sync")? {
+        options.set_assert_send_sync(assert_send_sync);
+    };
+
+    if let Some(json_scalars) = attributes::extract_bool_attr(input, "json_scalars")? {
+        options.set_json_scalars(json_scalars);
+    };
+
+    if let Some(extra_fields) = attributes::extract_bool_attr(input, "extra_fields")? {
+        options.set_extra_fields(extra_fields);
+    };
+
+    if let Some(typestate_builders) = attributes::extract_bool_attr(input, "typestate_builders")? {
+        options.set_typestate_builders(typestate_builders);
+    };
+
+    if let Some(register_operations) = attributes::extract_bool_attr(input, "register_operations")?
+    {
+        options.set_register_operations(register_operations);
+    };
+
+    if let Some(all_operations) = attributes::extract_bool_attr(input, "all_operations")? {
+        options.set_all_operations(all_operations);
+    };
+
+    if let Some(share_variables) = attributes::extract_bool_attr(input, "share_variables")? {
+        options.set_share_variables(share_variables);
+    };
+
+    // An environment variable rather than an attribute, to profile a build without editing it.
+    if ::std::env::var_os("GRAPHQL_CLIENT_TIMINGS").is_some() {
+        options.set_timings(true);
+    };
+
+    if let Some(serialize_responses) = attributes::extract_bool_attr(input, "serialize_responses")?
+    {
+        options.set_serialize_responses(serialize_responses);
+    };
+
+    if let Some(max_depth) = max_depth {
+        options.set_max_depth(max_depth.parse()?);
+    };
+
+    if let Some(max_complexity) = max_complexity {
+        options.set_max_complexity(max_complexity.parse()?);
+    };
+
+    if let Some(list_multipliers) = list_multipliers {
+        let mut complexity_options = graphql_client_codegen::ComplexityOptions::new();
+        complexity_options.set_list_multipliers(&list_multipliers)?;
+        options.set_complexity_options(complexity_options);
+    };
+
+    if let Some(validate_variables) = attributes::extract_bool_attr(input, "validate_variables")? {
+        options.set_validate_variables(validate_variables);
+    };
+
+    if let Some(format_agnostic_serde) =
+        attributes::extract_bool_attr(input, "format_agnostic_serde")?
+    {
+        options.set_format_agnostic_serde(format_agnostic_serde);
+    };
+
     options.set_struct_ident(input.ident.clone());
     options.set_module_visibility(input.vis.clone());
     options.set_operation_name(input.ident.to_string());
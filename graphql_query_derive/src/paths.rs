@@ -0,0 +1,124 @@
+use failure::format_err;
+use std::path::{Path, PathBuf};
+
+/// Resolves a `schema_path` or `query_path` attribute.
+///
+/// `${NAME}` is replaced with the value of the `NAME` environment variable, and `${WORKSPACE_ROOT}`
+/// with the root of the cargo workspace the crate belongs to (the crate root if it is not in a
+/// workspace) unless the environment defines it. Relative paths are resolved against the crate
+/// root, absolute paths are used as is.
+///
+/// Files written by a build script are found with `${OUT_DIR}`. Since they are included in the
+/// generated module like any other file, Cargo rebuilds the crate when the build script changes
+/// them.
+pub fn resolve_path(cargo_manifest_dir: &Path, path: &str) -> Result<PathBuf, failure::Error> {
+    let path = interpolate(path, |name| match std::env::var(name) {
+        Ok(value) => Some(value),
+        Err(_) if name == "WORKSPACE_ROOT" => Some(
+            workspace_root(cargo_manifest_dir)
+                .to_string_lossy()
+                .into_owned(),
+        ),
+        Err(_) => None,
+    })?;
+
+    Ok(cargo_manifest_dir.join(path))
+}
+
+/// Replaces the `${NAME}` variables in `path` with their values.
+fn interpolate(
+    path: &str,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<String, failure::Error> {
+    let mut interpolated = String::with_capacity(path.len());
+    let mut rest = path;
+
+    while let Some(start) = rest.find("${") {
+        interpolated.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| format_err!("Unterminated variable in path {:?}.", path))?;
+        let name = &rest[start + 2..start + end];
+        let value = lookup(name).ok_or_else(|| match name {
+            "OUT_DIR" => format_err!(
+                "OUT_DIR in path {:?} is not defined. Cargo only defines it for crates with a build script.",
+                path
+            ),
+            _ => format_err!(
+                "The environment variable {} in path {:?} is not defined.",
+                name,
+                path
+            ),
+        })?;
+        interpolated.push_str(&value);
+        rest = &rest[start + end + 1..];
+    }
+
+    interpolated.push_str(rest);
+    Ok(interpolated)
+}
+
+/// The outermost directory above the crate root whose Cargo.toml declares a workspace.
+fn workspace_root(cargo_manifest_dir: &Path) -> PathBuf {
+    cargo_manifest_dir
+        .ancestors()
+        .filter(|dir| {
+            std::fs::read_to_string(dir.join("Cargo.toml"))
+                .map(|manifest| manifest.lines().any(|line| line.trim() == "[workspace]"))
+                .unwrap_or(false)
+        })
+        .last()
+        .unwrap_or(cargo_manifest_dir)
+        .to_path_buf()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "SCHEMAS" => Some("/shared/schemas".to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn interpolate_replaces_variables() {
+        assert_eq!(
+            interpolate("${SCHEMAS}/github.graphql", lookup).unwrap(),
+            "/shared/schemas/github.graphql"
+        );
+        assert_eq!(
+            interpolate("src/schema.graphql", lookup).unwrap(),
+            "src/schema.graphql"
+        );
+    }
+
+    #[test]
+    fn interpolate_rejects_undefined_and_unterminated_variables() {
+        assert!(interpolate("${MISSING}/schema.graphql", lookup).is_err());
+        assert!(interpolate("${SCHEMAS/schema.graphql", lookup).is_err());
+    }
+
+    #[test]
+    fn undefined_out_dir_is_explained() {
+        let err = interpolate("${OUT_DIR}/schema.graphql", lookup).unwrap_err();
+        assert!(err.to_string().contains("build script"));
+    }
+
+    #[test]
+    fn absolute_paths_are_kept() {
+        let resolved = resolve_path(Path::new("/crate"), "/shared/schema.graphql").unwrap();
+        assert_eq!(resolved, Path::new("/shared/schema.graphql"));
+
+        let resolved = resolve_path(Path::new("/crate"), "schema.graphql").unwrap();
+        assert_eq!(resolved, Path::new("/crate/schema.graphql"));
+    }
+
+    #[test]
+    fn workspace_root_is_found() {
+        let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+        assert_eq!(workspace_root(manifest_dir), manifest_dir.parent().unwrap());
+    }
+}